@@ -1,6 +1,22 @@
 use std::{env, path::PathBuf};
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(simconnect_no_link)");
+
+    // The import library only exists for Windows. Everywhere else (or when asked to with the
+    // `no-link` feature) use the pregenerated bindings in `src/bindings.rs` and stub out the
+    // functions, so the crate still builds and everything above the FFI layer can be tested.
+    // With the `dynamic` feature the library is loaded at runtime, see `src/dynamic.rs`.
+    if env::var_os("CARGO_FEATURE_DYNAMIC").is_some() {
+        return;
+    }
+
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if target_os != "windows" || env::var_os("CARGO_FEATURE_NO_LINK").is_some() {
+        println!("cargo:rustc-cfg=simconnect_no_link");
+        return;
+    }
+
     println!("cargo:rustc-link-search=libsrc/lib");
     println!("cargo:rustc-link-lib=static=SimConnect");
 
@@ -1,4 +1,4 @@
-use simconnect::SIMCONNECT_CLIENT_EVENT_ID;
+use simconnect::{SimConnectError, SIMCONNECT_CLIENT_EVENT_ID};
 use std::collections::HashMap;
 
 // define a struct that holds the event and the input id
@@ -7,7 +7,7 @@ struct Input {
     event: String, // The event to be triggered i.e.
     input_id: u32, // The id we use to trigger the event
 }
-fn main() {
+fn main() -> Result<(), SimConnectError> {
     let input_parking_brakes = Input {
         event: "PARKING_BRAKES".to_string(),
         input_id: 1,
@@ -28,7 +28,7 @@ fn main() {
     events.insert(input_gear_down.input_id, input_gear_down);
 
     let mut conn = simconnect::SimConnector::new();
-    conn.connect("Program that inputs commands to the sim"); // Initialize connection with SimConnect
+    conn.connect("Program that inputs commands to the sim")?; // Initialize connection with SimConnect
 
     // loop over all the events we want to define and map them to the input id
     for event in &events {
@@ -38,7 +38,7 @@ fn main() {
             // if input id 1 is triggered, the PARKING_BRAKES event is triggered
             event.1.input_id as SIMCONNECT_CLIENT_EVENT_ID,
             event.1.event.as_str(),
-        );
+        )?;
     }
     // loop over user input from console and trigger the corresponding events
     loop {
@@ -60,11 +60,11 @@ fn main() {
                 */
                 conn.transmit_client_event(
                     0,
-                    input_id as u32,
+                    input_id,
                     0,
                     simconnect::SIMCONNECT_GROUP_PRIORITY_HIGHEST,
                     simconnect::SIMCONNECT_EVENT_FLAG_GROUPID_IS_PRIORITY,
-                );
+                )?;
             }
             None => println!("No event found for input id: {}", input_id),
         }
@@ -1,16 +1,16 @@
 use std::thread::sleep;
 use std::time::Duration;
 
-use simconnect::DispatchResult;
+use simconnect::{DispatchResult, SimConnectError};
 
 struct DataStruct {
     lat: f64,
     lon: f64,
     alt: f64,
 }
-fn main() {
+fn main() -> Result<(), SimConnectError> {
     let mut conn = simconnect::SimConnector::new();
-    conn.connect("Simple Program"); // Intialize connection with SimConnect
+    conn.connect("Simple Program")?; // Intialize connection with SimConnect
     conn.add_data_definition(
         0,
         "PLANE LATITUDE",
@@ -18,7 +18,7 @@ fn main() {
         simconnect::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
         u32::MAX,
         0.0,
-    ); // Assign a sim variable to a client defined id
+    )?; // Assign a sim variable to a client defined id
     conn.add_data_definition(
         0,
         "PLANE LONGITUDE",
@@ -26,7 +26,7 @@ fn main() {
         simconnect::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
         u32::MAX,
         0.0,
-    );
+    )?;
     conn.add_data_definition(
         0,
         "PLANE ALTITUDE",
@@ -34,7 +34,7 @@ fn main() {
         simconnect::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
         u32::MAX,
         1.0,
-    ); //define_id, units, data_type, datum_id, epsilon (update threshold)
+    )?; //define_id, units, data_type, datum_id, epsilon (update threshold)
     conn.request_data_on_sim_object(
         0,
         0,
@@ -44,26 +44,35 @@ fn main() {
         0,
         0,
         0,
-    ); //request_id, define_id, object_id (user), period, falgs, origin, interval, limit - tells simconnect to send data for the defined id and on the user aircraft
+    )?; //request_id, define_id, object_id (user), period, falgs, origin, interval, limit - tells simconnect to send data for the defined id and on the user aircraft
 
     loop {
         match conn.get_next_message() {
-            Ok(DispatchResult::SimObjectData(data)) => unsafe {
-                if data.dwDefineID == 0 {
-                    let sim_data_ptr = std::ptr::addr_of!(data.dwData) as *const DataStruct;
-                    let sim_data_value = std::ptr::read_unaligned(sim_data_ptr);
-                    println!(
-                        "{:?} {:?} {:?}",
-                        sim_data_value.lat, sim_data_value.lon, sim_data_value.alt
-                    );
-                }
-            },
+            Ok(DispatchResult::SimObjectData(data)) if data.dwDefineID == 0 => {
+                // Fails instead of reading garbage if the sim sent less than defined
+                let mut reader = data.reader();
+                let sim_data_value = DataStruct {
+                    lat: reader.read_f64()?,
+                    lon: reader.read_f64()?,
+                    alt: reader.read_f64()?,
+                };
+                println!(
+                    "{:?} {:?} {:?}",
+                    sim_data_value.lat, sim_data_value.lon, sim_data_value.alt
+                );
+            }
             Ok(DispatchResult::Open(_)) => {
                 println!("Connected to simulator.");
             }
             Ok(DispatchResult::Quit(_)) => {
                 println!("Disconnected from simulator.");
             }
+            Ok(DispatchResult::Exception(exception)) => {
+                // The message borrows `conn`, copy it out to look up the call behind it
+                let exception = *exception;
+                // e.g. "the simulation event name is not recognized (parameter 2) caused by ..."
+                println!("{}", conn.resolve_exception(&exception));
+            }
             _ => (),
         }
 
@@ -0,0 +1,345 @@
+//! `#[derive(SimConnectData)]` and `#[derive(ClientData)]`, re-exported by `simconnect` with
+//! its `derive` feature
+//!
+//! See `simconnect::SimConnectData` and `simconnect::ClientData` for the attributes.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Ident, Lit, LitStr, Token, Type};
+
+/// Lengths of the `STRINGn` data types
+const STRING_LENGTHS: [usize; 6] = [8, 32, 64, 128, 256, 260];
+
+#[proc_macro_derive(SimConnectData, attributes(simvar))]
+pub fn derive_simconnect_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(ClientData, attributes(client_data))]
+pub fn derive_client_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_client_data(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// The arguments of `#[simvar("NAME", unit = "...", epsilon = ..., string = ... | stringv)]`
+struct SimVar {
+    name: LitStr,
+    unit: Option<LitStr>,
+    epsilon: f32,
+    string: Option<usize>,
+    stringv: bool,
+}
+
+impl Parse for SimVar {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut simvar = SimVar {
+            name: input.parse()?,
+            unit: None,
+            epsilon: 0.0,
+            string: None,
+            stringv: false,
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            if key == "stringv" {
+                simvar.stringv = true;
+                continue;
+            }
+            input.parse::<Token![=]>()?;
+            let value: Lit = input.parse()?;
+
+            match (key.to_string().as_str(), &value) {
+                ("unit", Lit::Str(unit)) => simvar.unit = Some(unit.clone()),
+                ("epsilon", Lit::Float(epsilon)) => simvar.epsilon = epsilon.base10_parse()?,
+                ("epsilon", Lit::Int(epsilon)) => simvar.epsilon = epsilon.base10_parse()?,
+                ("string", Lit::Int(len)) => {
+                    let len = len.base10_parse()?;
+                    if !STRING_LENGTHS.contains(&len) {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "string length has to be one of 8, 32, 64, 128, 256 or 260",
+                        ));
+                    }
+                    simvar.string = Some(len);
+                }
+                ("unit", _) | ("epsilon", _) | ("string", _) => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        format!("unexpected value for `{}`", key),
+                    ))
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "expected `unit`, `epsilon`, `string` or `stringv`",
+                    ))
+                }
+            }
+        }
+
+        Ok(simvar)
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "String"),
+        _ => false,
+    }
+}
+
+/// The named fields of `input`, which has to be a non-generic struct.
+fn named_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> syn::Result<&'a Punctuated<Field, Token![,]>> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            format!("{} can't be derived for generic structs", derive),
+        ));
+    }
+
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(
+                name,
+                format!("{} needs a struct with named fields", derive),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            name,
+            format!("{} can only be derived for structs", derive),
+        )),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = named_fields(&input, "SimConnectData")?;
+
+    let mut definitions = Vec::new();
+    let mut reads = Vec::new();
+    let mut values = Vec::new();
+
+    for (datum_id, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let attr = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("simvar"))
+            .ok_or_else(|| {
+                syn::Error::new_spanned(ident, "missing #[simvar(\"NAME\", ...)] attribute")
+            })?;
+        let simvar: SimVar = attr.parse_args()?;
+
+        let datum_name = &simvar.name;
+        let datum_id = Literal::usize_unsuffixed(datum_id);
+        let epsilon = Literal::f32_suffixed(simvar.epsilon);
+
+        if simvar.stringv && simvar.string.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`string` and `stringv` can't be combined",
+            ));
+        }
+
+        let (datatype, units, read) = if simvar.stringv && is_string(ty) {
+            (
+                quote!(::simconnect::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV),
+                quote!(""),
+                quote!(reader.read_string_v()?),
+            )
+        } else if is_string(ty) {
+            let len = simvar.string.unwrap_or(256);
+            let datatype = Ident::new(
+                &format!("SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING{}", len),
+                Span::call_site(),
+            );
+            let len = Literal::usize_unsuffixed(len);
+
+            (
+                quote!(::simconnect::#datatype),
+                quote!(""),
+                quote!(reader.read_string(#len)?),
+            )
+        } else {
+            if simvar.string.is_some() || simvar.stringv {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "`string` and `stringv` need a `String` field",
+                ));
+            }
+
+            (
+                quote!(<#ty as ::simconnect::DataField>::DATATYPE),
+                quote!(<#ty as ::simconnect::DataField>::UNITS),
+                quote!(<#ty as ::simconnect::DataField>::read(reader)?),
+            )
+        };
+        let value = if is_string(ty) {
+            quote!(::simconnect::Value::String(::std::clone::Clone::clone(&self.#ident)))
+        } else {
+            quote!(<#ty as ::simconnect::DataField>::value(&self.#ident))
+        };
+        let units = match &simvar.unit {
+            Some(unit) => quote!(#unit),
+            None => units,
+        };
+
+        definitions.push(quote! {
+            conn.add_data_definition(
+                define_id,
+                #datum_name,
+                #units,
+                #datatype,
+                #datum_id,
+                #epsilon,
+            )?;
+        });
+        reads.push(quote!(#ident: #read,));
+        values.push(value);
+    }
+
+    Ok(quote! {
+        impl ::simconnect::SimConnectData for #name {
+            fn add_definition<B: ::simconnect::SimConnectBackend>(
+                conn: &::simconnect::SimConnector<B>,
+                define_id: ::simconnect::SIMCONNECT_DATA_DEFINITION_ID,
+            ) -> ::std::result::Result<(), ::simconnect::SimConnectError> {
+                #(#definitions)*
+                Ok(())
+            }
+
+            fn read(
+                reader: &mut ::simconnect::PayloadReader<'_>,
+            ) -> ::std::result::Result<Self, ::simconnect::SimConnectError> {
+                Ok(Self { #(#reads)* })
+            }
+
+            fn values(&self) -> ::std::vec::Vec<::simconnect::Value> {
+                ::std::vec![#(#values),*]
+            }
+        }
+    })
+}
+
+/// The arguments of `#[client_data(epsilon = ...)]`
+struct ClientDataAttr {
+    epsilon: f32,
+}
+
+impl Parse for ClientDataAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "epsilon" {
+            return Err(syn::Error::new_spanned(key, "expected `epsilon`"));
+        }
+        input.parse::<Token![=]>()?;
+
+        let epsilon = match input.parse()? {
+            Lit::Float(epsilon) => epsilon.base10_parse()?,
+            Lit::Int(epsilon) => epsilon.base10_parse()?,
+            value => {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    "unexpected value for `epsilon`",
+                ))
+            }
+        };
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(Self { epsilon })
+    }
+}
+
+fn is_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            if meta.input.peek(syn::token::Paren) {
+                // e.g. `align(8)`
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(repr_c)
+}
+
+fn expand_client_data(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = named_fields(&input, "ClientData")?;
+    if !is_repr_c(&input)? {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ClientData needs a #[repr(C)] struct",
+        ));
+    }
+
+    let mut client_fields = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let epsilon = match field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("client_data"))
+        {
+            Some(attr) => attr.parse_args::<ClientDataAttr>()?.epsilon,
+            None => 0.0,
+        };
+        let epsilon = Literal::f32_suffixed(epsilon);
+
+        client_fields.push(quote! {
+            ::simconnect::ClientDataField {
+                offset: ::core::mem::offset_of!(Self, #ident),
+                size: ::core::mem::size_of::<#ty>(),
+                size_or_type: <#ty as ::simconnect::ClientDataType>::SIZE_OR_TYPE,
+                epsilon: #epsilon,
+            }
+        });
+    }
+
+    // Sound since every field is a `ClientDataType` of a `#[repr(C)]` struct
+    Ok(quote! {
+        unsafe impl ::simconnect::ClientData for #name {
+            fn fields() -> ::std::vec::Vec<::simconnect::ClientDataField> {
+                ::std::vec![#(#client_fields),*]
+            }
+        }
+    })
+}
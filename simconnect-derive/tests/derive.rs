@@ -0,0 +1,206 @@
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    ClientData, ClientDataArea, DispatchResult, SimConnectData, SimConnectError, SimConnector,
+    Value, SIMCONNECT_CLIENTDATATYPE_FLOAT32, SIMCONNECT_CLIENTDATATYPE_INT16,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV, SIMCONNECT_OBJECT_ID_USER,
+    SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+#[derive(Debug, PartialEq, SimConnectData)]
+struct Aircraft {
+    #[simvar("PLANE ALTITUDE", unit = "feet", epsilon = 1.0)]
+    altitude: f64,
+    #[simvar("SIM ON GROUND")]
+    on_ground: bool,
+    #[simvar("ATC ID", string = 32)]
+    atc_id: String,
+    #[simvar("TRANSPONDER CODE:1", unit = "bco16")]
+    transponder: i32,
+    #[simvar("ZULU TIME", unit = "seconds")]
+    zulu_time: i64,
+    #[simvar("AIRSPEED INDICATED", unit = "knots", epsilon = 2)]
+    airspeed: f32,
+}
+
+#[derive(Debug, PartialEq, SimConnectData)]
+struct Title {
+    #[simvar("TITLE", stringv)]
+    title: String,
+    #[simvar("ATC AIRLINE", stringv)]
+    airline: String,
+}
+
+fn queue_data(conn: &SimConnector<MockBackend>, payload: &[u8]) {
+    conn.backend()
+        .queue_message_with_payload(SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed(), payload);
+}
+
+#[test]
+fn registers_fields_in_order() {
+    let conn = MockBackend::connected();
+    Aircraft::add_definition(&conn, 3).unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_AddToDataDefinition");
+    assert_eq!(calls.len(), 6);
+
+    let altitude = &calls[0];
+    assert_eq!(altitude.arg("define_id"), Some("3"));
+    assert_eq!(altitude.arg("datum_name"), Some("\"PLANE ALTITUDE\""));
+    assert_eq!(altitude.arg("units_name"), Some("\"feet\""));
+    assert_eq!(
+        altitude.arg("datum_type"),
+        Some(&*SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64.to_string())
+    );
+    assert_eq!(altitude.arg("epsilon"), Some("1.0"));
+    assert_eq!(altitude.arg("datum_id"), Some("0"));
+
+    let on_ground = &calls[1];
+    assert_eq!(on_ground.arg("units_name"), Some("\"bool\""));
+    assert_eq!(
+        on_ground.arg("datum_type"),
+        Some(&*SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32.to_string())
+    );
+
+    let atc_id = &calls[2];
+    assert_eq!(atc_id.arg("units_name"), Some("\"\""));
+    assert_eq!(
+        atc_id.arg("datum_type"),
+        Some(&*SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32.to_string())
+    );
+
+    assert_eq!(calls[5].arg("epsilon"), Some("2.0"));
+}
+
+#[test]
+fn decodes_object_data() {
+    let mut conn = MockBackend::connected();
+
+    let mut payload = 3500.5_f64.to_le_bytes().to_vec();
+    payload.extend_from_slice(&1_i32.to_le_bytes());
+    let mut atc_id = b"N172SP".to_vec();
+    atc_id.resize(32, 0);
+    payload.extend_from_slice(&atc_id);
+    payload.extend_from_slice(&0x7000_i32.to_le_bytes());
+    payload.extend_from_slice(&43_200_i64.to_le_bytes());
+    payload.extend_from_slice(&110.0_f32.to_le_bytes());
+    queue_data(&conn, &payload);
+
+    let aircraft: Aircraft = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => data.decode().unwrap(),
+        other => panic!("expected object data, got {:?}", other),
+    };
+
+    assert_eq!(
+        aircraft,
+        Aircraft {
+            altitude: 3500.5,
+            on_ground: true,
+            atc_id: "N172SP".to_string(),
+            transponder: 0x7000,
+            zulu_time: 43_200,
+            airspeed: 110.0,
+        }
+    );
+}
+
+#[test]
+fn short_payload_fails() {
+    let mut conn = MockBackend::connected();
+    queue_data(&conn, &3500.5_f64.to_le_bytes());
+
+    match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => assert!(matches!(
+            data.decode::<Aircraft>(),
+            Err(SimConnectError::PayloadOutOfBounds { offset: 8, .. })
+        )),
+        other => panic!("expected object data, got {:?}", other),
+    }
+}
+
+#[test]
+fn decodes_variable_length_strings() {
+    let mut conn = MockBackend::connected();
+    Title::add_definition(&conn, 1).unwrap();
+    let calls = conn.backend().calls_to("SimConnect_AddToDataDefinition");
+    assert_eq!(
+        calls[0].arg("datum_type"),
+        Some(&*SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV.to_string())
+    );
+
+    queue_data(&conn, b"Cessna Skyhawk\0Cessna\0");
+    let title: Title = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => data.decode().unwrap(),
+        other => panic!("expected object data, got {:?}", other),
+    };
+
+    assert_eq!(title.title, "Cessna Skyhawk");
+    assert_eq!(title.airline, "Cessna");
+}
+
+#[test]
+fn writes_fields_with_set_data() {
+    let conn = MockBackend::connected();
+    conn.define_data::<Aircraft>(2).unwrap();
+    let aircraft = Aircraft {
+        altitude: 3500.5,
+        on_ground: true,
+        atc_id: "N172SP".to_string(),
+        transponder: 0x7000,
+        zulu_time: 43_200,
+        airspeed: 110.0,
+    };
+
+    assert_eq!(
+        aircraft.values(),
+        vec![
+            Value::Float64(3500.5),
+            Value::Int32(1),
+            Value::String("N172SP".to_string()),
+            Value::Int32(0x7000),
+            Value::Int64(43_200),
+            Value::Float32(110.0),
+        ]
+    );
+
+    conn.set_data(SIMCONNECT_OBJECT_ID_USER, &aircraft).unwrap();
+    conn.set_data_tagged(2, SIMCONNECT_OBJECT_ID_USER, &[(5, Value::Float32(120.0))])
+        .unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_SetDataOnSimObject");
+    assert_eq!(calls[0].arg("unit_size"), Some("60"));
+    assert_eq!(calls[1].arg("unit_size"), Some("8"));
+}
+
+#[derive(Debug, Clone, Copy, ClientData)]
+#[repr(C)]
+struct Lights {
+    mask: u8,
+    #[client_data(epsilon = 0.01)]
+    brightness: f32,
+    label: [u8; 3],
+    count: i16,
+}
+
+#[test]
+fn derives_client_data_offsets() {
+    let fields = Lights::fields();
+
+    assert_eq!(
+        fields
+            .iter()
+            .map(|field| (field.offset, field.size))
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (4, 4), (8, 3), (12, 2)]
+    );
+    assert_eq!(fields[0].size_or_type, 1);
+    assert_eq!(fields[1].size_or_type, SIMCONNECT_CLIENTDATATYPE_FLOAT32);
+    assert_eq!(fields[1].epsilon, 0.01);
+    assert_eq!(fields[2].size_or_type, 3);
+    assert_eq!(fields[3].size_or_type, SIMCONNECT_CLIENTDATATYPE_INT16);
+
+    let conn = MockBackend::connected();
+    let area = ClientDataArea::<Lights>::create(&conn, "Test.Lights", 1, 1).unwrap();
+    assert_eq!(area.data_size(), 10);
+}
@@ -0,0 +1,624 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use crate::error::{check, E_FAIL};
+use crate::*;
+
+/// Invokes `$m!` with every SimConnect function a [`SimConnectBackend`] has to provide, apart
+/// from the ones with out parameters (`Open`, `Close`, `GetLastSentPacketID` and
+/// `GetNextDispatch`), which are declared by hand.
+///
+/// Arguments are the ones of the C function minus the handle, with strings passed as `&CStr`.
+/// Functions taking raw pointers are listed separately and become `unsafe fn`s.
+macro_rules! for_each_backend_function {
+    ($m:ident) => {
+        $m! {
+            safe {
+            fn add_to_data_definition => SimConnect_AddToDataDefinition(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                datum_name: &CStr,
+                units_name: &CStr,
+                datum_type: SIMCONNECT_DATATYPE,
+                epsilon: f32,
+                datum_id: DWORD,
+            );
+            fn set_system_event_state => SimConnect_SetSystemEventState(
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                state: SIMCONNECT_STATE,
+            );
+            fn remove_client_event => SimConnect_RemoveClientEvent(
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn clear_notification_group => SimConnect_ClearNotificationGroup(
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+            );
+            fn request_notification_group => SimConnect_RequestNotificationGroup(
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                reserved: DWORD,
+                flags: DWORD,
+            );
+            fn clear_data_definition => SimConnect_ClearDataDefinition(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+            );
+            fn create_client_data => SimConnect_CreateClientData(
+                client_data_id: SIMCONNECT_CLIENT_DATA_ID,
+                size: DWORD,
+                flags: SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+            );
+            fn request_data_on_sim_object_type => SimConnect_RequestDataOnSimObjectType(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                radius_meters: DWORD,
+                type_: SIMCONNECT_SIMOBJECT_TYPE,
+            );
+            fn remove_input_event => SimConnect_RemoveInputEvent(
+                group_id: SIMCONNECT_INPUT_GROUP_ID,
+                input_definition: &CStr,
+            );
+            fn clear_input_group => SimConnect_ClearInputGroup(group_id: SIMCONNECT_INPUT_GROUP_ID);
+            fn request_reserved_key => SimConnect_RequestReservedKey(
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                key_choice_1: &CStr,
+                key_choice_2: &CStr,
+                key_choice_3: &CStr,
+            );
+            fn unsubscribe_from_system_event => SimConnect_UnsubscribeFromSystemEvent(
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn weather_request_interpolated_observation => SimConnect_WeatherRequestInterpolatedObservation(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+            );
+            fn weather_request_observation_at_station => SimConnect_WeatherRequestObservationAtStation(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                icao: &CStr,
+            );
+            fn weather_request_observation_at_nearest_station => SimConnect_WeatherRequestObservationAtNearestStation(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+            );
+            fn weather_create_station => SimConnect_WeatherCreateStation(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                icao: &CStr,
+                name: &CStr,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+            );
+            fn weather_remove_station => SimConnect_WeatherRemoveStation(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                icao: &CStr,
+            );
+            fn weather_set_observation => SimConnect_WeatherSetObservation(
+                seconds: DWORD,
+                metar: &CStr,
+            );
+            fn weather_set_mode_server => SimConnect_WeatherSetModeServer(port: DWORD, seconds: DWORD);
+            fn weather_set_mode_theme => SimConnect_WeatherSetModeTheme(theme_name: &CStr);
+            fn weather_set_mode_global => SimConnect_WeatherSetModeGlobal();
+            fn weather_set_mode_custom => SimConnect_WeatherSetModeCustom();
+            fn weather_set_dynamic_update_rate => SimConnect_WeatherSetDynamicUpdateRate(rate: DWORD);
+            fn weather_request_cloud_state => SimConnect_WeatherRequestCloudState(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                min_lat: f32,
+                min_lon: f32,
+                min_alt: f32,
+                max_lat: f32,
+                max_lon: f32,
+                max_alt: f32,
+                flags: DWORD,
+            );
+            fn weather_create_thermal => SimConnect_WeatherCreateThermal(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+                radius: f32,
+                height: f32,
+                core_rate: f32,
+                core_turbulence: f32,
+                sink_rate: f32,
+                sink_turbulence: f32,
+                core_size: f32,
+                core_transition_size: f32,
+                sink_layer_size: f32,
+                sink_transition_size: f32,
+            );
+            fn weather_remove_thermal => SimConnect_WeatherRemoveThermal(object_id: SIMCONNECT_OBJECT_ID);
+            fn ai_create_parked_atc_aircraft => SimConnect_AICreateParkedATCAircraft(
+                container_title: &CStr,
+                tail_number: &CStr,
+                airport_id: &CStr,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_create_enroute_atc_aircraft => SimConnect_AICreateEnrouteATCAircraft(
+                container_title: &CStr,
+                tail_number: &CStr,
+                flight_number: c_int,
+                flight_plan_path: &CStr,
+                flight_plan_position: f64,
+                touch_and_go: BOOL,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_create_non_atc_aircraft => SimConnect_AICreateNonATCAircraft(
+                container_title: &CStr,
+                tail_number: &CStr,
+                init_pos: SIMCONNECT_DATA_INITPOSITION,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_create_simulated_object => SimConnect_AICreateSimulatedObject(
+                container_title: &CStr,
+                init_pos: SIMCONNECT_DATA_INITPOSITION,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_release_control => SimConnect_AIReleaseControl(
+                object_id: SIMCONNECT_OBJECT_ID,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_remove_object => SimConnect_AIRemoveObject(
+                object_id: SIMCONNECT_OBJECT_ID,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn ai_set_aircraft_flight_plan => SimConnect_AISetAircraftFlightPlan(
+                object_id: SIMCONNECT_OBJECT_ID,
+                flight_plan_path: &CStr,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn execute_mission_action => SimConnect_ExecuteMissionAction(instance_id: GUID);
+            fn complete_custom_mission_action => SimConnect_CompleteCustomMissionAction(
+                instance_id: GUID,
+            );
+            fn camera_set_relative_6dof => SimConnect_CameraSetRelative6DOF(
+                delta_x: f32,
+                delta_y: f32,
+                delta_z: f32,
+                pitch_deg: f32,
+                bank_deg: f32,
+                heading_deg: f32,
+            );
+            fn menu_add_item => SimConnect_MenuAddItem(
+                menu_item: &CStr,
+                menu_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                data: DWORD,
+            );
+            fn menu_delete_item => SimConnect_MenuDeleteItem(
+                menu_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn menu_delete_sub_item => SimConnect_MenuDeleteSubItem(
+                menu_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                sub_menu_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn request_system_state => SimConnect_RequestSystemState(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                state: &CStr,
+            );
+            fn map_client_data_name_to_id => SimConnect_MapClientDataNameToID(
+                client_data_name: &CStr,
+                client_data_id: SIMCONNECT_CLIENT_DATA_ID,
+            );
+            fn add_to_client_data_definition => SimConnect_AddToClientDataDefinition(
+                define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                offset: DWORD,
+                size_or_type: DWORD,
+                epsilon: f32,
+                datum_id: DWORD,
+            );
+            fn clear_client_data_definition => SimConnect_ClearClientDataDefinition(
+                define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+            );
+            fn request_client_data => SimConnect_RequestClientData(
+                client_data_id: SIMCONNECT_CLIENT_DATA_ID,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                period: SIMCONNECT_CLIENT_DATA_PERIOD,
+                flags: SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+                origin: DWORD,
+                interval: DWORD,
+                limit: DWORD,
+            );
+            fn flight_load => SimConnect_FlightLoad(file_name: &CStr);
+            fn subscribe_to_facilities => SimConnect_SubscribeToFacilities(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn unsubscribe_to_facilities => SimConnect_UnsubscribeToFacilities(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+            );
+            fn request_facilities_list => SimConnect_RequestFacilitiesList(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn request_data_on_sim_object => SimConnect_RequestDataOnSimObject(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                object_id: SIMCONNECT_OBJECT_ID,
+                period: SIMCONNECT_PERIOD,
+                flags: SIMCONNECT_DATA_REQUEST_FLAG,
+                origin: DWORD,
+                interval: DWORD,
+                limit: DWORD,
+            );
+            fn subscribe_to_system_event => SimConnect_SubscribeToSystemEvent(
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                system_event_name: &CStr,
+            );
+            fn map_client_event_to_sim_event => SimConnect_MapClientEventToSimEvent(
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                event_name: &CStr,
+            );
+            fn transmit_client_event => SimConnect_TransmitClientEvent(
+                object_id: SIMCONNECT_OBJECT_ID,
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                data: DWORD,
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                flags: SIMCONNECT_EVENT_FLAG,
+            );
+            fn add_client_event_to_notification_group => SimConnect_AddClientEventToNotificationGroup(
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                maskable: BOOL,
+            );
+            fn set_notification_group_priority => SimConnect_SetNotificationGroupPriority(
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                priority: DWORD,
+            );
+            fn map_input_event_to_client_event => SimConnect_MapInputEventToClientEvent(
+                group_id: SIMCONNECT_INPUT_GROUP_ID,
+                input_definition: &CStr,
+                down_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                down_value: DWORD,
+                up_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                up_value: DWORD,
+                maskable: BOOL,
+            );
+            fn set_input_group_state => SimConnect_SetInputGroupState(
+                group_id: SIMCONNECT_INPUT_GROUP_ID,
+                state: DWORD,
+            );
+            fn set_input_group_priority => SimConnect_SetInputGroupPriority(
+                group_id: SIMCONNECT_INPUT_GROUP_ID,
+                priority: DWORD,
+            );
+            fn transmit_client_event_ex1 => SimConnect_TransmitClientEvent_EX1(
+                object_id: SIMCONNECT_OBJECT_ID,
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                flags: SIMCONNECT_EVENT_FLAG,
+                data0: DWORD,
+                data1: DWORD,
+                data2: DWORD,
+                data3: DWORD,
+                data4: DWORD,
+            );
+            fn add_to_facility_definition => SimConnect_AddToFacilityDefinition(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                field_name: &CStr,
+            );
+            fn request_facility_data => SimConnect_RequestFacilityData(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                icao: &CStr,
+                region: Option<&CStr>,
+            );
+            fn subscribe_to_facilities_ex1 => SimConnect_SubscribeToFacilities_EX1(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                new_elem_in_range_request_id: SIMCONNECT_DATA_REQUEST_ID,
+                old_elem_out_range_request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn unsubscribe_to_facilities_ex1 => SimConnect_UnsubscribeToFacilities_EX1(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                unsubscribe_new_in_range: bool,
+                unsubscribe_old_out_range: bool,
+            );
+            fn request_facilities_list_ex1 => SimConnect_RequestFacilitiesList_EX1(
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn request_facility_data_ex1 => SimConnect_RequestFacilityData_EX1(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                icao: &CStr,
+                region: Option<&CStr>,
+                type_: c_char,
+            );
+            fn enumerate_controllers => SimConnect_EnumerateControllers();
+            fn map_input_event_to_client_event_ex1 => SimConnect_MapInputEventToClientEvent_EX1(
+                group_id: SIMCONNECT_INPUT_GROUP_ID,
+                input_definition: &CStr,
+                down_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                down_value: DWORD,
+                up_event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                up_value: DWORD,
+                maskable: BOOL,
+            );
+            fn enumerate_input_events => SimConnect_EnumerateInputEvents(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn get_input_event => SimConnect_GetInputEvent(
+                request_id: SIMCONNECT_DATA_REQUEST_ID,
+                hash: UINT64,
+            );
+            fn subscribe_input_event => SimConnect_SubscribeInputEvent(hash: UINT64);
+            fn unsubscribe_input_event => SimConnect_UnsubscribeInputEvent(hash: UINT64);
+            fn enumerate_input_event_params => SimConnect_EnumerateInputEventParams(hash: UINT64);
+            fn clear_all_facility_data_definition_filters => SimConnect_ClearAllFacilityDataDefinitionFilters(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+            );
+            }
+            unsafe {
+            fn call_dispatch => SimConnect_CallDispatch(
+                dispatch: DispatchProc,
+                context: *mut c_void,
+            );
+            fn request_response_times => SimConnect_RequestResponseTimes(
+                count: DWORD,
+                elapsed_seconds: *mut f32,
+            );
+            fn set_client_data => SimConnect_SetClientData(
+                client_data_id: SIMCONNECT_CLIENT_DATA_ID,
+                define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                flags: SIMCONNECT_CLIENT_DATA_SET_FLAG,
+                reserved: DWORD,
+                unit_size: DWORD,
+                data_set: *mut c_void,
+            );
+            fn text => SimConnect_Text(
+                type_: SIMCONNECT_TEXT_TYPE,
+                time_seconds: f32,
+                event_id: SIMCONNECT_CLIENT_EVENT_ID,
+                unit_size: DWORD,
+                data_set: *mut c_void,
+            );
+            fn set_data_on_sim_object => SimConnect_SetDataOnSimObject(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                object_id: SIMCONNECT_OBJECT_ID,
+                flags: SIMCONNECT_DATA_SET_FLAG,
+                array_count: DWORD,
+                unit_size: DWORD,
+                data_set: *mut c_void,
+            );
+            fn request_jetway_data => SimConnect_RequestJetwayData(
+                airport_icao: &CStr,
+                array_count: DWORD,
+                indexes: *mut c_int,
+            );
+            fn execute_action => SimConnect_ExecuteAction(
+                request_id: DWORD,
+                action_id: &CStr,
+                unit_size: DWORD,
+                param_values: *mut c_void,
+            );
+            fn set_input_event => SimConnect_SetInputEvent(
+                hash: UINT64,
+                unit_size: DWORD,
+                value: *mut c_void,
+            );
+            fn add_facility_data_definition_filter => SimConnect_AddFacilityDataDefinitionFilter(
+                define_id: SIMCONNECT_DATA_DEFINITION_ID,
+                filter_path: &CStr,
+                unit_size: DWORD,
+                filter_data: *mut c_void,
+            );
+            }
+        }
+    };
+}
+
+pub(crate) use for_each_backend_function;
+
+/// Calls the C function `$ffi`, either the linked one or, with the `dynamic` feature, the one
+/// loaded from the backend's DLL.
+#[cfg(not(feature = "dynamic"))]
+macro_rules! ffi {
+    ($backend:expr, $ffi:ident($($arg:expr),* $(,)?)) => {
+        $ffi($($arg),*)
+    };
+}
+
+#[cfg(feature = "dynamic")]
+macro_rules! ffi {
+    ($backend:expr, $ffi:ident($($arg:expr),* $(,)?)) => {
+        $backend.api()?.$ffi($($arg),*)?
+    };
+}
+
+macro_rules! declare_backend {
+    (
+        safe { $(fn $name:ident => $ffi:ident($($arg:ident: $ty:ty),* $(,)?);)* }
+        unsafe { $(fn $unsafe_name:ident => $unsafe_ffi:ident($($unsafe_arg:ident: $unsafe_ty:ty),* $(,)?);)* }
+    ) => {
+        /// The operations [`SimConnector`] performs against SimConnect
+        ///
+        /// Every method mirrors the `SimConnect_*` function of the same name, minus the handle.
+        /// [`FfiBackend`] calls into the SimConnect library, [`MockBackend`](crate::mock::MockBackend)
+        /// is a scriptable in-process stand-in for tests.
+        pub trait SimConnectBackend {
+            /// Opens the connection, `config_index` selects the section of `SimConnect.cfg`.
+            fn open(
+                &mut self,
+                name: &CStr,
+                config_index: DWORD,
+                event_handle: HANDLE,
+            ) -> Result<(), SimConnectError>;
+
+            fn close(&self) -> Result<(), SimConnectError>;
+
+            fn get_last_sent_packet_id(&self) -> Result<DWORD, SimConnectError>;
+
+            /// Returns the next message and its size, or a null pointer if there is none.
+            ///
+            /// The SimConnect library fails with `E_FAIL` on an empty queue, which has to be
+            /// reported as a null pointer as well.
+            ///
+            /// The message stays valid until the next call.
+            fn get_next_dispatch(&self) -> Result<(*mut SIMCONNECT_RECV, DWORD), SimConnectError>;
+
+            $(fn $name(&self, $($arg: $ty),*) -> Result<(), SimConnectError>;)*
+
+            $(unsafe fn $unsafe_name(&self, $($unsafe_arg: $unsafe_ty),*) -> Result<(), SimConnectError>;)*
+        }
+
+        impl SimConnectBackend for FfiBackend {
+            // The event handle is only handed to SimConnect, which signals it
+            #[allow(clippy::not_unsafe_ptr_arg_deref)]
+            fn open(
+                &mut self,
+                name: &CStr,
+                config_index: DWORD,
+                event_handle: HANDLE,
+            ) -> Result<(), SimConnectError> {
+                let mut handle: HANDLE = ptr::null_mut();
+
+                check("SimConnect_Open", unsafe {
+                    ffi!(self, SimConnect_Open(
+                        &mut handle,
+                        name.as_ptr(),
+                        ptr::null_mut(),
+                        0,
+                        event_handle,
+                        config_index,
+                    ))
+                })?;
+                self.handle = handle;
+
+                Ok(())
+            }
+
+            fn close(&self) -> Result<(), SimConnectError> {
+                check("SimConnect_Close", unsafe { ffi!(self, SimConnect_Close(self.handle)) })
+            }
+
+            fn get_last_sent_packet_id(&self) -> Result<DWORD, SimConnectError> {
+                let mut send_id: DWORD = 0;
+
+                check("SimConnect_GetLastSentPacketID", unsafe {
+                    ffi!(self, SimConnect_GetLastSentPacketID(self.handle, &mut send_id))
+                })?;
+
+                Ok(send_id)
+            }
+
+            fn get_next_dispatch(&self) -> Result<(*mut SIMCONNECT_RECV, DWORD), SimConnectError> {
+                let mut data: *mut SIMCONNECT_RECV = ptr::null_mut();
+                let mut size: DWORD = 0;
+
+                match check("SimConnect_GetNextDispatch", unsafe {
+                    ffi!(self, SimConnect_GetNextDispatch(self.handle, &mut data, &mut size))
+                }) {
+                    Err(error) if is_empty_queue(&error) => Ok((ptr::null_mut(), 0)),
+                    result => result.map(|()| (data, size)),
+                }
+            }
+
+            $(fn $name(&self, $($arg: $ty),*) -> Result<(), SimConnectError> {
+                check(stringify!($ffi), unsafe { ffi!(self, $ffi(self.handle, $($arg.into_ffi()),*)) })
+            })*
+
+            $(unsafe fn $unsafe_name(&self, $($unsafe_arg: $unsafe_ty),*) -> Result<(), SimConnectError> {
+                check(stringify!($unsafe_ffi), ffi!(self, $unsafe_ffi(self.handle, $($unsafe_arg.into_ffi()),*)))
+            })*
+        }
+    };
+}
+
+for_each_backend_function!(declare_backend);
+
+/// Whether `error` is the `E_FAIL` that `SimConnect_GetNextDispatch` returns on an empty queue.
+pub(crate) fn is_empty_queue(error: &SimConnectError) -> bool {
+    matches!(
+        error,
+        SimConnectError::CallFailed {
+            call: "SimConnect_GetNextDispatch",
+            hresult: E_FAIL,
+        }
+    )
+}
+
+/// [`SimConnectBackend`] calling the `SimConnect_*` functions of the SimConnect library
+///
+/// With the `dynamic` feature the library is loaded on first use from the DLL chosen with
+/// `FfiBackend::with_dll`, instead of being linked.
+#[derive(Debug)]
+pub struct FfiBackend {
+    pub(crate) handle: HANDLE,
+    #[cfg(feature = "dynamic")]
+    pub(crate) dll: SimulatorDll,
+    #[cfg(feature = "dynamic")]
+    pub(crate) api: std::cell::OnceCell<crate::dynamic::Api>,
+}
+
+impl Default for FfiBackend {
+    fn default() -> Self {
+        Self {
+            handle: ptr::null_mut(),
+            #[cfg(feature = "dynamic")]
+            dll: SimulatorDll::default(),
+            #[cfg(feature = "dynamic")]
+            api: std::cell::OnceCell::new(),
+        }
+    }
+}
+
+impl FfiBackend {
+    /// The raw `SimConnect` handle, null until [`SimConnectBackend::open`] succeeded.
+    pub fn handle(&self) -> HANDLE {
+        self.handle
+    }
+}
+
+/// Conversion of a backend argument to the type the C function expects.
+trait IntoFfi {
+    type Ffi;
+
+    fn into_ffi(self) -> Self::Ffi;
+}
+
+macro_rules! into_ffi_identity {
+    ($($ty:ty),*) => {
+        $(impl IntoFfi for $ty {
+            type Ffi = Self;
+
+            fn into_ffi(self) -> Self {
+                self
+            }
+        })*
+    };
+}
+
+into_ffi_identity!(
+    bool,
+    i8,
+    u8,
+    i32,
+    u32,
+    u64,
+    f32,
+    f64,
+    GUID,
+    SIMCONNECT_DATA_INITPOSITION,
+    DispatchProc,
+    *mut c_void,
+    *mut c_int,
+    *mut f32
+);
+
+impl IntoFfi for &CStr {
+    type Ffi = *const c_char;
+
+    fn into_ffi(self) -> *const c_char {
+        self.as_ptr()
+    }
+}
+
+impl IntoFfi for Option<&CStr> {
+    type Ffi = *const c_char;
+
+    fn into_ffi(self) -> *const c_char {
+        self.map_or(ptr::null(), CStr::as_ptr)
+    }
+}
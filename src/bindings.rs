@@ -0,0 +1,823 @@
+/* Pregenerated SimConnect bindings.
+ *
+ * Mirrors what rust-bindgen produces from `libsrc/include/SimConnect.hpp`
+ * for `x86_64-pc-windows-msvc` with the allowlist in `build.rs`, minus the function
+ * declarations and the layout tests. Windows integer typedefs are pinned to their
+ * fixed-width equivalents so the struct layouts are identical on every host.
+ *
+ * Used instead of running bindgen when the crate is built without the SimConnect import
+ * library (see `build.rs`). Regenerate when the header changes.
+ */
+
+pub type BYTE = u8;
+pub type DWORD = u32;
+pub type LONG = i32;
+pub type BOOL = ::std::os::raw::c_int;
+pub type UINT64 = u64;
+pub type HRESULT = LONG;
+pub type LPCSTR = *const ::std::os::raw::c_char;
+pub type HANDLE = *mut ::std::os::raw::c_void;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct HWND__ {
+    pub unused: ::std::os::raw::c_int,
+}
+pub type HWND = *mut HWND__;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _GUID {
+    pub Data1: DWORD,
+    pub Data2: ::std::os::raw::c_ushort,
+    pub Data3: ::std::os::raw::c_ushort,
+    pub Data4: [::std::os::raw::c_uchar; 8usize],
+}
+pub type GUID = _GUID;
+pub type SIMCONNECT_OBJECT_ID = DWORD;
+pub const SIMCONNECT_UNUSED: DWORD = 4294967295;
+pub const SIMCONNECT_OBJECT_ID_USER: DWORD = 0;
+pub const SIMCONNECT_CAMERA_IGNORE_FIELD: f32 = 340282346638528860000000000000000000000.0;
+pub const SIMCONNECT_CLIENTDATA_MAX_SIZE: DWORD = 8192;
+pub const SIMCONNECT_GROUP_PRIORITY_HIGHEST: DWORD = 1;
+pub const SIMCONNECT_GROUP_PRIORITY_HIGHEST_MASKABLE: DWORD = 10000000;
+pub const SIMCONNECT_GROUP_PRIORITY_STANDARD: DWORD = 1900000000;
+pub const SIMCONNECT_GROUP_PRIORITY_DEFAULT: DWORD = 2000000000;
+pub const SIMCONNECT_GROUP_PRIORITY_LOWEST: DWORD = 4000000000;
+pub const MAX_METAR_LENGTH: DWORD = 2000;
+pub const MAX_THERMAL_SIZE: f32 = 100000.0;
+pub const MAX_THERMAL_RATE: f32 = 1000.0;
+pub const INITPOSITION_AIRSPEED_CRUISE: DWORD = 4294967295;
+pub const INITPOSITION_AIRSPEED_KEEP: DWORD = 4294967294;
+pub const SIMCONNECT_CLIENTDATATYPE_INT8: DWORD = 4294967295;
+pub const SIMCONNECT_CLIENTDATATYPE_INT16: DWORD = 4294967294;
+pub const SIMCONNECT_CLIENTDATATYPE_INT32: DWORD = 4294967293;
+pub const SIMCONNECT_CLIENTDATATYPE_INT64: DWORD = 4294967292;
+pub const SIMCONNECT_CLIENTDATATYPE_FLOAT32: DWORD = 4294967291;
+pub const SIMCONNECT_CLIENTDATATYPE_FLOAT64: DWORD = 4294967290;
+pub const SIMCONNECT_CLIENTDATAOFFSET_AUTO: DWORD = 4294967295;
+pub const SIMCONNECT_OPEN_CONFIGINDEX_LOCAL: DWORD = 4294967295;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NULL: SIMCONNECT_RECV_ID = 0;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION: SIMCONNECT_RECV_ID = 1;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN: SIMCONNECT_RECV_ID = 2;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT: SIMCONNECT_RECV_ID = 3;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT: SIMCONNECT_RECV_ID = 4;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE: SIMCONNECT_RECV_ID = 5;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME: SIMCONNECT_RECV_ID = 6;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FRAME: SIMCONNECT_RECV_ID = 7;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA: SIMCONNECT_RECV_ID = 8;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE: SIMCONNECT_RECV_ID = 9;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WEATHER_OBSERVATION: SIMCONNECT_RECV_ID = 10;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLOUD_STATE: SIMCONNECT_RECV_ID = 11;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ASSIGNED_OBJECT_ID: SIMCONNECT_RECV_ID = 12;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_RESERVED_KEY: SIMCONNECT_RECV_ID = 13;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CUSTOM_ACTION: SIMCONNECT_RECV_ID = 14;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SYSTEM_STATE: SIMCONNECT_RECV_ID = 15;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA: SIMCONNECT_RECV_ID = 16;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_WEATHER_MODE: SIMCONNECT_RECV_ID = 17;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST: SIMCONNECT_RECV_ID = 18;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST: SIMCONNECT_RECV_ID = 19;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST: SIMCONNECT_RECV_ID = 20;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST: SIMCONNECT_RECV_ID = 21;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SERVER_STARTED:
+    SIMCONNECT_RECV_ID = 22;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_CLIENT_STARTED:
+    SIMCONNECT_RECV_ID = 23;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SESSION_ENDED:
+    SIMCONNECT_RECV_ID = 24;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_END: SIMCONNECT_RECV_ID = 25;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_LAP: SIMCONNECT_RECV_ID = 26;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_EX1: SIMCONNECT_RECV_ID = 27;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA: SIMCONNECT_RECV_ID = 28;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA_END: SIMCONNECT_RECV_ID = 29;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_MINIMAL_LIST: SIMCONNECT_RECV_ID = 30;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_JETWAY_DATA: SIMCONNECT_RECV_ID = 31;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CONTROLLERS_LIST: SIMCONNECT_RECV_ID = 32;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ACTION_CALLBACK: SIMCONNECT_RECV_ID = 33;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENTS: SIMCONNECT_RECV_ID = 34;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_GET_INPUT_EVENT: SIMCONNECT_RECV_ID = 35;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SUBSCRIBE_INPUT_EVENT: SIMCONNECT_RECV_ID = 36;
+pub const SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS: SIMCONNECT_RECV_ID =
+    37;
+pub type SIMCONNECT_RECV_ID = ::std::os::raw::c_int;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INVALID: SIMCONNECT_DATATYPE = 0;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32: SIMCONNECT_DATATYPE = 1;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64: SIMCONNECT_DATATYPE = 2;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32: SIMCONNECT_DATATYPE = 3;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64: SIMCONNECT_DATATYPE = 4;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8: SIMCONNECT_DATATYPE = 5;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32: SIMCONNECT_DATATYPE = 6;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING64: SIMCONNECT_DATATYPE = 7;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING128: SIMCONNECT_DATATYPE = 8;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256: SIMCONNECT_DATATYPE = 9;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING260: SIMCONNECT_DATATYPE = 10;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV: SIMCONNECT_DATATYPE = 11;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INITPOSITION: SIMCONNECT_DATATYPE = 12;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_MARKERSTATE: SIMCONNECT_DATATYPE = 13;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_WAYPOINT: SIMCONNECT_DATATYPE = 14;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT: SIMCONNECT_DATATYPE = 15;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_XYZ: SIMCONNECT_DATATYPE = 16;
+pub const SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_MAX: SIMCONNECT_DATATYPE = 17;
+pub type SIMCONNECT_DATATYPE = ::std::os::raw::c_int;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NONE: SIMCONNECT_EXCEPTION = 0;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ERROR: SIMCONNECT_EXCEPTION = 1;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SIZE_MISMATCH: SIMCONNECT_EXCEPTION = 2;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNRECOGNIZED_ID: SIMCONNECT_EXCEPTION = 3;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNOPENED: SIMCONNECT_EXCEPTION = 4;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_VERSION_MISMATCH: SIMCONNECT_EXCEPTION = 5;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_GROUPS: SIMCONNECT_EXCEPTION = 6;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED: SIMCONNECT_EXCEPTION = 7;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_EVENT_NAMES: SIMCONNECT_EXCEPTION = 8;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_EVENT_ID_DUPLICATE: SIMCONNECT_EXCEPTION = 9;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_MAPS: SIMCONNECT_EXCEPTION = 10;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_OBJECTS: SIMCONNECT_EXCEPTION = 11;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_REQUESTS: SIMCONNECT_EXCEPTION = 12;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_PORT: SIMCONNECT_EXCEPTION = 13;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_METAR: SIMCONNECT_EXCEPTION =
+    14;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_GET_OBSERVATION:
+    SIMCONNECT_EXCEPTION = 15;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_CREATE_STATION:
+    SIMCONNECT_EXCEPTION = 16;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_REMOVE_STATION:
+    SIMCONNECT_EXCEPTION = 17;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_TYPE: SIMCONNECT_EXCEPTION = 18;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_SIZE: SIMCONNECT_EXCEPTION = 19;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATA_ERROR: SIMCONNECT_EXCEPTION = 20;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ARRAY: SIMCONNECT_EXCEPTION = 21;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_CREATE_OBJECT_FAILED: SIMCONNECT_EXCEPTION = 22;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_LOAD_FLIGHTPLAN_FAILED: SIMCONNECT_EXCEPTION =
+    23;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OPERATION_INVALID_FOR_OBJECT_TYPE:
+    SIMCONNECT_EXCEPTION = 24;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ILLEGAL_OPERATION: SIMCONNECT_EXCEPTION = 25;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_SUBSCRIBED: SIMCONNECT_EXCEPTION = 26;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ENUM: SIMCONNECT_EXCEPTION = 27;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DEFINITION_ERROR: SIMCONNECT_EXCEPTION = 28;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DUPLICATE_ID: SIMCONNECT_EXCEPTION = 29;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATUM_ID: SIMCONNECT_EXCEPTION = 30;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OUT_OF_BOUNDS: SIMCONNECT_EXCEPTION = 31;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_CREATED: SIMCONNECT_EXCEPTION = 32;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_OUTSIDE_REALITY_BUBBLE:
+    SIMCONNECT_EXCEPTION = 33;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_CONTAINER: SIMCONNECT_EXCEPTION = 34;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_AI: SIMCONNECT_EXCEPTION = 35;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_ATC: SIMCONNECT_EXCEPTION = 36;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_SCHEDULE: SIMCONNECT_EXCEPTION = 37;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_JETWAY_DATA: SIMCONNECT_EXCEPTION = 38;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ACTION_NOT_FOUND: SIMCONNECT_EXCEPTION = 39;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NOT_AN_ACTION: SIMCONNECT_EXCEPTION = 40;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INCORRECT_ACTION_PARAMS: SIMCONNECT_EXCEPTION =
+    41;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_GET_INPUT_EVENT_FAILED: SIMCONNECT_EXCEPTION =
+    42;
+pub const SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SET_INPUT_EVENT_FAILED: SIMCONNECT_EXCEPTION =
+    43;
+pub type SIMCONNECT_EXCEPTION = ::std::os::raw::c_int;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_USER: SIMCONNECT_SIMOBJECT_TYPE = 0;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_ALL: SIMCONNECT_SIMOBJECT_TYPE = 1;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_AIRCRAFT: SIMCONNECT_SIMOBJECT_TYPE =
+    2;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_HELICOPTER:
+    SIMCONNECT_SIMOBJECT_TYPE = 3;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_BOAT: SIMCONNECT_SIMOBJECT_TYPE = 4;
+pub const SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_GROUND: SIMCONNECT_SIMOBJECT_TYPE = 5;
+pub type SIMCONNECT_SIMOBJECT_TYPE = ::std::os::raw::c_int;
+pub const SIMCONNECT_STATE_SIMCONNECT_STATE_OFF: SIMCONNECT_STATE = 0;
+pub const SIMCONNECT_STATE_SIMCONNECT_STATE_ON: SIMCONNECT_STATE = 1;
+pub type SIMCONNECT_STATE = ::std::os::raw::c_int;
+pub const SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_NEVER: SIMCONNECT_PERIOD = 0;
+pub const SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_ONCE: SIMCONNECT_PERIOD = 1;
+pub const SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_VISUAL_FRAME: SIMCONNECT_PERIOD = 2;
+pub const SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SIM_FRAME: SIMCONNECT_PERIOD = 3;
+pub const SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SECOND: SIMCONNECT_PERIOD = 4;
+pub type SIMCONNECT_PERIOD = ::std::os::raw::c_int;
+pub const SIMCONNECT_MISSION_END_SIMCONNECT_MISSION_FAILED: SIMCONNECT_MISSION_END = 0;
+pub const SIMCONNECT_MISSION_END_SIMCONNECT_MISSION_CRASHED: SIMCONNECT_MISSION_END = 1;
+pub const SIMCONNECT_MISSION_END_SIMCONNECT_MISSION_SUCCEEDED: SIMCONNECT_MISSION_END = 2;
+pub type SIMCONNECT_MISSION_END = ::std::os::raw::c_int;
+pub const SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER:
+    SIMCONNECT_CLIENT_DATA_PERIOD = 0;
+pub const SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ONCE:
+    SIMCONNECT_CLIENT_DATA_PERIOD = 1;
+pub const SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_VISUAL_FRAME:
+    SIMCONNECT_CLIENT_DATA_PERIOD = 2;
+pub const SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ON_SET:
+    SIMCONNECT_CLIENT_DATA_PERIOD = 3;
+pub const SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_SECOND:
+    SIMCONNECT_CLIENT_DATA_PERIOD = 4;
+pub type SIMCONNECT_CLIENT_DATA_PERIOD = ::std::os::raw::c_int;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_BLACK: SIMCONNECT_TEXT_TYPE = 0;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_WHITE: SIMCONNECT_TEXT_TYPE = 1;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_RED: SIMCONNECT_TEXT_TYPE = 2;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_GREEN: SIMCONNECT_TEXT_TYPE = 3;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_BLUE: SIMCONNECT_TEXT_TYPE = 4;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_YELLOW: SIMCONNECT_TEXT_TYPE = 5;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_MAGENTA: SIMCONNECT_TEXT_TYPE = 6;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_SCROLL_CYAN: SIMCONNECT_TEXT_TYPE = 7;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_BLACK: SIMCONNECT_TEXT_TYPE = 256;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_WHITE: SIMCONNECT_TEXT_TYPE = 257;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_RED: SIMCONNECT_TEXT_TYPE = 258;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_GREEN: SIMCONNECT_TEXT_TYPE = 259;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_BLUE: SIMCONNECT_TEXT_TYPE = 260;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_YELLOW: SIMCONNECT_TEXT_TYPE = 261;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_MAGENTA: SIMCONNECT_TEXT_TYPE = 262;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_PRINT_CYAN: SIMCONNECT_TEXT_TYPE = 263;
+pub const SIMCONNECT_TEXT_TYPE_SIMCONNECT_TEXT_TYPE_MENU: SIMCONNECT_TEXT_TYPE = 512;
+pub type SIMCONNECT_TEXT_TYPE = ::std::os::raw::c_int;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_1: SIMCONNECT_TEXT_RESULT = 0;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_2: SIMCONNECT_TEXT_RESULT = 1;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_3: SIMCONNECT_TEXT_RESULT = 2;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_4: SIMCONNECT_TEXT_RESULT = 3;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_5: SIMCONNECT_TEXT_RESULT = 4;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_6: SIMCONNECT_TEXT_RESULT = 5;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_7: SIMCONNECT_TEXT_RESULT = 6;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_8: SIMCONNECT_TEXT_RESULT = 7;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_9: SIMCONNECT_TEXT_RESULT = 8;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_MENU_SELECT_10: SIMCONNECT_TEXT_RESULT = 9;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_DISPLAYED: SIMCONNECT_TEXT_RESULT = 65536;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_QUEUED: SIMCONNECT_TEXT_RESULT = 65537;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_REMOVED: SIMCONNECT_TEXT_RESULT = 65538;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_REPLACED: SIMCONNECT_TEXT_RESULT = 65539;
+pub const SIMCONNECT_TEXT_RESULT_SIMCONNECT_TEXT_RESULT_TIMEOUT: SIMCONNECT_TEXT_RESULT = 65540;
+pub type SIMCONNECT_TEXT_RESULT = ::std::os::raw::c_int;
+pub const SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_THEME: SIMCONNECT_WEATHER_MODE = 0;
+pub const SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_RWW: SIMCONNECT_WEATHER_MODE = 1;
+pub const SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_CUSTOM: SIMCONNECT_WEATHER_MODE = 2;
+pub const SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_GLOBAL: SIMCONNECT_WEATHER_MODE = 3;
+pub type SIMCONNECT_WEATHER_MODE = ::std::os::raw::c_int;
+pub const SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_AIRPORT:
+    SIMCONNECT_FACILITY_LIST_TYPE = 0;
+pub const SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_WAYPOINT:
+    SIMCONNECT_FACILITY_LIST_TYPE = 1;
+pub const SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_NDB:
+    SIMCONNECT_FACILITY_LIST_TYPE = 2;
+pub const SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_VOR:
+    SIMCONNECT_FACILITY_LIST_TYPE = 3;
+pub const SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_COUNT:
+    SIMCONNECT_FACILITY_LIST_TYPE = 4;
+pub type SIMCONNECT_FACILITY_LIST_TYPE = ::std::os::raw::c_int;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_AIRPORT:
+    SIMCONNECT_FACILITY_DATA_TYPE = 0;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_RUNWAY:
+    SIMCONNECT_FACILITY_DATA_TYPE = 1;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_START:
+    SIMCONNECT_FACILITY_DATA_TYPE = 2;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_FREQUENCY:
+    SIMCONNECT_FACILITY_DATA_TYPE = 3;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_HELIPAD:
+    SIMCONNECT_FACILITY_DATA_TYPE = 4;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH:
+    SIMCONNECT_FACILITY_DATA_TYPE = 5;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH_TRANSITION:
+    SIMCONNECT_FACILITY_DATA_TYPE = 6;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH_LEG:
+    SIMCONNECT_FACILITY_DATA_TYPE = 7;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_FINAL_APPROACH_LEG:
+    SIMCONNECT_FACILITY_DATA_TYPE = 8;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_MISSED_APPROACH_LEG:
+    SIMCONNECT_FACILITY_DATA_TYPE = 9;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_DEPARTURE:
+    SIMCONNECT_FACILITY_DATA_TYPE = 10;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_ARRIVAL:
+    SIMCONNECT_FACILITY_DATA_TYPE = 11;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_RUNWAY_TRANSITION:
+    SIMCONNECT_FACILITY_DATA_TYPE = 12;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_ENROUTE_TRANSITION:
+    SIMCONNECT_FACILITY_DATA_TYPE = 13;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_TAXI_POINT:
+    SIMCONNECT_FACILITY_DATA_TYPE = 14;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_TAXI_PARKING:
+    SIMCONNECT_FACILITY_DATA_TYPE = 15;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_TAXI_PATH:
+    SIMCONNECT_FACILITY_DATA_TYPE = 16;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_TAXI_NAME:
+    SIMCONNECT_FACILITY_DATA_TYPE = 17;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_JETWAY:
+    SIMCONNECT_FACILITY_DATA_TYPE = 18;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_VOR:
+    SIMCONNECT_FACILITY_DATA_TYPE = 19;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_NDB:
+    SIMCONNECT_FACILITY_DATA_TYPE = 20;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_WAYPOINT:
+    SIMCONNECT_FACILITY_DATA_TYPE = 21;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_ROUTE:
+    SIMCONNECT_FACILITY_DATA_TYPE = 22;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_PAVEMENT:
+    SIMCONNECT_FACILITY_DATA_TYPE = 23;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH_LIGHTS:
+    SIMCONNECT_FACILITY_DATA_TYPE = 24;
+pub const SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_VASI:
+    SIMCONNECT_FACILITY_DATA_TYPE = 25;
+pub type SIMCONNECT_FACILITY_DATA_TYPE = ::std::os::raw::c_int;
+pub const SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_DOUBLE:
+    SIMCONNECT_INPUT_EVENT_TYPE = 0;
+pub const SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_STRING:
+    SIMCONNECT_INPUT_EVENT_TYPE = 1;
+pub type SIMCONNECT_INPUT_EVENT_TYPE = DWORD;
+pub type SIMCONNECT_VOR_FLAGS = DWORD;
+pub const SIMCONNECT_RECV_ID_VOR_LIST_HAS_NAV_SIGNAL: DWORD = 1;
+pub const SIMCONNECT_RECV_ID_VOR_LIST_HAS_LOCALIZER: DWORD = 2;
+pub const SIMCONNECT_RECV_ID_VOR_LIST_HAS_GLIDE_SLOPE: DWORD = 4;
+pub const SIMCONNECT_RECV_ID_VOR_LIST_HAS_DME: DWORD = 8;
+pub type SIMCONNECT_WAYPOINT_FLAGS = DWORD;
+pub const SIMCONNECT_WAYPOINT_NONE: DWORD = 0;
+pub const SIMCONNECT_WAYPOINT_SPEED_REQUESTED: DWORD = 4;
+pub const SIMCONNECT_WAYPOINT_THROTTLE_REQUESTED: DWORD = 8;
+pub const SIMCONNECT_WAYPOINT_COMPUTE_VERTICAL_SPEED: DWORD = 16;
+pub const SIMCONNECT_WAYPOINT_ALTITUDE_IS_AGL: DWORD = 32;
+pub const SIMCONNECT_WAYPOINT_ON_GROUND: DWORD = 1048576;
+pub const SIMCONNECT_WAYPOINT_REVERSE: DWORD = 2097152;
+pub const SIMCONNECT_WAYPOINT_WRAP_TO_FIRST: DWORD = 4194304;
+pub const SIMCONNECT_WAYPOINT_ALWAYS_BACKUP: DWORD = 8388608;
+pub const SIMCONNECT_WAYPOINT_KEEP_LAST_HEADING: DWORD = 16777216;
+pub const SIMCONNECT_WAYPOINT_YIELD_TO_USER: DWORD = 33554432;
+pub const SIMCONNECT_WAYPOINT_CAN_REVERSE: DWORD = 67108864;
+pub type SIMCONNECT_EVENT_FLAG = DWORD;
+pub const SIMCONNECT_EVENT_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_EVENT_FLAG_FAST_REPEAT_TIMER: DWORD = 1;
+pub const SIMCONNECT_EVENT_FLAG_SLOW_REPEAT_TIMER: DWORD = 2;
+pub const SIMCONNECT_EVENT_FLAG_GROUPID_IS_PRIORITY: DWORD = 16;
+pub type SIMCONNECT_DATA_REQUEST_FLAG = DWORD;
+pub const SIMCONNECT_DATA_REQUEST_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_DATA_REQUEST_FLAG_CHANGED: DWORD = 1;
+pub const SIMCONNECT_DATA_REQUEST_FLAG_TAGGED: DWORD = 2;
+pub type SIMCONNECT_DATA_SET_FLAG = DWORD;
+pub const SIMCONNECT_DATA_SET_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_DATA_SET_FLAG_TAGGED: DWORD = 1;
+pub type SIMCONNECT_CREATE_CLIENT_DATA_FLAG = DWORD;
+pub const SIMCONNECT_CREATE_CLIENT_DATA_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY: DWORD = 1;
+pub type SIMCONNECT_CLIENT_DATA_REQUEST_FLAG = DWORD;
+pub const SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED: DWORD = 1;
+pub const SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_TAGGED: DWORD = 2;
+pub type SIMCONNECT_CLIENT_DATA_SET_FLAG = DWORD;
+pub const SIMCONNECT_CLIENT_DATA_SET_FLAG_DEFAULT: DWORD = 0;
+pub const SIMCONNECT_CLIENT_DATA_SET_FLAG_TAGGED: DWORD = 1;
+pub type SIMCONNECT_VIEW_SYSTEM_EVENT_DATA = DWORD;
+pub const SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_2D: DWORD = 1;
+pub const SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_VIRTUAL: DWORD = 2;
+pub const SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_ORTHOGONAL: DWORD = 4;
+pub type SIMCONNECT_SOUND_SYSTEM_EVENT_DATA = DWORD;
+pub const SIMCONNECT_SOUND_SYSTEM_EVENT_DATA_MASTER: DWORD = 1;
+pub type SIMCONNECT_NOTIFICATION_GROUP_ID = DWORD;
+pub type SIMCONNECT_INPUT_GROUP_ID = DWORD;
+pub type SIMCONNECT_DATA_DEFINITION_ID = DWORD;
+pub type SIMCONNECT_DATA_REQUEST_ID = DWORD;
+pub type SIMCONNECT_CLIENT_EVENT_ID = DWORD;
+pub type SIMCONNECT_CLIENT_DATA_ID = DWORD;
+pub type SIMCONNECT_CLIENT_DATA_DEFINITION_ID = DWORD;
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV {
+    pub dwSize: DWORD,
+    pub dwVersion: DWORD,
+    pub dwID: DWORD,
+}
+pub const SIMCONNECT_RECV_EXCEPTION_UNKNOWN_SENDID: DWORD = 0;
+pub const SIMCONNECT_RECV_EXCEPTION_UNKNOWN_INDEX: DWORD = 4294967295;
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EXCEPTION {
+    pub _base: SIMCONNECT_RECV,
+    pub dwException: DWORD,
+    pub dwSendID: DWORD,
+    pub dwIndex: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_OPEN {
+    pub _base: SIMCONNECT_RECV,
+    pub szApplicationName: [::std::os::raw::c_char; 256usize],
+    pub dwApplicationVersionMajor: DWORD,
+    pub dwApplicationVersionMinor: DWORD,
+    pub dwApplicationBuildMajor: DWORD,
+    pub dwApplicationBuildMinor: DWORD,
+    pub dwSimConnectVersionMajor: DWORD,
+    pub dwSimConnectVersionMinor: DWORD,
+    pub dwSimConnectBuildMajor: DWORD,
+    pub dwSimConnectBuildMinor: DWORD,
+    pub dwReserved1: DWORD,
+    pub dwReserved2: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_QUIT {
+    pub _base: SIMCONNECT_RECV,
+}
+pub const SIMCONNECT_RECV_EVENT_UNKNOWN_GROUP: DWORD = 4294967295;
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT {
+    pub _base: SIMCONNECT_RECV,
+    pub uGroupID: DWORD,
+    pub uEventID: DWORD,
+    pub dwData: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_FILENAME {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub szFileName: [::std::os::raw::c_char; 260usize],
+    pub dwFlags: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub eObjType: SIMCONNECT_SIMOBJECT_TYPE,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_FRAME {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub fFrameRate: f32,
+    pub fSimSpeed: f32,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_MULTIPLAYER_SERVER_STARTED {
+    pub _base: SIMCONNECT_RECV_EVENT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_MULTIPLAYER_CLIENT_STARTED {
+    pub _base: SIMCONNECT_RECV_EVENT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_MULTIPLAYER_SESSION_ENDED {
+    pub _base: SIMCONNECT_RECV_EVENT,
+}
+pub const SIMCONNECT_RECV_EVENT_EX1_UNKNOWN_GROUP: DWORD = 4294967295;
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_EX1 {
+    pub _base: SIMCONNECT_RECV,
+    pub uGroupID: DWORD,
+    pub uEventID: DWORD,
+    pub dwData0: DWORD,
+    pub dwData1: DWORD,
+    pub dwData2: DWORD,
+    pub dwData3: DWORD,
+    pub dwData4: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_RACE_RESULT {
+    pub dwNumberOfRacers: DWORD,
+    pub MissionGUID: GUID,
+    pub szPlayerName: [::std::os::raw::c_char; 260usize],
+    pub szSessionType: [::std::os::raw::c_char; 260usize],
+    pub szAircraft: [::std::os::raw::c_char; 260usize],
+    pub szPlayerRole: [::std::os::raw::c_char; 260usize],
+    pub fTotalTime: f64,
+    pub fPenaltyTime: f64,
+    pub dwIsDisqualified: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_RACE_END {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub dwRacerNumber: DWORD,
+    pub RacerData: SIMCONNECT_DATA_RACE_RESULT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_RACE_LAP {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub dwLapIndex: DWORD,
+    pub RacerData: SIMCONNECT_DATA_RACE_RESULT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_SIMOBJECT_DATA {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwObjectID: DWORD,
+    pub dwDefineID: DWORD,
+    pub dwFlags: DWORD,
+    pub dwentrynumber: DWORD,
+    pub dwoutof: DWORD,
+    pub dwDefineCount: DWORD,
+    pub dwData: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE {
+    pub _base: SIMCONNECT_RECV_SIMOBJECT_DATA,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_CLIENT_DATA {
+    pub _base: SIMCONNECT_RECV_SIMOBJECT_DATA,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_WEATHER_OBSERVATION {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub szMetar: [::std::os::raw::c_char; 1usize],
+}
+pub const SIMCONNECT_CLOUD_STATE_ARRAY_WIDTH: ::std::os::raw::c_int = 64;
+pub const SIMCONNECT_CLOUD_STATE_ARRAY_SIZE: ::std::os::raw::c_int = 4096;
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_CLOUD_STATE {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwArraySize: DWORD,
+    pub rgbData: [BYTE; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_ASSIGNED_OBJECT_ID {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwObjectID: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_RESERVED_KEY {
+    pub _base: SIMCONNECT_RECV,
+    pub szChoiceReserved: [::std::os::raw::c_char; 30usize],
+    pub szReservedKey: [::std::os::raw::c_char; 50usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_SYSTEM_STATE {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwInteger: DWORD,
+    pub fFloat: f32,
+    pub szString: [::std::os::raw::c_char; 260usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_CUSTOM_ACTION {
+    pub _base: SIMCONNECT_RECV_EVENT,
+    pub guidInstanceId: GUID,
+    pub dwWaitForCompletion: DWORD,
+    pub szPayLoad: [::std::os::raw::c_char; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_EVENT_WEATHER_MODE {
+    pub _base: SIMCONNECT_RECV_EVENT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_FACILITIES_LIST {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwArraySize: DWORD,
+    pub dwEntryNumber: DWORD,
+    pub dwOutOf: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_LIST_TEMPLATE {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub dwArraySize: DWORD,
+    pub dwEntryNumber: DWORD,
+    pub dwOutOf: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_FACILITY_AIRPORT {
+    pub Ident: [::std::os::raw::c_char; 6usize],
+    pub Region: [::std::os::raw::c_char; 3usize],
+    pub Latitude: f64,
+    pub Longitude: f64,
+    pub Altitude: f64,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_AIRPORT_LIST {
+    pub _base: SIMCONNECT_RECV_FACILITIES_LIST,
+    pub rgData: [SIMCONNECT_DATA_FACILITY_AIRPORT; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_FACILITY_WAYPOINT {
+    pub _base: SIMCONNECT_DATA_FACILITY_AIRPORT,
+    pub fMagVar: f32,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_WAYPOINT_LIST {
+    pub _base: SIMCONNECT_RECV_FACILITIES_LIST,
+    pub rgData: [SIMCONNECT_DATA_FACILITY_WAYPOINT; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_FACILITY_NDB {
+    pub _base: SIMCONNECT_DATA_FACILITY_WAYPOINT,
+    pub fFrequency: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_NDB_LIST {
+    pub _base: SIMCONNECT_RECV_FACILITIES_LIST,
+    pub rgData: [SIMCONNECT_DATA_FACILITY_NDB; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_FACILITY_VOR {
+    pub _base: SIMCONNECT_DATA_FACILITY_NDB,
+    pub Flags: DWORD,
+    pub fLocalizer: f32,
+    pub GlideLat: f64,
+    pub GlideLon: f64,
+    pub GlideAlt: f64,
+    pub fGlideSlopeAngle: f32,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_VOR_LIST {
+    pub _base: SIMCONNECT_RECV_FACILITIES_LIST,
+    pub rgData: [SIMCONNECT_DATA_FACILITY_VOR; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_FACILITY_DATA {
+    pub _base: SIMCONNECT_RECV,
+    pub UserRequestId: DWORD,
+    pub UniqueRequestId: DWORD,
+    pub ParentUniqueRequestId: DWORD,
+    pub Type: DWORD,
+    pub IsListItem: DWORD,
+    pub ItemIndex: DWORD,
+    pub ListSize: DWORD,
+    pub Data: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_FACILITY_DATA_END {
+    pub _base: SIMCONNECT_RECV,
+    pub RequestId: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_ICAO {
+    pub Type: ::std::os::raw::c_char,
+    pub Ident: [::std::os::raw::c_char; 6usize],
+    pub Region: [::std::os::raw::c_char; 3usize],
+    pub Airport: [::std::os::raw::c_char; 5usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_LATLONALT {
+    pub Latitude: f64,
+    pub Longitude: f64,
+    pub Altitude: f64,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_PBH {
+    pub Pitch: f32,
+    pub Bank: f32,
+    pub Heading: f32,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_FACILITY_MINIMAL {
+    pub icao: SIMCONNECT_ICAO,
+    pub lla: SIMCONNECT_DATA_LATLONALT,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_FACILITY_MINIMAL_LIST {
+    pub _base: SIMCONNECT_RECV_LIST_TEMPLATE,
+    pub rgData: [SIMCONNECT_FACILITY_MINIMAL; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_INITPOSITION {
+    pub Latitude: f64,
+    pub Longitude: f64,
+    pub Altitude: f64,
+    pub Pitch: f64,
+    pub Bank: f64,
+    pub Heading: f64,
+    pub OnGround: DWORD,
+    pub Airspeed: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_MARKERSTATE {
+    pub szMarkerName: [::std::os::raw::c_char; 64usize],
+    pub dwMarkerState: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_WAYPOINT {
+    pub Latitude: f64,
+    pub Longitude: f64,
+    pub Altitude: f64,
+    pub Flags: DWORD,
+    pub ktsSpeed: f64,
+    pub percentThrottle: f64,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_DATA_XYZ {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_JETWAY_DATA {
+    pub AirportIcao: [::std::os::raw::c_char; 8usize],
+    pub ParkingIndex: ::std::os::raw::c_int,
+    pub Lla: SIMCONNECT_DATA_LATLONALT,
+    pub Pbh: SIMCONNECT_DATA_PBH,
+    pub Status: ::std::os::raw::c_int,
+    pub Door: ::std::os::raw::c_int,
+    pub ExitDoorRelativePos: SIMCONNECT_DATA_XYZ,
+    pub MainHandlePos: SIMCONNECT_DATA_XYZ,
+    pub SecondaryHandle: SIMCONNECT_DATA_XYZ,
+    pub WheelGroundLock: SIMCONNECT_DATA_XYZ,
+    pub JetwayObjectId: DWORD,
+    pub AttachedObjectId: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_JETWAY_DATA {
+    pub _base: SIMCONNECT_RECV_LIST_TEMPLATE,
+    pub rgData: [SIMCONNECT_JETWAY_DATA; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_ACTION_CALLBACK {
+    pub _base: SIMCONNECT_RECV,
+    pub szActionID: [::std::os::raw::c_char; 260usize],
+    pub cbRequestId: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_INPUT_EVENT_DESCRIPTOR {
+    pub Name: [::std::os::raw::c_char; 64usize],
+    pub Hash: ::std::os::raw::c_ulonglong,
+    pub eType: SIMCONNECT_INPUT_EVENT_TYPE,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_ENUMERATE_INPUT_EVENTS {
+    pub _base: SIMCONNECT_RECV_LIST_TEMPLATE,
+    pub rgData: [SIMCONNECT_INPUT_EVENT_DESCRIPTOR; 1usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_GET_INPUT_EVENT {
+    pub _base: SIMCONNECT_RECV,
+    pub dwRequestID: DWORD,
+    pub eType: SIMCONNECT_INPUT_EVENT_TYPE,
+    pub Value: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT {
+    pub _base: SIMCONNECT_RECV,
+    pub Hash: UINT64,
+    pub eType: SIMCONNECT_INPUT_EVENT_TYPE,
+    pub Value: DWORD,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_ENUMERATE_INPUT_EVENT_PARAMS {
+    pub _base: SIMCONNECT_RECV,
+    pub Hash: UINT64,
+    pub Value: [::std::os::raw::c_char; 260usize],
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_VERSION_BASE_TYPE {
+    pub Major: ::std::os::raw::c_ushort,
+    pub Minor: ::std::os::raw::c_ushort,
+    pub Revision: ::std::os::raw::c_ushort,
+    pub Build: ::std::os::raw::c_ushort,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_CONTROLLER_ITEM {
+    pub DeviceName: [::std::os::raw::c_char; 256usize],
+    pub DeviceId: ::std::os::raw::c_uint,
+    pub ProductId: ::std::os::raw::c_uint,
+    pub CompositeID: ::std::os::raw::c_uint,
+    pub HardwareVersion: SIMCONNECT_VERSION_BASE_TYPE,
+}
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIMCONNECT_RECV_CONTROLLERS_LIST {
+    pub _base: SIMCONNECT_RECV_LIST_TEMPLATE,
+    pub rgData: [SIMCONNECT_CONTROLLER_ITEM; 1usize],
+}
+pub type DispatchProc = ::std::option::Option<
+    unsafe extern "C" fn(
+        pData: *mut SIMCONNECT_RECV,
+        cbData: DWORD,
+        pContext: *mut ::std::os::raw::c_void,
+    ),
+>;
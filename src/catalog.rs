@@ -0,0 +1,646 @@
+//! A catalog of simulation variables, to catch misspelled names and units before SimConnect
+//! answers with `SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED`
+//!
+//! Names and units are matched without regard to case, like SimConnect does. The catalog
+//! covers the commonly used variables, not every one of them, so
+//! [`SimConnector::add_data_definition_checked`](crate::SimConnector::add_data_definition_checked)
+//! should only be used for those.
+//!
+//! ```
+//! use simconnect::catalog;
+//!
+//! let altitude = catalog::lookup("PLANE ALTITUDE").unwrap();
+//! assert!(altitude.settable && altitude.allows_units("meters"));
+//!
+//! assert_eq!(catalog::suggest("PLANE ALTITUDE ")[0], "PLANE ALTITUDE");
+//! assert!(catalog::check("GENERAL ENG RPM:1", "rpm").is_ok());
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+/// What a variable measures, which decides the units it can be requested in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitKind {
+    Length,
+    Angle,
+    AngularVelocity,
+    Speed,
+    Acceleration,
+    Pressure,
+    Temperature,
+    Weight,
+    Volume,
+    FuelFlow,
+    Torque,
+    Voltage,
+    Density,
+    Frequency,
+    Rpm,
+    Percent,
+    Position,
+    Bool,
+    Number,
+    Enum,
+    Time,
+    Bco16,
+    String,
+}
+
+impl UnitKind {
+    /// The unit names SimConnect accepts for this kind, in lowercase.
+    pub fn units(self) -> &'static [&'static str] {
+        match self {
+            Self::Length => &[
+                "feet",
+                "foot",
+                "ft",
+                "meters",
+                "meter",
+                "m",
+                "centimeters",
+                "centimeter",
+                "cm",
+                "millimeters",
+                "millimeter",
+                "kilometers",
+                "kilometer",
+                "km",
+                "inches",
+                "inch",
+                "in",
+                "yards",
+                "yard",
+                "miles",
+                "mile",
+                "nautical miles",
+                "nautical mile",
+                "nmiles",
+                "nmile",
+            ],
+            Self::Angle => &[
+                "radians",
+                "radian",
+                "degrees",
+                "degree",
+                "grads",
+                "degree latitude",
+                "degree longitude",
+                "radians latitude",
+                "radians longitude",
+            ],
+            Self::AngularVelocity => &[
+                "radians per second",
+                "degrees per second",
+                "degrees per minute",
+                "radians per minute",
+            ],
+            Self::Speed => &[
+                "knots",
+                "knot",
+                "kts",
+                "feet per second",
+                "feet/second",
+                "feet per minute",
+                "feet/minute",
+                "ft/min",
+                "meters per second",
+                "meters/second",
+                "m/s",
+                "meters per minute",
+                "kilometers per hour",
+                "kilometers/hour",
+                "km/h",
+                "miles per hour",
+                "mph",
+                "mach",
+            ],
+            Self::Acceleration => &[
+                "feet per second squared",
+                "meters per second squared",
+                "gforce",
+                "g force",
+            ],
+            Self::Pressure => &[
+                "inches of mercury",
+                "inhg",
+                "millibars",
+                "millibar",
+                "mbar",
+                "hectopascals",
+                "hpa",
+                "pascals",
+                "pascal",
+                "kilopascal",
+                "kpa",
+                "psi",
+                "pounds per square inch",
+                "atmospheres",
+                "atm",
+            ],
+            Self::Temperature => &[
+                "celsius",
+                "fahrenheit",
+                "kelvin",
+                "rankine",
+                "degrees celsius",
+                "degrees fahrenheit",
+            ],
+            Self::Weight => &[
+                "pounds",
+                "pound",
+                "lbs",
+                "kilograms",
+                "kilogram",
+                "kg",
+                "slugs",
+                "slug",
+                "ounces",
+                "ounce",
+            ],
+            Self::Volume => &[
+                "gallons",
+                "gallon",
+                "liters",
+                "liter",
+                "cubic feet",
+                "cubic meters",
+                "cubic inches",
+            ],
+            Self::FuelFlow => &[
+                "pounds per hour",
+                "kilograms per hour",
+                "kilograms per second",
+                "gallons per hour",
+                "liters per hour",
+            ],
+            Self::Torque => &["foot pounds", "foot-pounds", "newton meters"],
+            Self::Voltage => &["volts", "volt"],
+            Self::Density => &[
+                "slugs per cubic feet",
+                "slug per cubic foot",
+                "kilograms per cubic meter",
+            ],
+            Self::Frequency => &[
+                "mhz",
+                "khz",
+                "hz",
+                "frequency bcd16",
+                "frequency bcd32",
+                "frequency adf bcd32",
+            ],
+            Self::Rpm => &["rpm", "revolutions per minute", "percent"],
+            Self::Percent => &["percent", "percent over 100", "part", "number"],
+            Self::Position => &[
+                "position",
+                "position 16k",
+                "position 32k",
+                "position 128",
+                "percent",
+                "percent over 100",
+            ],
+            Self::Bool => &["bool", "boolean"],
+            Self::Number => &["number", "integer"],
+            Self::Enum => &["enum", "number", "mask", "flags"],
+            Self::Time => &[
+                "seconds",
+                "second",
+                "minutes",
+                "minute",
+                "hours",
+                "hour",
+                "days",
+                "day",
+                "milliseconds",
+            ],
+            Self::Bco16 => &["bco16", "bcd16", "number"],
+            Self::String => &[""],
+        }
+    }
+}
+
+/// The simulators a variable exists in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sims {
+    pub fsx: bool,
+    pub p3d: bool,
+    pub msfs: bool,
+}
+
+impl Sims {
+    pub const ALL: Sims = Sims {
+        fsx: true,
+        p3d: true,
+        msfs: true,
+    };
+}
+
+/// A simulation variable as described in the SimConnect SDK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SimVar {
+    /// The name without index, e.g. `"GENERAL ENG RPM"`.
+    pub name: &'static str,
+    pub kind: UnitKind,
+    /// The units the SDK documents it in.
+    pub default_units: &'static str,
+    /// Whether the name takes an index, as in `"GENERAL ENG RPM:1"`.
+    pub indexed: bool,
+    /// Whether it can be written with `SimConnect_SetDataOnSimObject`.
+    pub settable: bool,
+    pub sims: Sims,
+}
+
+impl SimVar {
+    const fn new(name: &'static str, kind: UnitKind, default_units: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            default_units,
+            indexed: false,
+            settable: false,
+            sims: Sims::ALL,
+        }
+    }
+
+    const fn indexed(self) -> Self {
+        Self {
+            indexed: true,
+            ..self
+        }
+    }
+
+    const fn settable(self) -> Self {
+        Self {
+            settable: true,
+            ..self
+        }
+    }
+
+    /// Only in Microsoft Flight Simulator.
+    const fn msfs(self) -> Self {
+        Self {
+            sims: Sims {
+                fsx: false,
+                p3d: false,
+                msfs: true,
+            },
+            ..self
+        }
+    }
+
+    /// The units it can be requested in.
+    pub fn units(&self) -> &'static [&'static str] {
+        self.kind.units()
+    }
+
+    pub fn allows_units(&self, units: &str) -> bool {
+        let units = units.to_ascii_lowercase();
+        self.units().contains(&units.as_str())
+    }
+}
+
+macro_rules! simvars {
+    ($($name:literal: $kind:ident = $units:literal $(, $flag:ident)*;)*) => {
+        static SIMVARS: &[SimVar] = &[
+            $(SimVar::new($name, UnitKind::$kind, $units)$(.$flag())*,)*
+        ];
+    };
+}
+
+simvars! {
+    // Position and attitude
+    "PLANE LATITUDE": Angle = "radians", settable;
+    "PLANE LONGITUDE": Angle = "radians", settable;
+    "PLANE ALTITUDE": Length = "feet", settable;
+    "PLANE ALT ABOVE GROUND": Length = "feet", settable;
+    "PLANE ALT ABOVE GROUND MINUS CG": Length = "feet", msfs;
+    "PLANE PITCH DEGREES": Angle = "radians", settable;
+    "PLANE BANK DEGREES": Angle = "radians", settable;
+    "PLANE HEADING DEGREES TRUE": Angle = "radians", settable;
+    "PLANE HEADING DEGREES MAGNETIC": Angle = "radians", settable;
+    "PLANE HEADING DEGREES GYRO": Angle = "radians", settable;
+    "MAGVAR": Angle = "degrees";
+    "GROUND ALTITUDE": Length = "meters";
+    "SIM ON GROUND": Bool = "bool";
+    "ON ANY RUNWAY": Bool = "bool";
+    // Speeds and accelerations
+    "AIRSPEED INDICATED": Speed = "knots", settable;
+    "AIRSPEED TRUE": Speed = "knots", settable;
+    "AIRSPEED MACH": Speed = "mach";
+    "GROUND VELOCITY": Speed = "knots";
+    "VERTICAL SPEED": Speed = "feet per second", settable;
+    "VELOCITY BODY X": Speed = "feet per second", settable;
+    "VELOCITY BODY Y": Speed = "feet per second", settable;
+    "VELOCITY BODY Z": Speed = "feet per second", settable;
+    "VELOCITY WORLD X": Speed = "feet per second", settable;
+    "VELOCITY WORLD Y": Speed = "feet per second", settable;
+    "VELOCITY WORLD Z": Speed = "feet per second", settable;
+    "ROTATION VELOCITY BODY X": AngularVelocity = "radians per second", settable;
+    "ROTATION VELOCITY BODY Y": AngularVelocity = "radians per second", settable;
+    "ROTATION VELOCITY BODY Z": AngularVelocity = "radians per second", settable;
+    "ACCELERATION BODY X": Acceleration = "feet per second squared", settable;
+    "ACCELERATION BODY Y": Acceleration = "feet per second squared", settable;
+    "ACCELERATION BODY Z": Acceleration = "feet per second squared", settable;
+    "G FORCE": Acceleration = "gforce";
+    "INCIDENCE ALPHA": Angle = "radians";
+    "INCIDENCE BETA": Angle = "radians";
+    "STALL WARNING": Bool = "bool";
+    "OVERSPEED WARNING": Bool = "bool";
+    // Aircraft
+    "TITLE": String = "";
+    "ATC ID": String = "", settable;
+    "ATC AIRLINE": String = "", settable;
+    "ATC FLIGHT NUMBER": String = "", settable;
+    "ATC TYPE": String = "";
+    "ATC MODEL": String = "";
+    "CATEGORY": String = "";
+    "TOTAL WEIGHT": Weight = "pounds";
+    "EMPTY WEIGHT": Weight = "pounds";
+    "NUMBER OF ENGINES": Number = "number";
+    "ENGINE TYPE": Enum = "enum";
+    "IS GEAR RETRACTABLE": Bool = "bool";
+    // Engines
+    "GENERAL ENG RPM": Rpm = "rpm", indexed;
+    "GENERAL ENG COMBUSTION": Bool = "bool", indexed, settable;
+    "GENERAL ENG THROTTLE LEVER POSITION": Percent = "percent", indexed, settable;
+    "GENERAL ENG MIXTURE LEVER POSITION": Percent = "percent", indexed, settable;
+    "GENERAL ENG PROPELLER LEVER POSITION": Percent = "percent", indexed, settable;
+    "GENERAL ENG OIL TEMPERATURE": Temperature = "rankine", indexed;
+    "GENERAL ENG OIL PRESSURE": Pressure = "psi", indexed;
+    "GENERAL ENG EXHAUST GAS TEMPERATURE": Temperature = "rankine", indexed, settable;
+    "GENERAL ENG FUEL PRESSURE": Pressure = "psi", indexed, settable;
+    "ENG N1 RPM": Percent = "percent", indexed;
+    "ENG N2 RPM": Percent = "percent", indexed;
+    "ENG FUEL FLOW PPH": FuelFlow = "pounds per hour", indexed;
+    "ENG EXHAUST GAS TEMPERATURE": Temperature = "rankine", indexed, settable;
+    "ENG MANIFOLD PRESSURE": Pressure = "inches of mercury", indexed;
+    "ENG TORQUE": Torque = "foot pounds", indexed;
+    "RECIP ENG MANIFOLD PRESSURE": Pressure = "psi", indexed, settable;
+    "TURB ENG N1": Percent = "percent", indexed, settable;
+    "TURB ENG N2": Percent = "percent", indexed, settable;
+    "TURB ENG ITT": Temperature = "rankine", indexed, settable;
+    "PROP RPM": Rpm = "rpm", indexed, settable;
+    "PROP BETA": Angle = "radians", indexed;
+    // Fuel
+    "FUEL TOTAL QUANTITY": Volume = "gallons";
+    "FUEL TOTAL QUANTITY WEIGHT": Weight = "pounds";
+    "FUEL TOTAL CAPACITY": Volume = "gallons";
+    "FUEL TANK CENTER QUANTITY": Volume = "gallons", settable;
+    "FUEL TANK LEFT MAIN QUANTITY": Volume = "gallons", settable;
+    "FUEL TANK RIGHT MAIN QUANTITY": Volume = "gallons", settable;
+    "FUEL WEIGHT PER GALLON": Weight = "pounds";
+    // Controls
+    "ELEVATOR POSITION": Position = "position", settable;
+    "AILERON POSITION": Position = "position", settable;
+    "RUDDER POSITION": Position = "position", settable;
+    "ELEVATOR TRIM POSITION": Angle = "radians", settable;
+    "FLAPS HANDLE INDEX": Number = "number", settable;
+    "FLAPS HANDLE PERCENT": Percent = "percent over 100";
+    "TRAILING EDGE FLAPS LEFT PERCENT": Percent = "percent over 100", settable;
+    "SPOILERS HANDLE POSITION": Percent = "percent over 100", settable;
+    "GEAR HANDLE POSITION": Bool = "bool", settable;
+    "GEAR POSITION": Enum = "enum", indexed;
+    "GEAR CENTER POSITION": Percent = "percent over 100", settable;
+    "GEAR LEFT POSITION": Percent = "percent over 100", settable;
+    "GEAR RIGHT POSITION": Percent = "percent over 100", settable;
+    "BRAKE PARKING POSITION": Position = "position", settable;
+    "BRAKE LEFT POSITION": Position = "position", settable;
+    "BRAKE RIGHT POSITION": Position = "position", settable;
+    // Radios and navigation
+    "COM ACTIVE FREQUENCY": Frequency = "mhz", indexed;
+    "COM STANDBY FREQUENCY": Frequency = "mhz", indexed;
+    "NAV ACTIVE FREQUENCY": Frequency = "mhz", indexed;
+    "NAV STANDBY FREQUENCY": Frequency = "mhz", indexed;
+    "ADF ACTIVE FREQUENCY": Frequency = "frequency adf bcd32", indexed;
+    "NAV OBS": Angle = "degrees", indexed;
+    "NAV CDI": Number = "number", indexed;
+    "NAV GSI": Number = "number", indexed;
+    "NAV HAS NAV": Bool = "bool", indexed;
+    "NAV HAS LOCALIZER": Bool = "bool", indexed;
+    "NAV HAS GLIDE SLOPE": Bool = "bool", indexed;
+    "NAV DME": Length = "nautical miles", indexed;
+    "NAV IDENT": String = "", indexed;
+    "TRANSPONDER CODE": Bco16 = "bco16", indexed;
+    "KOHLSMAN SETTING MB": Pressure = "millibars", indexed, settable;
+    "KOHLSMAN SETTING HG": Pressure = "inches of mercury", indexed;
+    "INDICATED ALTITUDE": Length = "feet", settable;
+    "PRESSURE ALTITUDE": Length = "meters";
+    "HEADING INDICATOR": Angle = "radians";
+    "ATTITUDE INDICATOR PITCH DEGREES": Angle = "radians";
+    "ATTITUDE INDICATOR BANK DEGREES": Angle = "radians";
+    "GPS POSITION LAT": Angle = "degrees";
+    "GPS POSITION LON": Angle = "degrees";
+    "GPS POSITION ALT": Length = "meters";
+    "GPS GROUND SPEED": Speed = "meters per second";
+    "GPS WP DISTANCE": Length = "meters";
+    "GPS WP NEXT ID": String = "";
+    "GPS IS ACTIVE FLIGHT PLAN": Bool = "bool";
+    // Autopilot
+    "AUTOPILOT MASTER": Bool = "bool";
+    "AUTOPILOT HEADING LOCK": Bool = "bool";
+    "AUTOPILOT HEADING LOCK DIR": Angle = "degrees";
+    "AUTOPILOT ALTITUDE LOCK": Bool = "bool";
+    "AUTOPILOT ALTITUDE LOCK VAR": Length = "feet", indexed;
+    "AUTOPILOT VERTICAL HOLD VAR": Speed = "feet per minute";
+    "AUTOPILOT AIRSPEED HOLD VAR": Speed = "knots";
+    "AUTOPILOT NAV1 LOCK": Bool = "bool";
+    "AUTOPILOT APPROACH HOLD": Bool = "bool";
+    "AUTOPILOT FLIGHT DIRECTOR ACTIVE": Bool = "bool", indexed;
+    "AUTOTHROTTLE ACTIVE": Bool = "bool";
+    // Lights and systems
+    "LIGHT NAV": Bool = "bool";
+    "LIGHT BEACON": Bool = "bool";
+    "LIGHT LANDING": Bool = "bool";
+    "LIGHT TAXI": Bool = "bool";
+    "LIGHT STROBE": Bool = "bool";
+    "LIGHT LOGO": Bool = "bool";
+    "LIGHT PANEL": Bool = "bool";
+    "ELECTRICAL MASTER BATTERY": Bool = "bool", indexed;
+    "ELECTRICAL MAIN BUS VOLTAGE": Voltage = "volts", indexed;
+    "PITOT HEAT": Bool = "bool";
+    "STRUCTURAL DEICE SWITCH": Bool = "bool";
+    "CANOPY OPEN": Percent = "percent";
+    "EXIT OPEN": Percent = "percent", indexed, settable;
+    // Environment
+    "AMBIENT TEMPERATURE": Temperature = "celsius";
+    "AMBIENT PRESSURE": Pressure = "inches of mercury";
+    "SEA LEVEL PRESSURE": Pressure = "millibars";
+    "AMBIENT WIND VELOCITY": Speed = "knots";
+    "AMBIENT WIND DIRECTION": Angle = "degrees";
+    "AMBIENT VISIBILITY": Length = "meters";
+    "AMBIENT IN CLOUD": Bool = "bool";
+    "AMBIENT PRECIP STATE": Enum = "mask";
+    "AMBIENT DENSITY": Density = "slugs per cubic feet";
+    "TOTAL AIR TEMPERATURE": Temperature = "celsius";
+    // Time
+    "ZULU TIME": Time = "seconds";
+    "ZULU DAY OF MONTH": Number = "number";
+    "ZULU MONTH OF YEAR": Number = "number";
+    "ZULU YEAR": Number = "number";
+    "LOCAL TIME": Time = "seconds";
+    "ABSOLUTE TIME": Time = "seconds";
+    "SIMULATION RATE": Number = "number";
+    "SIM DISABLED": Bool = "bool", settable;
+    "REALISM": Percent = "number", settable;
+}
+
+/// Every variable in the catalog.
+pub fn simvars() -> &'static [SimVar] {
+    SIMVARS
+}
+
+/// Splits `"GENERAL ENG RPM:1"` into the name and the index.
+fn split_index(datum_name: &str) -> (&str, Option<&str>) {
+    match datum_name.rsplit_once(':') {
+        Some((name, index)) => (name, Some(index)),
+        None => (datum_name, None),
+    }
+}
+
+/// The variable of `datum_name`, which may carry an index.
+pub fn lookup(datum_name: &str) -> Option<&'static SimVar> {
+    let (name, _) = split_index(datum_name);
+    SIMVARS
+        .iter()
+        .find(|simvar| simvar.name.eq_ignore_ascii_case(name))
+}
+
+/// The variables whose name contains `query`.
+pub fn search(query: &str) -> impl Iterator<Item = &'static SimVar> {
+    let query = query.to_ascii_uppercase();
+    SIMVARS
+        .iter()
+        .filter(move |simvar| simvar.name.contains(query.as_str()))
+}
+
+/// Catalog names close to `datum_name`, the closest first.
+pub fn suggest(datum_name: &str) -> Vec<&'static str> {
+    let (name, _) = split_index(datum_name);
+    closest(
+        &name.to_ascii_uppercase(),
+        SIMVARS.iter().map(|simvar| simvar.name),
+    )
+}
+
+/// Up to three of `candidates` within a few edits of `input`.
+fn closest(input: &str, candidates: impl Iterator<Item = &'static str>) -> Vec<&'static str> {
+    let max_distance = (input.len() / 4).max(2);
+    let mut close: Vec<_> = candidates
+        .map(|candidate| (edit_distance(input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    close.sort();
+
+    close
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Checks `datum_name` and `units_name` as they would be passed to `add_data_definition`.
+pub fn check(datum_name: &str, units_name: &str) -> Result<&'static SimVar, CatalogError> {
+    let simvar = lookup(datum_name).ok_or_else(|| CatalogError::UnknownVariable {
+        name: datum_name.to_string(),
+        suggestions: suggest(datum_name),
+    })?;
+
+    match (split_index(datum_name).1, simvar.indexed) {
+        (None, true) => return Err(CatalogError::MissingIndex(simvar.name)),
+        (Some(_), false) => return Err(CatalogError::UnexpectedIndex(simvar.name)),
+        (Some(index), true) if index.parse::<u32>().is_err() => {
+            return Err(CatalogError::InvalidIndex {
+                name: simvar.name,
+                index: index.to_string(),
+            })
+        }
+        _ => (),
+    }
+
+    if !simvar.allows_units(units_name) {
+        return Err(CatalogError::UnknownUnits {
+            name: simvar.name,
+            units: units_name.to_string(),
+            suggestions: closest(
+                &units_name.to_ascii_lowercase(),
+                simvar.units().iter().copied(),
+            ),
+        });
+    }
+
+    Ok(simvar)
+}
+
+/// Errors returned by [`check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogError {
+    /// The variable isn't in the catalog.
+    UnknownVariable {
+        name: String,
+        /// Similar names from the catalog.
+        suggestions: Vec<&'static str>,
+    },
+    /// The variable can't be requested in these units.
+    UnknownUnits {
+        name: &'static str,
+        units: String,
+        suggestions: Vec<&'static str>,
+    },
+    /// The variable needs an index, as in `"NAME:1"`.
+    MissingIndex(&'static str),
+    /// The variable takes no index.
+    UnexpectedIndex(&'static str),
+    /// The index isn't a number.
+    InvalidIndex { name: &'static str, index: String },
+}
+
+fn write_suggestions(f: &mut fmt::Formatter<'_>, suggestions: &[&str]) -> fmt::Result {
+    if !suggestions.is_empty() {
+        write!(f, ", did you mean \"{}\"?", suggestions.join("\", \""))?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownVariable { name, suggestions } => {
+                write!(f, "unknown simulation variable \"{}\"", name)?;
+                write_suggestions(f, suggestions)
+            }
+            Self::UnknownUnits {
+                name,
+                units,
+                suggestions,
+            } => {
+                write!(f, "\"{}\" can't be requested in \"{}\"", name, units)?;
+                write_suggestions(f, suggestions)
+            }
+            Self::MissingIndex(name) => write!(f, "\"{}\" needs an index like \":1\"", name),
+            Self::UnexpectedIndex(name) => write!(f, "\"{}\" takes no index", name),
+            Self::InvalidIndex { name, index } => {
+                write!(f, "invalid index \"{}\" for \"{}\"", index, name)
+            }
+        }
+    }
+}
+
+impl Error for CatalogError {}
@@ -0,0 +1,353 @@
+//! Client data areas described by `#[repr(C)]` structs
+//!
+//! A [`ClientDataArea`] keeps the name, IDs and layout of an area together, so the calls to
+//! `SimConnect_MapClientDataNameToID`, `SimConnect_CreateClientData`,
+//! `SimConnect_AddToClientDataDefinition` and `SimConnect_SetClientData` can't drift apart.
+
+use std::marker::PhantomData;
+use std::mem::{size_of, MaybeUninit};
+use std::os::raw::c_void;
+use std::ptr;
+
+use crate::*;
+
+/// A `#[repr(C)]` struct that describes the layout of a client data area
+///
+/// Implement it with `#[derive(ClientData)]` (feature `derive`), which adds one datum per field
+/// at the field's offset:
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// # use simconnect::*;
+/// #[derive(Clone, Copy, ClientData)]
+/// #[repr(C)]
+/// struct Lights {
+///     #[client_data(epsilon = 0.01)]
+///     brightness: f32,
+///     mask: u32,
+///     label: [u8; 16],
+/// }
+///
+/// let fields = Lights::fields();
+/// assert_eq!(fields[2].offset, 8);
+/// assert_eq!(fields[2].size, 16);
+/// ```
+///
+/// # Safety
+///
+/// The struct has to be `#[repr(C)]`, and `fields` has to describe fields within it that are
+/// valid for any bit pattern, as the derive guarantees with [`ClientDataType`].
+pub unsafe trait ClientData: Copy + 'static {
+    /// The fields to add to the client data definition, in order.
+    fn fields() -> Vec<ClientDataField>;
+}
+
+/// A field of a [`ClientData`] struct, added as one datum of the client data definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientDataField {
+    /// Offset of the field in the struct, and in the client data area.
+    pub offset: usize,
+    /// Size of the field in bytes.
+    pub size: usize,
+    /// A `SIMCONNECT_CLIENTDATATYPE_*` or the size in bytes, see [`ClientDataType`].
+    pub size_or_type: DWORD,
+    /// How much a numeric field has to change for a change subscription to send it.
+    pub epsilon: f32,
+}
+
+/// A type that can be the field of a [`ClientData`] struct
+///
+/// Signed integers and floats are added as their `SIMCONNECT_CLIENTDATATYPE_*`, which
+/// `epsilon` applies to. Anything else is added by its size and compared byte by byte.
+///
+/// # Safety
+///
+/// Every bit pattern has to be a valid value of the type.
+pub unsafe trait ClientDataType: Copy + 'static {
+    const SIZE_OR_TYPE: DWORD;
+}
+
+macro_rules! client_data_types {
+    ($($ty:ty => $size_or_type:expr,)*) => {
+        $(
+            unsafe impl ClientDataType for $ty {
+                const SIZE_OR_TYPE: DWORD = $size_or_type;
+            }
+        )*
+    };
+}
+
+client_data_types! {
+    i8 => SIMCONNECT_CLIENTDATATYPE_INT8,
+    i16 => SIMCONNECT_CLIENTDATATYPE_INT16,
+    i32 => SIMCONNECT_CLIENTDATATYPE_INT32,
+    i64 => SIMCONNECT_CLIENTDATATYPE_INT64,
+    f32 => SIMCONNECT_CLIENTDATATYPE_FLOAT32,
+    f64 => SIMCONNECT_CLIENTDATATYPE_FLOAT64,
+    u8 => 1,
+    u16 => 2,
+    u32 => 4,
+    u64 => 8,
+}
+
+unsafe impl<T: ClientDataType, const N: usize> ClientDataType for [T; N] {
+    const SIZE_OR_TYPE: DWORD = (size_of::<T>() * N) as DWORD;
+}
+
+/// A client data area laid out like `T`
+///
+/// ```no_run
+/// # use simconnect::*;
+/// # #[derive(Clone, Copy)]
+/// # #[repr(C)]
+/// # struct Lights { brightness: f32 }
+/// # unsafe impl ClientData for Lights {
+/// #     fn fields() -> Vec<ClientDataField> {
+/// #         vec![ClientDataField { offset: 0, size: 4, size_or_type: 4, epsilon: 0.0 }]
+/// #     }
+/// # }
+/// # fn main() -> Result<(), SimConnectError> {
+/// let mut conn = SimConnector::new();
+/// conn.connect("Client data")?;
+///
+/// let lights = ClientDataArea::<Lights>::create(&conn, "MyAddon.Lights", 1, 1)?;
+/// lights.write(&conn, &Lights { brightness: 0.5 })?;
+/// lights.subscribe(&conn, 7)?;
+///
+/// if let DispatchResult::ClientData(data) = conn.get_next_message()? {
+///     if let Some(state) = lights.decode(&data)? {
+///         println!("brightness: {}", state.brightness);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ClientDataArea<T> {
+    data_id: SIMCONNECT_CLIENT_DATA_ID,
+    define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    fields: Vec<ClientDataField>,
+    _data: PhantomData<T>,
+}
+
+impl<T: ClientData> ClientDataArea<T> {
+    /// Maps `name` to `data_id` and adds the fields of `T` to `define_id`, for an area created
+    /// by another client.
+    ///
+    /// Fails with [`SimConnectError::ClientDataTooLarge`] before sending anything if `T` is
+    /// larger than `SIMCONNECT_CLIENTDATA_MAX_SIZE`.
+    #[track_caller]
+    pub fn map<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        data_id: SIMCONNECT_CLIENT_DATA_ID,
+        define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    ) -> Result<Self, SimConnectError> {
+        if size_of::<T>() > SIMCONNECT_CLIENTDATA_MAX_SIZE as usize {
+            return Err(SimConnectError::ClientDataTooLarge {
+                data_id,
+                size: size_of::<T>(),
+            });
+        }
+
+        conn.map_client_data_name_to_id(name, data_id)?;
+
+        let fields = T::fields();
+        for (datum_id, field) in fields.iter().enumerate() {
+            conn.add_to_client_data_definition(
+                define_id,
+                field.offset as DWORD,
+                field.size_or_type,
+                field.epsilon,
+                datum_id as DWORD,
+            )?;
+        }
+
+        Ok(Self {
+            data_id,
+            define_id,
+            fields,
+            _data: PhantomData,
+        })
+    }
+
+    /// [`map`](Self::map), then creates the area with the size of `T`.
+    #[track_caller]
+    pub fn create<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        data_id: SIMCONNECT_CLIENT_DATA_ID,
+        define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    ) -> Result<Self, SimConnectError> {
+        Self::create_with_flags(
+            conn,
+            name,
+            data_id,
+            define_id,
+            SIMCONNECT_CREATE_CLIENT_DATA_FLAG_DEFAULT,
+        )
+    }
+
+    /// [`create`](Self::create) with `SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY`, so only this
+    /// client can write the area.
+    #[track_caller]
+    pub fn create_read_only<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        data_id: SIMCONNECT_CLIENT_DATA_ID,
+        define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    ) -> Result<Self, SimConnectError> {
+        Self::create_with_flags(
+            conn,
+            name,
+            data_id,
+            define_id,
+            SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY,
+        )
+    }
+
+    #[track_caller]
+    fn create_with_flags<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        data_id: SIMCONNECT_CLIENT_DATA_ID,
+        define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+        flags: SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+    ) -> Result<Self, SimConnectError> {
+        let area = Self::map(conn, name, data_id, define_id)?;
+        conn.create_client_data(data_id, size_of::<T>() as DWORD, flags)?;
+
+        Ok(area)
+    }
+
+    pub fn data_id(&self) -> SIMCONNECT_CLIENT_DATA_ID {
+        self.data_id
+    }
+
+    pub fn define_id(&self) -> SIMCONNECT_CLIENT_DATA_DEFINITION_ID {
+        self.define_id
+    }
+
+    /// Size of the data SimConnect sends and receives for the area: the fields without the
+    /// padding between them.
+    pub fn data_size(&self) -> usize {
+        self.fields.iter().map(|field| field.size).sum()
+    }
+
+    /// Writes `data` to the whole area.
+    #[track_caller]
+    pub fn write<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        data: &T,
+    ) -> Result<(), SimConnectError> {
+        let mut bytes = self.pack(data);
+
+        // SimConnect copies the data before returning and never writes to it
+        unsafe {
+            conn.set_client_data(
+                self.data_id,
+                self.define_id,
+                SIMCONNECT_CLIENT_DATA_SET_FLAG_DEFAULT,
+                0,
+                bytes.len() as DWORD,
+                bytes.as_mut_ptr() as *mut c_void,
+            )
+        }
+    }
+
+    /// Requests the area with `period` and `flags`, answered with `request_id`.
+    #[track_caller]
+    pub fn request<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        period: SIMCONNECT_CLIENT_DATA_PERIOD,
+        flags: SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+    ) -> Result<(), SimConnectError> {
+        conn.request_client_data(
+            self.data_id,
+            request_id,
+            self.define_id,
+            period,
+            flags,
+            0,
+            0,
+            0,
+        )
+    }
+
+    /// Requests the area every time a field changes by more than its epsilon.
+    #[track_caller]
+    pub fn subscribe<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        self.request(
+            conn,
+            request_id,
+            SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ON_SET,
+            SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED,
+        )
+    }
+
+    /// Stops the request `request_id`.
+    #[track_caller]
+    pub fn unsubscribe<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        self.request(
+            conn,
+            request_id,
+            SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER,
+            SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+        )
+    }
+
+    /// Decodes `data` if it was requested with the definition of this area.
+    ///
+    /// Only untagged requests can be decoded, as made by [`request`](Self::request) without
+    /// `SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_TAGGED`.
+    pub fn decode(
+        &self,
+        data: &Payload<'_, SIMCONNECT_RECV_CLIENT_DATA>,
+    ) -> Result<Option<T>, SimConnectError> {
+        if data._base.dwDefineID != self.define_id {
+            return Ok(None);
+        }
+
+        let mut reader = data.reader();
+        let mut value = MaybeUninit::<T>::zeroed();
+        for field in &self.fields {
+            let bytes = reader.read_bytes(field.size)?;
+            // `ClientData` guarantees that the field is within `T` and valid for any bytes
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    (value.as_mut_ptr() as *mut u8).add(field.offset),
+                    field.size,
+                );
+            }
+        }
+
+        // Padding stays zeroed, and every field was written
+        Ok(Some(unsafe { value.assume_init() }))
+    }
+
+    /// The fields of `data` one after the other, as `SimConnect_SetClientData` expects them.
+    fn pack(&self, data: &T) -> Vec<u8> {
+        let base = data as *const T as *const u8;
+        let mut bytes = Vec::with_capacity(self.data_size());
+        for field in &self.fields {
+            // `ClientData` guarantees that the field is within `T`
+            bytes.extend_from_slice(unsafe {
+                std::slice::from_raw_parts(base.add(field.offset), field.size)
+            });
+        }
+
+        bytes
+    }
+}
@@ -0,0 +1,294 @@
+//! Cloud densities of a `SimConnect_WeatherRequestCloudState` placed on the map
+//!
+//! A [`CloudState`] is a grid of [`CloudState::WIDTH`]² densities from 0 to 255 without any
+//! position. [`CloudGrid`] adds the area it was requested for, so cells map to coordinates
+//! and coordinates to cells, and writes the grid as an image or a table:
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # use simconnect::cloud_grid::{CloudBounds, CloudGrid};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("Radar")?;
+//!
+//! let bounds = CloudBounds::new(47.0, 8.0, 0.0, 48.0, 9.0, 20_000.0);
+//! bounds.request(&conn, 1, 0)?;
+//!
+//! loop {
+//!     if let Some(state) = conn.get_next_message()?.cloud_state() {
+//!         let grid = CloudGrid::new(&state, bounds);
+//!         println!("{:?}", grid.density_at(47.45, 8.56, 5000.0));
+//!         grid.write_png(std::fs::File::create("clouds.png")?)?;
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! Rows run from the minimum latitude to the maximum, columns from the minimum longitude to
+//! the maximum. The images are drawn north up.
+
+use std::io::{self, Write};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::message::CloudState;
+use crate::*;
+
+const WIDTH: usize = CloudState::WIDTH;
+
+/// The area of a cloud state request, in degrees and feet
+///
+/// A `max_lon` below `min_lon` is an area across the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CloudBounds {
+    pub min_lat: f32,
+    pub min_lon: f32,
+    pub min_alt: f32,
+    pub max_lat: f32,
+    pub max_lon: f32,
+    pub max_alt: f32,
+}
+
+impl CloudBounds {
+    pub fn new(
+        min_lat: f32,
+        min_lon: f32,
+        min_alt: f32,
+        max_lat: f32,
+        max_lon: f32,
+        max_alt: f32,
+    ) -> Self {
+        Self {
+            min_lat,
+            min_lon,
+            min_alt,
+            max_lat,
+            max_lon,
+            max_alt,
+        }
+    }
+
+    /// Requests the cloud state of the area with
+    /// [`weather_request_cloud_state`](SimConnector::weather_request_cloud_state).
+    #[track_caller]
+    pub fn request<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        flags: DWORD,
+    ) -> Result<(), SimConnectError> {
+        conn.weather_request_cloud_state(
+            request_id,
+            self.min_lat,
+            self.min_lon,
+            self.min_alt,
+            self.max_lat,
+            self.max_lon,
+            self.max_alt,
+            flags,
+        )
+    }
+
+    /// Degrees of latitude and longitude the area spans.
+    fn span(&self) -> (f64, f64) {
+        let lon = (f64::from(self.max_lon) - f64::from(self.min_lon)).rem_euclid(360.0);
+        (f64::from(self.max_lat) - f64::from(self.min_lat), lon)
+    }
+}
+
+/// A [`CloudState`] with the area it covers
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CloudGrid {
+    pub request_id: DWORD,
+    pub bounds: CloudBounds,
+    /// The densities by row, as many as the message held.
+    data: Vec<u8>,
+}
+
+impl CloudGrid {
+    pub fn new(state: &CloudState, bounds: CloudBounds) -> Self {
+        let mut data = state.data.clone();
+        data.truncate(WIDTH * WIDTH);
+
+        Self {
+            request_id: state.request_id,
+            bounds,
+            data,
+        }
+    }
+
+    /// Density of the cell in `row` and `column`, if the message covered it.
+    pub fn density(&self, row: usize, column: usize) -> Option<u8> {
+        if row >= WIDTH || column >= WIDTH {
+            return None;
+        }
+
+        self.data.get(row * WIDTH + column).copied()
+    }
+
+    /// Latitude and longitude of the center of a cell.
+    pub fn cell_center(&self, row: usize, column: usize) -> Option<(f32, f32)> {
+        if row >= WIDTH || column >= WIDTH {
+            return None;
+        }
+
+        let (lat_span, lon_span) = self.bounds.span();
+        let cells = WIDTH as f64;
+        let lat = f64::from(self.bounds.min_lat) + lat_span * (row as f64 + 0.5) / cells;
+        let lon = f64::from(self.bounds.min_lon) + lon_span * (column as f64 + 0.5) / cells;
+
+        Some((lat as f32, ((lon + 180.0).rem_euclid(360.0) - 180.0) as f32))
+    }
+
+    /// Row and column of the cell a position lies in, if it lies within the bounds.
+    pub fn cell_at(&self, lat: f32, lon: f32) -> Option<(usize, usize)> {
+        let (lat_span, lon_span) = self.bounds.span();
+        let lat = f64::from(lat) - f64::from(self.bounds.min_lat);
+        let lon = (f64::from(lon) - f64::from(self.bounds.min_lon)).rem_euclid(360.0);
+        if !(0.0..=lat_span).contains(&lat) || lon > lon_span {
+            return None;
+        }
+
+        // The maximum belongs to the last cell
+        let index =
+            |offset: f64, span: f64| ((offset / span * WIDTH as f64) as usize).min(WIDTH - 1);
+        Some((index(lat, lat_span), index(lon, lon_span)))
+    }
+
+    /// Density at a position, if it lies within the bounds and the message covered it.
+    pub fn density_at(&self, lat: f32, lon: f32, alt: f32) -> Option<u8> {
+        if !(self.bounds.min_alt..=self.bounds.max_alt).contains(&alt) {
+            return None;
+        }
+
+        let (row, column) = self.cell_at(lat, lon)?;
+        self.density(row, column)
+    }
+
+    /// The densities, north up, as a row of bytes per line. Cells the message didn't cover
+    /// are 0.
+    fn pixels(&self) -> impl Iterator<Item = [u8; WIDTH]> + '_ {
+        (0..WIDTH).rev().map(move |row| {
+            let mut line = [0; WIDTH];
+            for (column, pixel) in line.iter_mut().enumerate() {
+                *pixel = self.density(row, column).unwrap_or(0);
+            }
+            line
+        })
+    }
+
+    /// Writes the grid as a binary PGM image, one gray pixel per cell.
+    pub fn write_pgm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P5\n{} {}\n255\n", WIDTH, WIDTH)?;
+        for line in self.pixels() {
+            writer.write_all(&line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the grid as a grayscale PNG image, one pixel per cell.
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        // Every line starts with filter type 0, none
+        let mut raw = Vec::with_capacity(WIDTH * (WIDTH + 1));
+        for line in self.pixels() {
+            raw.push(0);
+            raw.extend_from_slice(&line);
+        }
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(WIDTH as u32).to_be_bytes());
+        header.extend_from_slice(&(WIDTH as u32).to_be_bytes());
+        // 8 bits grayscale, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_chunk(&mut writer, b"IHDR", &header)?;
+        write_chunk(&mut writer, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(&mut writer, b"IEND", &[])
+    }
+
+    /// Writes a `row,column,lat,lon,density` line per cell the message covered, after a
+    /// header line.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "row,column,lat,lon,density")?;
+        for row in 0..WIDTH {
+            for column in 0..WIDTH {
+                if let (Some(density), Some((lat, lon))) =
+                    (self.density(row, column), self.cell_center(row, column))
+                {
+                    writeln!(writer, "{},{},{},{},{}", row, column, lat, lon, density)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a PNG chunk: length, type, data and the CRC of type and data
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.finish().to_be_bytes())
+}
+
+/// `data` as a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, no dictionary, fastest
+    let mut stream = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        stream.push(last as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65_521;
+    let (a, b) = data.iter().fold((1, 0), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % MOD;
+        (a, (b + a) % MOD)
+    });
+
+    b << 16 | a
+}
+
+/// The CRC-32 of PNG and zlib, computed bit by bit
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= u32::from(byte);
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
@@ -0,0 +1,338 @@
+//! `SimConnect.cfg` files, which tell a client how to reach the simulator
+//!
+//! The file has one section per connection, `[SimConnect]` (index 0) and `[SimConnect.N]`, and
+//! `SimConnect_Open` picks one by its config index:
+//!
+//! ```text
+//! [SimConnect]
+//! Protocol=IPv4
+//! Address=127.0.0.1
+//! Port=500
+//!
+//! [SimConnect.1]
+//! Protocol=IPv4
+//! Address=192.168.1.20
+//! Port=500
+//! MaxReceiveSize=4096
+//! DisableNagle=1
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{DWORD, HANDLE};
+
+/// Name of the file the SimConnect library looks for, in the current directory among others.
+pub const CONFIG_FILE_NAME: &str = "SimConnect.cfg";
+
+/// Transport of a `SimConnect.cfg` section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Ipv4,
+    Ipv6,
+    /// A named pipe, `Port` is the pipe name.
+    Pipe,
+}
+
+impl FromStr for Protocol {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "ipv4" => Ok(Self::Ipv4),
+            "ipv6" => Ok(Self::Ipv6),
+            "pipe" => Ok(Self::Pipe),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One `[SimConnect.N]` section
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConfigSection {
+    /// `Protocol`, the library defaults to a pipe.
+    pub protocol: Option<Protocol>,
+    /// `Address`, a host name or IP address.
+    pub address: Option<String>,
+    /// `Port`, a TCP port or, with [`Protocol::Pipe`], the pipe name.
+    pub port: Option<String>,
+    /// `MaxReceiveSize`, the largest message in bytes the client accepts.
+    pub max_receive_size: Option<DWORD>,
+    /// `DisableNagle`
+    pub disable_nagle: Option<bool>,
+}
+
+impl ConfigSection {
+    /// The TCP port, if the section uses IPv4 or IPv6.
+    pub fn tcp_port(&self) -> Option<u16> {
+        match self.protocol {
+            Some(Protocol::Ipv4) | Some(Protocol::Ipv6) => self.port.as_ref()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// `Address:Port` of an IPv4 or IPv6 section.
+    pub fn socket_address(&self) -> Option<String> {
+        let port = self.tcp_port()?;
+
+        match (self.protocol?, self.address.as_deref()?) {
+            (Protocol::Ipv6, address) if address.contains(':') => {
+                Some(format!("[{}]:{}", address, port))
+            }
+            (_, address) => Some(format!("{}:{}", address, port)),
+        }
+    }
+
+    /// Checks that the section has everything its protocol needs.
+    pub fn validate(&self, index: DWORD) -> Result<(), ConfigError> {
+        let missing = |key| ConfigError::MissingKey { index, key };
+
+        if let Some(Protocol::Ipv4) | Some(Protocol::Ipv6) = self.protocol {
+            self.address.as_ref().ok_or_else(|| missing("Address"))?;
+            let port = self.port.as_ref().ok_or_else(|| missing("Port"))?;
+
+            if !matches!(port.parse::<u16>(), Ok(port) if port != 0) {
+                return Err(ConfigError::InvalidValue {
+                    index,
+                    key: "Port",
+                    value: port.clone(),
+                });
+            }
+        }
+
+        if self.max_receive_size == Some(0) {
+            return Err(ConfigError::InvalidValue {
+                index,
+                key: "MaxReceiveSize",
+                value: "0".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// A parsed `SimConnect.cfg`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SimConnectConfig {
+    sections: BTreeMap<DWORD, ConfigSection>,
+}
+
+impl SimConnectConfig {
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut sections = BTreeMap::new();
+        let mut current: Option<(DWORD, ConfigSection)> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let syntax = |message: &str| ConfigError::Syntax {
+                line: line_number,
+                message: message.to_owned(),
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| syntax("unterminated section header"))?;
+                let index = section_index(header.trim())
+                    .ok_or_else(|| syntax("expected [SimConnect] or [SimConnect.N]"))?;
+
+                if let Some((index, section)) = current.take() {
+                    sections.insert(index, section);
+                }
+                if sections.contains_key(&index) {
+                    return Err(syntax("duplicate section"));
+                }
+                current = Some((index, ConfigSection::default()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax("expected Key=Value"))?;
+            let (key, value) = (key.trim(), value.trim());
+            let (index, section) = current
+                .as_mut()
+                .ok_or_else(|| syntax("key outside of a section"))?;
+            let invalid = |key| ConfigError::InvalidValue {
+                index: *index,
+                key,
+                value: value.to_owned(),
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "protocol" => {
+                    section.protocol = Some(value.parse().map_err(|_| invalid("Protocol"))?)
+                }
+                "address" => section.address = Some(value.to_owned()),
+                "port" => section.port = Some(value.to_owned()),
+                "maxreceivesize" => {
+                    section.max_receive_size =
+                        Some(value.parse().map_err(|_| invalid("MaxReceiveSize"))?)
+                }
+                "disablenagle" => {
+                    section.disable_nagle = Some(match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid("DisableNagle")),
+                    })
+                }
+                // Settings of newer libraries, e.g. `MaxClients`, don't affect the connection
+                _ => {}
+            }
+        }
+
+        if let Some((index, section)) = current {
+            sections.insert(index, section);
+        }
+
+        Ok(Self { sections })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Reads `SimConnect.cfg` from the current directory, the first place the SimConnect library
+    /// looks. Returns `None` if there is no such file.
+    pub fn from_current_dir() -> Result<Option<Self>, ConfigError> {
+        match Self::from_file(CONFIG_FILE_NAME) {
+            Ok(config) => Ok(Some(config)),
+            Err(ConfigError::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The section with config index `index`.
+    pub fn section(&self, index: DWORD) -> Option<&ConfigSection> {
+        self.sections.get(&index)
+    }
+
+    /// All sections by config index, in ascending order.
+    pub fn sections(&self) -> impl Iterator<Item = (DWORD, &ConfigSection)> {
+        self.sections
+            .iter()
+            .map(|(index, section)| (*index, section))
+    }
+
+    /// Checks that there is a valid section for `index`.
+    pub fn validate(&self, index: DWORD) -> Result<&ConfigSection, ConfigError> {
+        let section = self
+            .section(index)
+            .ok_or(ConfigError::MissingSection(index))?;
+        section.validate(index)?;
+
+        Ok(section)
+    }
+}
+
+impl FromStr for SimConnectConfig {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, ConfigError> {
+        Self::parse(s)
+    }
+}
+
+fn section_index(header: &str) -> Option<DWORD> {
+    let prefix = header.get(..10)?;
+    if !prefix.eq_ignore_ascii_case("SimConnect") {
+        return None;
+    }
+
+    match &header[10..] {
+        "" => Some(0),
+        rest => rest.strip_prefix('.')?.parse().ok(),
+    }
+}
+
+/// Errors reading or validating a `SimConnect.cfg`
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A line that is neither a section header, a `Key=Value` pair nor a comment.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// A value that cannot be parsed or doesn't fit the protocol.
+    InvalidValue {
+        index: DWORD,
+        key: &'static str,
+        value: String,
+    },
+    /// The section is missing a key its protocol needs.
+    MissingKey {
+        index: DWORD,
+        key: &'static str,
+    },
+    /// There is no `[SimConnect.N]` section for the config index.
+    MissingSection(DWORD),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            Self::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            Self::InvalidValue { index, key, value } => write!(
+                f,
+                "invalid {} `{}` in section [SimConnect.{}]",
+                key, value, index
+            ),
+            Self::MissingKey { index, key } => {
+                write!(f, "section [SimConnect.{}] has no {}", index, key)
+            }
+            Self::MissingSection(index) => write!(f, "no section [SimConnect.{}]", index),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Options for [`SimConnector::connect_with_options`](crate::SimConnector::connect_with_options)
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    /// Section of `SimConnect.cfg` to connect with, `0` for `[SimConnect]`, or
+    /// [`SIMCONNECT_OPEN_CONFIGINDEX_LOCAL`](crate::SIMCONNECT_OPEN_CONFIGINDEX_LOCAL) to ignore the file and connect locally.
+    pub config_index: DWORD,
+    /// Win32 event SimConnect signals when a message is waiting, or null.
+    pub event_handle: HANDLE,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            config_index: 0,
+            event_handle: std::ptr::null_mut(),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Data definitions declared as Rust structs
+//!
+//! Implement [`SimConnectData`] with `#[derive(SimConnectData)]` (feature `derive`) instead of
+//! pairing `add_data_definition` calls with a packed struct by hand.
+
+use crate::*;
+
+/// A struct that describes a data definition and decodes the data SimConnect sends for it
+///
+/// The fields are registered and read in declaration order, so the layout always matches.
+/// With the `derive` feature:
+///
+#[cfg_attr(feature = "derive", doc = "```no_run")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// # use simconnect::*;
+/// # fn main() -> Result<(), SimConnectError> {
+/// # let mut conn = SimConnector::new();
+/// #[derive(SimConnectData)]
+/// struct Position {
+///     #[simvar("PLANE ALTITUDE", unit = "feet", epsilon = 1.0)]
+///     altitude: f64,
+///     #[simvar("SIM ON GROUND")]
+///     on_ground: bool,
+///     #[simvar("TITLE", string = 128)]
+///     title: String,
+/// }
+///
+/// Position::add_definition(&conn, 0)?;
+/// // ...
+/// if let DispatchResult::SimObjectData(data) = conn.get_next_message()? {
+///     let position: Position = data.decode()?;
+///     println!("{} at {} ft", position.title, position.altitude);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// `unit` defaults to the one of [`DataField::UNITS`] and `epsilon` to `0.0`. `String` fields
+/// take the length of their `STRINGn` type with `string = 8`, `32`, `64`, `128`, `256` (the
+/// default) or `260`, or are requested as a variable length `STRINGV` with `stringv`.
+///
+/// The fields are added with their index as datum ID, which tagged requests and
+/// [`SimConnector::set_data_tagged`] refer to them by.
+pub trait SimConnectData: Sized {
+    /// Adds every field to the data definition `define_id`.
+    fn add_definition<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError>;
+
+    /// Reads the fields from `reader`, which is left after the last one.
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError>;
+
+    /// The fields as [`Value`]s, in declaration order, for [`SimConnector::set_data`].
+    fn values(&self) -> Vec<Value>;
+
+    /// Decodes the payload of a received message.
+    fn from_payload<T: PayloadMessage>(payload: &Payload<'_, T>) -> Result<Self, SimConnectError> {
+        Self::read(&mut payload.reader())
+    }
+}
+
+/// A type that can be the field of a [`SimConnectData`] struct
+///
+/// `String` fields are handled by the derive itself, since their size is part of the
+/// attribute.
+pub trait DataField: Sized {
+    /// The type the value is requested as.
+    const DATATYPE: SIMCONNECT_DATATYPE;
+    /// Units used when the attribute doesn't name any.
+    const UNITS: &'static str;
+
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError>;
+
+    /// The field as the [`Value`] of its [`DATATYPE`](Self::DATATYPE).
+    fn value(&self) -> Value;
+}
+
+macro_rules! data_fields {
+    ($($ty:ty => $datatype:ident, $units:expr, $read:ident, $value:ident,)*) => {
+        $(
+            impl DataField for $ty {
+                const DATATYPE: SIMCONNECT_DATATYPE = $datatype;
+                const UNITS: &'static str = $units;
+
+                fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError> {
+                    reader.$read()
+                }
+
+                fn value(&self) -> Value {
+                    Value::$value(*self)
+                }
+            }
+        )*
+    };
+}
+
+data_fields! {
+    i32 => SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32, "number", read_i32, Int32,
+    i64 => SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64, "number", read_i64, Int64,
+    f32 => SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32, "number", read_f32, Float32,
+    f64 => SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64, "number", read_f64, Float64,
+}
+
+impl DataField for bool {
+    const DATATYPE: SIMCONNECT_DATATYPE = SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32;
+    const UNITS: &'static str = "bool";
+
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError> {
+        reader.read_i32().map(|value| value != 0)
+    }
+
+    fn value(&self) -> Value {
+        Value::Int32(*self as i32)
+    }
+}
@@ -0,0 +1,483 @@
+//! Data definitions known at runtime, and the values decoded with them
+//!
+//! Unlike [`SimConnectData`], a [`DataDefinition`] can mix any `SIMCONNECT_DATATYPE`s and
+//! decode data requested with `SIMCONNECT_DATA_REQUEST_FLAG_TAGGED`, where only the datums that
+//! changed are sent.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// One datum of a [`DataDefinition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datum {
+    /// The `datum_id` it was added with, which tagged data refers to.
+    pub datum_id: DWORD,
+    pub datatype: SIMCONNECT_DATATYPE,
+}
+
+/// The datums registered for a `define_id`, in the order they were added
+///
+/// ```no_run
+/// # use simconnect::*;
+/// # fn main() -> Result<(), SimConnectError> {
+/// let mut conn = SimConnector::new();
+/// conn.connect("Tagged data")?;
+///
+/// let float64 = SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64;
+/// let string256 = SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256;
+///
+/// let mut definition = DataDefinition::new(0);
+/// definition.add(&conn, "PLANE ALTITUDE", "feet", float64, 1, 100.0)?;
+/// definition.add(&conn, "TITLE", "", string256, 2, 0.0)?;
+///
+/// if let DispatchResult::SimObjectData(data) = conn.get_next_message()? {
+///     for value in definition.values(&data) {
+///         let (datum_id, value) = value?;
+///         println!("{}: {:?}", datum_id, value);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDefinition {
+    define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    datums: Vec<Datum>,
+}
+
+impl DataDefinition {
+    pub fn new(define_id: SIMCONNECT_DATA_DEFINITION_ID) -> Self {
+        Self {
+            define_id,
+            datums: Vec::new(),
+        }
+    }
+
+    pub fn define_id(&self) -> SIMCONNECT_DATA_DEFINITION_ID {
+        self.define_id
+    }
+
+    pub fn datums(&self) -> &[Datum] {
+        &self.datums
+    }
+
+    /// The datum added with `datum_id`.
+    pub fn datum(&self, datum_id: DWORD) -> Option<&Datum> {
+        self.datums.iter().find(|datum| datum.datum_id == datum_id)
+    }
+
+    /// Adds a datum with [`SimConnector::add_data_definition`] and records it.
+    #[track_caller]
+    pub fn add<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        datum_name: &str,
+        units_name: &str,
+        datatype: SIMCONNECT_DATATYPE,
+        datum_id: DWORD,
+        epsilon: f32,
+    ) -> Result<(), SimConnectError> {
+        conn.add_data_definition(
+            self.define_id,
+            datum_name,
+            units_name,
+            datatype,
+            datum_id,
+            epsilon,
+        )?;
+        self.push(datum_id, datatype);
+
+        Ok(())
+    }
+
+    /// Records a datum that was added to the definition some other way.
+    pub fn push(&mut self, datum_id: DWORD, datatype: SIMCONNECT_DATATYPE) -> &mut Self {
+        self.datums.push(Datum { datum_id, datatype });
+        self
+    }
+
+    /// The `(datum_id, value)` pairs received in `data`.
+    ///
+    /// Tagged data yields the datums that were sent, anything else every datum in order.
+    /// `data` has to be for this definition, its `dwDefineID` isn't checked. Iteration stops
+    /// after the first error.
+    pub fn values<'a, T: PayloadMessage>(&'a self, data: &Payload<'a, T>) -> Values<'a> {
+        let header = data.message().header();
+        let tagged = header.dwFlags & SIMCONNECT_DATA_REQUEST_FLAG_TAGGED != 0;
+
+        Values {
+            definition: self,
+            reader: data.reader(),
+            tagged,
+            index: 0,
+            count: if tagged {
+                header.dwDefineCount as usize
+            } else {
+                self.datums.len()
+            },
+        }
+    }
+
+    /// Encodes one value per datum, in order, for `SimConnect_SetDataOnSimObject`.
+    pub fn encode(&self, values: &[Value]) -> Result<Vec<u8>, SimConnectError> {
+        if values.len() != self.datums.len() {
+            return Err(SimConnectError::ValueCountMismatch {
+                define_id: self.define_id,
+                expected: self.datums.len(),
+                found: values.len(),
+            });
+        }
+
+        let mut out = Vec::new();
+        for (datum, value) in self.datums.iter().zip(values) {
+            value.write(datum.datatype, &mut out)?;
+        }
+
+        Ok(out)
+    }
+
+    /// Encodes `(datum_id, value)` pairs for `SimConnect_SetDataOnSimObject` with
+    /// `SIMCONNECT_DATA_SET_FLAG_TAGGED`, to set only some of the datums.
+    pub fn encode_tagged(&self, values: &[(DWORD, Value)]) -> Result<Vec<u8>, SimConnectError> {
+        let mut out = Vec::new();
+        for (datum_id, value) in values {
+            let datum = self.datum(*datum_id).ok_or(SimConnectError::UnknownDatum {
+                define_id: self.define_id,
+                datum_id: *datum_id,
+            })?;
+            out.extend_from_slice(&datum_id.to_le_bytes());
+            value.write(datum.datatype, &mut out)?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Length of the `STRING8` to `STRING260` types.
+fn fixed_string_len(datatype: SIMCONNECT_DATATYPE) -> Option<usize> {
+    Some(match datatype {
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8 => 8,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32 => 32,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING64 => 64,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING128 => 128,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256 => 256,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING260 => 260,
+        _ => return None,
+    })
+}
+
+/// Writes `value` into a NUL padded field of `len` bytes.
+fn put_fixed_string(out: &mut Vec<u8>, value: &str, len: usize) {
+    let bytes = &value.as_bytes()[..value.len().min(len - 1)];
+    out.extend_from_slice(bytes);
+    out.resize(out.len() + len - bytes.len(), 0);
+}
+
+/// Iterator returned by [`DataDefinition::values`]
+#[derive(Debug, Clone)]
+pub struct Values<'a> {
+    definition: &'a DataDefinition,
+    reader: PayloadReader<'a>,
+    tagged: bool,
+    index: usize,
+    count: usize,
+}
+
+impl Values<'_> {
+    fn read_next(&mut self) -> Result<(DWORD, Value), SimConnectError> {
+        let datum = if self.tagged {
+            let datum_id = self.reader.read_u32()?;
+            self.definition
+                .datum(datum_id)
+                .ok_or(SimConnectError::UnknownDatum {
+                    define_id: self.definition.define_id,
+                    datum_id,
+                })?
+        } else {
+            &self.definition.datums[self.index]
+        };
+
+        Ok((
+            datum.datum_id,
+            Value::read(&mut self.reader, datum.datatype)?,
+        ))
+    }
+}
+
+impl Iterator for Values<'_> {
+    type Item = Result<(DWORD, Value), SimConnectError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let result = self.read_next();
+        self.index = if result.is_ok() {
+            self.index + 1
+        } else {
+            self.count
+        };
+
+        Some(result)
+    }
+}
+
+/// `SIMCONNECT_DATA_LATLONALT`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LatLonAlt {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+/// `SIMCONNECT_DATA_PBH`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pbh {
+    pub pitch: f32,
+    pub bank: f32,
+    pub heading: f32,
+}
+
+/// `SIMCONNECT_DATA_XYZ`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Xyz {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// `SIMCONNECT_DATA_INITPOSITION`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InitPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub pitch: f64,
+    pub bank: f64,
+    pub heading: f64,
+    pub on_ground: bool,
+    pub airspeed: DWORD,
+}
+
+/// `SIMCONNECT_DATA_MARKERSTATE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MarkerState {
+    pub name: String,
+    pub on: bool,
+}
+
+/// `SIMCONNECT_DATA_WAYPOINT`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Waypoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// `SIMCONNECT_WAYPOINT_*` flags.
+    pub flags: DWORD,
+    pub speed: f64,
+    pub throttle: f64,
+}
+
+/// A datum value of any `SIMCONNECT_DATATYPE`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value {
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    /// `STRING8` to `STRING260` up to the first NUL, or `STRINGV`.
+    String(String),
+    InitPosition(InitPosition),
+    MarkerState(MarkerState),
+    Waypoint(Waypoint),
+    LatLonAlt(LatLonAlt),
+    Xyz(Xyz),
+}
+
+impl Value {
+    /// Reads a value of `datatype`.
+    pub fn read(
+        reader: &mut PayloadReader<'_>,
+        datatype: SIMCONNECT_DATATYPE,
+    ) -> Result<Self, SimConnectError> {
+        if datatype == SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV {
+            return Ok(Self::String(reader.read_string_v()?));
+        }
+
+        // Checked up front, so a failing read leaves the reader where it was
+        let size = Self::size(datatype).ok_or(SimConnectError::InvalidDatatype(datatype))?;
+        let mut value = PayloadReader::new(reader.read_bytes(size)?);
+        let value = &mut value;
+
+        Ok(match datatype {
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32 => Self::Int32(value.read_i32()?),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64 => Self::Int64(value.read_i64()?),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32 => Self::Float32(value.read_f32()?),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64 => Self::Float64(value.read_f64()?),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INITPOSITION => {
+                Self::InitPosition(InitPosition {
+                    latitude: value.read_f64()?,
+                    longitude: value.read_f64()?,
+                    altitude: value.read_f64()?,
+                    pitch: value.read_f64()?,
+                    bank: value.read_f64()?,
+                    heading: value.read_f64()?,
+                    on_ground: value.read_u32()? != 0,
+                    airspeed: value.read_u32()?,
+                })
+            }
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_MARKERSTATE => Self::MarkerState(MarkerState {
+                name: value.read_string64()?,
+                on: value.read_u32()? != 0,
+            }),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_WAYPOINT => Self::Waypoint(Waypoint {
+                latitude: value.read_f64()?,
+                longitude: value.read_f64()?,
+                altitude: value.read_f64()?,
+                flags: value.read_u32()?,
+                speed: value.read_f64()?,
+                throttle: value.read_f64()?,
+            }),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT => Self::LatLonAlt(LatLonAlt {
+                latitude: value.read_f64()?,
+                longitude: value.read_f64()?,
+                altitude: value.read_f64()?,
+            }),
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_XYZ => Self::Xyz(Xyz {
+                x: value.read_f64()?,
+                y: value.read_f64()?,
+                z: value.read_f64()?,
+            }),
+            // The fixed size strings
+            _ => Self::String(value.read_string(size)?),
+        })
+    }
+
+    /// Appends the value to `out` as `datatype`, in the layout SimConnect expects for
+    /// `SimConnect_SetDataOnSimObject`.
+    ///
+    /// Fixed size strings are truncated to leave room for their NUL. Fails with
+    /// [`SimConnectError::InvalidDatatype`] if the value isn't of that type.
+    pub fn write(
+        &self,
+        datatype: SIMCONNECT_DATATYPE,
+        out: &mut Vec<u8>,
+    ) -> Result<(), SimConnectError> {
+        fn put_f64s(out: &mut Vec<u8>, values: &[f64]) {
+            for value in values {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        match (self, datatype) {
+            (Self::Int32(value), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32) => {
+                out.extend_from_slice(&value.to_le_bytes())
+            }
+            (Self::Int64(value), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64) => {
+                out.extend_from_slice(&value.to_le_bytes())
+            }
+            (Self::Float32(value), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32) => {
+                out.extend_from_slice(&value.to_le_bytes())
+            }
+            (Self::Float64(value), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64) => {
+                out.extend_from_slice(&value.to_le_bytes())
+            }
+            (Self::String(value), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV) => {
+                // What `SimConnect_InsertString` does: the string and its NUL
+                out.extend_from_slice(value.as_bytes());
+                out.push(0);
+            }
+            (Self::InitPosition(p), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INITPOSITION) => {
+                put_f64s(
+                    out,
+                    &[
+                        p.latitude,
+                        p.longitude,
+                        p.altitude,
+                        p.pitch,
+                        p.bank,
+                        p.heading,
+                    ],
+                );
+                out.extend_from_slice(&u32::from(p.on_ground).to_le_bytes());
+                out.extend_from_slice(&p.airspeed.to_le_bytes());
+            }
+            (Self::MarkerState(m), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_MARKERSTATE) => {
+                put_fixed_string(out, &m.name, 64);
+                out.extend_from_slice(&u32::from(m.on).to_le_bytes());
+            }
+            (Self::Waypoint(w), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_WAYPOINT) => {
+                put_f64s(out, &[w.latitude, w.longitude, w.altitude]);
+                out.extend_from_slice(&w.flags.to_le_bytes());
+                put_f64s(out, &[w.speed, w.throttle]);
+            }
+            (Self::LatLonAlt(l), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT) => {
+                put_f64s(out, &[l.latitude, l.longitude, l.altitude])
+            }
+            (Self::Xyz(xyz), SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_XYZ) => {
+                put_f64s(out, &[xyz.x, xyz.y, xyz.z])
+            }
+            (Self::String(value), _) => match fixed_string_len(datatype) {
+                Some(len) => put_fixed_string(out, value, len),
+                None => return Err(SimConnectError::InvalidDatatype(datatype)),
+            },
+            _ => return Err(SimConnectError::InvalidDatatype(datatype)),
+        }
+
+        Ok(())
+    }
+
+    /// Size in bytes of a value of `datatype`, `None` if it has no fixed size.
+    pub fn size(datatype: SIMCONNECT_DATATYPE) -> Option<usize> {
+        Some(match datatype {
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32
+            | SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32 => 4,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64
+            | SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64 => 8,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INITPOSITION => 56,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_MARKERSTATE => 68,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_WAYPOINT => 44,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT
+            | SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_XYZ => 24,
+            _ => return fixed_string_len(datatype),
+        })
+    }
+
+    /// The value as a float, if it is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Int32(value) => Some(value.into()),
+            Self::Int64(value) => Some(value as f64),
+            Self::Float32(value) => Some(value.into()),
+            Self::Float64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Int32(value) => Some(value.into()),
+            Self::Int64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
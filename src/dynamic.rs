@@ -0,0 +1,147 @@
+//! Loading the SimConnect library at runtime (`dynamic` feature).
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use libloading::Library;
+
+use crate::functions::for_each_function;
+use crate::*;
+
+/// Which SimConnect DLL a [`FfiBackend`] loads
+///
+/// The client libraries of the different simulators are all called `SimConnect.dll`. To ship
+/// them side by side, the P3D and FSX ones are expected to be renamed as listed below. Bare file
+/// names are looked up with the usual DLL search order, i.e. next to the executable first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SimulatorDll {
+    /// `SimConnect.dll` from the MSFS SDK
+    #[default]
+    Msfs,
+    /// `SimConnect_P3D.dll`, the Prepar3D client library
+    P3d,
+    /// `SimConnect_FSX.dll`, the FSX SP2 client library
+    Fsx,
+    /// A library at an arbitrary path
+    Path(PathBuf),
+}
+
+impl SimulatorDll {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Msfs => Path::new("SimConnect.dll"),
+            Self::P3d => Path::new("SimConnect_P3D.dll"),
+            Self::Fsx => Path::new("SimConnect_FSX.dll"),
+            Self::Path(path) => path,
+        }
+    }
+}
+
+/// Entry points without which the library is useless. Everything else may be missing, e.g. the
+/// `_EX1` functions in older libraries, and only fails when called.
+const REQUIRED: &[&str] = &[
+    "SimConnect_Open",
+    "SimConnect_Close",
+    "SimConnect_GetNextDispatch",
+    "SimConnect_GetLastSentPacketID",
+];
+
+macro_rules! declare_api {
+    ($(fn $name:ident($($arg:ident: $ty:ty),* $(,)?);)*) => {
+        /// The entry points of a loaded SimConnect library
+        pub(crate) struct Api {
+            path: PathBuf,
+            $($name: Option<unsafe extern "C" fn($($ty),*) -> HRESULT>,)*
+            _library: Library,
+        }
+
+        impl Api {
+            pub(crate) fn load(path: &Path) -> Result<Self, SimConnectError> {
+                let library = unsafe { Library::new(path) }.map_err(|source| {
+                    SimConnectError::LibraryNotFound {
+                        path: path.to_owned(),
+                        source,
+                    }
+                })?;
+
+                let api = unsafe {
+                    Self {
+                        path: path.to_owned(),
+                        $($name: library
+                            .get(concat!(stringify!($name), "\0").as_bytes())
+                            .ok()
+                            .map(|symbol| *symbol),)*
+                        _library: library,
+                    }
+                };
+
+                match REQUIRED.iter().find(|symbol| !api.has(symbol)) {
+                    Some(symbol) => Err(api.missing(symbol)),
+                    None => Ok(api),
+                }
+            }
+
+            fn has(&self, symbol: &str) -> bool {
+                match symbol {
+                    $(stringify!($name) => self.$name.is_some(),)*
+                    _ => false,
+                }
+            }
+        }
+
+        // The table covers the whole library, the backend only calls what it wraps
+        #[allow(dead_code)]
+        impl Api {
+            $(
+                pub(crate) unsafe fn $name(&self, $($arg: $ty),*) -> Result<HRESULT, SimConnectError> {
+                    match self.$name {
+                        Some(function) => Ok(function($($arg),*)),
+                        None => Err(self.missing(stringify!($name))),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+for_each_function!(declare_api);
+
+impl Api {
+    fn missing(&self, symbol: &'static str) -> SimConnectError {
+        SimConnectError::SymbolMissing {
+            path: self.path.clone(),
+            symbol,
+        }
+    }
+}
+
+impl fmt::Debug for Api {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Api")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FfiBackend {
+    pub fn with_dll(dll: SimulatorDll) -> Self {
+        Self {
+            dll,
+            ..Self::default()
+        }
+    }
+
+    pub fn dll(&self) -> &SimulatorDll {
+        &self.dll
+    }
+
+    /// The loaded library, loading it on first use.
+    pub(crate) fn api(&self) -> Result<&Api, SimConnectError> {
+        if let Some(api) = self.api.get() {
+            return Ok(api);
+        }
+
+        let api = Api::load(self.dll.path())?;
+        Ok(self.api.get_or_init(|| api))
+    }
+}
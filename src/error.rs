@@ -0,0 +1,420 @@
+use std::error::Error;
+use std::ffi::{CString, NulError};
+use std::fmt;
+use std::io;
+#[cfg(feature = "dynamic")]
+use std::path::PathBuf;
+
+use crate::catalog::CatalogError;
+use crate::metar::MetarError;
+use crate::rpc::RpcError;
+use crate::thermal::ThermalError;
+use crate::{
+    KeyParam, KeyValue, DWORD, HRESULT, SIMCONNECT_CLIENTDATA_MAX_SIZE, SIMCONNECT_DATATYPE,
+};
+
+/// `HRESULT` returned by SimConnect when an argument is out of range or otherwise invalid.
+const E_INVALIDARG: HRESULT = 0x8007_0057_u32 as HRESULT;
+
+/// Unspecified failure, also what `SimConnect_GetNextDispatch` returns when the queue is empty.
+pub(crate) const E_FAIL: HRESULT = 0x8000_4005_u32 as HRESULT;
+
+/// Failing `HRESULT` with the customer bit set, returned by the stand-ins for the SimConnect
+/// functions when the crate is built without the SimConnect library.
+pub(crate) const E_UNAVAILABLE: HRESULT = 0xA000_0001_u32 as HRESULT;
+
+/// Errors returned by the [`SimConnector`](crate::SimConnector) wrappers
+#[derive(Debug)]
+pub enum SimConnectError {
+    /// A `SimConnect_*` call returned a failing `HRESULT`.
+    CallFailed {
+        /// Name of the SimConnect function that failed, e.g. `"SimConnect_Open"`.
+        call: &'static str,
+        hresult: HRESULT,
+    },
+    /// A string argument contains an interior NUL byte and cannot be passed to SimConnect.
+    InteriorNul {
+        call: &'static str,
+        /// Name of the offending argument of the wrapper method.
+        argument: &'static str,
+        source: NulError,
+    },
+    /// A string argument is longer than the `max` bytes that fit its fixed size buffer.
+    StringTooLong {
+        call: &'static str,
+        argument: &'static str,
+        len: usize,
+        max: usize,
+    },
+    /// The crate was built without the SimConnect library (e.g. on a non-Windows host or with
+    /// the `no-link` feature), or the backend does not support `call`.
+    Unavailable { call: &'static str },
+    /// Talking to a remote simulator over the network failed.
+    Io {
+        call: &'static str,
+        source: io::Error,
+    },
+    /// The SimConnect library could not be loaded.
+    #[cfg(feature = "dynamic")]
+    LibraryNotFound {
+        path: PathBuf,
+        source: libloading::Error,
+    },
+    /// The SimConnect library does not export `symbol`, e.g. because it belongs to an older
+    /// simulator.
+    #[cfg(feature = "dynamic")]
+    SymbolMissing { path: PathBuf, symbol: &'static str },
+    /// A [`PayloadReader`](crate::PayloadReader) was asked for `len` bytes at `offset`, past the
+    /// end of the `size` bytes received.
+    PayloadOutOfBounds {
+        offset: usize,
+        len: usize,
+        size: usize,
+    },
+    /// A data definition was rejected by the [`catalog`](crate::catalog).
+    Catalog(CatalogError),
+    /// `SimConnect_GetNextDispatch` returned a message with a `SIMCONNECT_RECV_ID` this crate
+    /// does not know about.
+    UnhandledRecvId(DWORD),
+    /// `SimConnect_GetNextDispatch` returned a message of `size` bytes, too short for the
+    /// `expected` bytes of the struct its `SIMCONNECT_RECV_ID` `id` stands for.
+    TruncatedMessage {
+        id: DWORD,
+        size: usize,
+        expected: usize,
+    },
+    /// Tagged data carried a datum the [`DataDefinition`](crate::DataDefinition) doesn't have.
+    UnknownDatum { define_id: DWORD, datum_id: DWORD },
+    /// A value can't be read or written as this `SIMCONNECT_DATATYPE`.
+    InvalidDatatype(SIMCONNECT_DATATYPE),
+    /// `value` isn't a valid parameter for the key event `event`.
+    InvalidEventData {
+        event: &'static str,
+        value: KeyValue,
+        expected: KeyParam,
+    },
+    /// The number of values to encode doesn't match the datums of the definition.
+    ValueCountMismatch {
+        define_id: DWORD,
+        expected: usize,
+        found: usize,
+    },
+    /// Data was written for a definition no datums were added to.
+    UndefinedDefinition(DWORD),
+    /// Data of the type `type_name` was written before
+    /// [`define_data`](crate::SimConnector::define_data) registered it.
+    UnregisteredData(&'static str),
+    /// A request over an [`RpcChannel`](crate::rpc::RpcChannel) failed.
+    Rpc(RpcError),
+    /// A [`Metar`](crate::metar::Metar) couldn't be encoded.
+    Metar(MetarError),
+    /// A [`Thermal`](crate::thermal::Thermal) exceeds `MAX_THERMAL_SIZE` or `MAX_THERMAL_RATE`.
+    Thermal(ThermalError),
+    /// A client data area laid out like a struct of `size` bytes would exceed
+    /// `SIMCONNECT_CLIENTDATA_MAX_SIZE`.
+    ClientDataTooLarge { data_id: DWORD, size: usize },
+    /// An element of an array write encoded to a different size than the first one.
+    DataSizeMismatch {
+        define_id: DWORD,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl SimConnectError {
+    /// Name of the SimConnect function the error relates to, if any.
+    pub fn call(&self) -> Option<&'static str> {
+        match self {
+            Self::CallFailed { call, .. }
+            | Self::InteriorNul { call, .. }
+            | Self::StringTooLong { call, .. }
+            | Self::Unavailable { call }
+            | Self::Io { call, .. } => Some(call),
+            #[cfg(feature = "dynamic")]
+            Self::SymbolMissing { symbol, .. } => Some(symbol),
+            #[cfg(feature = "dynamic")]
+            Self::LibraryNotFound { .. } => None,
+            Self::PayloadOutOfBounds { .. }
+            | Self::Catalog(_)
+            | Self::UnhandledRecvId(_)
+            | Self::TruncatedMessage { .. }
+            | Self::UnknownDatum { .. }
+            | Self::InvalidDatatype(_)
+            | Self::ValueCountMismatch { .. }
+            | Self::InvalidEventData { .. }
+            | Self::UndefinedDefinition(_)
+            | Self::UnregisteredData(_)
+            | Self::DataSizeMismatch { .. }
+            | Self::ClientDataTooLarge { .. }
+            | Self::Rpc(_)
+            | Self::Metar(_)
+            | Self::Thermal(_) => None,
+        }
+    }
+
+    /// The failing `HRESULT`, if the error came from SimConnect itself.
+    pub fn hresult(&self) -> Option<HRESULT> {
+        match self {
+            Self::CallFailed { hresult, .. } => Some(*hresult),
+            _ => None,
+        }
+    }
+
+    /// Whether this is `SimConnect_Open` failing, which almost always means the simulator is
+    /// not running (or not accepting connections yet).
+    pub fn is_sim_not_running(&self) -> bool {
+        matches!(
+            self,
+            Self::CallFailed {
+                call: "SimConnect_Open",
+                ..
+            } | Self::Io {
+                call: "SimConnect_Open",
+                ..
+            }
+        )
+    }
+
+    /// Whether the error was caused by an argument passed to the wrapper rather than by the
+    /// state of the connection.
+    pub fn is_bad_argument(&self) -> bool {
+        match self {
+            Self::InteriorNul { .. }
+            | Self::Catalog(_)
+            | Self::InvalidEventData { .. }
+            | Self::ClientDataTooLarge { .. }
+            | Self::Metar(_)
+            | Self::Thermal(_)
+            | Self::StringTooLong { .. } => true,
+            Self::CallFailed { hresult, .. } => *hresult == E_INVALIDARG,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SimConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CallFailed { call, hresult } => {
+                write!(f, "{} failed with HRESULT {:#010x}", call, *hresult as u32)
+            }
+            Self::InteriorNul { call, argument, .. } => write!(
+                f,
+                "argument `{}` of {} contains an interior NUL byte",
+                argument, call
+            ),
+            Self::StringTooLong {
+                call,
+                argument,
+                len,
+                max,
+            } => write!(
+                f,
+                "argument `{}` of {} is {} bytes long, at most {} fit",
+                argument, call, len, max
+            ),
+            Self::Unavailable { call } => write!(
+                f,
+                "{} is not available, the crate was built without the SimConnect library \
+                 or the backend does not support it",
+                call
+            ),
+            Self::Io { call, source } => write!(f, "{} failed: {}", call, source),
+            #[cfg(feature = "dynamic")]
+            Self::LibraryNotFound { path, source } => {
+                write!(f, "failed to load {}: {}", path.display(), source)
+            }
+            #[cfg(feature = "dynamic")]
+            Self::SymbolMissing { path, symbol } => {
+                write!(f, "{} does not export {}", path.display(), symbol)
+            }
+            Self::PayloadOutOfBounds { offset, len, size } => write!(
+                f,
+                "reading {} bytes at offset {} exceeds the payload of {} bytes",
+                len, offset, size
+            ),
+            Self::Catalog(e) => write!(f, "invalid data definition: {}", e),
+            Self::UnhandledRecvId(id) => write!(f, "unhandled SIMCONNECT_RECV_ID {}", id),
+            Self::TruncatedMessage { id, size, expected } => write!(
+                f,
+                "message with SIMCONNECT_RECV_ID {} has {} bytes, expected at least {}",
+                id, size, expected
+            ),
+            Self::UnknownDatum {
+                define_id,
+                datum_id,
+            } => write!(
+                f,
+                "datum {} is not part of data definition {}",
+                datum_id, define_id
+            ),
+            Self::InvalidDatatype(datatype) => {
+                write!(f, "unsupported SIMCONNECT_DATATYPE {}", datatype)
+            }
+            Self::InvalidEventData {
+                event,
+                value,
+                expected,
+            } => write!(
+                f,
+                "{:?} is not valid for {}, expected {}",
+                value, event, expected
+            ),
+            Self::ValueCountMismatch {
+                define_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "data definition {} has {} datums, got {} values",
+                define_id, expected, found
+            ),
+            Self::UndefinedDefinition(define_id) => {
+                write!(f, "data definition {} has no datums", define_id)
+            }
+            Self::UnregisteredData(type_name) => write!(
+                f,
+                "{} has no data definition, register it with define_data first",
+                type_name
+            ),
+            Self::Rpc(e) => write!(f, "RPC failed: {}", e),
+            Self::Metar(e) => write!(f, "invalid METAR: {}", e),
+            Self::Thermal(e) => write!(f, "invalid thermal: {}", e),
+            Self::ClientDataTooLarge { data_id, size } => write!(
+                f,
+                "client data area {} needs {} bytes, more than the {} of SIMCONNECT_CLIENTDATA_MAX_SIZE",
+                data_id, size, SIMCONNECT_CLIENTDATA_MAX_SIZE
+            ),
+            Self::DataSizeMismatch {
+                define_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "elements of data definition {} have to be {} bytes, got {}",
+                define_id, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for SimConnectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InteriorNul { source, .. } => Some(source),
+            Self::Io { source, .. } => Some(source),
+            Self::Catalog(source) => Some(source),
+            Self::Rpc(source) => Some(source),
+            Self::Metar(source) => Some(source),
+            Self::Thermal(source) => Some(source),
+            #[cfg(feature = "dynamic")]
+            Self::LibraryNotFound { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Maps the `HRESULT` of a SimConnect call to a `Result`.
+pub(crate) fn check(call: &'static str, hresult: HRESULT) -> Result<(), SimConnectError> {
+    if hresult >= 0 {
+        Ok(())
+    } else if hresult == E_UNAVAILABLE {
+        Err(SimConnectError::Unavailable { call })
+    } else {
+        Err(SimConnectError::CallFailed { call, hresult })
+    }
+}
+
+/// Converts a string argument of `call` to a `CString`, reporting interior NUL bytes.
+pub(crate) fn to_cstring(
+    call: &'static str,
+    argument: &'static str,
+    value: &str,
+) -> Result<CString, SimConnectError> {
+    CString::new(value).map_err(|source| SimConnectError::InteriorNul {
+        call,
+        argument,
+        source,
+    })
+}
+
+/// Checks that the string argument `argument` of `call` fits a buffer of `max` bytes plus the
+/// NUL.
+pub(crate) fn check_length(
+    call: &'static str,
+    argument: &'static str,
+    value: &str,
+    max: usize,
+) -> Result<(), SimConnectError> {
+    if value.len() > max {
+        return Err(SimConnectError::StringTooLong {
+            call,
+            argument,
+            len: value.len(),
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_hresults() {
+        assert!(check("SimConnect_Open", 0).is_ok());
+        assert!(check("SimConnect_Open", 1).is_ok());
+
+        let error = check("SimConnect_Open", E_UNAVAILABLE).unwrap_err();
+        assert!(matches!(
+            error,
+            SimConnectError::Unavailable {
+                call: "SimConnect_Open"
+            }
+        ));
+        assert_eq!(error.hresult(), None);
+
+        let error = check("SimConnect_Open", E_FAIL).unwrap_err();
+        assert_eq!(error.hresult(), Some(E_FAIL));
+        assert_eq!(error.call(), Some("SimConnect_Open"));
+        assert!(error.is_sim_not_running());
+        assert!(!error.is_bad_argument());
+    }
+
+    #[test]
+    fn flags_invalid_arguments() {
+        let error = check("SimConnect_SetDataOnSimObject", E_INVALIDARG).unwrap_err();
+        assert!(error.is_bad_argument());
+        assert!(!error.is_sim_not_running());
+
+        let error = check("SimConnect_SetDataOnSimObject", E_FAIL).unwrap_err();
+        assert!(!error.is_bad_argument());
+    }
+
+    #[test]
+    fn reports_interior_nuls() {
+        assert_eq!(
+            to_cstring("SimConnect_Open", "name", "app")
+                .unwrap()
+                .as_bytes(),
+            b"app"
+        );
+
+        let error = to_cstring("SimConnect_Open", "name", "a\0b").unwrap_err();
+        match &error {
+            SimConnectError::InteriorNul {
+                call,
+                argument,
+                source,
+            } => {
+                assert_eq!((*call, *argument), ("SimConnect_Open", "name"));
+                assert_eq!(source.nul_position(), 1);
+            }
+            other => panic!("expected InteriorNul, got {:?}", other),
+        }
+        assert!(error.is_bad_argument());
+        assert!(error.source().is_some());
+    }
+}
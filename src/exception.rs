@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::Location;
+
+use crate::*;
+
+/// Number of sent packets remembered by a [`SimConnector`] for exception lookups.
+pub(crate) const DEFAULT_PACKET_HISTORY: usize = 256;
+
+/// Decoded `SIMCONNECT_EXCEPTION` code of a [`SIMCONNECT_RECV_EXCEPTION`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimConnectException {
+    None,
+    Error,
+    SizeMismatch,
+    UnrecognizedId,
+    Unopened,
+    VersionMismatch,
+    TooManyGroups,
+    NameUnrecognized,
+    TooManyEventNames,
+    EventIdDuplicate,
+    TooManyMaps,
+    TooManyObjects,
+    TooManyRequests,
+    WeatherInvalidPort,
+    WeatherInvalidMetar,
+    WeatherUnableToGetObservation,
+    WeatherUnableToCreateStation,
+    WeatherUnableToRemoveStation,
+    InvalidDataType,
+    InvalidDataSize,
+    DataError,
+    InvalidArray,
+    CreateObjectFailed,
+    LoadFlightplanFailed,
+    OperationInvalidForObjectType,
+    IllegalOperation,
+    AlreadySubscribed,
+    InvalidEnum,
+    DefinitionError,
+    DuplicateId,
+    DatumId,
+    OutOfBounds,
+    AlreadyCreated,
+    ObjectOutsideRealityBubble,
+    ObjectContainer,
+    ObjectAi,
+    ObjectAtc,
+    ObjectSchedule,
+    JetwayData,
+    ActionNotFound,
+    NotAnAction,
+    IncorrectActionParams,
+    GetInputEventFailed,
+    SetInputEventFailed,
+    /// A code this crate does not know about, e.g. from a newer simulator.
+    Unknown(DWORD),
+}
+
+impl SimConnectException {
+    /// Decodes the `dwException` field of a [`SIMCONNECT_RECV_EXCEPTION`].
+    pub fn from_raw(code: DWORD) -> Self {
+        match code as SIMCONNECT_EXCEPTION {
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NONE => Self::None,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ERROR => Self::Error,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SIZE_MISMATCH => Self::SizeMismatch,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNRECOGNIZED_ID => Self::UnrecognizedId,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNOPENED => Self::Unopened,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_VERSION_MISMATCH => Self::VersionMismatch,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_GROUPS => Self::TooManyGroups,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED => Self::NameUnrecognized,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_EVENT_NAMES => {
+                Self::TooManyEventNames
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_EVENT_ID_DUPLICATE => Self::EventIdDuplicate,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_MAPS => Self::TooManyMaps,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_OBJECTS => Self::TooManyObjects,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_REQUESTS => Self::TooManyRequests,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_PORT => {
+                Self::WeatherInvalidPort
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_METAR => {
+                Self::WeatherInvalidMetar
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_GET_OBSERVATION => {
+                Self::WeatherUnableToGetObservation
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_CREATE_STATION => {
+                Self::WeatherUnableToCreateStation
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_REMOVE_STATION => {
+                Self::WeatherUnableToRemoveStation
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_TYPE => Self::InvalidDataType,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_SIZE => Self::InvalidDataSize,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATA_ERROR => Self::DataError,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ARRAY => Self::InvalidArray,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_CREATE_OBJECT_FAILED => {
+                Self::CreateObjectFailed
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_LOAD_FLIGHTPLAN_FAILED => {
+                Self::LoadFlightplanFailed
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OPERATION_INVALID_FOR_OBJECT_TYPE => {
+                Self::OperationInvalidForObjectType
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ILLEGAL_OPERATION => Self::IllegalOperation,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_SUBSCRIBED => Self::AlreadySubscribed,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ENUM => Self::InvalidEnum,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DEFINITION_ERROR => Self::DefinitionError,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DUPLICATE_ID => Self::DuplicateId,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATUM_ID => Self::DatumId,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OUT_OF_BOUNDS => Self::OutOfBounds,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_CREATED => Self::AlreadyCreated,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_OUTSIDE_REALITY_BUBBLE => {
+                Self::ObjectOutsideRealityBubble
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_CONTAINER => Self::ObjectContainer,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_AI => Self::ObjectAi,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_ATC => Self::ObjectAtc,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_SCHEDULE => Self::ObjectSchedule,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_JETWAY_DATA => Self::JetwayData,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ACTION_NOT_FOUND => Self::ActionNotFound,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NOT_AN_ACTION => Self::NotAnAction,
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INCORRECT_ACTION_PARAMS => {
+                Self::IncorrectActionParams
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_GET_INPUT_EVENT_FAILED => {
+                Self::GetInputEventFailed
+            }
+            SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SET_INPUT_EVENT_FAILED => {
+                Self::SetInputEventFailed
+            }
+            _ => Self::Unknown(code),
+        }
+    }
+
+    /// Human readable explanation of the exception, paraphrasing the SimConnect SDK documentation.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::None => "no exception",
+            Self::Error => "an unspecific error has occurred",
+            Self::SizeMismatch => "the size of the data provided does not match the size required",
+            Self::UnrecognizedId => "the client event, request ID, data definition ID, or object ID was not recognized",
+            Self::Unopened => "communication with the SimConnect server has not been opened",
+            Self::VersionMismatch => "a versioning error has occurred",
+            Self::TooManyGroups => "the maximum number of groups allowed has been reached",
+            Self::NameUnrecognized => "the simulation event name is not recognized",
+            Self::TooManyEventNames => "the maximum number of event names allowed has been reached",
+            Self::EventIdDuplicate => "the event ID has been used already",
+            Self::TooManyMaps => "the maximum number of mappings allowed has been reached",
+            Self::TooManyObjects => "the maximum number of objects allowed has been reached",
+            Self::TooManyRequests => "the maximum number of requests allowed has been reached",
+            Self::WeatherInvalidPort => "an invalid port number was requested",
+            Self::WeatherInvalidMetar => "the METAR data is invalid",
+            Self::WeatherUnableToGetObservation => "the weather observation could not be retrieved",
+            Self::WeatherUnableToCreateStation => "the weather station could not be created",
+            Self::WeatherUnableToRemoveStation => "the weather station could not be removed",
+            Self::InvalidDataType => "the data type requested does not apply to the type of data requested",
+            Self::InvalidDataSize => "the size of the data provided is not what is expected",
+            Self::DataError => "a generic data error",
+            Self::InvalidArray => "an invalid array has been sent",
+            Self::CreateObjectFailed => "the attempt to create an AI object failed",
+            Self::LoadFlightplanFailed => "the specified flight plan could not be found, or did not load correctly",
+            Self::OperationInvalidForObjectType => "the operation requested does not apply to the object type",
+            Self::IllegalOperation => "the AI operation requested cannot be completed",
+            Self::AlreadySubscribed => "the client has already subscribed to that event",
+            Self::InvalidEnum => "the member of the enumeration provided was not valid",
+            Self::DefinitionError => "there is a problem with a data definition",
+            Self::DuplicateId => "the ID has already been used",
+            Self::DatumId => "the datum ID is not recognized",
+            Self::OutOfBounds => "the radius given was outside the acceptable range, or a parameter was out of bounds",
+            Self::AlreadyCreated => "a client data area with the requested name has already been created by another addon",
+            Self::ObjectOutsideRealityBubble => "the object is outside the reality bubble",
+            Self::ObjectContainer => "the object could not be created because of an error in its container",
+            Self::ObjectAi => "the object could not be created because of an error in its AI",
+            Self::ObjectAtc => "the object could not be created because of an error in its ATC",
+            Self::ObjectSchedule => "the object could not be created because of an error in its schedule",
+            Self::JetwayData => "the jetway data could not be retrieved",
+            Self::ActionNotFound => "the action was not found",
+            Self::NotAnAction => "the name given is not an action",
+            Self::IncorrectActionParams => "the parameters given to the action are incorrect",
+            Self::GetInputEventFailed => "the input event could not be read",
+            Self::SetInputEventFailed => "the input event could not be set",
+            Self::Unknown(_) => "unknown exception",
+        }
+    }
+}
+
+impl From<DWORD> for SimConnectException {
+    fn from(code: DWORD) -> Self {
+        Self::from_raw(code)
+    }
+}
+
+impl fmt::Display for SimConnectException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "unknown exception {}", code),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+/// A packet sent to SimConnect by one of the [`SimConnector`] wrappers
+#[derive(Debug, Clone)]
+pub struct SentPacket {
+    /// ID reported by `SimConnect_GetLastSentPacketID`, matched against `dwSendID`.
+    pub send_id: DWORD,
+    /// Name of the SimConnect function, e.g. `"SimConnect_AddToDataDefinition"`.
+    pub call: &'static str,
+    /// The arguments of the wrapper method, formatted as `name: value` pairs.
+    pub args: String,
+    /// Where the wrapper method was called from.
+    pub location: Option<&'static Location<'static>>,
+}
+
+impl fmt::Display for SentPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.call, self.args)?;
+        if let Some(location) = self.location {
+            write!(f, " at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`SIMCONNECT_RECV_EXCEPTION`] matched back to the packet that caused it
+#[derive(Debug, Clone)]
+pub struct ResolvedException {
+    pub exception: SimConnectException,
+    /// Index of the offending parameter, as reported in `dwIndex`.
+    pub index: DWORD,
+    pub send_id: DWORD,
+    /// The packet with a matching send ID, if it is still in the history.
+    pub packet: Option<SentPacket>,
+}
+
+impl fmt::Display for ResolvedException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (parameter {})", self.exception, self.index)?;
+        match &self.packet {
+            Some(packet) => write!(f, " caused by {}", packet),
+            None => write!(f, " caused by unknown packet {}", self.send_id),
+        }
+    }
+}
+
+/// Bounded history of [`SentPacket`]s, oldest first. Disabled with a capacity of `0`.
+#[derive(Debug)]
+pub(crate) struct PacketHistory {
+    packets: VecDeque<SentPacket>,
+    capacity: usize,
+}
+
+impl Default for PacketHistory {
+    fn default() -> Self {
+        Self {
+            packets: VecDeque::new(),
+            capacity: DEFAULT_PACKET_HISTORY,
+        }
+    }
+}
+
+impl PacketHistory {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.packets.len() > capacity {
+            self.packets.pop_front();
+        }
+    }
+
+    pub(crate) fn push(&mut self, packet: SentPacket) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.packets.len() == self.capacity {
+            self.packets.pop_front();
+        }
+        self.packets.push_back(packet);
+    }
+
+    pub(crate) fn find(&self, send_id: DWORD) -> Option<&SentPacket> {
+        self.packets.iter().rev().find(|p| p.send_id == send_id)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.packets.clear();
+    }
+}
+
+/// Formats the arguments of a wrapper method as `name: value` pairs, lazily.
+macro_rules! args {
+    ($($arg:ident),* $(,)?) => {
+        || {
+            let args: &[(&str, &dyn std::fmt::Debug)] = &[$((stringify!($arg), &$arg)),*];
+            args.iter()
+                .map(|(name, value)| format!("{}: {:?}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+}
+
+pub(crate) use args;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(send_id: DWORD) -> SentPacket {
+        SentPacket {
+            send_id,
+            call: "SimConnect_TransmitClientEvent",
+            args: format!("event_id: {}", send_id),
+            location: None,
+        }
+    }
+
+    fn history(capacity: usize) -> PacketHistory {
+        let mut history = PacketHistory::default();
+        history.set_capacity(capacity);
+        history
+    }
+
+    fn send_ids(history: &PacketHistory) -> Vec<DWORD> {
+        history
+            .packets
+            .iter()
+            .map(|packet| packet.send_id)
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_default_number_of_packets() {
+        let mut history = PacketHistory::default();
+        assert!(history.is_enabled());
+        for send_id in 0..=DEFAULT_PACKET_HISTORY as DWORD {
+            history.push(packet(send_id));
+        }
+        assert_eq!(history.packets.len(), DEFAULT_PACKET_HISTORY);
+        assert!(history.find(0).is_none());
+        assert!(history.find(1).is_some());
+    }
+
+    #[test]
+    fn is_disabled_with_no_capacity() {
+        let mut history = history(0);
+        assert!(!history.is_enabled());
+        history.push(packet(1));
+        assert!(history.find(1).is_none());
+    }
+
+    #[test]
+    fn finds_packets_by_send_id() {
+        let mut history = history(4);
+        for send_id in 1..=3 {
+            history.push(packet(send_id));
+        }
+        assert_eq!(history.find(2).unwrap().args, "event_id: 2");
+        assert!(history.find(4).is_none());
+
+        // IDs wrap around eventually, the latest packet wins
+        history.push(SentPacket {
+            call: "SimConnect_SetDataOnSimObject",
+            ..packet(2)
+        });
+        assert_eq!(
+            history.find(2).unwrap().call,
+            "SimConnect_SetDataOnSimObject"
+        );
+
+        history.clear();
+        assert!(history.find(2).is_none());
+    }
+
+    #[test]
+    fn evicts_the_oldest_packets() {
+        let mut history = history(3);
+        for send_id in 1..=5 {
+            history.push(packet(send_id));
+        }
+        assert_eq!(send_ids(&history), [3, 4, 5]);
+        assert!(history.find(2).is_none());
+
+        history.set_capacity(2);
+        assert_eq!(send_ids(&history), [4, 5]);
+        history.set_capacity(0);
+        assert!(!history.is_enabled());
+        assert!(send_ids(&history).is_empty());
+    }
+}
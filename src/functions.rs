@@ -0,0 +1,526 @@
+/// Invokes `$m!` with the signature of every SimConnect function in the bindings, in the
+/// order of `SimConnect.h`. Every function returns an `HRESULT`.
+macro_rules! for_each_function {
+    ($m:ident) => {
+        $m! {
+            fn SimConnect_MapClientEventToSimEvent(
+                hSimConnect: HANDLE,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                EventName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_TransmitClientEvent(
+                hSimConnect: HANDLE,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                dwData: DWORD,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                Flags: SIMCONNECT_EVENT_FLAG,
+            );
+            fn SimConnect_SetSystemEventState(
+                hSimConnect: HANDLE,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                dwState: SIMCONNECT_STATE,
+            );
+            fn SimConnect_AddClientEventToNotificationGroup(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                bMaskable: BOOL,
+            );
+            fn SimConnect_RemoveClientEvent(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn SimConnect_SetNotificationGroupPriority(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                uPriority: DWORD,
+            );
+            fn SimConnect_ClearNotificationGroup(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+            );
+            fn SimConnect_RequestNotificationGroup(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                dwReserved: DWORD,
+                Flags: DWORD,
+            );
+            fn SimConnect_AddToDataDefinition(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                DatumName: *const ::std::os::raw::c_char,
+                UnitsName: *const ::std::os::raw::c_char,
+                DatumType: SIMCONNECT_DATATYPE,
+                fEpsilon: f32,
+                DatumID: DWORD,
+            );
+            fn SimConnect_ClearDataDefinition(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+            );
+            fn SimConnect_RequestDataOnSimObject(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                Period: SIMCONNECT_PERIOD,
+                Flags: SIMCONNECT_DATA_REQUEST_FLAG,
+                origin: DWORD,
+                interval: DWORD,
+                limit: DWORD,
+            );
+            fn SimConnect_RequestDataOnSimObjectType(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                dwRadiusMeters: DWORD,
+                type_: SIMCONNECT_SIMOBJECT_TYPE,
+            );
+            fn SimConnect_SetDataOnSimObject(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                Flags: SIMCONNECT_DATA_SET_FLAG,
+                ArrayCount: DWORD,
+                cbUnitSize: DWORD,
+                pDataSet: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_MapInputEventToClientEvent(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_INPUT_GROUP_ID,
+                szInputDefinition: *const ::std::os::raw::c_char,
+                DownEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                DownValue: DWORD,
+                UpEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                UpValue: DWORD,
+                bMaskable: BOOL,
+            );
+            fn SimConnect_SetInputGroupPriority(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_INPUT_GROUP_ID,
+                uPriority: DWORD,
+            );
+            fn SimConnect_RemoveInputEvent(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_INPUT_GROUP_ID,
+                szInputDefinition: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_ClearInputGroup(hSimConnect: HANDLE, GroupID: SIMCONNECT_INPUT_GROUP_ID);
+            fn SimConnect_SetInputGroupState(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_INPUT_GROUP_ID,
+                dwState: DWORD,
+            );
+            fn SimConnect_RequestReservedKey(
+                hSimConnect: HANDLE,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                szKeyChoice1: *const ::std::os::raw::c_char,
+                szKeyChoice2: *const ::std::os::raw::c_char,
+                szKeyChoice3: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_SubscribeToSystemEvent(
+                hSimConnect: HANDLE,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                SystemEventName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_UnsubscribeFromSystemEvent(
+                hSimConnect: HANDLE,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn SimConnect_WeatherRequestInterpolatedObservation(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+            );
+            fn SimConnect_WeatherRequestObservationAtStation(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                szICAO: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_WeatherRequestObservationAtNearestStation(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+            );
+            fn SimConnect_WeatherCreateStation(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                szICAO: *const ::std::os::raw::c_char,
+                szName: *const ::std::os::raw::c_char,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+            );
+            fn SimConnect_WeatherRemoveStation(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                szICAO: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_WeatherSetObservation(
+                hSimConnect: HANDLE,
+                Seconds: DWORD,
+                szMETAR: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_WeatherSetModeServer(
+                hSimConnect: HANDLE,
+                dwPort: DWORD,
+                dwSeconds: DWORD,
+            );
+            fn SimConnect_WeatherSetModeTheme(
+                hSimConnect: HANDLE,
+                szThemeName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_WeatherSetModeGlobal(hSimConnect: HANDLE);
+            fn SimConnect_WeatherSetModeCustom(hSimConnect: HANDLE);
+            fn SimConnect_WeatherSetDynamicUpdateRate(hSimConnect: HANDLE, dwRate: DWORD);
+            fn SimConnect_WeatherRequestCloudState(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                minLat: f32,
+                minLon: f32,
+                minAlt: f32,
+                maxLat: f32,
+                maxLon: f32,
+                maxAlt: f32,
+                dwFlags: DWORD,
+            );
+            fn SimConnect_WeatherCreateThermal(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                lat: f32,
+                lon: f32,
+                alt: f32,
+                radius: f32,
+                height: f32,
+                coreRate: f32,
+                coreTurbulence: f32,
+                sinkRate: f32,
+                sinkTurbulence: f32,
+                coreSize: f32,
+                coreTransitionSize: f32,
+                sinkLayerSize: f32,
+                sinkTransitionSize: f32,
+            );
+            fn SimConnect_WeatherRemoveThermal(hSimConnect: HANDLE, ObjectID: SIMCONNECT_OBJECT_ID);
+            fn SimConnect_AICreateParkedATCAircraft(
+                hSimConnect: HANDLE,
+                szContainerTitle: *const ::std::os::raw::c_char,
+                szTailNumber: *const ::std::os::raw::c_char,
+                szAirportID: *const ::std::os::raw::c_char,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AICreateEnrouteATCAircraft(
+                hSimConnect: HANDLE,
+                szContainerTitle: *const ::std::os::raw::c_char,
+                szTailNumber: *const ::std::os::raw::c_char,
+                iFlightNumber: ::std::os::raw::c_int,
+                szFlightPlanPath: *const ::std::os::raw::c_char,
+                dFlightPlanPosition: f64,
+                bTouchAndGo: BOOL,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AICreateNonATCAircraft(
+                hSimConnect: HANDLE,
+                szContainerTitle: *const ::std::os::raw::c_char,
+                szTailNumber: *const ::std::os::raw::c_char,
+                InitPos: SIMCONNECT_DATA_INITPOSITION,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AICreateSimulatedObject(
+                hSimConnect: HANDLE,
+                szContainerTitle: *const ::std::os::raw::c_char,
+                InitPos: SIMCONNECT_DATA_INITPOSITION,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AIReleaseControl(
+                hSimConnect: HANDLE,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AIRemoveObject(
+                hSimConnect: HANDLE,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_AISetAircraftFlightPlan(
+                hSimConnect: HANDLE,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                szFlightPlanPath: *const ::std::os::raw::c_char,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_ExecuteMissionAction(hSimConnect: HANDLE, guidInstanceId: GUID);
+            fn SimConnect_CompleteCustomMissionAction(hSimConnect: HANDLE, guidInstanceId: GUID);
+            fn SimConnect_Close(hSimConnect: HANDLE);
+            fn SimConnect_RetrieveString(
+                pData: *mut SIMCONNECT_RECV,
+                cbData: DWORD,
+                pStringV: *mut ::std::os::raw::c_void,
+                pszString: *mut *mut ::std::os::raw::c_char,
+                pcbString: *mut DWORD,
+            );
+            fn SimConnect_GetLastSentPacketID(hSimConnect: HANDLE, pdwError: *mut DWORD);
+            fn SimConnect_Open(
+                phSimConnect: *mut HANDLE,
+                szName: LPCSTR,
+                hWnd: HWND,
+                UserEventWin32: DWORD,
+                hEventHandle: HANDLE,
+                ConfigIndex: DWORD,
+            );
+            fn SimConnect_CallDispatch(
+                hSimConnect: HANDLE,
+                pfcnDispatch: DispatchProc,
+                pContext: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_GetNextDispatch(
+                hSimConnect: HANDLE,
+                ppData: *mut *mut SIMCONNECT_RECV,
+                pcbData: *mut DWORD,
+            );
+            fn SimConnect_RequestResponseTimes(
+                hSimConnect: HANDLE,
+                nCount: DWORD,
+                fElapsedSeconds: *mut f32,
+            );
+            fn SimConnect_InsertString(
+                pDest: *mut ::std::os::raw::c_char,
+                cbDest: DWORD,
+                ppEnd: *mut *mut ::std::os::raw::c_void,
+                pcbStringV: *mut DWORD,
+                pSource: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_CameraSetRelative6DOF(
+                hSimConnect: HANDLE,
+                fDeltaX: f32,
+                fDeltaY: f32,
+                fDeltaZ: f32,
+                fPitchDeg: f32,
+                fBankDeg: f32,
+                fHeadingDeg: f32,
+            );
+            fn SimConnect_MenuAddItem(
+                hSimConnect: HANDLE,
+                szMenuItem: *const ::std::os::raw::c_char,
+                MenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                dwData: DWORD,
+            );
+            fn SimConnect_MenuDeleteItem(
+                hSimConnect: HANDLE,
+                MenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn SimConnect_MenuAddSubItem(
+                hSimConnect: HANDLE,
+                MenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                szMenuItem: *const ::std::os::raw::c_char,
+                SubMenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                dwData: DWORD,
+            );
+            fn SimConnect_MenuDeleteSubItem(
+                hSimConnect: HANDLE,
+                MenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                SubMenuEventID: SIMCONNECT_CLIENT_EVENT_ID,
+            );
+            fn SimConnect_RequestSystemState(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                szState: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_SetSystemState(
+                hSimConnect: HANDLE,
+                szState: *const ::std::os::raw::c_char,
+                dwInteger: DWORD,
+                fFloat: f32,
+                szString: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_MapClientDataNameToID(
+                hSimConnect: HANDLE,
+                szClientDataName: *const ::std::os::raw::c_char,
+                ClientDataID: SIMCONNECT_CLIENT_DATA_ID,
+            );
+            fn SimConnect_CreateClientData(
+                hSimConnect: HANDLE,
+                ClientDataID: SIMCONNECT_CLIENT_DATA_ID,
+                dwSize: DWORD,
+                Flags: SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+            );
+            fn SimConnect_AddToClientDataDefinition(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                dwOffset: DWORD,
+                dwSizeOrType: DWORD,
+                fEpsilon: f32,
+                DatumID: DWORD,
+            );
+            fn SimConnect_ClearClientDataDefinition(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+            );
+            fn SimConnect_RequestClientData(
+                hSimConnect: HANDLE,
+                ClientDataID: SIMCONNECT_CLIENT_DATA_ID,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                DefineID: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                Period: SIMCONNECT_CLIENT_DATA_PERIOD,
+                Flags: SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+                origin: DWORD,
+                interval: DWORD,
+                limit: DWORD,
+            );
+            fn SimConnect_SetClientData(
+                hSimConnect: HANDLE,
+                ClientDataID: SIMCONNECT_CLIENT_DATA_ID,
+                DefineID: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+                Flags: SIMCONNECT_CLIENT_DATA_SET_FLAG,
+                dwReserved: DWORD,
+                cbUnitSize: DWORD,
+                pDataSet: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_FlightLoad(
+                hSimConnect: HANDLE,
+                szFileName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_FlightSave(
+                hSimConnect: HANDLE,
+                szFileName: *const ::std::os::raw::c_char,
+                szTitle: *const ::std::os::raw::c_char,
+                szDescription: *const ::std::os::raw::c_char,
+                Flags: DWORD,
+            );
+            fn SimConnect_FlightPlanLoad(
+                hSimConnect: HANDLE,
+                szFileName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_Text(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_TEXT_TYPE,
+                fTimeSeconds: f32,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                cbUnitSize: DWORD,
+                pDataSet: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_SubscribeToFacilities(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_UnsubscribeToFacilities(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+            );
+            fn SimConnect_RequestFacilitiesList(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_TransmitClientEvent_EX1(
+                hSimConnect: HANDLE,
+                ObjectID: SIMCONNECT_OBJECT_ID,
+                EventID: SIMCONNECT_CLIENT_EVENT_ID,
+                GroupID: SIMCONNECT_NOTIFICATION_GROUP_ID,
+                Flags: SIMCONNECT_EVENT_FLAG,
+                dwData0: DWORD,
+                dwData1: DWORD,
+                dwData2: DWORD,
+                dwData3: DWORD,
+                dwData4: DWORD,
+            );
+            fn SimConnect_AddToFacilityDefinition(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                FieldName: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_RequestFacilityData(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                ICAO: *const ::std::os::raw::c_char,
+                Region: *const ::std::os::raw::c_char,
+            );
+            fn SimConnect_SubscribeToFacilities_EX1(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                newElemInRangeRequestID: SIMCONNECT_DATA_REQUEST_ID,
+                oldElemOutRangeRequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_UnsubscribeToFacilities_EX1(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                bUnsubscribeNewInRange: bool,
+                bUnsubscribeOldOutRange: bool,
+            );
+            fn SimConnect_RequestFacilitiesList_EX1(
+                hSimConnect: HANDLE,
+                type_: SIMCONNECT_FACILITY_LIST_TYPE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_RequestFacilityData_EX1(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                ICAO: *const ::std::os::raw::c_char,
+                Region: *const ::std::os::raw::c_char,
+                Type: ::std::os::raw::c_char,
+            );
+            fn SimConnect_RequestJetwayData(
+                hSimConnect: HANDLE,
+                AirportIcao: *const ::std::os::raw::c_char,
+                ArrayCount: DWORD,
+                Indexes: *mut ::std::os::raw::c_int,
+            );
+            fn SimConnect_EnumerateControllers(hSimConnect: HANDLE);
+            fn SimConnect_MapInputEventToClientEvent_EX1(
+                hSimConnect: HANDLE,
+                GroupID: SIMCONNECT_INPUT_GROUP_ID,
+                szInputDefinition: *const ::std::os::raw::c_char,
+                DownEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                DownValue: DWORD,
+                UpEventID: SIMCONNECT_CLIENT_EVENT_ID,
+                UpValue: DWORD,
+                bMaskable: BOOL,
+            );
+            fn SimConnect_ExecuteAction(
+                hSimConnect: HANDLE,
+                cbRequestID: DWORD,
+                szActionID: *const ::std::os::raw::c_char,
+                cbUnitSize: DWORD,
+                pParamValues: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_EnumerateInputEvents(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+            );
+            fn SimConnect_GetInputEvent(
+                hSimConnect: HANDLE,
+                RequestID: SIMCONNECT_DATA_REQUEST_ID,
+                Hash: UINT64,
+            );
+            fn SimConnect_SetInputEvent(
+                hSimConnect: HANDLE,
+                Hash: UINT64,
+                cbUnitSize: DWORD,
+                Value: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_SubscribeInputEvent(hSimConnect: HANDLE, Hash: UINT64);
+            fn SimConnect_UnsubscribeInputEvent(hSimConnect: HANDLE, Hash: UINT64);
+            fn SimConnect_EnumerateInputEventParams(hSimConnect: HANDLE, Hash: UINT64);
+            fn SimConnect_AddFacilityDataDefinitionFilter(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+                szFilterPath: *const ::std::os::raw::c_char,
+                cbUnitSize: DWORD,
+                pFilterData: *mut ::std::os::raw::c_void,
+            );
+            fn SimConnect_ClearAllFacilityDataDefinitionFilters(
+                hSimConnect: HANDLE,
+                DefineID: SIMCONNECT_DATA_DEFINITION_ID,
+            );
+        }
+    };
+}
+
+pub(crate) use for_each_function;
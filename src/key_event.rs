@@ -0,0 +1,324 @@
+//! Sim key events with the meaning of their parameter
+//!
+//! [`SimConnector::transmit_key_event`](crate::SimConnector::transmit_key_event) maps a
+//! [`KeyEvent`] under a client event ID of its own on first use and encodes its parameter, so
+//! axis values, BCD16 frequencies and transponder codes are written as the simulator expects
+//! them.
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("Key events")?;
+//!
+//! conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::PARKING_BRAKES, ())?;
+//! conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::AXIS_ELEVATOR_SET, -8000)?;
+//! conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::COM_STBY_RADIO_SET, 121.5)?;
+//! conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::XPNDR_SET, 7000)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use crate::{SimConnectError, DWORD, SIMCONNECT_CLIENT_EVENT_ID};
+
+/// First client event ID used for key events, far above the IDs programs pick by hand
+pub const KEY_EVENT_ID_BASE: SIMCONNECT_CLIENT_EVENT_ID = 0x1000_0000;
+
+/// What the `dwData` of a key event means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyParam {
+    /// The event takes no parameter.
+    None,
+    /// An axis position from -16383 to 16383, or a float from -1.0 to 1.0.
+    Axis,
+    /// An integer from `min` to `max`.
+    Range {
+        min: i32,
+        max: i32,
+    },
+    Bool,
+    /// A heading from 0 to 360 degrees.
+    Degrees,
+    /// A frequency in MHz, sent as the four BCD16 digits after the leading 1 (`121.50` is
+    /// `0x2150`). The range is in kHz.
+    Bcd16Frequency {
+        min_khz: u32,
+        max_khz: u32,
+    },
+    /// A transponder code of four octal digits written in decimal (`7000`), sent as BCO16.
+    Bco16,
+}
+
+impl fmt::Display for KeyParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "no parameter"),
+            Self::Axis => write!(f, "an axis value from -16383 to 16383 or -1.0 to 1.0"),
+            Self::Range { min, max } => write!(f, "an integer from {} to {}", min, max),
+            Self::Bool => write!(f, "a bool"),
+            Self::Degrees => write!(f, "a heading from 0 to 360 degrees"),
+            Self::Bcd16Frequency { min_khz, max_khz } => write!(
+                f,
+                "a frequency from {:.3} to {:.3} MHz",
+                f64::from(*min_khz) / 1000.0,
+                f64::from(*max_khz) / 1000.0
+            ),
+            Self::Bco16 => write!(f, "a transponder code from 0000 to 7777"),
+        }
+    }
+}
+
+/// A parameter for a key event, before it is encoded according to its [`KeyParam`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyValue {
+    None,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<()> for KeyValue {
+    fn from(_: ()) -> Self {
+        Self::None
+    }
+}
+
+impl From<bool> for KeyValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for KeyValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+macro_rules! int_key_values {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for KeyValue {
+                fn from(value: $ty) -> Self {
+                    Self::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+int_key_values!(i16, u16, i32, u32, i64);
+
+/// A sim key event, as passed to `SimConnect_MapClientEventToSimEvent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub name: &'static str,
+    pub param: KeyParam,
+}
+
+impl KeyEvent {
+    /// Encodes `value` as the `dwData` of the event.
+    pub fn encode(&self, value: impl Into<KeyValue>) -> Result<DWORD, SimConnectError> {
+        let value = value.into();
+        let invalid = || SimConnectError::InvalidEventData {
+            event: self.name,
+            value,
+            expected: self.param,
+        };
+        let in_range = |v: i64, min: i64, max: i64| {
+            if (min..=max).contains(&v) {
+                Ok(v as i32 as DWORD)
+            } else {
+                Err(invalid())
+            }
+        };
+
+        match (self.param, value) {
+            (KeyParam::None, KeyValue::None) => Ok(0),
+            (KeyParam::Axis, KeyValue::Int(v)) => in_range(v, -16383, 16383),
+            (KeyParam::Axis, KeyValue::Float(v)) if (-1.0..=1.0).contains(&v) => {
+                Ok((v * 16383.0).round() as i32 as DWORD)
+            }
+            (KeyParam::Range { min, max }, KeyValue::Int(v)) => in_range(v, min.into(), max.into()),
+            (KeyParam::Bool, KeyValue::Bool(v)) => Ok(v.into()),
+            (KeyParam::Bool, KeyValue::Int(v)) => in_range(v, 0, 1),
+            (KeyParam::Degrees, KeyValue::Int(v)) => in_range(v, 0, 360),
+            (KeyParam::Degrees, KeyValue::Float(v)) if (0.0..=360.0).contains(&v) => {
+                Ok(v.round() as DWORD)
+            }
+            (KeyParam::Bcd16Frequency { min_khz, max_khz }, KeyValue::Float(mhz)) => {
+                let khz = (mhz * 1000.0).round();
+                if !(f64::from(min_khz)..=f64::from(max_khz)).contains(&khz) {
+                    return Err(invalid());
+                }
+
+                // BCD16 holds the 10 kHz digits after the leading 1, the 25 kHz steps are
+                // implied
+                Ok(bcd(khz as u32 / 10 % 10_000, 10))
+            }
+            (KeyParam::Bco16, KeyValue::Int(code)) => {
+                let valid = (0..=7777).contains(&code)
+                    && code.to_string().bytes().all(|digit| digit <= b'7');
+                if valid {
+                    Ok(bcd(code as u32, 10))
+                } else {
+                    Err(invalid())
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Puts every digit of `value` in base `radix` into a nibble of its own.
+fn bcd(mut value: u32, radix: u32) -> DWORD {
+    let mut encoded = 0;
+    let mut shift = 0;
+    while value > 0 {
+        encoded |= (value % radix) << shift;
+        value /= radix;
+        shift += 4;
+    }
+
+    encoded
+}
+
+macro_rules! key_events {
+    ($($name:ident: $param:expr;)*) => {
+        impl KeyEvent {
+            $(
+                #[doc = concat!("`", stringify!($name), "`")]
+                pub const $name: KeyEvent = KeyEvent {
+                    name: stringify!($name),
+                    param: $param,
+                };
+            )*
+        }
+
+        static KEY_EVENTS: &[KeyEvent] = &[$(KeyEvent::$name),*];
+    };
+}
+
+const AXIS: KeyParam = KeyParam::Axis;
+const NONE: KeyParam = KeyParam::None;
+const BOOL: KeyParam = KeyParam::Bool;
+const POSITION: KeyParam = KeyParam::Range { min: 0, max: 16383 };
+const COM: KeyParam = KeyParam::Bcd16Frequency {
+    min_khz: 118_000,
+    max_khz: 136_990,
+};
+const NAV: KeyParam = KeyParam::Bcd16Frequency {
+    min_khz: 108_000,
+    max_khz: 117_950,
+};
+const COM_HZ: KeyParam = KeyParam::Range {
+    min: 118_000_000,
+    max: 136_990_000,
+};
+
+key_events! {
+    // Controls
+    AXIS_ELEVATOR_SET: AXIS;
+    AXIS_AILERONS_SET: AXIS;
+    AXIS_RUDDER_SET: AXIS;
+    AXIS_THROTTLE_SET: AXIS;
+    AXIS_MIXTURE_SET: AXIS;
+    AXIS_PROPELLER_SET: AXIS;
+    AXIS_LEFT_BRAKE_SET: AXIS;
+    AXIS_RIGHT_BRAKE_SET: AXIS;
+    AXIS_SPOILER_SET: AXIS;
+    AXIS_FLAPS_SET: AXIS;
+    AXIS_ELEV_TRIM_SET: AXIS;
+    ELEVATOR_SET: AXIS;
+    AILERON_SET: AXIS;
+    RUDDER_SET: AXIS;
+    ELEVATOR_TRIM_SET: AXIS;
+    THROTTLE_SET: POSITION;
+    MIXTURE_SET: POSITION;
+    PROP_PITCH_SET: POSITION;
+    FLAPS_SET: POSITION;
+    SPOILERS_SET: POSITION;
+    THROTTLE_FULL: NONE;
+    THROTTLE_CUT: NONE;
+    THROTTLE_INCR: NONE;
+    THROTTLE_DECR: NONE;
+    FLAPS_UP: NONE;
+    FLAPS_DOWN: NONE;
+    FLAPS_INCR: NONE;
+    FLAPS_DECR: NONE;
+    SPOILERS_TOGGLE: NONE;
+    SPOILERS_ARM_TOGGLE: NONE;
+    BRAKES: NONE;
+    PARKING_BRAKES: NONE;
+    PARKING_BRAKE_SET: BOOL;
+    GEAR_UP: NONE;
+    GEAR_DOWN: NONE;
+    GEAR_TOGGLE: NONE;
+    GEAR_SET: BOOL;
+    // Engines
+    ENGINE_AUTO_START: NONE;
+    ENGINE_AUTO_SHUTDOWN: NONE;
+    // Autopilot
+    AP_MASTER: NONE;
+    AUTOPILOT_ON: NONE;
+    AUTOPILOT_OFF: NONE;
+    AP_HDG_HOLD: NONE;
+    AP_ALT_HOLD: NONE;
+    AP_NAV1_HOLD: NONE;
+    AP_APR_HOLD: NONE;
+    HEADING_BUG_SET: KeyParam::Degrees;
+    AP_ALT_VAR_SET_ENGLISH: KeyParam::Range { min: 0, max: 100_000 };
+    AP_VS_VAR_SET_ENGLISH: KeyParam::Range { min: -10_000, max: 10_000 };
+    AP_SPD_VAR_SET: KeyParam::Range { min: 0, max: 999 };
+    // Radios
+    COM_RADIO_SET: COM;
+    COM_STBY_RADIO_SET: COM;
+    COM2_RADIO_SET: COM;
+    COM2_STBY_RADIO_SET: COM;
+    COM_RADIO_SET_HZ: COM_HZ;
+    COM_STBY_RADIO_SET_HZ: COM_HZ;
+    COM_STBY_RADIO_SWAP: NONE;
+    NAV1_RADIO_SET: NAV;
+    NAV1_STBY_SET: NAV;
+    NAV2_RADIO_SET: NAV;
+    NAV2_STBY_SET: NAV;
+    NAV1_RADIO_SWAP: NONE;
+    NAV2_RADIO_SWAP: NONE;
+    VOR1_SET: KeyParam::Degrees;
+    VOR2_SET: KeyParam::Degrees;
+    XPNDR_SET: KeyParam::Bco16;
+    // Millibars times 16
+    KOHLSMAN_SET: KeyParam::Range { min: 15_168, max: 17_344 };
+    // Lights and systems
+    TOGGLE_NAV_LIGHTS: NONE;
+    TOGGLE_BEACON_LIGHTS: NONE;
+    TOGGLE_TAXI_LIGHTS: NONE;
+    LANDING_LIGHTS_TOGGLE: NONE;
+    STROBES_TOGGLE: NONE;
+    TOGGLE_MASTER_BATTERY: NONE;
+    PITOT_HEAT_TOGGLE: NONE;
+    PITOT_HEAT_SET: BOOL;
+    TOGGLE_AIRCRAFT_EXIT: NONE;
+    // Simulation
+    PAUSE_TOGGLE: NONE;
+    PAUSE_ON: NONE;
+    PAUSE_OFF: NONE;
+    SIM_RATE_INCR: NONE;
+    SIM_RATE_DECR: NONE;
+    SLEW_TOGGLE: NONE;
+}
+
+/// Every key event in the catalog.
+pub fn key_events() -> &'static [KeyEvent] {
+    KEY_EVENTS
+}
+
+/// The key event called `name`, without regard to case.
+pub fn lookup(name: &str) -> Option<KeyEvent> {
+    KEY_EVENTS
+        .iter()
+        .find(|event| event.name.eq_ignore_ascii_case(name))
+        .copied()
+}
@@ -3,11 +3,75 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
-use std::ffi::CString;
-use std::mem::transmute_copy;
-use std::ptr;
-
+use std::any::{type_name, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::{size_of, transmute_copy};
+use std::panic::Location;
+
+mod backend;
+pub mod catalog;
+mod client_data;
+pub mod cloud_grid;
+mod config;
+mod data;
+mod definition;
+mod error;
+mod exception;
+pub mod key_event;
+pub mod message;
+pub mod metar;
+pub mod mobiflight;
+pub mod mock;
+mod payload;
+pub mod rpc;
+pub mod system_event;
+mod tcp;
+pub mod thermal;
+pub mod weather;
+pub mod weather_replay;
+
+pub use backend::{FfiBackend, SimConnectBackend};
+pub use client_data::{ClientData, ClientDataArea, ClientDataField, ClientDataType};
+pub use config::{
+    ConfigError, ConfigSection, ConnectOptions, Protocol, SimConnectConfig, CONFIG_FILE_NAME,
+};
+pub use data::{DataField, SimConnectData};
+pub use definition::{
+    DataDefinition, Datum, InitPosition, LatLonAlt, MarkerState, Pbh, Value, Values, Waypoint, Xyz,
+};
+pub use error::SimConnectError;
+pub use exception::{ResolvedException, SentPacket, SimConnectException};
+pub use key_event::{KeyEvent, KeyParam, KeyValue};
+pub use message::Message;
+pub use metar::Metar;
+pub use payload::{Payload, PayloadMessage, PayloadReader};
+pub use system_event::{SystemEvent, SystemNotification, ViewKind};
+pub use tcp::TcpBackend;
+pub use weather::{WeatherMode, MAX_ICAO_LENGTH, MAX_STATION_NAME_LENGTH};
+
+#[cfg(feature = "derive")]
+pub use simconnect_derive::{ClientData, SimConnectData};
+
+use error::{check_length, to_cstring};
+use exception::{args, PacketHistory};
+
+#[cfg(not(any(simconnect_no_link, feature = "dynamic")))]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+#[cfg(any(simconnect_no_link, feature = "dynamic"))]
+include!("bindings.rs");
+
+#[cfg(feature = "dynamic")]
+mod dynamic;
+#[cfg(any(simconnect_no_link, feature = "dynamic"))]
+mod functions;
+#[cfg(simconnect_no_link)]
+mod stubs;
+
+#[cfg(feature = "dynamic")]
+pub use dynamic::SimulatorDll;
+#[cfg(simconnect_no_link)]
+pub use stubs::*;
 
 /// Enumerations for all the possible data types received from SimConnect
 #[derive(Debug)]
@@ -20,15 +84,15 @@ pub enum DispatchResult<'a> {
     EventObjectAddRemove(&'a SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE),
     EventFilename(&'a SIMCONNECT_RECV_EVENT_FILENAME),
     EventFrame(&'a SIMCONNECT_RECV_EVENT_FRAME),
-    SimObjectData(&'a SIMCONNECT_RECV_SIMOBJECT_DATA),
-    SimObjectDataByType(&'a SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE),
-    WeatherObservation(&'a SIMCONNECT_RECV_WEATHER_OBSERVATION),
-    CloudState(&'a SIMCONNECT_RECV_CLOUD_STATE),
+    SimObjectData(Payload<'a, SIMCONNECT_RECV_SIMOBJECT_DATA>),
+    SimObjectDataByType(Payload<'a, SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE>),
+    WeatherObservation(Payload<'a, SIMCONNECT_RECV_WEATHER_OBSERVATION>),
+    CloudState(Payload<'a, SIMCONNECT_RECV_CLOUD_STATE>),
     AssignedObjectId(&'a SIMCONNECT_RECV_ASSIGNED_OBJECT_ID),
     ReservedKey(&'a SIMCONNECT_RECV_RESERVED_KEY),
     CustomAction(&'a SIMCONNECT_RECV_CUSTOM_ACTION),
     SystemState(&'a SIMCONNECT_RECV_SYSTEM_STATE),
-    ClientData(&'a SIMCONNECT_RECV_CLIENT_DATA),
+    ClientData(Payload<'a, SIMCONNECT_RECV_CLIENT_DATA>),
     EventWeatherMode(&'a SIMCONNECT_RECV_EVENT_WEATHER_MODE),
     AirportList(&'a SIMCONNECT_RECV_AIRPORT_LIST),
     VorList(&'a SIMCONNECT_RECV_VOR_LIST),
@@ -53,16 +117,25 @@ pub enum DispatchResult<'a> {
 }
 
 /// Handles communication between the client program and SimConnect
+///
+/// All calls go through a [`SimConnectBackend`], by default the SimConnect library itself.
+/// Use [`with_backend`](Self::with_backend) to run against e.g. a [`MockBackend`](mock::MockBackend).
 #[derive(Debug)]
-pub struct SimConnector {
-    sim_connect_handle: HANDLE,
+pub struct SimConnector<B: SimConnectBackend = FfiBackend> {
+    backend: B,
+    connected: bool,
+    sent_packets: RefCell<PacketHistory>,
+    /// Key events mapped so far, each under `KEY_EVENT_ID_BASE` plus its index
+    key_events: RefCell<Vec<KeyEvent>>,
+    /// Datums added to each data definition, to encode the writes of `set_data`
+    definitions: RefCell<HashMap<SIMCONNECT_DATA_DEFINITION_ID, DataDefinition>>,
+    /// Data definitions registered for `SimConnectData` types by `define_data`
+    data_types: RefCell<HashMap<TypeId, SIMCONNECT_DATA_DEFINITION_ID>>,
 }
 
-impl Default for SimConnector {
+impl<B: SimConnectBackend + Default> Default for SimConnector<B> {
     fn default() -> Self {
-        Self {
-            sim_connect_handle: std::ptr::null_mut(),
-        }
+        Self::with_backend(B::default())
     }
 }
 
@@ -71,27 +144,77 @@ impl SimConnector {
         Self::default()
     }
 
-    pub fn connect(&mut self, program_name: &str) -> bool {
-        unsafe {
-            let temp_1 = ptr::null_mut();
-            let temp_2 = ptr::null_mut();
+    /// Connector loading the SimConnect library from `dll` on first use.
+    #[cfg(feature = "dynamic")]
+    pub fn with_dll(dll: SimulatorDll) -> Self {
+        Self::with_backend(FfiBackend::with_dll(dll))
+    }
+}
+
+impl<B: SimConnectBackend> SimConnector<B> {
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            backend,
+            connected: false,
+            sent_packets: RefCell::default(),
+            key_events: RefCell::default(),
+            definitions: RefCell::default(),
+            data_types: RefCell::default(),
+        }
+    }
 
-            let program_name = CString::new(program_name).unwrap();
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
 
-            SimConnect_Open(
-                &mut self.sim_connect_handle,
-                program_name.as_ptr(),
-                temp_1,
-                0,
-                temp_2,
-                0,
-            );
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
 
-            !self.sim_connect_handle.is_null()
-        }
+    pub fn connect(&mut self, program_name: &str) -> Result<(), SimConnectError> {
+        self.open(program_name, 0, std::ptr::null_mut())
     }
 
-    pub fn add_data_definition(
+    /// Connects with the section `options.config_index` of `SimConnect.cfg`.
+    ///
+    /// The SimConnect library finds the file on its own, to check a section beforehand read the
+    /// file with [`SimConnectConfig`] and [`validate`](SimConnectConfig::validate) it.
+    pub fn connect_with_options(
+        &mut self,
+        program_name: &str,
+        options: ConnectOptions,
+    ) -> Result<(), SimConnectError> {
+        self.open(program_name, options.config_index, options.event_handle)
+    }
+
+    fn open(
+        &mut self,
+        program_name: &str,
+        config_index: DWORD,
+        event_handle: HANDLE,
+    ) -> Result<(), SimConnectError> {
+        let program_name = to_cstring("SimConnect_Open", "program_name", program_name)?;
+
+        // Send IDs, event mappings and data definitions start over with every connection
+        self.sent_packets.get_mut().clear();
+        self.key_events.get_mut().clear();
+        self.definitions.get_mut().clear();
+        self.data_types.get_mut().clear();
+
+        self.backend
+            .open(&program_name, config_index, event_handle)?;
+        self.connected = true;
+
+        Ok(())
+    }
+
+    /// [`add_data_definition`](Self::add_data_definition), after checking the name, index and
+    /// units against the [`catalog`].
+    ///
+    /// Fails with [`SimConnectError::Catalog`] without calling SimConnect, for variables that
+    /// aren't in the catalog too.
+    #[track_caller]
+    pub fn add_data_definition_checked(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
         datum_name: &str,
@@ -99,154 +222,608 @@ impl SimConnector {
         datum_type: SIMCONNECT_DATATYPE,
         datum_id: DWORD,
         epsilon: f32,
-    ) -> bool {
-        let datum_name = CString::new(datum_name).unwrap();
-        let units_name = CString::new(units_name).unwrap();
+    ) -> Result<(), SimConnectError> {
+        catalog::check(datum_name, units_name).map_err(SimConnectError::Catalog)?;
 
-        unsafe {
-            SimConnect_AddToDataDefinition(
-                self.sim_connect_handle,
+        self.add_data_definition(
+            define_id, datum_name, units_name, datum_type, datum_id, epsilon,
+        )
+    }
+
+    #[track_caller]
+    pub fn add_data_definition(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+        datum_name: &str,
+        units_name: &str,
+        datum_type: SIMCONNECT_DATATYPE,
+        datum_id: DWORD,
+        epsilon: f32,
+    ) -> Result<(), SimConnectError> {
+        let datum_name = to_cstring("SimConnect_AddToDataDefinition", "datum_name", datum_name)?;
+        let units_name = to_cstring("SimConnect_AddToDataDefinition", "units_name", units_name)?;
+
+        self.sent(
+            "SimConnect_AddToDataDefinition",
+            args!(define_id, datum_name, units_name, datum_type, datum_id, epsilon),
+            self.backend.add_to_data_definition(
                 define_id,
-                datum_name.as_ptr(),
-                units_name.as_ptr(),
+                &datum_name,
+                &units_name,
                 datum_type,
                 epsilon,
                 datum_id,
-            ) == 0
-        }
+            ),
+        )?;
+
+        self.definitions
+            .borrow_mut()
+            .entry(define_id)
+            .or_insert_with(|| DataDefinition::new(define_id))
+            .push(datum_id, datum_type);
+
+        Ok(())
+    }
+
+    /// Adds the fields of `T` to the data definition `define_id` and registers it as the
+    /// definition [`set_data`](Self::set_data) writes `T` with.
+    #[track_caller]
+    pub fn define_data<T: SimConnectData + 'static>(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        T::add_definition(self, define_id)?;
+        self.data_types
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), define_id);
+
+        Ok(())
+    }
+
+    /// The data definition registered for `T` by [`define_data`](Self::define_data).
+    pub fn data_definition_id<T: SimConnectData + 'static>(
+        &self,
+    ) -> Option<SIMCONNECT_DATA_DEFINITION_ID> {
+        self.data_types.borrow().get(&TypeId::of::<T>()).copied()
+    }
+
+    /// The datums added to `define_id` on this connection so far.
+    pub fn data_definition(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Option<DataDefinition> {
+        self.definitions.borrow().get(&define_id).cloned()
     }
 
+    #[track_caller]
     pub fn set_system_event_state(
         &self,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         state: SIMCONNECT_STATE,
-    ) -> bool {
-        unsafe { SimConnect_SetSystemEventState(self.sim_connect_handle, event_id, state) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetSystemEventState",
+            args!(event_id, state),
+            self.backend.set_system_event_state(event_id, state),
+        )
     }
 
+    #[track_caller]
     pub fn remove_client_event(
         &self,
         group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
-    ) -> bool {
-        unsafe { SimConnect_RemoveClientEvent(self.sim_connect_handle, group_id, event_id) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RemoveClientEvent",
+            args!(group_id, event_id),
+            self.backend.remove_client_event(group_id, event_id),
+        )
     }
 
-    pub fn clear_notification_group(&self, group_id: SIMCONNECT_NOTIFICATION_GROUP_ID) -> bool {
-        unsafe { SimConnect_ClearNotificationGroup(self.sim_connect_handle, group_id) == 0 }
+    #[track_caller]
+    pub fn clear_notification_group(
+        &self,
+        group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ClearNotificationGroup",
+            args!(group_id),
+            self.backend.clear_notification_group(group_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_notification_group(
         &self,
         group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
         reserved: DWORD,
         flags: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestNotificationGroup(self.sim_connect_handle, group_id, reserved, flags)
-                == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestNotificationGroup",
+            args!(group_id, reserved, flags),
+            self.backend
+                .request_notification_group(group_id, reserved, flags),
+        )
     }
 
-    pub fn clear_data_definition(&self, define_id: SIMCONNECT_DATA_DEFINITION_ID) -> bool {
-        unsafe { SimConnect_ClearDataDefinition(self.sim_connect_handle, define_id) == 0 }
+    #[track_caller]
+    pub fn clear_data_definition(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ClearDataDefinition",
+            args!(define_id),
+            self.backend.clear_data_definition(define_id),
+        )?;
+
+        self.definitions.borrow_mut().remove(&define_id);
+        self.data_types
+            .borrow_mut()
+            .retain(|_, registered| *registered != define_id);
+
+        Ok(())
     }
 
+    #[track_caller]
     pub fn create_client_data(
         &self,
         data_id: SIMCONNECT_CLIENT_DATA_ID,
         size: DWORD,
         flags: SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
-    ) -> bool {
-        unsafe { SimConnect_CreateClientData(self.sim_connect_handle, data_id, size, flags) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_CreateClientData",
+            args!(data_id, size, flags),
+            self.backend.create_client_data(data_id, size, flags),
+        )
     }
 
+    #[track_caller]
     pub fn request_data_on_sim_object_type(
         &self,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
         radius_in_meters: DWORD,
         object_type: SIMCONNECT_SIMOBJECT_TYPE,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestDataOnSimObjectType(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestDataOnSimObjectType",
+            args!(request_id, define_id, radius_in_meters, object_type),
+            self.backend.request_data_on_sim_object_type(
                 request_id,
                 define_id,
                 radius_in_meters,
                 object_type,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn remove_input_event(
         &self,
         group_id: SIMCONNECT_INPUT_GROUP_ID,
         input_definition: &str,
-    ) -> bool {
-        let input_definition = CString::new(input_definition).unwrap();
-
-        unsafe {
-            SimConnect_RemoveInputEvent(
-                self.sim_connect_handle,
-                group_id,
-                input_definition.as_ptr(),
-            ) == 0
-        }
-    }
-
-    pub fn clear_input_group(&self, group_id: SIMCONNECT_INPUT_GROUP_ID) -> bool {
-        unsafe { SimConnect_ClearInputGroup(self.sim_connect_handle, group_id) == 0 }
+    ) -> Result<(), SimConnectError> {
+        let input_definition = to_cstring(
+            "SimConnect_RemoveInputEvent",
+            "input_definition",
+            input_definition,
+        )?;
+
+        self.sent(
+            "SimConnect_RemoveInputEvent",
+            args!(group_id, input_definition),
+            self.backend.remove_input_event(group_id, &input_definition),
+        )
+    }
+
+    #[track_caller]
+    pub fn clear_input_group(
+        &self,
+        group_id: SIMCONNECT_INPUT_GROUP_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ClearInputGroup",
+            args!(group_id),
+            self.backend.clear_input_group(group_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_reserved_key(
         &self,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         key_choice_1: &str,
         key_choice_2: &str,
         key_choice_3: &str,
-    ) -> bool {
-        let key_choice_1 = CString::new(key_choice_1).unwrap();
-        let key_choice_2 = CString::new(key_choice_2).unwrap();
-        let key_choice_3 = CString::new(key_choice_3).unwrap();
-
-        unsafe {
-            SimConnect_RequestReservedKey(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        let key_choice_1 = to_cstring(
+            "SimConnect_RequestReservedKey",
+            "key_choice_1",
+            key_choice_1,
+        )?;
+        let key_choice_2 = to_cstring(
+            "SimConnect_RequestReservedKey",
+            "key_choice_2",
+            key_choice_2,
+        )?;
+        let key_choice_3 = to_cstring(
+            "SimConnect_RequestReservedKey",
+            "key_choice_3",
+            key_choice_3,
+        )?;
+
+        self.sent(
+            "SimConnect_RequestReservedKey",
+            args!(event_id, key_choice_1, key_choice_2, key_choice_3),
+            self.backend.request_reserved_key(
                 event_id,
-                key_choice_1.as_ptr(),
-                key_choice_2.as_ptr(),
-                key_choice_3.as_ptr(),
-            ) == 0
-        }
+                &key_choice_1,
+                &key_choice_2,
+                &key_choice_3,
+            ),
+        )
     }
 
-    pub fn unsubscribe_from_system_event(&self, event_id: SIMCONNECT_CLIENT_EVENT_ID) -> bool {
-        unsafe { SimConnect_UnsubscribeFromSystemEvent(self.sim_connect_handle, event_id) == 0 }
+    #[track_caller]
+    pub fn unsubscribe_from_system_event(
+        &self,
+        event_id: SIMCONNECT_CLIENT_EVENT_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_UnsubscribeFromSystemEvent",
+            args!(event_id),
+            self.backend.unsubscribe_from_system_event(event_id),
+        )
     }
 
+    #[track_caller]
+    pub fn weather_request_interpolated_observation(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        lat: f32,
+        lon: f32,
+        alt: f32,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherRequestInterpolatedObservation",
+            args!(request_id, lat, lon, alt),
+            self.backend
+                .weather_request_interpolated_observation(request_id, lat, lon, alt),
+        )
+    }
+
+    /// Requests the observation of the station `icao`, answered with a
+    /// [`DispatchResult::WeatherObservation`].
+    #[track_caller]
+    pub fn weather_request_observation_at_station(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        icao: &str,
+    ) -> Result<(), SimConnectError> {
+        check_length(
+            "SimConnect_WeatherRequestObservationAtStation",
+            "icao",
+            icao,
+            MAX_ICAO_LENGTH,
+        )?;
+        let icao = to_cstring(
+            "SimConnect_WeatherRequestObservationAtStation",
+            "icao",
+            icao,
+        )?;
+
+        self.sent(
+            "SimConnect_WeatherRequestObservationAtStation",
+            args!(request_id, icao),
+            self.backend
+                .weather_request_observation_at_station(request_id, &icao),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_request_observation_at_nearest_station(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        lat: f32,
+        lon: f32,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherRequestObservationAtNearestStation",
+            args!(request_id, lat, lon),
+            self.backend
+                .weather_request_observation_at_nearest_station(request_id, lat, lon),
+        )
+    }
+
+    /// Creates the weather station `icao` at a position, answered with a
+    /// [`DispatchResult::AssignedObjectId`].
+    #[track_caller]
+    pub fn weather_create_station(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        icao: &str,
+        name: &str,
+        lat: f32,
+        lon: f32,
+        alt: f32,
+    ) -> Result<(), SimConnectError> {
+        check_length(
+            "SimConnect_WeatherCreateStation",
+            "icao",
+            icao,
+            MAX_ICAO_LENGTH,
+        )?;
+        check_length(
+            "SimConnect_WeatherCreateStation",
+            "name",
+            name,
+            MAX_STATION_NAME_LENGTH,
+        )?;
+        let icao = to_cstring("SimConnect_WeatherCreateStation", "icao", icao)?;
+        let name = to_cstring("SimConnect_WeatherCreateStation", "name", name)?;
+
+        self.sent(
+            "SimConnect_WeatherCreateStation",
+            args!(request_id, icao, name, lat, lon, alt),
+            self.backend
+                .weather_create_station(request_id, &icao, &name, lat, lon, alt),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_remove_station(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        icao: &str,
+    ) -> Result<(), SimConnectError> {
+        check_length(
+            "SimConnect_WeatherRemoveStation",
+            "icao",
+            icao,
+            MAX_ICAO_LENGTH,
+        )?;
+        let icao = to_cstring("SimConnect_WeatherRemoveStation", "icao", icao)?;
+
+        self.sent(
+            "SimConnect_WeatherRemoveStation",
+            args!(request_id, icao),
+            self.backend.weather_remove_station(request_id, &icao),
+        )
+    }
+
+    /// Sets the observation of the station named in `metar`, blending into it over `seconds`.
+    #[track_caller]
+    pub fn weather_set_observation(
+        &self,
+        seconds: DWORD,
+        metar: &str,
+    ) -> Result<(), SimConnectError> {
+        check_length(
+            "SimConnect_WeatherSetObservation",
+            "metar",
+            metar,
+            MAX_METAR_LENGTH as usize - 1,
+        )?;
+        let metar = to_cstring("SimConnect_WeatherSetObservation", "metar", metar)?;
+
+        self.sent(
+            "SimConnect_WeatherSetObservation",
+            args!(seconds, metar),
+            self.backend.weather_set_observation(seconds, &metar),
+        )
+    }
+
+    /// Encodes `metar` and sets it as the observation of its station, blending into it over
+    /// `seconds`.
+    #[track_caller]
+    pub fn weather_set_metar(&self, seconds: DWORD, metar: &Metar) -> Result<(), SimConnectError> {
+        let metar = metar.encode().map_err(SimConnectError::Metar)?;
+        self.weather_set_observation(seconds, &metar)
+    }
+
+    #[track_caller]
+    pub fn weather_set_mode_server(
+        &self,
+        port: DWORD,
+        seconds: DWORD,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherSetModeServer",
+            args!(port, seconds),
+            self.backend.weather_set_mode_server(port, seconds),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_set_mode_theme(&self, theme_name: &str) -> Result<(), SimConnectError> {
+        let theme_name = to_cstring("SimConnect_WeatherSetModeTheme", "theme_name", theme_name)?;
+
+        self.sent(
+            "SimConnect_WeatherSetModeTheme",
+            args!(theme_name),
+            self.backend.weather_set_mode_theme(&theme_name),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_set_mode_global(&self) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherSetModeGlobal",
+            args!(),
+            self.backend.weather_set_mode_global(),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_set_mode_custom(&self) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherSetModeCustom",
+            args!(),
+            self.backend.weather_set_mode_custom(),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_set_dynamic_update_rate(&self, rate: DWORD) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherSetDynamicUpdateRate",
+            args!(rate),
+            self.backend.weather_set_dynamic_update_rate(rate),
+        )
+    }
+
+    /// Requests the cloud densities in a box, answered with a [`DispatchResult::CloudState`].
+    #[track_caller]
+    pub fn weather_request_cloud_state(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        min_lat: f32,
+        min_lon: f32,
+        min_alt: f32,
+        max_lat: f32,
+        max_lon: f32,
+        max_alt: f32,
+        flags: DWORD,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherRequestCloudState",
+            args!(request_id, min_lat, min_lon, min_alt, max_lat, max_lon, max_alt, flags),
+            self.backend.weather_request_cloud_state(
+                request_id, min_lat, min_lon, min_alt, max_lat, max_lon, max_alt, flags,
+            ),
+        )
+    }
+
+    /// Creates a thermal, answered with a [`DispatchResult::AssignedObjectId`].
+    ///
+    /// Fails with [`SimConnectError::Thermal`] without calling SimConnect if the thermal
+    /// doesn't pass [`Thermal::validate`](thermal::Thermal::validate).
+    #[track_caller]
+    pub fn weather_create_thermal(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        lat: f32,
+        lon: f32,
+        alt: f32,
+        radius: f32,
+        height: f32,
+        core_rate: f32,
+        core_turbulence: f32,
+        sink_rate: f32,
+        sink_turbulence: f32,
+        core_size: f32,
+        core_transition_size: f32,
+        sink_layer_size: f32,
+        sink_transition_size: f32,
+    ) -> Result<(), SimConnectError> {
+        thermal::Thermal {
+            lat,
+            lon,
+            alt,
+            radius,
+            height,
+            core_rate,
+            core_turbulence,
+            sink_rate,
+            sink_turbulence,
+            core_size,
+            core_transition_size,
+            sink_layer_size,
+            sink_transition_size,
+        }
+        .validate()
+        .map_err(SimConnectError::Thermal)?;
+
+        self.sent(
+            "SimConnect_WeatherCreateThermal",
+            args!(
+                request_id,
+                lat,
+                lon,
+                alt,
+                radius,
+                height,
+                core_rate,
+                core_turbulence,
+                sink_rate,
+                sink_turbulence,
+                core_size,
+                core_transition_size,
+                sink_layer_size,
+                sink_transition_size
+            ),
+            self.backend.weather_create_thermal(
+                request_id,
+                lat,
+                lon,
+                alt,
+                radius,
+                height,
+                core_rate,
+                core_turbulence,
+                sink_rate,
+                sink_turbulence,
+                core_size,
+                core_transition_size,
+                sink_layer_size,
+                sink_transition_size,
+            ),
+        )
+    }
+
+    #[track_caller]
+    pub fn weather_remove_thermal(
+        &self,
+        object_id: SIMCONNECT_OBJECT_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_WeatherRemoveThermal",
+            args!(object_id),
+            self.backend.weather_remove_thermal(object_id),
+        )
+    }
+
+    #[track_caller]
     pub fn ai_create_parked_atc_aircraft(
         &self,
         container_title: &str,
         tail_number: &str,
         airport_id: &str,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        let container_title = CString::new(container_title).unwrap();
-        let tail_number = CString::new(tail_number).unwrap();
-        let airport_id = CString::new(airport_id).unwrap();
-
-        unsafe {
-            SimConnect_AICreateParkedATCAircraft(
-                self.sim_connect_handle,
-                container_title.as_ptr(),
-                tail_number.as_ptr(),
-                airport_id.as_ptr(),
+    ) -> Result<(), SimConnectError> {
+        let container_title = to_cstring(
+            "SimConnect_AICreateParkedATCAircraft",
+            "container_title",
+            container_title,
+        )?;
+        let tail_number = to_cstring(
+            "SimConnect_AICreateParkedATCAircraft",
+            "tail_number",
+            tail_number,
+        )?;
+        let airport_id = to_cstring(
+            "SimConnect_AICreateParkedATCAircraft",
+            "airport_id",
+            airport_id,
+        )?;
+
+        self.sent(
+            "SimConnect_AICreateParkedATCAircraft",
+            args!(container_title, tail_number, airport_id, request_id),
+            self.backend.ai_create_parked_atc_aircraft(
+                &container_title,
+                &tail_number,
+                &airport_id,
                 request_id,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn ai_create_enroute_atc_aircraft(
         &self,
         container_title: &str,
@@ -256,128 +833,247 @@ impl SimConnector {
         flight_plan_position: f64,
         touch_and_go: bool,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        let container_title = CString::new(container_title).unwrap();
-        let tail_number = CString::new(tail_number).unwrap();
-        let flight_plan_path = CString::new(flight_plan_path).unwrap();
-
-        unsafe {
-            SimConnect_AICreateEnrouteATCAircraft(
-                self.sim_connect_handle,
-                container_title.as_ptr(),
-                tail_number.as_ptr(),
+    ) -> Result<(), SimConnectError> {
+        let container_title = to_cstring(
+            "SimConnect_AICreateEnrouteATCAircraft",
+            "container_title",
+            container_title,
+        )?;
+        let tail_number = to_cstring(
+            "SimConnect_AICreateEnrouteATCAircraft",
+            "tail_number",
+            tail_number,
+        )?;
+        let flight_plan_path = to_cstring(
+            "SimConnect_AICreateEnrouteATCAircraft",
+            "flight_plan_path",
+            flight_plan_path,
+        )?;
+
+        self.sent(
+            "SimConnect_AICreateEnrouteATCAircraft",
+            args!(
+                container_title,
+                tail_number,
                 flight_number,
-                flight_plan_path.as_ptr(),
+                flight_plan_path,
+                flight_plan_position,
+                touch_and_go,
+                request_id
+            ),
+            self.backend.ai_create_enroute_atc_aircraft(
+                &container_title,
+                &tail_number,
+                flight_number,
+                &flight_plan_path,
                 flight_plan_position,
                 touch_and_go as i32,
                 request_id,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn ai_create_non_atc_aircraft(
         &self,
         container_title: &str,
         tail_number: &str,
         init_pos: SIMCONNECT_DATA_INITPOSITION,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        let container_title = CString::new(container_title).unwrap();
-        let tail_number = CString::new(tail_number).unwrap();
-
-        unsafe {
-            SimConnect_AICreateNonATCAircraft(
-                self.sim_connect_handle,
-                container_title.as_ptr(),
-                tail_number.as_ptr(),
+    ) -> Result<(), SimConnectError> {
+        let container_title = to_cstring(
+            "SimConnect_AICreateNonATCAircraft",
+            "container_title",
+            container_title,
+        )?;
+        let tail_number = to_cstring(
+            "SimConnect_AICreateNonATCAircraft",
+            "tail_number",
+            tail_number,
+        )?;
+
+        self.sent(
+            "SimConnect_AICreateNonATCAircraft",
+            args!(container_title, tail_number, init_pos, request_id),
+            self.backend.ai_create_non_atc_aircraft(
+                &container_title,
+                &tail_number,
                 init_pos,
                 request_id,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn ai_create_simulated_object(
         &self,
         container_title: &str,
         init_pos: SIMCONNECT_DATA_INITPOSITION,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        let container_title = CString::new(container_title).unwrap();
-
-        unsafe {
-            SimConnect_AICreateSimulatedObject(
-                self.sim_connect_handle,
-                container_title.as_ptr(),
-                init_pos,
-                request_id,
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let container_title = to_cstring(
+            "SimConnect_AICreateSimulatedObject",
+            "container_title",
+            container_title,
+        )?;
+
+        self.sent(
+            "SimConnect_AICreateSimulatedObject",
+            args!(container_title, init_pos, request_id),
+            self.backend
+                .ai_create_simulated_object(&container_title, init_pos, request_id),
+        )
+    }
+
+    #[track_caller]
     pub fn ai_release_control(
         &self,
         object_id: SIMCONNECT_OBJECT_ID,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe { SimConnect_AIReleaseControl(self.sim_connect_handle, object_id, request_id) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_AIReleaseControl",
+            args!(object_id, request_id),
+            self.backend.ai_release_control(object_id, request_id),
+        )
     }
 
+    #[track_caller]
     pub fn ai_remove_object(
         &self,
         object_id: SIMCONNECT_OBJECT_ID,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe { SimConnect_AIRemoveObject(self.sim_connect_handle, object_id, request_id) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_AIRemoveObject",
+            args!(object_id, request_id),
+            self.backend.ai_remove_object(object_id, request_id),
+        )
     }
 
+    #[track_caller]
     pub fn ai_set_aircraft_flight_plan(
         &self,
         object_id: SIMCONNECT_OBJECT_ID,
         flight_plan_path: &str,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        let flight_plan_path = CString::new(flight_plan_path).unwrap();
-
-        unsafe {
-            SimConnect_AISetAircraftFlightPlan(
-                self.sim_connect_handle,
-                object_id,
-                flight_plan_path.as_ptr(),
-                request_id,
-            ) == 0
+    ) -> Result<(), SimConnectError> {
+        let flight_plan_path = to_cstring(
+            "SimConnect_AISetAircraftFlightPlan",
+            "flight_plan_path",
+            flight_plan_path,
+        )?;
+
+        self.sent(
+            "SimConnect_AISetAircraftFlightPlan",
+            args!(object_id, flight_plan_path, request_id),
+            self.backend
+                .ai_set_aircraft_flight_plan(object_id, &flight_plan_path, request_id),
+        )
+    }
+
+    #[track_caller]
+    pub fn execute_mission_action(&self, instance_id: GUID) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ExecuteMissionAction",
+            args!(instance_id),
+            self.backend.execute_mission_action(instance_id),
+        )
+    }
+
+    #[track_caller]
+    pub fn complete_custom_mission_action(&self, instance_id: GUID) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_CompleteCustomMissionAction",
+            args!(instance_id),
+            self.backend.complete_custom_mission_action(instance_id),
+        )
+    }
+
+    pub fn close(&self) -> Result<(), SimConnectError> {
+        self.backend.close()
+    }
+
+    /// Returns the ID of the last packet sent to SimConnect, as reported in `dwSendID` of
+    /// [`SIMCONNECT_RECV_EXCEPTION`].
+    pub fn get_last_sent_packet_id(&self) -> Result<DWORD, SimConnectError> {
+        self.backend.get_last_sent_packet_id()
+    }
+
+    /// Sets how many sent packets are remembered for
+    /// [`resolve_exception`](Self::resolve_exception).
+    ///
+    /// Defaults to 256. Each packet costs a `SimConnect_GetLastSentPacketID` call and its
+    /// formatted arguments, `0` turns the history off.
+    pub fn set_packet_history_capacity(&mut self, capacity: usize) {
+        self.sent_packets.get_mut().set_capacity(capacity);
+    }
+
+    /// Looks up the packet that caused `exception`, decoding its exception code along the way.
+    ///
+    /// Only packets sent through the wrapper methods of this connector are known, and only as
+    /// long as they are within the packet history.
+    pub fn resolve_exception(&self, exception: &SIMCONNECT_RECV_EXCEPTION) -> ResolvedException {
+        let send_id = exception.dwSendID;
+
+        ResolvedException {
+            exception: SimConnectException::from_raw(exception.dwException),
+            index: exception.dwIndex,
+            send_id,
+            packet: self.sent_packets.borrow().find(send_id).cloned(),
         }
     }
 
-    pub fn execute_mission_action(&self, instance_id: GUID) -> bool {
-        unsafe { SimConnect_ExecuteMissionAction(self.sim_connect_handle, instance_id) == 0 }
-    }
-
-    pub fn complete_custom_mission_action(&self, instance_id: GUID) -> bool {
-        unsafe { SimConnect_CompleteCustomMissionAction(self.sim_connect_handle, instance_id) == 0 }
-    }
+    /// Passes on the result of a backend call and, if the packet was sent, remembers it together
+    /// with the arguments and caller location for [`resolve_exception`](Self::resolve_exception).
+    #[track_caller]
+    fn sent(
+        &self,
+        call: &'static str,
+        args: impl FnOnce() -> String,
+        result: Result<(), SimConnectError>,
+    ) -> Result<(), SimConnectError> {
+        result?;
+        if !self.sent_packets.borrow().is_enabled() {
+            return Ok(());
+        }
 
-    pub fn close(&self) -> bool {
-        unsafe { SimConnect_Close(self.sim_connect_handle) == 0 }
-    }
+        // A failure here must not turn the successful call into an error, the packet simply
+        // stays unknown.
+        if let Ok(send_id) = self.get_last_sent_packet_id() {
+            self.sent_packets.borrow_mut().push(SentPacket {
+                send_id,
+                call,
+                args: args(),
+                location: Some(Location::caller()),
+            });
+        }
 
-    pub unsafe fn get_last_sent_packet_id(&self, error: *mut DWORD) -> bool {
-        unsafe { SimConnect_GetLastSentPacketID(self.sim_connect_handle, error) == 0 }
+        Ok(())
     }
 
     pub unsafe fn call_dispatch(
         &self,
         dispatch_callback: DispatchProc,
         context: *mut std::os::raw::c_void,
-    ) -> bool {
-        unsafe { SimConnect_CallDispatch(self.sim_connect_handle, dispatch_callback, context) == 0 }
+    ) -> Result<(), SimConnectError> {
+        self.backend.call_dispatch(dispatch_callback, context)
     }
 
-    pub unsafe fn request_response_times(&self, count: DWORD, elapsed_seconds: *mut f32) -> bool {
-        unsafe {
-            SimConnect_RequestResponseTimes(self.sim_connect_handle, count, elapsed_seconds) == 0
-        }
+    #[track_caller]
+    pub unsafe fn request_response_times(
+        &self,
+        count: DWORD,
+        elapsed_seconds: *mut f32,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestResponseTimes",
+            args!(count, elapsed_seconds),
+            unsafe { self.backend.request_response_times(count, elapsed_seconds) },
+        )
     }
 
+    #[track_caller]
     pub fn camera_set_relative_6dof(
         &self,
         delta_x: f32,
@@ -386,75 +1082,92 @@ impl SimConnector {
         pitch: f32,
         bank: f32,
         heading: f32,
-    ) -> bool {
-        unsafe {
-            SimConnect_CameraSetRelative6DOF(
-                self.sim_connect_handle,
-                delta_x,
-                delta_y,
-                delta_z,
-                pitch,
-                bank,
-                heading,
-            ) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_CameraSetRelative6DOF",
+            args!(delta_x, delta_y, delta_z, pitch, bank, heading),
+            self.backend
+                .camera_set_relative_6dof(delta_x, delta_y, delta_z, pitch, bank, heading),
+        )
     }
 
+    #[track_caller]
     pub fn menu_add_item(
         &self,
         menu_item: &str,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         data: DWORD,
-    ) -> bool {
-        let menu_item = CString::new(menu_item).unwrap();
+    ) -> Result<(), SimConnectError> {
+        let menu_item = to_cstring("SimConnect_MenuAddItem", "menu_item", menu_item)?;
 
-        unsafe {
-            SimConnect_MenuAddItem(self.sim_connect_handle, menu_item.as_ptr(), event_id, data) == 0
-        }
+        self.sent(
+            "SimConnect_MenuAddItem",
+            args!(menu_item, event_id, data),
+            self.backend.menu_add_item(&menu_item, event_id, data),
+        )
     }
 
-    pub fn menu_delete_item(&self, event_id: SIMCONNECT_CLIENT_EVENT_ID) -> bool {
-        unsafe { SimConnect_MenuDeleteItem(self.sim_connect_handle, event_id) == 0 }
+    #[track_caller]
+    pub fn menu_delete_item(
+        &self,
+        event_id: SIMCONNECT_CLIENT_EVENT_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_MenuDeleteItem",
+            args!(event_id),
+            self.backend.menu_delete_item(event_id),
+        )
     }
 
+    #[track_caller]
     pub fn menu_delete_sub_item(
         &self,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         sub_event_id: SIMCONNECT_CLIENT_EVENT_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_MenuDeleteSubItem(self.sim_connect_handle, event_id, sub_event_id) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_MenuDeleteSubItem",
+            args!(event_id, sub_event_id),
+            self.backend.menu_delete_sub_item(event_id, sub_event_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_system_state(
         &self,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
         state: &str,
-    ) -> bool {
-        let state = CString::new(state).unwrap();
+    ) -> Result<(), SimConnectError> {
+        let state = to_cstring("SimConnect_RequestSystemState", "state", state)?;
 
-        unsafe {
-            SimConnect_RequestSystemState(self.sim_connect_handle, request_id, state.as_ptr()) == 0
-        }
+        self.sent(
+            "SimConnect_RequestSystemState",
+            args!(request_id, state),
+            self.backend.request_system_state(request_id, &state),
+        )
     }
 
+    #[track_caller]
     pub fn map_client_data_name_to_id(
         &self,
         client_data_name: &str,
         data_id: SIMCONNECT_CLIENT_DATA_ID,
-    ) -> bool {
-        let client_data_name = CString::new(client_data_name).unwrap();
-
-        unsafe {
-            SimConnect_MapClientDataNameToID(
-                self.sim_connect_handle,
-                client_data_name.as_ptr(),
-                data_id,
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let client_data_name = to_cstring(
+            "SimConnect_MapClientDataNameToID",
+            "client_data_name",
+            client_data_name,
+        )?;
+
+        self.sent(
+            "SimConnect_MapClientDataNameToID",
+            args!(client_data_name, data_id),
+            self.backend
+                .map_client_data_name_to_id(&client_data_name, data_id),
+        )
+    }
+
+    #[track_caller]
     pub fn add_to_client_data_definition(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
@@ -462,23 +1175,33 @@ impl SimConnector {
         size_or_type: DWORD,
         epsilon: f32,
         datum_id: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_AddToClientDataDefinition(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_AddToClientDataDefinition",
+            args!(define_id, offset, size_or_type, epsilon, datum_id),
+            self.backend.add_to_client_data_definition(
                 define_id,
                 offset,
                 size_or_type,
                 epsilon,
                 datum_id,
-            ) == 0
-        }
+            ),
+        )
     }
 
-    pub fn clear_client_data_definition(&self, define_id: SIMCONNECT_DATA_DEFINITION_ID) -> bool {
-        unsafe { SimConnect_ClearClientDataDefinition(self.sim_connect_handle, define_id) == 0 }
+    #[track_caller]
+    pub fn clear_client_data_definition(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ClearClientDataDefinition",
+            args!(define_id),
+            self.backend.clear_client_data_definition(define_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_client_data(
         &self,
         data_id: SIMCONNECT_CLIENT_DATA_ID,
@@ -489,22 +1212,17 @@ impl SimConnector {
         origin: DWORD,
         interval: DWORD,
         limit: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestClientData(
-                self.sim_connect_handle,
-                data_id,
-                request_id,
-                define_id,
-                period,
-                flags,
-                origin,
-                interval,
-                limit,
-            ) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestClientData",
+            args!(data_id, request_id, define_id, period, flags, origin, interval, limit),
+            self.backend.request_client_data(
+                data_id, request_id, define_id, period, flags, origin, interval, limit,
+            ),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn set_client_data(
         &self,
         data_id: SIMCONNECT_CLIENT_DATA_ID,
@@ -513,26 +1231,29 @@ impl SimConnector {
         reserved: DWORD,
         unit_size: DWORD,
         data_set: *mut std::os::raw::c_void,
-    ) -> bool {
-        unsafe {
-            SimConnect_SetClientData(
-                self.sim_connect_handle,
-                data_id,
-                define_id,
-                flags,
-                reserved,
-                unit_size,
-                data_set,
-            ) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetClientData",
+            args!(data_id, define_id, flags, reserved, unit_size, data_set),
+            unsafe {
+                self.backend
+                    .set_client_data(data_id, define_id, flags, reserved, unit_size, data_set)
+            },
+        )
     }
 
-    pub fn flight_load(&self, file_name: &str) -> bool {
-        let file_name = CString::new(file_name).unwrap();
+    #[track_caller]
+    pub fn flight_load(&self, file_name: &str) -> Result<(), SimConnectError> {
+        let file_name = to_cstring("SimConnect_FlightLoad", "file_name", file_name)?;
 
-        unsafe { SimConnect_FlightLoad(self.sim_connect_handle, file_name.as_ptr()) == 0 }
+        self.sent(
+            "SimConnect_FlightLoad",
+            args!(file_name),
+            self.backend.flight_load(&file_name),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn text(
         &self,
         text_type: SIMCONNECT_TEXT_TYPE,
@@ -540,43 +1261,56 @@ impl SimConnector {
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         unit_size: DWORD,
         data_set: *mut std::os::raw::c_void,
-    ) -> bool {
-        unsafe {
-            SimConnect_Text(
-                self.sim_connect_handle,
-                text_type,
-                time_in_seconds,
-                event_id,
-                unit_size,
-                data_set,
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_Text",
+            args!(text_type, time_in_seconds, event_id, unit_size, data_set),
+            unsafe {
+                self.backend
+                    .text(text_type, time_in_seconds, event_id, unit_size, data_set)
+            },
+        )
+    }
+
+    #[track_caller]
     pub fn subscribe_to_facilities(
         &self,
         list_type: SIMCONNECT_FACILITY_LIST_TYPE,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_SubscribeToFacilities(self.sim_connect_handle, list_type, request_id) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SubscribeToFacilities",
+            args!(list_type, request_id),
+            self.backend.subscribe_to_facilities(list_type, request_id),
+        )
     }
 
-    pub fn unsubscribe_to_facilities(&self, list_type: SIMCONNECT_FACILITY_LIST_TYPE) -> bool {
-        unsafe { SimConnect_UnsubscribeToFacilities(self.sim_connect_handle, list_type) == 0 }
+    #[track_caller]
+    pub fn unsubscribe_to_facilities(
+        &self,
+        list_type: SIMCONNECT_FACILITY_LIST_TYPE,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_UnsubscribeToFacilities",
+            args!(list_type),
+            self.backend.unsubscribe_to_facilities(list_type),
+        )
     }
 
+    #[track_caller]
     pub fn request_facilities_list(
         &self,
         list_type: SIMCONNECT_FACILITY_LIST_TYPE,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestFacilitiesList(self.sim_connect_handle, list_type, request_id) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestFacilitiesList",
+            args!(list_type, request_id),
+            self.backend.request_facilities_list(list_type, request_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_data_on_sim_object(
         &self,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
@@ -587,22 +1321,17 @@ impl SimConnector {
         origin: DWORD,
         interval: DWORD,
         limit: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestDataOnSimObject(
-                self.sim_connect_handle,
-                request_id,
-                define_id,
-                object_id,
-                period,
-                flags,
-                origin,
-                interval,
-                limit,
-            ) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestDataOnSimObject",
+            args!(request_id, define_id, object_id, period, flags, origin, interval, limit),
+            self.backend.request_data_on_sim_object(
+                request_id, define_id, object_id, period, flags, origin, interval, limit,
+            ),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn set_data_on_sim_object(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
@@ -611,52 +1340,210 @@ impl SimConnector {
         array_count: DWORD,
         size: DWORD,
         pntr: *mut ::std::os::raw::c_void,
-    ) -> bool {
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetDataOnSimObject",
+            args!(define_id, object_id, flags, array_count, size, pntr),
+            unsafe {
+                self.backend.set_data_on_sim_object(
+                    define_id,
+                    object_id,
+                    flags,
+                    array_count,
+                    size,
+                    pntr,
+                )
+            },
+        )
+    }
+
+    /// Writes `data` to `object_id` with the definition registered for `T` by
+    /// [`define_data`](Self::define_data).
+    ///
+    /// The values are encoded according to the datums of the definition, so a field that
+    /// doesn't match its datum fails before anything is sent.
+    #[track_caller]
+    pub fn set_data<T: SimConnectData + 'static>(
+        &self,
+        object_id: SIMCONNECT_OBJECT_ID,
+        data: &T,
+    ) -> Result<(), SimConnectError> {
+        let definition = self.registered_definition::<T>()?;
+        let bytes = definition.encode(&data.values())?;
+
+        self.set_data_bytes(
+            definition.define_id(),
+            object_id,
+            SIMCONNECT_DATA_SET_FLAG_DEFAULT,
+            1,
+            bytes,
+        )
+    }
+
+    /// Writes only some datums of `define_id` to `object_id`, as `(datum_id, value)` pairs with
+    /// `SIMCONNECT_DATA_SET_FLAG_TAGGED`.
+    ///
+    /// The datums of a [`define_data`](Self::define_data) type are its field indices.
+    #[track_caller]
+    pub fn set_data_tagged(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+        object_id: SIMCONNECT_OBJECT_ID,
+        values: &[(DWORD, Value)],
+    ) -> Result<(), SimConnectError> {
+        let bytes = self.added_definition(define_id)?.encode_tagged(values)?;
+
+        self.set_data_bytes(
+            define_id,
+            object_id,
+            SIMCONNECT_DATA_SET_FLAG_TAGGED,
+            1,
+            bytes,
+        )
+    }
+
+    /// Writes `data` as an array of `data.len()` elements to `object_id` in a single call, e.g.
+    /// the waypoints of an AI aircraft.
+    ///
+    /// Every element has to encode to the same size, which rules out different `STRINGV`
+    /// lengths. Nothing is sent for an empty slice.
+    #[track_caller]
+    pub fn set_data_array<T: SimConnectData + 'static>(
+        &self,
+        object_id: SIMCONNECT_OBJECT_ID,
+        data: &[T],
+    ) -> Result<(), SimConnectError> {
+        let definition = self.registered_definition::<T>()?;
+
+        let mut bytes = Vec::new();
+        let mut unit_size = None;
+        for element in data {
+            let element = definition.encode(&element.values())?;
+            let expected = *unit_size.get_or_insert(element.len());
+            if element.len() != expected {
+                return Err(SimConnectError::DataSizeMismatch {
+                    define_id: definition.define_id(),
+                    expected,
+                    found: element.len(),
+                });
+            }
+            bytes.extend_from_slice(&element);
+        }
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        self.set_data_bytes(
+            definition.define_id(),
+            object_id,
+            SIMCONNECT_DATA_SET_FLAG_DEFAULT,
+            data.len() as DWORD,
+            bytes,
+        )
+    }
+
+    fn registered_definition<T: SimConnectData + 'static>(
+        &self,
+    ) -> Result<DataDefinition, SimConnectError> {
+        let define_id = self
+            .data_definition_id::<T>()
+            .ok_or_else(|| SimConnectError::UnregisteredData(type_name::<T>()))?;
+
+        self.added_definition(define_id)
+    }
+
+    fn added_definition(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<DataDefinition, SimConnectError> {
+        self.data_definition(define_id)
+            .ok_or(SimConnectError::UndefinedDefinition(define_id))
+    }
+
+    /// Sends `array_count` elements of `bytes.len() / array_count` bytes each.
+    #[track_caller]
+    fn set_data_bytes(
+        &self,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+        object_id: SIMCONNECT_OBJECT_ID,
+        flags: SIMCONNECT_DATA_SET_FLAG,
+        array_count: DWORD,
+        mut bytes: Vec<u8>,
+    ) -> Result<(), SimConnectError> {
+        let unit_size = (bytes.len() / array_count as usize) as DWORD;
+
+        // SimConnect copies the data before returning and never writes to it
         unsafe {
-            SimConnect_SetDataOnSimObject(
-                self.sim_connect_handle,
+            self.set_data_on_sim_object(
                 define_id,
                 object_id,
                 flags,
                 array_count,
-                size,
-                pntr,
-            ) == 0
+                unit_size,
+                bytes.as_mut_ptr() as *mut std::os::raw::c_void,
+            )
         }
     }
 
+    #[track_caller]
     pub fn subscribe_to_system_event(
         &self,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         event_name: &str,
-    ) -> bool {
-        let event_name = CString::new(event_name).unwrap();
+    ) -> Result<(), SimConnectError> {
+        let event_name = to_cstring(
+            "SimConnect_SubscribeToSystemEvent",
+            "event_name",
+            event_name,
+        )?;
+
+        self.sent(
+            "SimConnect_SubscribeToSystemEvent",
+            args!(event_id, event_name),
+            self.backend
+                .subscribe_to_system_event(event_id, &event_name),
+        )
+    }
+
+    /// Subscribes to `event`, whose notifications [`DispatchResult::system_event`] decodes.
+    #[track_caller]
+    pub fn subscribe(
+        &self,
+        event: SystemEvent,
+    ) -> Result<SIMCONNECT_CLIENT_EVENT_ID, SimConnectError> {
+        let event_id = event.event_id();
+        self.subscribe_to_system_event(event_id, event.name())?;
+        Ok(event_id)
+    }
 
-        unsafe {
-            SimConnect_SubscribeToSystemEvent(
-                self.sim_connect_handle,
-                event_id,
-                event_name.as_ptr(),
-            ) == 0
-        }
+    /// Reverts [`subscribe`](Self::subscribe).
+    #[track_caller]
+    pub fn unsubscribe(&self, event: SystemEvent) -> Result<(), SimConnectError> {
+        self.unsubscribe_from_system_event(event.event_id())
     }
 
+    #[track_caller]
     pub fn map_client_event_to_sim_event(
         &self,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         event_name: &str,
-    ) -> bool {
-        let event_name = CString::new(event_name).unwrap();
-
-        unsafe {
-            SimConnect_MapClientEventToSimEvent(
-                self.sim_connect_handle,
-                event_id,
-                event_name.as_ptr(),
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let event_name = to_cstring(
+            "SimConnect_MapClientEventToSimEvent",
+            "event_name",
+            event_name,
+        )?;
+
+        self.sent(
+            "SimConnect_MapClientEventToSimEvent",
+            args!(event_id, event_name),
+            self.backend
+                .map_client_event_to_sim_event(event_id, &event_name),
+        )
+    }
+
+    #[track_caller]
     pub fn transmit_client_event(
         &self,
         object_id: SIMCONNECT_OBJECT_ID,
@@ -664,46 +1551,99 @@ impl SimConnector {
         dw_data: DWORD,
         group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
         flags: SIMCONNECT_EVENT_FLAG,
-    ) -> bool {
-        unsafe {
-            SimConnect_TransmitClientEvent(
-                self.sim_connect_handle,
-                object_id,
-                event_id,
-                dw_data,
-                group_id,
-                flags,
-            ) == 0
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_TransmitClientEvent",
+            args!(object_id, event_id, dw_data, group_id, flags),
+            self.backend
+                .transmit_client_event(object_id, event_id, dw_data, group_id, flags),
+        )
+    }
+
+    /// The client event ID `event` is mapped under, mapping it on first use.
+    #[track_caller]
+    pub fn key_event_id(
+        &self,
+        event: KeyEvent,
+    ) -> Result<SIMCONNECT_CLIENT_EVENT_ID, SimConnectError> {
+        let index = self
+            .key_events
+            .borrow()
+            .iter()
+            .position(|e| e.name == event.name);
+        if let Some(index) = index {
+            return Ok(key_event::KEY_EVENT_ID_BASE + index as SIMCONNECT_CLIENT_EVENT_ID);
         }
+
+        let event_id = key_event::KEY_EVENT_ID_BASE
+            + self.key_events.borrow().len() as SIMCONNECT_CLIENT_EVENT_ID;
+        self.map_client_event_to_sim_event(event_id, event.name)?;
+        self.key_events.borrow_mut().push(event);
+
+        Ok(event_id)
     }
 
+    /// The key event mapped under `event_id` by [`key_event_id`](Self::key_event_id), e.g. to
+    /// match a received `SIMCONNECT_RECV_EVENT`.
+    pub fn key_event_for_id(&self, event_id: SIMCONNECT_CLIENT_EVENT_ID) -> Option<KeyEvent> {
+        let index = event_id.checked_sub(key_event::KEY_EVENT_ID_BASE)?;
+        self.key_events.borrow().get(index as usize).copied()
+    }
+
+    /// Transmits `event` to `object_id` with the highest priority, after encoding `value`
+    /// according to the event's [`KeyParam`] and mapping the event if needed.
+    #[track_caller]
+    pub fn transmit_key_event(
+        &self,
+        object_id: SIMCONNECT_OBJECT_ID,
+        event: KeyEvent,
+        value: impl Into<KeyValue>,
+    ) -> Result<(), SimConnectError> {
+        let data = event.encode(value)?;
+        let event_id = self.key_event_id(event)?;
+
+        self.transmit_client_event(
+            object_id,
+            event_id,
+            data,
+            SIMCONNECT_GROUP_PRIORITY_HIGHEST,
+            SIMCONNECT_EVENT_FLAG_GROUPID_IS_PRIORITY,
+        )
+    }
+
+    #[track_caller]
     pub fn add_client_event_to_notification_group(
         &self,
         group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
         event_id: SIMCONNECT_CLIENT_EVENT_ID,
         maskable: bool,
-    ) -> bool {
-        unsafe {
-            SimConnect_AddClientEventToNotificationGroup(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_AddClientEventToNotificationGroup",
+            args!(group_id, event_id, maskable),
+            self.backend.add_client_event_to_notification_group(
                 group_id,
                 event_id,
                 maskable as i32,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn set_notification_group_priority(
         &self,
         group_id: SIMCONNECT_NOTIFICATION_GROUP_ID,
         priority: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_SetNotificationGroupPriority(self.sim_connect_handle, group_id, priority)
-                == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetNotificationGroupPriority",
+            args!(group_id, priority),
+            self.backend
+                .set_notification_group_priority(group_id, priority),
+        )
     }
 
+    #[track_caller]
     pub fn map_input_event_to_client_event(
         &self,
         group_id: SIMCONNECT_INPUT_GROUP_ID,
@@ -713,33 +1653,63 @@ impl SimConnector {
         up_event: SIMCONNECT_CLIENT_EVENT_ID,
         up_return_value: DWORD,
         maskable: bool,
-    ) -> bool {
-        let input_definition = CString::new(input_definition).unwrap();
-
-        unsafe {
-            SimConnect_MapInputEventToClientEvent(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        let input_definition = to_cstring(
+            "SimConnect_MapInputEventToClientEvent",
+            "input_definition",
+            input_definition,
+        )?;
+
+        self.sent(
+            "SimConnect_MapInputEventToClientEvent",
+            args!(
                 group_id,
-                input_definition.as_ptr(),
+                input_definition,
+                down_event,
+                down_return_value,
+                up_event,
+                up_return_value,
+                maskable
+            ),
+            self.backend.map_input_event_to_client_event(
+                group_id,
+                &input_definition,
                 down_event,
                 down_return_value,
                 up_event,
                 up_return_value,
                 maskable as i32,
-            ) == 0
-        }
+            ),
+        )
     }
 
-    pub fn set_input_group_state(&self, group_id: SIMCONNECT_INPUT_GROUP_ID, state: DWORD) -> bool {
-        unsafe { SimConnect_SetInputGroupState(self.sim_connect_handle, group_id, state) == 0 }
+    #[track_caller]
+    pub fn set_input_group_state(
+        &self,
+        group_id: SIMCONNECT_INPUT_GROUP_ID,
+        state: DWORD,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetInputGroupState",
+            args!(group_id, state),
+            self.backend.set_input_group_state(group_id, state),
+        )
     }
 
-    pub fn set_input_priority(&self, group_id: SIMCONNECT_INPUT_GROUP_ID, priority: DWORD) -> bool {
-        unsafe {
-            SimConnect_SetInputGroupPriority(self.sim_connect_handle, group_id, priority) == 0
-        }
+    #[track_caller]
+    pub fn set_input_priority(
+        &self,
+        group_id: SIMCONNECT_INPUT_GROUP_ID,
+        priority: DWORD,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetInputGroupPriority",
+            args!(group_id, priority),
+            self.backend.set_input_group_priority(group_id, priority),
+        )
     }
 
+    #[track_caller]
     pub fn transmit_client_event_ex1(
         &self,
         object_id: SIMCONNECT_OBJECT_ID,
@@ -751,102 +1721,115 @@ impl SimConnector {
         data2: DWORD,
         data3: DWORD,
         data4: DWORD,
-    ) -> bool {
-        unsafe {
-            SimConnect_TransmitClientEvent_EX1(
-                self.sim_connect_handle,
-                object_id,
-                event_id,
-                group_id,
-                flags,
-                data0,
-                data1,
-                data2,
-                data3,
-                data4,
-            ) == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_TransmitClientEvent_EX1",
+            args!(object_id, event_id, group_id, flags, data0, data1, data2, data3, data4),
+            self.backend.transmit_client_event_ex1(
+                object_id, event_id, group_id, flags, data0, data1, data2, data3, data4,
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn add_to_facility_definition(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
         field_name: &str,
-    ) -> bool {
-        let field_name = CString::new(field_name).unwrap();
-        unsafe {
-            SimConnect_AddToFacilityDefinition(
-                self.sim_connect_handle,
-                define_id,
-                field_name.as_ptr(),
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let field_name = to_cstring(
+            "SimConnect_AddToFacilityDefinition",
+            "field_name",
+            field_name,
+        )?;
+        self.sent(
+            "SimConnect_AddToFacilityDefinition",
+            args!(define_id, field_name),
+            self.backend
+                .add_to_facility_definition(define_id, &field_name),
+        )
+    }
+
+    #[track_caller]
     pub fn request_facility_data(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
         icao: &str,
         region: Option<&str>,
-    ) -> bool {
-        let icao = CString::new(icao).unwrap();
-        let region = region.map(|s| CString::new(s).unwrap());
+    ) -> Result<(), SimConnectError> {
+        let icao = to_cstring("SimConnect_RequestFacilityData", "icao", icao)?;
+        let region = region
+            .map(|s| to_cstring("SimConnect_RequestFacilityData", "region", s))
+            .transpose()?;
 
-        unsafe {
-            SimConnect_RequestFacilityData(
-                self.sim_connect_handle,
-                define_id,
-                request_id,
-                icao.as_ptr(),
-                region.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
-            ) == 0
-        }
+        self.sent(
+            "SimConnect_RequestFacilityData",
+            args!(define_id, request_id, icao, region),
+            self.backend
+                .request_facility_data(define_id, request_id, &icao, region.as_deref()),
+        )
     }
 
+    #[track_caller]
     pub fn subscribe_to_facilities_ex1(
         &self,
         list_type: SIMCONNECT_FACILITY_LIST_TYPE,
         new_in_range_request_id: SIMCONNECT_DATA_REQUEST_ID,
         old_out_of_range_request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_SubscribeToFacilities_EX1(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SubscribeToFacilities_EX1",
+            args!(
+                list_type,
+                new_in_range_request_id,
+                old_out_of_range_request_id
+            ),
+            self.backend.subscribe_to_facilities_ex1(
                 list_type,
                 new_in_range_request_id,
                 old_out_of_range_request_id,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn unsubscribe_to_facilities_ex1(
         &self,
         list_type: SIMCONNECT_FACILITY_LIST_TYPE,
         unsubscribe_new_in_range: bool,
         unsubscribe_old_out_of_range: bool,
-    ) -> bool {
-        unsafe {
-            SimConnect_UnsubscribeToFacilities_EX1(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_UnsubscribeToFacilities_EX1",
+            args!(
+                list_type,
+                unsubscribe_new_in_range,
+                unsubscribe_old_out_of_range
+            ),
+            self.backend.unsubscribe_to_facilities_ex1(
                 list_type,
                 unsubscribe_new_in_range,
                 unsubscribe_old_out_of_range,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub fn request_facilities_list_ex1(
         &self,
         list_type: SIMCONNECT_FACILITY_LIST_TYPE,
         request_id: SIMCONNECT_DATA_REQUEST_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_RequestFacilitiesList_EX1(self.sim_connect_handle, list_type, request_id)
-                == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_RequestFacilitiesList_EX1",
+            args!(list_type, request_id),
+            self.backend
+                .request_facilities_list_ex1(list_type, request_id),
+        )
     }
 
+    #[track_caller]
     pub fn request_facility_data_ex1(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
@@ -854,43 +1837,54 @@ impl SimConnector {
         icao: &str,
         region: Option<&str>,
         type_: Option<i8>,
-    ) -> bool {
-        let icao = CString::new(icao).unwrap();
-        let region = region.map(|s| CString::new(s).unwrap());
-
-        unsafe {
-            SimConnect_RequestFacilityData_EX1(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        let icao = to_cstring("SimConnect_RequestFacilityData_EX1", "icao", icao)?;
+        let region = region
+            .map(|s| to_cstring("SimConnect_RequestFacilityData_EX1", "region", s))
+            .transpose()?;
+
+        self.sent(
+            "SimConnect_RequestFacilityData_EX1",
+            args!(define_id, request_id, icao, region, type_),
+            self.backend.request_facility_data_ex1(
                 define_id,
                 request_id,
-                icao.as_ptr(),
-                region.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                &icao,
+                region.as_deref(),
                 type_.unwrap_or(0) as ::std::os::raw::c_char,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn request_jetway_data(
         &self,
         airport_icao: &str,
         array_count: DWORD,
         indexes: *mut i32,
-    ) -> bool {
-        let airport_icao = CString::new(airport_icao).unwrap();
-        unsafe {
-            SimConnect_RequestJetwayData(
-                self.sim_connect_handle,
-                airport_icao.as_ptr(),
-                array_count,
-                indexes,
-            ) == 0
-        }
-    }
-
-    pub fn enumerate_controllers(&self) -> bool {
-        unsafe { SimConnect_EnumerateControllers(self.sim_connect_handle) == 0 }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let airport_icao =
+            to_cstring("SimConnect_RequestJetwayData", "airport_icao", airport_icao)?;
+        self.sent(
+            "SimConnect_RequestJetwayData",
+            args!(airport_icao, array_count, indexes),
+            unsafe {
+                self.backend
+                    .request_jetway_data(&airport_icao, array_count, indexes)
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn enumerate_controllers(&self) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_EnumerateControllers",
+            args!(),
+            self.backend.enumerate_controllers(),
+        )
+    }
+
+    #[track_caller]
     pub fn map_input_event_to_client_event_ex1(
         &self,
         group_id: SIMCONNECT_INPUT_GROUP_ID,
@@ -900,117 +1894,194 @@ impl SimConnector {
         up_event: SIMCONNECT_CLIENT_EVENT_ID,
         up_return_value: DWORD,
         maskable: bool,
-    ) -> bool {
-        let input_definition = CString::new(input_definition).unwrap();
-
-        unsafe {
-            SimConnect_MapInputEventToClientEvent_EX1(
-                self.sim_connect_handle,
+    ) -> Result<(), SimConnectError> {
+        let input_definition = to_cstring(
+            "SimConnect_MapInputEventToClientEvent_EX1",
+            "input_definition",
+            input_definition,
+        )?;
+
+        self.sent(
+            "SimConnect_MapInputEventToClientEvent_EX1",
+            args!(
                 group_id,
-                input_definition.as_ptr(),
+                input_definition,
+                down_event,
+                down_return_value,
+                up_event,
+                up_return_value,
+                maskable
+            ),
+            self.backend.map_input_event_to_client_event_ex1(
+                group_id,
+                &input_definition,
                 down_event,
                 down_return_value,
                 up_event,
                 up_return_value,
                 maskable as i32,
-            ) == 0
-        }
+            ),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn execute_action(
         &self,
         request_id: DWORD,
         action_id: &str,
         unit_size: DWORD,
         param_values: *mut std::os::raw::c_void,
-    ) -> bool {
-        let action_id = CString::new(action_id).unwrap();
-        unsafe {
-            SimConnect_ExecuteAction(
-                self.sim_connect_handle,
-                request_id,
-                action_id.as_ptr(),
-                unit_size,
-                param_values,
-            ) == 0
-        }
-    }
-
-    pub fn enumerate_input_events(&self, request_id: SIMCONNECT_DATA_REQUEST_ID) -> bool {
-        unsafe { SimConnect_EnumerateInputEvents(self.sim_connect_handle, request_id) == 0 }
+    ) -> Result<(), SimConnectError> {
+        let action_id = to_cstring("SimConnect_ExecuteAction", "action_id", action_id)?;
+        self.sent(
+            "SimConnect_ExecuteAction",
+            args!(request_id, action_id, unit_size, param_values),
+            unsafe {
+                self.backend
+                    .execute_action(request_id, &action_id, unit_size, param_values)
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn enumerate_input_events(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_EnumerateInputEvents",
+            args!(request_id),
+            self.backend.enumerate_input_events(request_id),
+        )
     }
 
-    pub fn get_input_event(&self, request_id: SIMCONNECT_DATA_REQUEST_ID, hash: u64) -> bool {
-        unsafe { SimConnect_GetInputEvent(self.sim_connect_handle, request_id, hash) == 0 }
+    #[track_caller]
+    pub fn get_input_event(
+        &self,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+        hash: u64,
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_GetInputEvent",
+            args!(request_id, hash),
+            self.backend.get_input_event(request_id, hash),
+        )
     }
 
+    #[track_caller]
     pub unsafe fn set_input_event(
         &self,
         hash: u64,
         unit_size: DWORD,
         value: *mut std::os::raw::c_void,
-    ) -> bool {
-        unsafe { SimConnect_SetInputEvent(self.sim_connect_handle, hash, unit_size, value) == 0 }
-    }
-
-    pub fn subscribe_input_event(&self, hash: u64) -> bool {
-        unsafe { SimConnect_SubscribeInputEvent(self.sim_connect_handle, hash) == 0 }
-    }
-
-    pub fn unsubscribe_input_event(&self, hash: u64) -> bool {
-        unsafe { SimConnect_UnsubscribeInputEvent(self.sim_connect_handle, hash) == 0 }
-    }
-
-    pub fn enumerate_input_event_params(&self, hash: u64) -> bool {
-        unsafe { SimConnect_EnumerateInputEventParams(self.sim_connect_handle, hash) == 0 }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SetInputEvent",
+            args!(hash, unit_size, value),
+            unsafe { self.backend.set_input_event(hash, unit_size, value) },
+        )
+    }
+
+    #[track_caller]
+    pub fn subscribe_input_event(&self, hash: u64) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_SubscribeInputEvent",
+            args!(hash),
+            self.backend.subscribe_input_event(hash),
+        )
+    }
+
+    #[track_caller]
+    pub fn unsubscribe_input_event(&self, hash: u64) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_UnsubscribeInputEvent",
+            args!(hash),
+            self.backend.unsubscribe_input_event(hash),
+        )
+    }
+
+    #[track_caller]
+    pub fn enumerate_input_event_params(&self, hash: u64) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_EnumerateInputEventParams",
+            args!(hash),
+            self.backend.enumerate_input_event_params(hash),
+        )
+    }
+
+    #[track_caller]
     pub fn add_facility_data_definition_filter(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
         filter_path: &str,
         filter_data: *const std::os::raw::c_void,
         cb_unit_size: DWORD,
-    ) -> bool {
-        let filter_path = CString::new(filter_path).unwrap();
-        unsafe {
-            SimConnect_AddFacilityDataDefinitionFilter(
-                self.sim_connect_handle,
-                define_id,
-                filter_path.as_ptr(),
-                cb_unit_size,
-                filter_data as *mut _,
-            ) == 0
-        }
-    }
-
+    ) -> Result<(), SimConnectError> {
+        let filter_path = to_cstring(
+            "SimConnect_AddFacilityDataDefinitionFilter",
+            "filter_path",
+            filter_path,
+        )?;
+        self.sent(
+            "SimConnect_AddFacilityDataDefinitionFilter",
+            args!(define_id, filter_path, filter_data, cb_unit_size),
+            unsafe {
+                self.backend.add_facility_data_definition_filter(
+                    define_id,
+                    &filter_path,
+                    cb_unit_size,
+                    filter_data as *mut _,
+                )
+            },
+        )
+    }
+
+    #[track_caller]
     pub fn clear_all_facility_data_definition_filters(
         &self,
         define_id: SIMCONNECT_DATA_DEFINITION_ID,
-    ) -> bool {
-        unsafe {
-            SimConnect_ClearAllFacilityDataDefinitionFilters(self.sim_connect_handle, define_id)
-                == 0
-        }
+    ) -> Result<(), SimConnectError> {
+        self.sent(
+            "SimConnect_ClearAllFacilityDataDefinitionFilters",
+            args!(define_id),
+            self.backend
+                .clear_all_facility_data_definition_filters(define_id),
+        )
     }
 
     /// Retrieves the next message from SimConnect. Nonblocking.
-    pub fn get_next_message(&self) -> Result<DispatchResult<'_>, &str> {
-        let mut data_buf: *mut SIMCONNECT_RECV = ptr::null_mut();
-        let mut size_buf: DWORD = 0;
-        let size_buf_pointer: *mut DWORD = &mut size_buf;
+    ///
+    /// The message lives in a buffer the next call overwrites, so it keeps the connector
+    /// borrowed until it is dropped. Use [`DispatchResult::to_owned`] to keep it longer.
+    ///
+    /// Holding on to a message across calls doesn't compile:
+    ///
+    /// ```compile_fail,E0499
+    /// # use simconnect::{mock::MockBackend, SimConnector};
+    /// let mut conn = SimConnector::with_backend(MockBackend::new());
+    /// let first = conn.get_next_message().unwrap();
+    /// let second = conn.get_next_message().unwrap();
+    /// println!("{:?} {:?}", first, second);
+    /// ```
+    ///
+    /// Neither does using a message after the connector is gone:
+    ///
+    /// ```compile_fail,E0505
+    /// # use simconnect::{mock::MockBackend, SimConnector};
+    /// let mut conn = SimConnector::with_backend(MockBackend::new());
+    /// let message = conn.get_next_message().unwrap();
+    /// drop(conn);
+    /// println!("{:?}", message);
+    /// ```
+    pub fn get_next_message(&mut self) -> Result<DispatchResult<'_>, SimConnectError> {
+        let (data_buf, size) = match self.backend.get_next_dispatch() {
+            // A backend passing on the library's empty queue error still means no message
+            Err(error) if backend::is_empty_queue(&error) => return Ok(DispatchResult::Null),
+            result => result?,
+        };
+        let size = size as usize;
 
         unsafe {
-            let result = SimConnect_GetNextDispatch(
-                self.sim_connect_handle,
-                &mut data_buf,
-                size_buf_pointer,
-            );
-
-            if result != 0 {
-                return Err("Failed getting data!");
-            }
-
             // SimConnect_GetNextDispatch returns S_OK with a NULL pointer when the queue is empty.
             // Dereferencing a null pointer would be UB, so we must check this explicitly.
             if data_buf.is_null() {
@@ -1019,188 +2090,140 @@ impl SimConnector {
 
             match (*data_buf).dwID as SIMCONNECT_RECV_ID {
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NULL => Ok(DispatchResult::Null),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION => Ok(DispatchResult::Exception(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_EXCEPTION)),
-                )),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN => Ok(DispatchResult::Open(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_OPEN)),
-                )),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT => Ok(DispatchResult::Quit(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_QUIT)),
-                )),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT => Ok(DispatchResult::Event(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_EVENT)),
-                )),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE => {
-                    Ok(DispatchResult::EventObjectAddRemove(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION => {
+                    Ok(DispatchResult::Exception(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME => {
-                    Ok(DispatchResult::EventFilename(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_FILENAME),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN => {
+                    Ok(DispatchResult::Open(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FRAME => {
-                    Ok(DispatchResult::EventFrame(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_FRAME),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT => {
+                    Ok(DispatchResult::Quit(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA => {
-                    Ok(DispatchResult::SimObjectData(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_SIMOBJECT_DATA),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT => {
+                    Ok(DispatchResult::Event(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE => {
-                    Ok(DispatchResult::SimObjectDataByType(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE => {
+                    Ok(DispatchResult::EventObjectAddRemove(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WEATHER_OBSERVATION => {
-                    Ok(DispatchResult::WeatherObservation(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_WEATHER_OBSERVATION),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME => {
+                    Ok(DispatchResult::EventFilename(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLOUD_STATE => {
-                    Ok(DispatchResult::CloudState(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_CLOUD_STATE),
-                    )))
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FRAME => {
+                    Ok(DispatchResult::EventFrame(recv(data_buf, size)?))
                 }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA => Ok(
+                    DispatchResult::SimObjectData(Payload::new(recv(data_buf, size)?, size)),
+                ),
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE => Ok(
+                    DispatchResult::SimObjectDataByType(Payload::new(recv(data_buf, size)?, size)),
+                ),
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WEATHER_OBSERVATION => Ok(
+                    DispatchResult::WeatherObservation(Payload::new(recv(data_buf, size)?, size)),
+                ),
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLOUD_STATE => Ok(
+                    DispatchResult::CloudState(Payload::new(recv(data_buf, size)?, size)),
+                ),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ASSIGNED_OBJECT_ID => {
-                    Ok(DispatchResult::AssignedObjectId(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_ASSIGNED_OBJECT_ID),
-                    )))
+                    Ok(DispatchResult::AssignedObjectId(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_RESERVED_KEY => {
-                    Ok(DispatchResult::ReservedKey(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_RESERVED_KEY),
-                    )))
+                    Ok(DispatchResult::ReservedKey(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CUSTOM_ACTION => {
-                    Ok(DispatchResult::CustomAction(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_CUSTOM_ACTION),
-                    )))
+                    Ok(DispatchResult::CustomAction(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SYSTEM_STATE => {
-                    Ok(DispatchResult::SystemState(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_SYSTEM_STATE),
-                    )))
-                }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA => {
-                    Ok(DispatchResult::ClientData(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_CLIENT_DATA),
-                    )))
+                    Ok(DispatchResult::SystemState(recv(data_buf, size)?))
                 }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA => Ok(
+                    DispatchResult::ClientData(Payload::new(recv(data_buf, size)?, size)),
+                ),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_WEATHER_MODE => {
-                    Ok(DispatchResult::EventWeatherMode(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_WEATHER_MODE),
-                    )))
+                    Ok(DispatchResult::EventWeatherMode(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST => {
-                    Ok(DispatchResult::AirportList(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_AIRPORT_LIST),
-                    )))
+                    Ok(DispatchResult::AirportList(recv(data_buf, size)?))
+                }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST => {
+                    Ok(DispatchResult::VorList(recv(data_buf, size)?))
+                }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST => {
+                    Ok(DispatchResult::NdbList(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST => Ok(DispatchResult::VorList(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_VOR_LIST)),
-                )),
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST => Ok(DispatchResult::NdbList(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_NDB_LIST)),
-                )),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST => {
-                    Ok(DispatchResult::WaypointList(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_WAYPOINT_LIST),
-                    )))
+                    Ok(DispatchResult::WaypointList(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SERVER_STARTED => Ok(
-                    DispatchResult::EventMultiplayerServerStarted(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_MULTIPLAYER_SERVER_STARTED),
-                    )),
+                    DispatchResult::EventMultiplayerServerStarted(recv(data_buf, size)?),
                 ),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_CLIENT_STARTED => Ok(
-                    DispatchResult::EventMultiplayerClientStarted(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_MULTIPLAYER_CLIENT_STARTED),
-                    )),
+                    DispatchResult::EventMultiplayerClientStarted(recv(data_buf, size)?),
                 ),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SESSION_ENDED => Ok(
-                    DispatchResult::EventMultiplayerSessionEnded(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_MULTIPLAYER_SESSION_ENDED),
-                    )),
+                    DispatchResult::EventMultiplayerSessionEnded(recv(data_buf, size)?),
                 ),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_END => {
-                    Ok(DispatchResult::EventRaceEnd(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_RACE_END),
-                    )))
+                    Ok(DispatchResult::EventRaceEnd(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_LAP => {
-                    Ok(DispatchResult::EventRaceLap(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_EVENT_RACE_LAP),
-                    )))
+                    Ok(DispatchResult::EventRaceLap(recv(data_buf, size)?))
+                }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_EX1 => {
+                    Ok(DispatchResult::EventEx1(recv(data_buf, size)?))
                 }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_EX1 => Ok(DispatchResult::EventEx1(
-                    transmute_copy(&(data_buf as *const SIMCONNECT_RECV_EVENT_EX1)),
-                )),
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA => {
-                    Ok(DispatchResult::FacilityData(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_FACILITY_DATA),
-                    )))
+                    Ok(DispatchResult::FacilityData(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA_END => {
-                    Ok(DispatchResult::FacilityDataEnd(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_FACILITY_DATA_END),
-                    )))
+                    Ok(DispatchResult::FacilityDataEnd(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_MINIMAL_LIST => {
-                    Ok(DispatchResult::FacilityMinimalList(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_FACILITY_MINIMAL_LIST),
-                    )))
+                    Ok(DispatchResult::FacilityMinimalList(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_JETWAY_DATA => {
-                    Ok(DispatchResult::JetwayData(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_JETWAY_DATA),
-                    )))
+                    Ok(DispatchResult::JetwayData(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CONTROLLERS_LIST => {
-                    Ok(DispatchResult::ControllersList(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_CONTROLLERS_LIST),
-                    )))
+                    Ok(DispatchResult::ControllersList(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ACTION_CALLBACK => {
-                    Ok(DispatchResult::ActionCallback(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_ACTION_CALLBACK),
-                    )))
+                    Ok(DispatchResult::ActionCallback(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENTS => {
-                    Ok(DispatchResult::EnumerateInputEvents(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_ENUMERATE_INPUT_EVENTS),
-                    )))
+                    Ok(DispatchResult::EnumerateInputEvents(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_GET_INPUT_EVENT => {
-                    Ok(DispatchResult::GetInputEvent(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_GET_INPUT_EVENT),
-                    )))
+                    Ok(DispatchResult::GetInputEvent(recv(data_buf, size)?))
                 }
                 SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SUBSCRIBE_INPUT_EVENT => {
-                    Ok(DispatchResult::SubscribeInputEvent(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT),
-                    )))
-                }
-                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS => {
-                    Ok(DispatchResult::EnumerateInputEventParams(transmute_copy(
-                        &(data_buf as *const SIMCONNECT_RECV_ENUMERATE_INPUT_EVENT_PARAMS),
-                    )))
+                    Ok(DispatchResult::SubscribeInputEvent(recv(data_buf, size)?))
                 }
+                SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS => Ok(
+                    DispatchResult::EnumerateInputEventParams(recv(data_buf, size)?),
+                ),
 
-                _ => Err("Unhandled RECV_ID"),
+                id => Err(SimConnectError::UnhandledRecvId(id as DWORD)),
             }
         }
     }
 }
 
-impl Drop for SimConnector {
+/// Reads the message at `data` as `T`, unless its `size` bytes are too few for that struct.
+unsafe fn recv<'a, T>(data: *mut SIMCONNECT_RECV, size: usize) -> Result<&'a T, SimConnectError> {
+    if size < size_of::<T>() {
+        return Err(SimConnectError::TruncatedMessage {
+            id: (*data).dwID,
+            size,
+            expected: size_of::<T>(),
+        });
+    }
+    Ok(transmute_copy(&(data as *const T)))
+}
+
+impl<B: SimConnectBackend> Drop for SimConnector<B> {
     fn drop(&mut self) {
-        if !self.sim_connect_handle.is_null() {
-            self.close();
+        if self.connected {
+            let _ = self.close();
         }
     }
 }
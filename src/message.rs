@@ -0,0 +1,764 @@
+//! Owned copies of the messages received from SimConnect
+//!
+//! A [`DispatchResult`] borrows the buffer of the backend, which the next call to
+//! [`get_next_message`](crate::SimConnector::get_next_message) overwrites. [`Message`] copies
+//! everything out of it, including the variable sized payloads, so it can be queued, sent to
+//! another thread or stored. With the `serde` feature it can also be serialized.
+
+use std::mem::size_of;
+use std::os::raw::c_char;
+use std::ptr::{self, addr_of};
+use std::slice;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Owned counterpart of [`DispatchResult`], one variant per `SIMCONNECT_RECV_ID`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Message {
+    Null,
+    Exception(Exception),
+    Open(Open),
+    Quit,
+    Event(Event),
+    EventObjectAddRemove(EventObjectAddRemove),
+    EventFilename(EventFilename),
+    EventFrame(EventFrame),
+    SimObjectData(SimObjectData),
+    SimObjectDataByType(SimObjectData),
+    WeatherObservation(WeatherObservation),
+    CloudState(CloudState),
+    AssignedObjectId(AssignedObjectId),
+    ReservedKey(ReservedKey),
+    CustomAction(CustomAction),
+    SystemState(SystemState),
+    ClientData(SimObjectData),
+    EventWeatherMode(Event),
+    AirportList(List<FacilityAirport>),
+    VorList(List<FacilityVor>),
+    NdbList(List<FacilityNdb>),
+    WaypointList(List<FacilityWaypoint>),
+    EventMultiplayerServerStarted(Event),
+    EventMultiplayerClientStarted(Event),
+    EventMultiplayerSessionEnded(Event),
+    EventRaceEnd(EventRace),
+    EventRaceLap(EventRace),
+    EventEx1(EventEx1),
+    FacilityData(FacilityData),
+    FacilityDataEnd(FacilityDataEnd),
+    FacilityMinimalList(List<FacilityMinimal>),
+    JetwayData(List<JetwayData>),
+    ActionCallback(ActionCallback),
+    EnumerateInputEvents(List<InputEventDescriptor>),
+    GetInputEvent(GetInputEvent),
+    SubscribeInputEvent(SubscribeInputEvent),
+    EnumerateInputEventParams(EnumerateInputEventParams),
+    ControllersList(List<ControllerItem>),
+}
+
+/// `SIMCONNECT_RECV_EXCEPTION`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Exception {
+    pub exception: SimConnectException,
+    pub send_id: DWORD,
+    pub index: DWORD,
+}
+
+/// `SIMCONNECT_RECV_OPEN`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Open {
+    pub application_name: String,
+    pub application_version_major: DWORD,
+    pub application_version_minor: DWORD,
+    pub application_build_major: DWORD,
+    pub application_build_minor: DWORD,
+    pub simconnect_version_major: DWORD,
+    pub simconnect_version_minor: DWORD,
+    pub simconnect_build_major: DWORD,
+    pub simconnect_build_minor: DWORD,
+}
+
+/// `SIMCONNECT_RECV_EVENT`, also the base of the other events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Event {
+    pub group_id: DWORD,
+    pub event_id: DWORD,
+    pub data: DWORD,
+}
+
+/// `SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventObjectAddRemove {
+    pub event: Event,
+    pub object_type: SIMCONNECT_SIMOBJECT_TYPE,
+}
+
+/// `SIMCONNECT_RECV_EVENT_FILENAME`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventFilename {
+    pub event: Event,
+    pub file_name: String,
+    pub flags: DWORD,
+}
+
+/// `SIMCONNECT_RECV_EVENT_FRAME`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventFrame {
+    pub event: Event,
+    pub frame_rate: f32,
+    pub sim_speed: f32,
+}
+
+/// `SIMCONNECT_RECV_SIMOBJECT_DATA`, also used for `_BYTYPE` and `CLIENT_DATA`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SimObjectData {
+    pub request_id: DWORD,
+    pub object_id: DWORD,
+    pub define_id: DWORD,
+    pub flags: DWORD,
+    pub entry_number: DWORD,
+    pub out_of: DWORD,
+    pub define_count: DWORD,
+    /// The data of the definition, starting at `dwData`.
+    pub data: Vec<u8>,
+}
+
+/// `SIMCONNECT_RECV_WEATHER_OBSERVATION`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WeatherObservation {
+    pub request_id: DWORD,
+    pub metar: String,
+}
+
+/// `SIMCONNECT_RECV_CLOUD_STATE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CloudState {
+    pub request_id: DWORD,
+    /// Cloud density per cell, row by row.
+    pub data: Vec<u8>,
+}
+
+/// `SIMCONNECT_RECV_ASSIGNED_OBJECT_ID`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AssignedObjectId {
+    pub request_id: DWORD,
+    pub object_id: DWORD,
+}
+
+/// `SIMCONNECT_RECV_RESERVED_KEY`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReservedKey {
+    pub choice_reserved: String,
+    pub reserved_key: String,
+}
+
+/// `SIMCONNECT_RECV_CUSTOM_ACTION`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CustomAction {
+    pub event: Event,
+    /// `guidInstanceId` in memory order.
+    pub instance_id: [u8; 16],
+    pub wait_for_completion: DWORD,
+    pub payload: String,
+}
+
+/// `SIMCONNECT_RECV_SYSTEM_STATE`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SystemState {
+    pub request_id: DWORD,
+    pub integer: DWORD,
+    pub float: f32,
+    pub string: String,
+}
+
+/// One part of a list reply, e.g. `SIMCONNECT_RECV_AIRPORT_LIST`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct List<T> {
+    pub request_id: DWORD,
+    pub entry_number: DWORD,
+    pub out_of: DWORD,
+    pub items: Vec<T>,
+}
+
+/// `SIMCONNECT_DATA_FACILITY_AIRPORT`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityAirport {
+    pub ident: String,
+    pub region: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+/// `SIMCONNECT_DATA_FACILITY_WAYPOINT`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityWaypoint {
+    pub facility: FacilityAirport,
+    pub mag_var: f32,
+}
+
+/// `SIMCONNECT_DATA_FACILITY_NDB`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityNdb {
+    pub waypoint: FacilityWaypoint,
+    pub frequency: DWORD,
+}
+
+/// `SIMCONNECT_DATA_FACILITY_VOR`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityVor {
+    pub ndb: FacilityNdb,
+    pub flags: DWORD,
+    pub localizer: f32,
+    pub glide_lat: f64,
+    pub glide_lon: f64,
+    pub glide_alt: f64,
+    pub glide_slope_angle: f32,
+}
+
+/// `SIMCONNECT_RECV_EVENT_RACE_END` and `SIMCONNECT_RECV_EVENT_RACE_LAP`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventRace {
+    pub event: Event,
+    /// `dwRacerNumber` of a race end, `dwLapIndex` of a lap.
+    pub number: DWORD,
+    pub racer: RaceResult,
+}
+
+/// `SIMCONNECT_DATA_RACE_RESULT`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RaceResult {
+    pub number_of_racers: DWORD,
+    /// `MissionGUID` in memory order.
+    pub mission_guid: [u8; 16],
+    pub player_name: String,
+    pub session_type: String,
+    pub aircraft: String,
+    pub player_role: String,
+    pub total_time: f64,
+    pub penalty_time: f64,
+    pub is_disqualified: bool,
+}
+
+/// `SIMCONNECT_RECV_EVENT_EX1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventEx1 {
+    pub group_id: DWORD,
+    pub event_id: DWORD,
+    pub data: [DWORD; 5],
+}
+
+/// `SIMCONNECT_RECV_FACILITY_DATA`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityData {
+    pub user_request_id: DWORD,
+    pub unique_request_id: DWORD,
+    pub parent_unique_request_id: DWORD,
+    pub type_: DWORD,
+    pub is_list_item: bool,
+    pub item_index: DWORD,
+    pub list_size: DWORD,
+    /// The data of the facility definition, starting at `Data`.
+    pub data: Vec<u8>,
+}
+
+/// `SIMCONNECT_RECV_FACILITY_DATA_END`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityDataEnd {
+    pub request_id: DWORD,
+}
+
+/// `SIMCONNECT_ICAO`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Icao {
+    pub type_: u8,
+    pub ident: String,
+    pub region: String,
+    pub airport: String,
+}
+
+/// `SIMCONNECT_FACILITY_MINIMAL`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FacilityMinimal {
+    pub icao: Icao,
+    pub lla: LatLonAlt,
+}
+
+/// `SIMCONNECT_JETWAY_DATA`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JetwayData {
+    pub airport_icao: String,
+    pub parking_index: i32,
+    pub lla: LatLonAlt,
+    pub pbh: Pbh,
+    pub status: i32,
+    pub door: i32,
+    pub exit_door_relative_pos: Xyz,
+    pub main_handle_pos: Xyz,
+    pub secondary_handle: Xyz,
+    pub wheel_ground_lock: Xyz,
+    pub jetway_object_id: DWORD,
+    pub attached_object_id: DWORD,
+}
+
+/// `SIMCONNECT_RECV_ACTION_CALLBACK`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActionCallback {
+    pub action_id: String,
+    pub request_id: DWORD,
+}
+
+/// `SIMCONNECT_INPUT_EVENT_DESCRIPTOR`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputEventDescriptor {
+    pub name: String,
+    pub hash: u64,
+    pub type_: SIMCONNECT_INPUT_EVENT_TYPE,
+}
+
+/// `SIMCONNECT_RECV_GET_INPUT_EVENT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetInputEvent {
+    pub request_id: DWORD,
+    pub type_: SIMCONNECT_INPUT_EVENT_TYPE,
+    pub value: DWORD,
+}
+
+/// `SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubscribeInputEvent {
+    pub hash: u64,
+    pub type_: SIMCONNECT_INPUT_EVENT_TYPE,
+    pub value: DWORD,
+}
+
+/// `SIMCONNECT_RECV_ENUMERATE_INPUT_EVENT_PARAMS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnumerateInputEventParams {
+    pub hash: u64,
+    pub value: String,
+}
+
+/// `SIMCONNECT_CONTROLLER_ITEM`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControllerItem {
+    pub device_name: String,
+    pub device_id: u32,
+    pub product_id: u32,
+    pub composite_id: u32,
+    /// Major, minor, revision and build.
+    pub hardware_version: [u16; 4],
+}
+
+impl DispatchResult<'_> {
+    /// Copies the message, including its variable sized payload, out of the backend's buffer.
+    pub fn to_owned(&self) -> Message {
+        // The references point into a buffer holding the whole message, `dwSize` bytes long.
+        unsafe {
+            match *self {
+                Self::Null => Message::Null,
+                Self::Exception(m) => Message::Exception(Exception {
+                    exception: SimConnectException::from_raw(m.dwException),
+                    send_id: m.dwSendID,
+                    index: m.dwIndex,
+                }),
+                Self::Open(m) => Message::Open(Open {
+                    application_name: string(&m.szApplicationName),
+                    application_version_major: m.dwApplicationVersionMajor,
+                    application_version_minor: m.dwApplicationVersionMinor,
+                    application_build_major: m.dwApplicationBuildMajor,
+                    application_build_minor: m.dwApplicationBuildMinor,
+                    simconnect_version_major: m.dwSimConnectVersionMajor,
+                    simconnect_version_minor: m.dwSimConnectVersionMinor,
+                    simconnect_build_major: m.dwSimConnectBuildMajor,
+                    simconnect_build_minor: m.dwSimConnectBuildMinor,
+                }),
+                Self::Quit(_) => Message::Quit,
+                Self::Event(m) => Message::Event(event(m)),
+                Self::EventObjectAddRemove(m) => {
+                    Message::EventObjectAddRemove(EventObjectAddRemove {
+                        event: event(&m._base),
+                        object_type: m.eObjType,
+                    })
+                }
+                Self::EventFilename(m) => Message::EventFilename(EventFilename {
+                    event: event(&m._base),
+                    file_name: string(&m.szFileName),
+                    flags: m.dwFlags,
+                }),
+                Self::EventFrame(m) => Message::EventFrame(EventFrame {
+                    event: event(&m._base),
+                    frame_rate: m.fFrameRate,
+                    sim_speed: m.fSimSpeed,
+                }),
+                Self::SimObjectData(m) => Message::SimObjectData(sim_object_data(m)),
+                Self::SimObjectDataByType(m) => Message::SimObjectDataByType(sim_object_data(m)),
+                Self::WeatherObservation(m) => {
+                    let (message, size) = (m.message(), Some(m.size()));
+
+                    Message::WeatherObservation(WeatherObservation {
+                        request_id: message.dwRequestID,
+                        metar: trailing_string(
+                            message,
+                            addr_of!(message.szMetar) as *const u8,
+                            size,
+                        ),
+                    })
+                }
+                Self::CloudState(m) => {
+                    let (message, size) = (m.message(), Some(m.size()));
+                    let mut data =
+                        trailing_bytes(message, addr_of!(message.rgbData) as *const u8, size);
+                    data.truncate(message.dwArraySize as usize);
+
+                    Message::CloudState(CloudState {
+                        request_id: message.dwRequestID,
+                        data,
+                    })
+                }
+                Self::AssignedObjectId(m) => Message::AssignedObjectId(AssignedObjectId {
+                    request_id: m.dwRequestID,
+                    object_id: m.dwObjectID,
+                }),
+                Self::ReservedKey(m) => Message::ReservedKey(ReservedKey {
+                    choice_reserved: string(&m.szChoiceReserved),
+                    reserved_key: string(&m.szReservedKey),
+                }),
+                Self::CustomAction(m) => Message::CustomAction(CustomAction {
+                    event: event(&m._base),
+                    instance_id: guid(m.guidInstanceId),
+                    wait_for_completion: m.dwWaitForCompletion,
+                    payload: trailing_string(m, addr_of!(m.szPayLoad) as *const u8, None),
+                }),
+                Self::SystemState(m) => Message::SystemState(SystemState {
+                    request_id: m.dwRequestID,
+                    integer: m.dwInteger,
+                    float: m.fFloat,
+                    string: string(&m.szString),
+                }),
+                Self::ClientData(m) => Message::ClientData(sim_object_data(m)),
+                Self::EventWeatherMode(m) => Message::EventWeatherMode(event(&m._base)),
+                Self::AirportList(m) => Message::AirportList(list(m, addr_of!(m.rgData), airport)),
+                Self::VorList(m) => Message::VorList(list(m, addr_of!(m.rgData), vor)),
+                Self::NdbList(m) => Message::NdbList(list(m, addr_of!(m.rgData), ndb)),
+                Self::WaypointList(m) => {
+                    Message::WaypointList(list(m, addr_of!(m.rgData), waypoint))
+                }
+                Self::EventMultiplayerServerStarted(m) => {
+                    Message::EventMultiplayerServerStarted(event(&m._base))
+                }
+                Self::EventMultiplayerClientStarted(m) => {
+                    Message::EventMultiplayerClientStarted(event(&m._base))
+                }
+                Self::EventMultiplayerSessionEnded(m) => {
+                    Message::EventMultiplayerSessionEnded(event(&m._base))
+                }
+                Self::EventRaceEnd(m) => Message::EventRaceEnd(EventRace {
+                    event: event(&m._base),
+                    number: m.dwRacerNumber,
+                    racer: race_result(&m.RacerData),
+                }),
+                Self::EventRaceLap(m) => Message::EventRaceLap(EventRace {
+                    event: event(&m._base),
+                    number: m.dwLapIndex,
+                    racer: race_result(&m.RacerData),
+                }),
+                Self::EventEx1(m) => Message::EventEx1(EventEx1 {
+                    group_id: m.uGroupID,
+                    event_id: m.uEventID,
+                    data: [m.dwData0, m.dwData1, m.dwData2, m.dwData3, m.dwData4],
+                }),
+                Self::FacilityData(m) => Message::FacilityData(FacilityData {
+                    user_request_id: m.UserRequestId,
+                    unique_request_id: m.UniqueRequestId,
+                    parent_unique_request_id: m.ParentUniqueRequestId,
+                    type_: m.Type,
+                    is_list_item: m.IsListItem != 0,
+                    item_index: m.ItemIndex,
+                    list_size: m.ListSize,
+                    data: trailing_bytes(m, addr_of!(m.Data) as *const u8, None),
+                }),
+                Self::FacilityDataEnd(m) => Message::FacilityDataEnd(FacilityDataEnd {
+                    request_id: m.RequestId,
+                }),
+                Self::FacilityMinimalList(m) => {
+                    Message::FacilityMinimalList(list(m, addr_of!(m.rgData), facility_minimal))
+                }
+                Self::JetwayData(m) => Message::JetwayData(list(m, addr_of!(m.rgData), jetway)),
+                Self::ActionCallback(m) => Message::ActionCallback(ActionCallback {
+                    action_id: string(&m.szActionID),
+                    request_id: m.cbRequestId,
+                }),
+                Self::EnumerateInputEvents(m) => Message::EnumerateInputEvents(list(
+                    m,
+                    addr_of!(m.rgData),
+                    input_event_descriptor,
+                )),
+                Self::GetInputEvent(m) => Message::GetInputEvent(GetInputEvent {
+                    request_id: m.dwRequestID,
+                    type_: m.eType,
+                    value: m.Value,
+                }),
+                Self::SubscribeInputEvent(m) => Message::SubscribeInputEvent(SubscribeInputEvent {
+                    hash: m.Hash,
+                    type_: m.eType,
+                    value: m.Value,
+                }),
+                Self::EnumerateInputEventParams(m) => {
+                    Message::EnumerateInputEventParams(EnumerateInputEventParams {
+                        hash: m.Hash,
+                        value: string(&m.Value),
+                    })
+                }
+                Self::ControllersList(m) => {
+                    Message::ControllersList(list(m, addr_of!(m.rgData), controller))
+                }
+            }
+        }
+    }
+}
+
+impl From<DispatchResult<'_>> for Message {
+    fn from(result: DispatchResult<'_>) -> Self {
+        result.to_owned()
+    }
+}
+
+/// The characters of a fixed size, NUL terminated field.
+pub(crate) fn string(chars: &[c_char]) -> String {
+    let bytes = unsafe { slice::from_raw_parts(chars.as_ptr() as *const u8, chars.len()) };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+fn guid(guid: GUID) -> [u8; 16] {
+    unsafe { std::mem::transmute(guid) }
+}
+
+/// The bytes of `message` from `field` up to the end given by its header, or by the `received`
+/// size `SimConnect_GetNextDispatch` reported if that is smaller.
+///
+/// # Safety
+///
+/// `message` has to be followed by the rest of the message in the same buffer.
+unsafe fn trailing_bytes<T>(message: &T, field: *const u8, received: Option<usize>) -> Vec<u8> {
+    let start = message as *const T as *const u8;
+    let header_size = (*(start as *const SIMCONNECT_RECV)).dwSize as usize;
+    let size = received.map_or(header_size, |received| header_size.min(received));
+    let len = size.saturating_sub(field.offset_from(start) as usize);
+
+    slice::from_raw_parts(field, len).to_vec()
+}
+
+/// The NUL terminated string of variable length starting at `field`.
+unsafe fn trailing_string<T>(message: &T, field: *const u8, received: Option<usize>) -> String {
+    let bytes = trailing_bytes(message, field, received);
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// The `dwArraySize` items of a list message, bounded by the size of the message.
+///
+/// # Safety
+///
+/// `message` has to start with a `SIMCONNECT_RECV_LIST_TEMPLATE`, which has the same layout as
+/// `SIMCONNECT_RECV_FACILITIES_LIST`.
+unsafe fn list<M, T: Copy, U>(message: &M, items: *const [T; 1], convert: fn(&T) -> U) -> List<U> {
+    let header = &*(message as *const M as *const SIMCONNECT_RECV_LIST_TEMPLATE);
+    let items = items as *const T;
+    let available = trailing_bytes(message, items as *const u8, None).len() / size_of::<T>();
+    let count = (header.dwArraySize as usize).min(available);
+
+    List {
+        request_id: header.dwRequestID,
+        entry_number: header.dwEntryNumber,
+        out_of: header.dwOutOf,
+        items: (0..count)
+            .map(|i| convert(&ptr::read_unaligned(items.add(i))))
+            .collect(),
+    }
+}
+
+fn event(m: &SIMCONNECT_RECV_EVENT) -> Event {
+    Event {
+        group_id: m.uGroupID,
+        event_id: m.uEventID,
+        data: m.dwData,
+    }
+}
+
+fn sim_object_data<T: PayloadMessage>(payload: Payload<'_, T>) -> SimObjectData {
+    let m = payload.header();
+
+    SimObjectData {
+        request_id: m.dwRequestID,
+        object_id: m.dwObjectID,
+        define_id: m.dwDefineID,
+        flags: m.dwFlags,
+        entry_number: m.dwentrynumber,
+        out_of: m.dwoutof,
+        define_count: m.dwDefineCount,
+        data: payload.payload().to_vec(),
+    }
+}
+
+fn race_result(m: &SIMCONNECT_DATA_RACE_RESULT) -> RaceResult {
+    RaceResult {
+        number_of_racers: m.dwNumberOfRacers,
+        mission_guid: guid(m.MissionGUID),
+        player_name: string(&m.szPlayerName),
+        session_type: string(&m.szSessionType),
+        aircraft: string(&m.szAircraft),
+        player_role: string(&m.szPlayerRole),
+        total_time: m.fTotalTime,
+        penalty_time: m.fPenaltyTime,
+        is_disqualified: m.dwIsDisqualified != 0,
+    }
+}
+
+fn airport(m: &SIMCONNECT_DATA_FACILITY_AIRPORT) -> FacilityAirport {
+    FacilityAirport {
+        ident: string(&m.Ident),
+        region: string(&m.Region),
+        latitude: m.Latitude,
+        longitude: m.Longitude,
+        altitude: m.Altitude,
+    }
+}
+
+fn waypoint(m: &SIMCONNECT_DATA_FACILITY_WAYPOINT) -> FacilityWaypoint {
+    FacilityWaypoint {
+        facility: airport(&m._base),
+        mag_var: m.fMagVar,
+    }
+}
+
+fn ndb(m: &SIMCONNECT_DATA_FACILITY_NDB) -> FacilityNdb {
+    FacilityNdb {
+        waypoint: waypoint(&m._base),
+        frequency: m.fFrequency,
+    }
+}
+
+fn vor(m: &SIMCONNECT_DATA_FACILITY_VOR) -> FacilityVor {
+    FacilityVor {
+        ndb: ndb(&m._base),
+        flags: m.Flags,
+        localizer: m.fLocalizer,
+        glide_lat: m.GlideLat,
+        glide_lon: m.GlideLon,
+        glide_alt: m.GlideAlt,
+        glide_slope_angle: m.fGlideSlopeAngle,
+    }
+}
+
+fn lat_lon_alt(m: &SIMCONNECT_DATA_LATLONALT) -> LatLonAlt {
+    LatLonAlt {
+        latitude: m.Latitude,
+        longitude: m.Longitude,
+        altitude: m.Altitude,
+    }
+}
+
+fn xyz(m: &SIMCONNECT_DATA_XYZ) -> Xyz {
+    Xyz {
+        x: m.x,
+        y: m.y,
+        z: m.z,
+    }
+}
+
+fn facility_minimal(m: &SIMCONNECT_FACILITY_MINIMAL) -> FacilityMinimal {
+    FacilityMinimal {
+        icao: Icao {
+            type_: m.icao.Type as u8,
+            ident: string(&m.icao.Ident),
+            region: string(&m.icao.Region),
+            airport: string(&m.icao.Airport),
+        },
+        lla: lat_lon_alt(&m.lla),
+    }
+}
+
+fn jetway(m: &SIMCONNECT_JETWAY_DATA) -> JetwayData {
+    JetwayData {
+        airport_icao: string(&m.AirportIcao),
+        parking_index: m.ParkingIndex,
+        lla: lat_lon_alt(&m.Lla),
+        pbh: Pbh {
+            pitch: m.Pbh.Pitch,
+            bank: m.Pbh.Bank,
+            heading: m.Pbh.Heading,
+        },
+        status: m.Status,
+        door: m.Door,
+        exit_door_relative_pos: xyz(&m.ExitDoorRelativePos),
+        main_handle_pos: xyz(&m.MainHandlePos),
+        secondary_handle: xyz(&m.SecondaryHandle),
+        wheel_ground_lock: xyz(&m.WheelGroundLock),
+        jetway_object_id: m.JetwayObjectId,
+        attached_object_id: m.AttachedObjectId,
+    }
+}
+
+fn input_event_descriptor(m: &SIMCONNECT_INPUT_EVENT_DESCRIPTOR) -> InputEventDescriptor {
+    InputEventDescriptor {
+        name: string(&m.Name),
+        hash: m.Hash,
+        type_: m.eType,
+    }
+}
+
+fn controller(m: &SIMCONNECT_CONTROLLER_ITEM) -> ControllerItem {
+    let version = m.HardwareVersion;
+
+    ControllerItem {
+        device_name: string(&m.DeviceName),
+        device_id: m.DeviceId,
+        product_id: m.ProductId,
+        composite_id: m.CompositeID,
+        hardware_version: [
+            version.Major,
+            version.Minor,
+            version.Revision,
+            version.Build,
+        ],
+    }
+}
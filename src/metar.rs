@@ -0,0 +1,1070 @@
+//! METAR reports as exchanged with the weather system of FSX and Prepar3D
+//!
+//! [`DispatchResult::weather_observation`](crate::DispatchResult::weather_observation) delivers
+//! the observation of a station as a METAR string, and
+//! [`SimConnector::weather_set_observation`](crate::SimConnector::weather_set_observation) takes
+//! one. [`Metar`] parses the standard groups as well as the FSX extensions that describe layers:
+//! `&D`/`&A` wind layers, `&B..&D` visibility layers, the `&` cloud details and `&A` temperature
+//! layers.
+//!
+//! ```
+//! use simconnect::metar::{Cloud, CloudCover, Metar};
+//!
+//! let mut metar: Metar = "EGLL 121250Z 24012KT&D980NG 9999 BKN025 12/08 Q1009".parse()?;
+//! assert_eq!(metar.winds[0].speed, 12);
+//!
+//! metar.clouds.push(Cloud::new(CloudCover::Overcast, 8000));
+//! assert_eq!(
+//!     metar.encode()?,
+//!     "EGLL 121250Z 24012KT&D980NG 9999 BKN025 OVC080 12/08 Q1009"
+//! );
+//! # Ok::<(), simconnect::metar::MetarError>(())
+//! ```
+//!
+//! Runway visual range, present weather, supplementary groups such as recent weather, trend and
+//! remarks are kept as the text of their groups.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::MAX_METAR_LENGTH;
+
+/// Errors parsing or encoding a [`Metar`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetarError {
+    /// The report contains no station.
+    Empty,
+    /// The station is not a 4 character code.
+    InvalidStation(String),
+    /// A group that doesn't belong where it appears.
+    UnexpectedToken(String),
+    /// A group with an FSX `&` extension that doesn't have its syntax.
+    InvalidExtension(String),
+    /// The encoded report is `len` bytes long and would not fit `MAX_METAR_LENGTH`.
+    TooLong { len: usize },
+}
+
+impl fmt::Display for MetarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the report is empty"),
+            Self::InvalidStation(station) => write!(f, "invalid station `{}`", station),
+            Self::UnexpectedToken(token) => write!(f, "unexpected group `{}`", token),
+            Self::InvalidExtension(token) => write!(f, "invalid extension in `{}`", token),
+            Self::TooLong { len } => write!(
+                f,
+                "the report is {} bytes long, at most {} fit",
+                len,
+                MAX_METAR_LENGTH - 1
+            ),
+        }
+    }
+}
+
+impl Error for MetarError {}
+
+macro_rules! codes {
+    ($(
+        $(#[$doc:meta])*
+        $name:ident {
+            $($(#[$variant_doc:meta])* $variant:ident => $code:literal,)*
+        }
+    )*) => {$(
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum $name {
+            $($(#[$variant_doc])* $variant,)*
+        }
+
+        impl $name {
+            /// The code of the value in a report.
+            pub fn code(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)*
+                }
+            }
+
+            fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.code())
+            }
+        }
+    )*};
+}
+
+codes! {
+    /// The report modifier following the time
+    Modifier {
+        Auto => "AUTO",
+        Corrected => "COR",
+    }
+
+    /// Unit of wind speeds
+    SpeedUnit {
+        Knots => "KT",
+        MetersPerSecond => "MPS",
+        KilometersPerHour => "KMH",
+    }
+
+    /// Turbulence of an FSX wind or cloud layer
+    Turbulence {
+        None => "N",
+        Occasional => "O",
+        Light => "L",
+        Moderate => "M",
+        Heavy => "H",
+        Severe => "S",
+    }
+
+    /// How an FSX wind layer blends into the next one
+    WindShear {
+        Gradual => "G",
+        Moderate => "M",
+        Steep => "S",
+        Instantaneous => "I",
+    }
+
+    /// Sky cover of a cloud group
+    CloudCover {
+        Few => "FEW",
+        Scattered => "SCT",
+        Broken => "BKN",
+        Overcast => "OVC",
+        /// An obscured sky, the height being the vertical visibility.
+        VerticalVisibility => "VV",
+        SkyClear => "SKC",
+        Clear => "CLR",
+        NoSignificantCloud => "NSC",
+        NoCloudDetected => "NCD",
+    }
+
+    /// Convective cloud suffix of a cloud group
+    Convective {
+        Cumulonimbus => "CB",
+        ToweringCumulus => "TCU",
+    }
+
+    /// Cloud type of an FSX cloud layer
+    CloudType {
+        Cirrus => "CI",
+        Cirrostratus => "CS",
+        Cirrocumulus => "CC",
+        Altostratus => "AS",
+        Altocumulus => "AC",
+        Stratocumulus => "SC",
+        Nimbostratus => "NS",
+        Stratus => "ST",
+        Cumulus => "CU",
+        Cumulonimbus => "CB",
+    }
+
+    /// Shape of the top of an FSX cloud layer
+    CloudTop {
+        Flat => "F",
+        Round => "R",
+        Anvil => "A",
+    }
+
+    /// Precipitation rate of an FSX cloud layer
+    PrecipitationRate {
+        VeryLight => "V",
+        Light => "L",
+        Moderate => "M",
+        Heavy => "H",
+        Dense => "D",
+    }
+
+    /// Precipitation type of an FSX cloud layer
+    Precipitation {
+        None => "N",
+        Rain => "R",
+        FreezingRain => "F",
+        Hail => "H",
+        Snow => "S",
+    }
+
+    /// Icing in an FSX cloud layer
+    Icing {
+        None => "N",
+        Trace => "T",
+        Light => "L",
+        Moderate => "M",
+        Severe => "S",
+    }
+}
+
+/// Day of the month and UTC time of the observation, `DDHHMMZ`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ObservationTime {
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl ObservationTime {
+    fn parse(token: &str) -> Option<Self> {
+        let digits = token.strip_suffix('Z')?;
+        if digits.len() != 6 || !is_digits(digits) {
+            return None;
+        }
+
+        let time = Self {
+            day: digits[0..2].parse().ok()?,
+            hour: digits[2..4].parse().ok()?,
+            minute: digits[4..6].parse().ok()?,
+        };
+        let valid = (1..=31).contains(&time.day) && time.hour < 24 && time.minute < 60;
+        Some(time).filter(|_| valid)
+    }
+}
+
+impl fmt::Display for ObservationTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}{:02}{:02}Z", self.day, self.hour, self.minute)
+    }
+}
+
+/// Height of an FSX wind layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LayerHeight {
+    /// `&D`, the depth in meters of the surface layer.
+    Depth(u32),
+    /// `&A`, the altitude in meters of a layer aloft.
+    Altitude(u32),
+}
+
+/// The FSX extension of a wind group, `&D980NG` or `&A1500LM`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindLayer {
+    pub height: LayerHeight,
+    pub turbulence: Turbulence,
+    pub shear: WindShear,
+}
+
+impl WindLayer {
+    fn parse(extension: &str) -> Option<Self> {
+        let (kind, rest) = extension.split_at(1.min(extension.len()));
+        let digits = rest.trim_end_matches(|c: char| !c.is_ascii_digit());
+        let codes = &rest[digits.len()..];
+        if digits.is_empty() || !is_digits(digits) || codes.len() != 2 {
+            return None;
+        }
+
+        let height = digits.parse().ok()?;
+        Some(Self {
+            height: match kind {
+                "D" => LayerHeight::Depth(height),
+                "A" => LayerHeight::Altitude(height),
+                _ => return None,
+            },
+            turbulence: Turbulence::from_code(&codes[0..1])?,
+            shear: WindShear::from_code(&codes[1..2])?,
+        })
+    }
+}
+
+impl fmt::Display for WindLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.height {
+            LayerHeight::Depth(depth) => write!(f, "&D{}", depth)?,
+            LayerHeight::Altitude(altitude) => write!(f, "&A{}", altitude)?,
+        }
+        write!(f, "{}{}", self.turbulence, self.shear)
+    }
+}
+
+/// A wind group, `24012G20KT`, with the variation group following it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Wind {
+    /// True direction the wind blows from in degrees, `None` for `VRB`.
+    pub direction: Option<u16>,
+    pub speed: u16,
+    pub gust: Option<u16>,
+    pub unit: SpeedUnit,
+    /// The range of directions of a `200V280` group.
+    pub variation: Option<(u16, u16)>,
+    pub layer: Option<WindLayer>,
+}
+
+impl Wind {
+    /// Wind from `direction` at `speed` knots.
+    pub fn new(direction: u16, speed: u16) -> Self {
+        Self {
+            direction: Some(direction),
+            speed,
+            gust: None,
+            unit: SpeedUnit::Knots,
+            variation: None,
+            layer: None,
+        }
+    }
+
+    fn parse(token: &str) -> Result<Option<Self>, MetarError> {
+        let (group, extension) = split_extension(token);
+        let (body, unit) = match ["KT", "MPS", "KMH"]
+            .iter()
+            .find_map(|unit| Some((group.strip_suffix(unit)?, *unit)))
+        {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        if body.len() < 5 {
+            return Ok(None);
+        }
+
+        let direction = match &body[0..3] {
+            "VRB" => None,
+            digits if is_digits(digits) => Some(digits.parse().unwrap()),
+            _ => return Ok(None),
+        };
+        let (speed, gust) = match body[3..].split_once('G') {
+            Some((speed, gust)) => (speed, Some(gust)),
+            None => (&body[3..], None),
+        };
+        let speed = match parse_speed(speed) {
+            Some(speed) => speed,
+            None => return Ok(None),
+        };
+        let gust = match gust.map(parse_speed) {
+            Some(None) => return Ok(None),
+            Some(gust) => gust,
+            None => None,
+        };
+
+        let layer = match extension {
+            Some(extension) => Some(
+                WindLayer::parse(extension)
+                    .ok_or_else(|| MetarError::InvalidExtension(token.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            direction,
+            speed,
+            gust,
+            unit: SpeedUnit::from_code(unit).unwrap(),
+            variation: None,
+            layer,
+        }))
+    }
+
+    fn parse_variation(token: &str) -> Option<(u16, u16)> {
+        let (from, to) = token.split_once('V')?;
+        if from.len() != 3 || to.len() != 3 || !is_digits(from) || !is_digits(to) {
+            return None;
+        }
+
+        Some((from.parse().ok()?, to.parse().ok()?))
+    }
+}
+
+impl fmt::Display for Wind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.direction {
+            Some(direction) => write!(f, "{:03}{:02}", direction, self.speed)?,
+            None => write!(f, "VRB{:02}", self.speed)?,
+        }
+        if let Some(gust) = self.gust {
+            write!(f, "G{:02}", gust)?;
+        }
+        write!(f, "{}", self.unit)?;
+        if let Some(layer) = &self.layer {
+            write!(f, "{}", layer)?;
+        }
+        if let Some((from, to)) = self.variation {
+            write!(f, " {:03}V{:03}", from, to)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A visibility distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Distance {
+    /// `9999`, where 9999 means 10 km or more.
+    Meters(u32),
+    /// `1 1/2SM`, or `M1/4SM` when `less_than`.
+    StatuteMiles { miles: f32, less_than: bool },
+}
+
+/// The FSX extension of a visibility group, `&B-443&D3500`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisibilityLayer {
+    /// Altitude of the base of the layer in meters.
+    pub base: i32,
+    /// Depth of the layer in meters.
+    pub depth: u32,
+}
+
+/// A visibility group
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Visibility {
+    pub distance: Distance,
+    pub layer: Option<VisibilityLayer>,
+}
+
+impl Visibility {
+    /// Visibility of `meters` without a layer.
+    pub fn meters(meters: u32) -> Self {
+        Self {
+            distance: Distance::Meters(meters),
+            layer: None,
+        }
+    }
+
+    /// Parses `token`, preceded by the whole miles of a mixed number in `whole`.
+    fn parse(whole: Option<&str>, token: &str) -> Result<Option<Self>, MetarError> {
+        let (group, extension) = split_extension(token);
+        let distance = if let Some(miles) = group.strip_suffix("SM") {
+            let (miles, less_than) = match miles.strip_prefix('M') {
+                Some(miles) => (miles, true),
+                None => (miles, false),
+            };
+            let mut miles = match parse_miles(miles) {
+                Some(miles) => miles,
+                None => return Ok(None),
+            };
+            if let Some(whole) = whole {
+                miles += whole.parse::<f32>().unwrap();
+            }
+
+            Distance::StatuteMiles { miles, less_than }
+        } else if whole.is_none() && group.len() == 4 && is_digits(group) {
+            Distance::Meters(group.parse().unwrap())
+        } else {
+            return Ok(None);
+        };
+
+        let layer = match extension {
+            Some(extension) => Some(
+                parse_visibility_layer(extension)
+                    .ok_or_else(|| MetarError::InvalidExtension(token.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(Self { distance, layer }))
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.distance {
+            Distance::Meters(meters) => write!(f, "{:04}", meters)?,
+            Distance::StatuteMiles { miles, less_than } => {
+                if less_than {
+                    f.write_str("M")?;
+                }
+                write!(f, "{}SM", format_miles(miles))?;
+            }
+        }
+        if let Some(layer) = &self.layer {
+            write!(f, "&B{}&D{}", layer.base, layer.depth)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The FSX extension of a cloud group, `&CU008FNVN000N`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CloudLayer {
+    pub kind: CloudType,
+    /// Thickness of the layer in feet, a multiple of 100.
+    pub depth: u32,
+    pub top: CloudTop,
+    pub turbulence: Turbulence,
+    pub precipitation_rate: PrecipitationRate,
+    pub precipitation: Precipitation,
+    /// Base of the precipitation in feet, a multiple of 100.
+    pub precipitation_base: u32,
+    pub icing: Icing,
+}
+
+impl CloudLayer {
+    fn parse(extension: &str) -> Option<Self> {
+        if extension.len() != 13 || !extension.is_ascii() {
+            return None;
+        }
+        let hundreds = |digits: &str| -> Option<u32> {
+            Some(digits.parse::<u32>().ok().filter(|_| is_digits(digits))? * 100)
+        };
+
+        Some(Self {
+            kind: CloudType::from_code(&extension[0..2])?,
+            depth: hundreds(&extension[2..5])?,
+            top: CloudTop::from_code(&extension[5..6])?,
+            turbulence: Turbulence::from_code(&extension[6..7])?,
+            precipitation_rate: PrecipitationRate::from_code(&extension[7..8])?,
+            precipitation: Precipitation::from_code(&extension[8..9])?,
+            precipitation_base: hundreds(&extension[9..12])?,
+            icing: Icing::from_code(&extension[12..13])?,
+        })
+    }
+}
+
+impl fmt::Display for CloudLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "&{}{:03}{}{}{}{}{:03}{}",
+            self.kind,
+            self.depth / 100,
+            self.top,
+            self.turbulence,
+            self.precipitation_rate,
+            self.precipitation,
+            self.precipitation_base / 100,
+            self.icing
+        )
+    }
+}
+
+impl CloudCover {
+    /// Whether groups of the cover give a height.
+    fn has_height(self) -> bool {
+        !matches!(
+            self,
+            Self::SkyClear | Self::Clear | Self::NoSignificantCloud | Self::NoCloudDetected
+        )
+    }
+}
+
+/// A cloud group, `BKN025CB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cloud {
+    pub cover: CloudCover,
+    /// Height of the base in feet above ground, a multiple of 100. `None` for the covers
+    /// without height and for `///`.
+    pub base: Option<u32>,
+    pub convective: Option<Convective>,
+    pub layer: Option<CloudLayer>,
+}
+
+impl Cloud {
+    /// A layer of `cover` with its base at `base` feet.
+    pub fn new(cover: CloudCover, base: u32) -> Self {
+        Self {
+            cover,
+            base: Some(base),
+            convective: None,
+            layer: None,
+        }
+    }
+
+    fn parse(token: &str) -> Result<Option<Self>, MetarError> {
+        let (group, extension) = split_extension(token);
+        let cover = ["FEW", "SCT", "BKN", "OVC", "VV", "SKC", "CLR", "NSC", "NCD"]
+            .iter()
+            .find(|code| group.starts_with(*code))
+            .map(|code| CloudCover::from_code(code).unwrap());
+        let cover = match cover {
+            Some(cover) => cover,
+            None => return Ok(None),
+        };
+        let rest = &group[cover.code().len()..];
+
+        let (base, rest) = match (cover.has_height(), rest.get(0..3)) {
+            (false, _) => (None, rest),
+            (true, Some("///")) => (None, &rest[3..]),
+            (true, Some(digits)) if is_digits(digits) => {
+                (Some(digits.parse::<u32>().unwrap() * 100), &rest[3..])
+            }
+            (true, _) => return Ok(None),
+        };
+        let convective = match rest {
+            "" => None,
+            code => match Convective::from_code(code) {
+                Some(convective) => Some(convective),
+                None => return Ok(None),
+            },
+        };
+
+        let layer = match extension {
+            Some(extension) => Some(
+                CloudLayer::parse(extension)
+                    .ok_or_else(|| MetarError::InvalidExtension(token.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            cover,
+            base,
+            convective,
+            layer,
+        }))
+    }
+}
+
+impl fmt::Display for Cloud {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cover)?;
+        match self.base {
+            Some(base) => write!(f, "{:03}", base / 100)?,
+            None if self.cover.has_height() => f.write_str("///")?,
+            None => (),
+        }
+        if let Some(convective) = self.convective {
+            write!(f, "{}", convective)?;
+        }
+        if let Some(layer) = &self.layer {
+            write!(f, "{}", layer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A temperature group, `12/08`, or an FSX temperature layer, `M05/M10&A3000`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Temperature {
+    /// Temperature in °C.
+    pub temperature: i32,
+    /// Dew point in °C, `None` when missing.
+    pub dew_point: Option<i32>,
+    /// Altitude of the layer in meters, `None` at the surface.
+    pub altitude: Option<u32>,
+}
+
+impl Temperature {
+    fn parse(token: &str) -> Result<Option<Self>, MetarError> {
+        let (group, extension) = split_extension(token);
+        let (temperature, dew_point) = match group.split_once('/') {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        let temperature = match parse_celsius(temperature) {
+            Some(temperature) => temperature,
+            None => return Ok(None),
+        };
+        let dew_point = match dew_point {
+            "" | "//" => None,
+            dew_point => match parse_celsius(dew_point) {
+                Some(dew_point) => Some(dew_point),
+                None => return Ok(None),
+            },
+        };
+
+        let altitude = match extension {
+            Some(extension) => Some(
+                extension
+                    .strip_prefix('A')
+                    .filter(|digits| !digits.is_empty() && is_digits(digits))
+                    .and_then(|digits| digits.parse().ok())
+                    .ok_or_else(|| MetarError::InvalidExtension(token.to_string()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            temperature,
+            dew_point,
+            altitude,
+        }))
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let celsius = |value: i32| {
+            let sign = if value < 0 { "M" } else { "" };
+            format!("{}{:02}", sign, value.abs())
+        };
+
+        write!(f, "{}/", celsius(self.temperature))?;
+        if let Some(dew_point) = self.dew_point {
+            f.write_str(&celsius(dew_point))?;
+        }
+        if let Some(altitude) = self.altitude {
+            write!(f, "&A{}", altitude)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The altimeter setting
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pressure {
+    /// `Q1013`
+    Hectopascals(u16),
+    /// `A2992`
+    InchesOfMercury(f32),
+}
+
+impl Pressure {
+    fn parse(token: &str) -> Option<Self> {
+        let digits = &token.get(1..)?;
+        if digits.len() != 4 || !is_digits(digits) {
+            return None;
+        }
+
+        let value: u16 = digits.parse().ok()?;
+        match &token[0..1] {
+            "Q" => Some(Self::Hectopascals(value)),
+            "A" => Some(Self::InchesOfMercury(f32::from(value) / 100.0)),
+            _ => None,
+        }
+    }
+
+    /// The pressure in hectopascals.
+    pub fn hectopascals(self) -> f32 {
+        match self {
+            Self::Hectopascals(hpa) => f32::from(hpa),
+            Self::InchesOfMercury(inhg) => inhg * 33.863_89,
+        }
+    }
+}
+
+impl fmt::Display for Pressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hectopascals(hpa) => write!(f, "Q{:04}", hpa),
+            Self::InchesOfMercury(inhg) => write!(f, "A{:04}", (inhg * 100.0).round() as u32),
+        }
+    }
+}
+
+/// A METAR report
+///
+/// Parsed with [`str::parse`] and written back with [`encode`](Self::encode) or `to_string`,
+/// in the standard order of the groups. A leading `METAR` or `SPECI` is dropped.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Metar {
+    /// ICAO code of the station, `GLOB` for the global weather of FSX.
+    pub station: String,
+    pub time: Option<ObservationTime>,
+    pub modifier: Option<Modifier>,
+    /// The surface wind followed by the FSX layers aloft.
+    pub winds: Vec<Wind>,
+    /// `CAVOK`, which replaces visibility, weather and clouds.
+    pub cavok: bool,
+    pub visibility: Vec<Visibility>,
+    /// Runway visual range groups, e.g. `R27L/1200N`.
+    pub runway_visual_range: Vec<String>,
+    /// Present weather groups, e.g. `-SHRA`.
+    pub weather: Vec<String>,
+    pub clouds: Vec<Cloud>,
+    /// The surface temperature followed by the FSX layers aloft.
+    pub temperatures: Vec<Temperature>,
+    pub pressure: Option<Pressure>,
+    /// Groups after the pressure that aren't parsed, e.g. `RERA`, `WS R27` or runway states.
+    pub supplementary: Vec<String>,
+    /// Trend forecast from `NOSIG`, `BECMG` or `TEMPO` on.
+    pub trend: Option<String>,
+    /// Text following `RMK`.
+    pub remarks: Option<String>,
+}
+
+impl Metar {
+    /// An empty report of `station`.
+    pub fn new(station: &str) -> Self {
+        Self {
+            station: station.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Parses a standard or FSX extended report.
+    pub fn parse(report: &str) -> Result<Self, MetarError> {
+        let mut tokens = report.split_whitespace().peekable();
+        if let Some(&"METAR") | Some(&"SPECI") = tokens.peek() {
+            tokens.next();
+        }
+
+        let station = tokens.next().ok_or(MetarError::Empty)?;
+        if station.len() != 4 || !station.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(MetarError::InvalidStation(station.to_string()));
+        }
+        let mut metar = Self::new(station);
+        let tokens = tokens.collect::<Vec<_>>();
+        // The groups are sliced by byte offsets below, which only works on ASCII
+        if let Some(token) = tokens.iter().find(|token| !token.is_ascii()) {
+            return Err(match token.split_once('&') {
+                Some((_, extension)) if !extension.is_ascii() => {
+                    MetarError::InvalidExtension(token.to_string())
+                }
+                _ => MetarError::UnexpectedToken(token.to_string()),
+            });
+        }
+        let mut i = 0;
+
+        if let Some(time) = tokens
+            .get(i)
+            .and_then(|token| ObservationTime::parse(token))
+        {
+            metar.time = Some(time);
+            i += 1;
+        }
+        if let Some(modifier) = tokens.get(i).and_then(|token| Modifier::from_code(token)) {
+            metar.modifier = Some(modifier);
+            i += 1;
+        }
+
+        while let Some(mut wind) = tokens.get(i).map_or(Ok(None), |token| Wind::parse(token))? {
+            i += 1;
+            if let Some(variation) = tokens.get(i).and_then(|token| Wind::parse_variation(token)) {
+                wind.variation = Some(variation);
+                i += 1;
+            }
+            metar.winds.push(wind);
+        }
+
+        if tokens.get(i) == Some(&"CAVOK") {
+            metar.cavok = true;
+            i += 1;
+        }
+        while i < tokens.len() {
+            // The whole miles of `1 1/2SM` are a group of their own
+            let whole = Some(tokens[i]).filter(|token| token.len() <= 2 && is_digits(token));
+            let visibility = match (whole, tokens.get(i + 1)) {
+                (Some(whole), Some(next)) if next.contains('/') => {
+                    Visibility::parse(Some(whole), next)?.map(|visibility| (visibility, 2))
+                }
+                _ => Visibility::parse(None, tokens[i])?.map(|visibility| (visibility, 1)),
+            };
+            match visibility {
+                Some((visibility, groups)) => {
+                    metar.visibility.push(visibility);
+                    i += groups;
+                }
+                None => break,
+            }
+        }
+
+        while let Some(token) = tokens.get(i).filter(|token| is_runway_visual_range(token)) {
+            metar.runway_visual_range.push(token.to_string());
+            i += 1;
+        }
+        while let Some(token) = tokens.get(i).filter(|token| is_weather(token)) {
+            metar.weather.push(token.to_string());
+            i += 1;
+        }
+        while let Some(cloud) = tokens
+            .get(i)
+            .map_or(Ok(None), |token| Cloud::parse(token))?
+        {
+            metar.clouds.push(cloud);
+            i += 1;
+        }
+        while let Some(temperature) = tokens
+            .get(i)
+            .map_or(Ok(None), |token| Temperature::parse(token))?
+        {
+            metar.temperatures.push(temperature);
+            i += 1;
+        }
+        if let Some(pressure) = tokens.get(i).and_then(|token| Pressure::parse(token)) {
+            metar.pressure = Some(pressure);
+            i += 1;
+        }
+
+        let rest = &tokens[i..];
+        let remarks = rest.iter().position(|&token| token == "RMK");
+        let before_remarks = &rest[..remarks.unwrap_or(rest.len())];
+        let trend = before_remarks
+            .iter()
+            .position(|token| matches!(*token, "NOSIG" | "BECMG" | "TEMPO"))
+            .unwrap_or(before_remarks.len());
+        metar.supplementary = before_remarks[..trend]
+            .iter()
+            .map(|token| token.to_string())
+            .collect();
+        if trend < before_remarks.len() {
+            metar.trend = Some(before_remarks[trend..].join(" "));
+        }
+        if let Some(remarks) = remarks {
+            metar.remarks = Some(rest[remarks + 1..].join(" "));
+        }
+
+        Ok(metar)
+    }
+
+    /// The report as
+    /// [`SimConnector::weather_set_observation`](crate::SimConnector::weather_set_observation)
+    /// takes it, checked against `MAX_METAR_LENGTH`.
+    pub fn encode(&self) -> Result<String, MetarError> {
+        let report = self.to_string();
+        if report.len() >= MAX_METAR_LENGTH as usize {
+            return Err(MetarError::TooLong { len: report.len() });
+        }
+
+        Ok(report)
+    }
+
+    /// The surface wind, the first wind group that is not an FSX layer aloft.
+    pub fn surface_wind(&self) -> Option<&Wind> {
+        self.winds.iter().find(|wind| {
+            !matches!(
+                wind.layer,
+                Some(WindLayer {
+                    height: LayerHeight::Altitude(_),
+                    ..
+                })
+            )
+        })
+    }
+
+    /// The surface temperature, the first temperature group without an altitude.
+    pub fn surface_temperature(&self) -> Option<&Temperature> {
+        self.temperatures
+            .iter()
+            .find(|temperature| temperature.altitude.is_none())
+    }
+}
+
+impl FromStr for Metar {
+    type Err = MetarError;
+
+    fn from_str(report: &str) -> Result<Self, MetarError> {
+        Self::parse(report)
+    }
+}
+
+impl fmt::Display for Metar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut groups = vec![self.station.clone()];
+        groups.extend(self.time.map(|time| time.to_string()));
+        groups.extend(self.modifier.map(|modifier| modifier.to_string()));
+        groups.extend(self.winds.iter().map(Wind::to_string));
+        if self.cavok {
+            groups.push("CAVOK".to_string());
+        }
+        groups.extend(self.visibility.iter().map(Visibility::to_string));
+        groups.extend(self.runway_visual_range.iter().cloned());
+        groups.extend(self.weather.iter().cloned());
+        groups.extend(self.clouds.iter().map(Cloud::to_string));
+        groups.extend(self.temperatures.iter().map(Temperature::to_string));
+        groups.extend(self.pressure.map(|pressure| pressure.to_string()));
+        groups.extend(self.supplementary.iter().cloned());
+        groups.extend(self.trend.clone());
+        if let Some(remarks) = &self.remarks {
+            groups.push(format!("RMK {}", remarks));
+        }
+
+        f.write_str(&groups.join(" "))
+    }
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Splits the FSX extension, everything from the first `&`, off a group.
+fn split_extension(token: &str) -> (&str, Option<&str>) {
+    match token.split_once('&') {
+        Some((group, extension)) => (group, Some(extension)),
+        None => (token, None),
+    }
+}
+
+fn parse_speed(digits: &str) -> Option<u16> {
+    if !(2..=3).contains(&digits.len()) || !is_digits(digits) {
+        return None;
+    }
+
+    digits.parse().ok()
+}
+
+fn parse_miles(miles: &str) -> Option<f32> {
+    match miles.split_once('/') {
+        Some((numerator, denominator)) if is_digits(numerator) && is_digits(denominator) => {
+            let denominator: f32 = denominator.parse().ok()?;
+            Some(numerator.parse::<f32>().ok()? / denominator).filter(|_| denominator != 0.0)
+        }
+        None if is_digits(miles) => miles.parse().ok(),
+        _ => None,
+    }
+}
+
+/// `miles` as a whole or mixed number with a denominator of up to 16.
+fn format_miles(miles: f32) -> String {
+    let sixteenths = (miles * 16.0).round() as u32;
+    let (whole, mut numerator, mut denominator) = (sixteenths / 16, sixteenths % 16, 16);
+    if numerator == 0 {
+        return whole.to_string();
+    }
+    let common = numerator.trailing_zeros();
+    numerator >>= common;
+    denominator >>= common;
+
+    match whole {
+        0 => format!("{}/{}", numerator, denominator),
+        whole => format!("{} {}/{}", whole, numerator, denominator),
+    }
+}
+
+/// `&B-443&D3500`, without the first `&`.
+fn parse_visibility_layer(extension: &str) -> Option<VisibilityLayer> {
+    let (base, depth) = extension.split_once('&')?;
+    let base = base.strip_prefix('B')?;
+    let depth = depth.strip_prefix('D')?;
+    if !is_digits(base.strip_prefix('-').unwrap_or(base)) || !is_digits(depth) {
+        return None;
+    }
+
+    Some(VisibilityLayer {
+        base: base.parse().ok()?,
+        depth: depth.parse().ok()?,
+    })
+}
+
+fn parse_celsius(text: &str) -> Option<i32> {
+    let (digits, sign) = match text.strip_prefix('M') {
+        Some(digits) => (digits, -1),
+        None => (text, 1),
+    };
+    if !(1..=2).contains(&digits.len()) || !is_digits(digits) {
+        return None;
+    }
+
+    Some(sign * digits.parse::<i32>().ok()?)
+}
+
+fn is_runway_visual_range(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() > 2 && bytes[0] == b'R' && bytes[1].is_ascii_digit() && token.contains('/')
+}
+
+/// Whether `token` is a present weather group: an intensity or `VC`, then descriptors and
+/// phenomena.
+fn is_weather(token: &str) -> bool {
+    const CODES: &[&[u8]] = &[
+        b"MI", b"PR", b"BC", b"DR", b"BL", b"SH", b"TS", b"FZ", b"DZ", b"RA", b"SN", b"SG", b"IC",
+        b"PL", b"GR", b"GS", b"UP", b"BR", b"FG", b"FU", b"VA", b"DU", b"SA", b"HZ", b"PY", b"PO",
+        b"SQ", b"FC", b"SS", b"DS",
+    ];
+
+    let codes = token.trim_start_matches(['+', '-']);
+    let codes = codes.strip_prefix("VC").unwrap_or(codes).as_bytes();
+    let mut pairs = codes.chunks_exact(2);
+    !codes.is_empty() && pairs.remainder().is_empty() && pairs.all(|code| CODES.contains(&code))
+}
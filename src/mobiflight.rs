@@ -0,0 +1,462 @@
+//! Client for the client data channels of the MobiFlight WASM module
+//!
+//! The module runs inside MSFS and gives access to L-vars, H-events and any calculator code.
+//! A [`MobiFlightClient`] registers itself on the shared `MobiFlight.Command` channel, after
+//! which the module creates the channels of the client: `<name>.Command`, `<name>.Response`
+//! and `<name>.LVars`, where the value of every subscribed expression is a `f32` at offset
+//! `4 * index`.
+//!
+//! Commands sent before the module confirmed the registration are queued and sent once it did.
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # use simconnect::mobiflight::{MobiFlightClient, MobiFlightEvent};
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("L-vars")?;
+//!
+//! let mut client = MobiFlightClient::connect(&conn, "MyPanel")?;
+//! client.subscribe(&conn, "(L:A32NX_EFIS_L_OPTION, enum)")?;
+//! client.execute(&conn, "1 (>L:A32NX_EFIS_L_OPTION)")?;
+//!
+//! loop {
+//!     if let Some(MobiFlightEvent::Value { expression, value, .. }) = client.poll(&mut conn)? {
+//!         println!("{} = {}", expression, value);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::mem::size_of;
+
+use crate::error::check_length;
+use crate::message::Message;
+use crate::*;
+
+/// Name of the channel shared by all clients.
+pub const DEFAULT_CHANNEL: &str = "MobiFlight";
+/// Size of the command and response areas, including the terminating NUL.
+pub const MESSAGE_SIZE: usize = 1024;
+/// First of the IDs [`MobiFlightClient::connect`] uses for client data, definitions and
+/// requests.
+pub const MOBIFLIGHT_ID_BASE: DWORD = 0x4D46_0000;
+
+/// Offset of the definition and request IDs of subscribed expressions from the ID base.
+const VARIABLE_ID_OFFSET: DWORD = 0x100;
+
+/// The content of a command or response area
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct Text([u8; MESSAGE_SIZE]);
+
+// A single byte array
+unsafe impl ClientData for Text {
+    fn fields() -> Vec<ClientDataField> {
+        vec![ClientDataField {
+            offset: 0,
+            size: MESSAGE_SIZE,
+            size_or_type: MESSAGE_SIZE as DWORD,
+            epsilon: 0.0,
+        }]
+    }
+}
+
+impl Text {
+    fn new(text: &str) -> Result<Self, SimConnectError> {
+        // Commands are written to the client data area of the module
+        check_length(
+            "SimConnect_SetClientData",
+            "command",
+            text,
+            MESSAGE_SIZE - 1,
+        )?;
+
+        let mut bytes = [0; MESSAGE_SIZE];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Ok(Self(bytes))
+    }
+
+    fn into_string(self) -> String {
+        let end = self.0.iter().position(|&b| b == 0).unwrap_or(MESSAGE_SIZE);
+        String::from_utf8_lossy(&self.0[..end]).into_owned()
+    }
+}
+
+/// A command and a response channel
+#[derive(Debug)]
+struct Channel {
+    command: ClientDataArea<Text>,
+    response: ClientDataArea<Text>,
+}
+
+impl Channel {
+    /// Maps the areas `<name>.Command` and `<name>.Response`, created by the module, and
+    /// subscribes to the responses.
+    #[track_caller]
+    fn map<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        data_id: SIMCONNECT_CLIENT_DATA_ID,
+        define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<Self, SimConnectError> {
+        let command = ClientDataArea::map(conn, &format!("{}.Command", name), data_id, define_id)?;
+        let response = ClientDataArea::map(
+            conn,
+            &format!("{}.Response", name),
+            data_id + 1,
+            define_id + 1,
+        )?;
+        response.subscribe(conn, request_id)?;
+
+        Ok(Self { command, response })
+    }
+
+    #[track_caller]
+    fn send<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        command: &str,
+    ) -> Result<(), SimConnectError> {
+        self.command.write(conn, &Text::new(command)?)
+    }
+
+    fn response(&self, message: &DispatchResult<'_>) -> Result<Option<String>, SimConnectError> {
+        match message {
+            DispatchResult::ClientData(data) => {
+                Ok(self.response.decode(data)?.map(Text::into_string))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A notification of the module, returned by [`MobiFlightClient::poll`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MobiFlightEvent {
+    /// The module registered the client and created its channels.
+    Registered,
+    /// The value of a subscribed expression changed.
+    Value {
+        /// Index of the expression, as returned by [`MobiFlightClient::subscribe`].
+        index: usize,
+        expression: String,
+        value: f32,
+    },
+    /// The answer to [`MobiFlightClient::ping`].
+    Pong,
+    /// Any other text the module wrote to the response channel of the client.
+    Response(String),
+}
+
+/// A client registered with the MobiFlight WASM module
+#[derive(Debug)]
+pub struct MobiFlightClient {
+    name: String,
+    id_base: DWORD,
+    default: Channel,
+    /// The channels of the client, once the module confirmed the registration
+    client: Option<Channel>,
+    /// Commands waiting for the registration
+    pending: Vec<String>,
+    expressions: Vec<String>,
+    values: Vec<Option<f32>>,
+    /// Messages read by [`poll`](Self::poll) that did not come from the module
+    messages: Vec<Message>,
+}
+
+impl MobiFlightClient {
+    /// Registers the client `name` with the module, using IDs from [`MOBIFLIGHT_ID_BASE`].
+    #[track_caller]
+    pub fn connect<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+    ) -> Result<Self, SimConnectError> {
+        Self::connect_with_id_base(conn, name, MOBIFLIGHT_ID_BASE)
+    }
+
+    /// Registers the client `name` with the module, using the client data, definition and
+    /// request IDs from `id_base` up to `id_base + 0x100` plus the number of expressions.
+    #[track_caller]
+    pub fn connect_with_id_base<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        name: &str,
+        id_base: DWORD,
+    ) -> Result<Self, SimConnectError> {
+        let default = Channel::map(conn, DEFAULT_CHANNEL, id_base, id_base, id_base)?;
+        default.send(conn, "MF.DummyCmd")?;
+        default.send(conn, &format!("MF.Clients.Add.{}", name))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            id_base,
+            default,
+            client: None,
+            pending: Vec::new(),
+            expressions: Vec::new(),
+            values: Vec::new(),
+            messages: Vec::new(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the module confirmed the registration.
+    pub fn is_registered(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// The expressions subscribed to, by index.
+    pub fn expressions(&self) -> &[String] {
+        &self.expressions
+    }
+
+    /// The last value received for `expression`.
+    pub fn value(&self, expression: &str) -> Option<f32> {
+        let index = self.expressions.iter().position(|e| e == expression)?;
+        self.values[index]
+    }
+
+    /// Subscribes to the value of `expression`, e.g. `"(L:A32NX_AUTOPILOT_1_ACTIVE)"`, and
+    /// returns its index.
+    #[track_caller]
+    pub fn subscribe<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        expression: &str,
+    ) -> Result<usize, SimConnectError> {
+        let index = self.expressions.len();
+        self.command(conn, &format!("MF.SimVars.Add.{}", expression))?;
+        self.expressions.push(expression.to_string());
+        self.values.push(None);
+        if self.client.is_some() {
+            self.request_value(conn, index)?;
+        }
+
+        Ok(index)
+    }
+
+    /// Drops every subscription.
+    #[track_caller]
+    pub fn clear<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        self.command(conn, "MF.SimVars.Clear")?;
+        if self.client.is_some() {
+            for index in 0..self.expressions.len() {
+                let id = self.variable_id(index);
+                conn.request_client_data(
+                    self.id_base + 2,
+                    id,
+                    id,
+                    SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER,
+                    SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+                    0,
+                    0,
+                    0,
+                )?;
+                conn.clear_client_data_definition(id)?;
+            }
+        }
+        self.expressions.clear();
+        self.values.clear();
+
+        Ok(())
+    }
+
+    /// Executes calculator code, e.g. `"1 (>L:A32NX_EFIS_L_OPTION)"`.
+    #[track_caller]
+    pub fn execute<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        code: &str,
+    ) -> Result<(), SimConnectError> {
+        self.command(conn, &format!("MF.SimVars.Set.{}", code))
+    }
+
+    /// Triggers the H-event `name`, e.g. `"AS1000_PFD_SOFTKEYS_1"`.
+    #[track_caller]
+    pub fn trigger_h_event<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        name: &str,
+    ) -> Result<(), SimConnectError> {
+        self.execute(conn, &format!("(>H:{})", name))
+    }
+
+    /// Asks the module for a [`MobiFlightEvent::Pong`].
+    #[track_caller]
+    pub fn ping<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        self.command(conn, "MF.Ping")
+    }
+
+    /// Reads the next message and returns what it notified, completing the registration when
+    /// the module confirms it. Messages that did not come from the module are kept for
+    /// [`take_messages`](Self::take_messages), an empty queue is `Ok(None)`.
+    #[track_caller]
+    pub fn poll<B: SimConnectBackend>(
+        &mut self,
+        conn: &mut SimConnector<B>,
+    ) -> Result<Option<MobiFlightEvent>, SimConnectError> {
+        let event = {
+            let message = conn.get_next_message()?;
+            if !self.is_module_message(&message) {
+                if !matches!(message, DispatchResult::Null) {
+                    self.messages.push(message.to_owned());
+                }
+                return Ok(None);
+            }
+            self.decode(&message)?
+        };
+
+        if event == Some(MobiFlightEvent::Registered) {
+            self.register(conn)?;
+        }
+        Ok(event)
+    }
+
+    /// Returns and forgets the messages [`poll`](Self::poll) read that did not come from the
+    /// module.
+    pub fn take_messages(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.messages)
+    }
+
+    /// Whether `message` is a write to one of the areas the client reads.
+    fn is_module_message(&self, message: &DispatchResult<'_>) -> bool {
+        let data = match message {
+            DispatchResult::ClientData(data) => data,
+            _ => return false,
+        };
+        let define_id = data._base.dwDefineID;
+
+        define_id == self.default.response.define_id()
+            || matches!(&self.client, Some(client) if define_id == client.response.define_id())
+            || (define_id.wrapping_sub(self.id_base + VARIABLE_ID_OFFSET) as usize)
+                < self.expressions.len()
+    }
+
+    fn decode(
+        &mut self,
+        message: &DispatchResult<'_>,
+    ) -> Result<Option<MobiFlightEvent>, SimConnectError> {
+        if let Some(response) = self.default.response(message)? {
+            if self.client.is_none() && response == format!("MF.Clients.Add.{}.Finished", self.name)
+            {
+                return Ok(Some(MobiFlightEvent::Registered));
+            }
+
+            // Answers to other clients
+            return Ok(None);
+        }
+
+        if let Some(client) = &self.client {
+            if let Some(response) = client.response(message)? {
+                return Ok(Some(match response.as_str() {
+                    "MF.Pong" => MobiFlightEvent::Pong,
+                    _ => MobiFlightEvent::Response(response),
+                }));
+            }
+        }
+
+        if let DispatchResult::ClientData(data) = message {
+            let index = data
+                ._base
+                .dwDefineID
+                .wrapping_sub(self.id_base + VARIABLE_ID_OFFSET) as usize;
+            if let Some(expression) = self.expressions.get(index) {
+                let value = data.reader().read_f32()?;
+                self.values[index] = Some(value);
+
+                return Ok(Some(MobiFlightEvent::Value {
+                    index,
+                    expression: expression.clone(),
+                    value,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Maps the channels of the client, then sends what was queued.
+    #[track_caller]
+    fn register<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        conn.map_client_data_name_to_id(&format!("{}.LVars", self.name), self.id_base + 2)?;
+        let client = Channel::map(
+            conn,
+            &self.name,
+            self.id_base + 3,
+            self.id_base + 2,
+            self.id_base + 1,
+        )?;
+
+        for command in self.pending.drain(..) {
+            client.send(conn, &command)?;
+        }
+        self.client = Some(client);
+        for index in 0..self.expressions.len() {
+            self.request_value(conn, index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends `command` on the channel of the client, or queues it until the registration.
+    #[track_caller]
+    fn command<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        command: &str,
+    ) -> Result<(), SimConnectError> {
+        match &self.client {
+            Some(client) => client.send(conn, command),
+            None => {
+                // Fail now rather than when the queue is sent
+                Text::new(command)?;
+                self.pending.push(command.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Requests the `f32` of the expression `index` from the LVars area whenever it changes.
+    #[track_caller]
+    fn request_value<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        index: usize,
+    ) -> Result<(), SimConnectError> {
+        let id = self.variable_id(index);
+        conn.add_to_client_data_definition(
+            id,
+            (index * size_of::<f32>()) as DWORD,
+            SIMCONNECT_CLIENTDATATYPE_FLOAT32,
+            0.0,
+            SIMCONNECT_UNUSED,
+        )?;
+        conn.request_client_data(
+            self.id_base + 2,
+            id,
+            id,
+            SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ON_SET,
+            SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED,
+            0,
+            0,
+            0,
+        )
+    }
+
+    /// Definition and request ID of the expression `index`.
+    fn variable_id(&self, index: usize) -> DWORD {
+        self.id_base + VARIABLE_ID_OFFSET + index as DWORD
+    }
+}
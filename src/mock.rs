@@ -0,0 +1,504 @@
+//! Scriptable in-process [`SimConnectBackend`] for testing code built on [`SimConnector`]
+//!
+//! ```no_run
+//! use simconnect::mock::{MockBackend, RecvMessage};
+//! use simconnect::{DispatchResult, SimConnector, SIMCONNECT_RECV_QUIT};
+//!
+//! let mut conn = SimConnector::with_backend(MockBackend::new());
+//! conn.connect("Test").unwrap();
+//! conn.map_client_event_to_sim_event(1, "PARKING_BRAKES").unwrap();
+//! assert!(conn.backend().was_called("SimConnect_MapClientEventToSimEvent"));
+//!
+//! conn.backend().queue_message(SIMCONNECT_RECV_QUIT::zeroed());
+//! assert!(matches!(conn.get_next_message(), Ok(DispatchResult::Quit(_))));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::mem::size_of;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use crate::backend::for_each_backend_function;
+use crate::*;
+
+/// A call made against a [`MockBackend`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockCall {
+    /// Name of the SimConnect function, e.g. `"SimConnect_AddToDataDefinition"`.
+    pub name: &'static str,
+    /// The arguments as `(name, Debug representation)` pairs, strings are quoted.
+    pub args: Vec<(&'static str, String)>,
+    /// Packet ID assigned to the call, as returned by `GetLastSentPacketID` right after it.
+    pub send_id: DWORD,
+    /// The bytes behind the data pointer of calls like `SimConnect_SetClientData`, as given by
+    /// their `unit_size` and `array_count`. Empty for other calls.
+    pub data: Vec<u8>,
+}
+
+impl MockCall {
+    /// Debug representation of the argument called `name`.
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct InjectedException {
+    call: &'static str,
+    exception: SIMCONNECT_EXCEPTION,
+    index: DWORD,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    name: Option<CString>,
+    calls: Vec<MockCall>,
+    last_send_id: DWORD,
+    messages: VecDeque<Vec<u8>>,
+    current: Vec<u8>,
+    failures: Vec<(&'static str, HRESULT)>,
+    empty_queue_failure: Option<HRESULT>,
+    exceptions: Vec<InjectedException>,
+}
+
+/// A handler registered with [`MockBackend::respond_to`]
+type Respond = Box<dyn FnMut(&MockCall) -> Vec<Vec<u8>>>;
+
+#[derive(Default)]
+struct Responders(Vec<(&'static str, Respond)>);
+
+impl fmt::Debug for Responders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(call, _)| call))
+            .finish()
+    }
+}
+
+/// [`SimConnectBackend`] that records calls and replays queued messages
+///
+/// Every call succeeds, gets the next packet ID and is recorded, unless a failure was injected
+/// with [`fail_next`](Self::fail_next). Messages are handed out by `GetNextDispatch` in the
+/// order they were queued.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    state: RefCell<MockState>,
+    responders: RefCell<Responders>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A [`SimConnector`] on a new `MockBackend`, connected as `"mock"`.
+    pub fn connected() -> SimConnector<Self> {
+        let mut conn = SimConnector::with_backend(Self::new());
+        conn.connect("mock")
+            .expect("connecting to a MockBackend can't fail");
+        conn
+    }
+
+    /// Program name passed to `SimConnect_Open`, if it was called.
+    pub fn program_name(&self) -> Option<CString> {
+        self.state.borrow().name.clone()
+    }
+
+    /// All calls made so far, oldest first.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.borrow().calls.clone()
+    }
+
+    /// Returns and forgets the calls made so far.
+    pub fn take_calls(&self) -> Vec<MockCall> {
+        std::mem::take(&mut self.state.borrow_mut().calls)
+    }
+
+    /// Calls to the SimConnect function `name`, oldest first.
+    pub fn calls_to(&self, name: &str) -> Vec<MockCall> {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .filter(|call| call.name == name)
+            .cloned()
+            .collect()
+    }
+
+    pub fn was_called(&self, name: &str) -> bool {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .any(|call| call.name == name)
+    }
+
+    /// Queues `message` for `GetNextDispatch`, filling in `dwSize` and `dwID` of its header.
+    pub fn queue_message<T: RecvMessage>(&self, message: T) {
+        self.queue_raw_message(message.to_bytes());
+    }
+
+    /// Queues `message` with `payload` as its variable sized data, see
+    /// [`WithPayload::with_payload`].
+    pub fn queue_message_with_payload<T: WithPayload>(&self, message: T, payload: &[u8]) {
+        self.queue_raw_message(message.with_payload(payload));
+    }
+
+    /// Queues a message as is, for variable sized messages like `SIMCONNECT_RECV_SIMOBJECT_DATA`.
+    ///
+    /// The bytes have to start with a [`SIMCONNECT_RECV`] header.
+    pub fn queue_raw_message(&self, bytes: Vec<u8>) {
+        assert!(
+            bytes.len() >= size_of::<SIMCONNECT_RECV>(),
+            "message is shorter than SIMCONNECT_RECV"
+        );
+        self.state.borrow_mut().messages.push_back(bytes);
+    }
+
+    /// Queues a `SIMCONNECT_RECV_EXCEPTION` for the packet `send_id`.
+    pub fn queue_exception(&self, exception: SIMCONNECT_EXCEPTION, send_id: DWORD, index: DWORD) {
+        let mut message = SIMCONNECT_RECV_EXCEPTION::zeroed();
+        message.dwException = exception as DWORD;
+        message.dwSendID = send_id;
+        message.dwIndex = index;
+
+        self.queue_message(message);
+    }
+
+    /// Number of queued messages not yet handed out.
+    pub fn pending_messages(&self) -> usize {
+        self.state.borrow().messages.len()
+    }
+
+    /// Answers every successful call to `call` with the raw messages `respond` returns, e.g. to
+    /// play the peer of a client data protocol.
+    ///
+    /// The messages are queued as with [`queue_raw_message`](Self::queue_raw_message), right
+    /// after the call was recorded.
+    pub fn respond_to(
+        &self,
+        call: &'static str,
+        respond: impl FnMut(&MockCall) -> Vec<Vec<u8>> + 'static,
+    ) {
+        self.responders
+            .borrow_mut()
+            .0
+            .push((call, Box::new(respond)));
+    }
+
+    /// Makes the next call to `call` fail with `hresult` without sending anything.
+    pub fn fail_next(&self, call: &'static str, hresult: HRESULT) {
+        self.state.borrow_mut().failures.push((call, hresult));
+    }
+
+    /// Makes `GetNextDispatch` fail with `hresult` while no message is queued, as the SimConnect
+    /// library does with `E_FAIL`, instead of returning a null message.
+    pub fn fail_when_empty(&self, hresult: HRESULT) {
+        self.state.borrow_mut().empty_queue_failure = Some(hresult);
+    }
+
+    /// Lets the next call to `call` succeed, but answers it with a `SIMCONNECT_RECV_EXCEPTION`
+    /// carrying its packet ID, as SimConnect does for invalid arguments.
+    pub fn exception_on_next(
+        &self,
+        call: &'static str,
+        exception: SIMCONNECT_EXCEPTION,
+        index: DWORD,
+    ) {
+        self.state.borrow_mut().exceptions.push(InjectedException {
+            call,
+            exception,
+            index,
+        });
+    }
+
+    fn call(
+        &self,
+        name: &'static str,
+        args: Vec<(&'static str, String)>,
+    ) -> Result<(), SimConnectError> {
+        self.call_with_data(name, args, Vec::new())
+    }
+
+    fn call_with_data(
+        &self,
+        name: &'static str,
+        args: Vec<(&'static str, String)>,
+        data: Vec<u8>,
+    ) -> Result<(), SimConnectError> {
+        let mut state = self.state.borrow_mut();
+
+        if let Some(i) = state.failures.iter().position(|(call, _)| *call == name) {
+            let (call, hresult) = state.failures.remove(i);
+            return Err(SimConnectError::CallFailed { call, hresult });
+        }
+
+        state.last_send_id += 1;
+        let send_id = state.last_send_id;
+        let call = MockCall {
+            name,
+            args,
+            send_id,
+            data,
+        };
+        state.calls.push(call.clone());
+
+        let injected = state.exceptions.iter().position(|e| e.call == name);
+        drop(state);
+        if let Some(i) = injected {
+            let injected = self.state.borrow_mut().exceptions.remove(i);
+            self.queue_exception(injected.exception, send_id, injected.index);
+        }
+
+        let mut responders = self.responders.borrow_mut();
+        for (_, respond) in responders.0.iter_mut().filter(|(call, _)| *call == name) {
+            for message in respond(&call) {
+                self.queue_raw_message(message);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Length of the data behind the data pointer of a call with `args`.
+    fn data_len(args: &[(&'static str, String)]) -> usize {
+        let arg = |name| {
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .and_then(|(_, value)| value.parse::<usize>().ok())
+        };
+
+        arg("unit_size").unwrap_or(0) * arg("array_count").unwrap_or(1).max(1)
+    }
+}
+
+fn write_dword(bytes: &mut [u8], offset: usize, value: DWORD) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_ne_bytes());
+}
+
+macro_rules! impl_mock_backend {
+    (
+        safe { $(fn $name:ident => $ffi:ident($($arg:ident: $ty:ty),* $(,)?);)* }
+        unsafe { $(fn $unsafe_name:ident => $unsafe_ffi:ident($($unsafe_arg:ident: $unsafe_ty:ty),* $(,)?);)* }
+    ) => {
+        impl SimConnectBackend for MockBackend {
+            fn open(
+                &mut self,
+                name: &CStr,
+                config_index: DWORD,
+                event_handle: HANDLE,
+            ) -> Result<(), SimConnectError> {
+                self.call(
+                    "SimConnect_Open",
+                    vec![
+                        ("name", format!("{:?}", name)),
+                        ("config_index", format!("{:?}", config_index)),
+                        ("event_handle", format!("{:?}", event_handle)),
+                    ],
+                )?;
+                self.state.get_mut().name = Some(name.to_owned());
+
+                Ok(())
+            }
+
+            fn close(&self) -> Result<(), SimConnectError> {
+                self.call("SimConnect_Close", Vec::new())
+            }
+
+            fn get_last_sent_packet_id(&self) -> Result<DWORD, SimConnectError> {
+                Ok(self.state.borrow().last_send_id)
+            }
+
+            fn get_next_dispatch(&self) -> Result<(*mut SIMCONNECT_RECV, DWORD), SimConnectError> {
+                let mut state = self.state.borrow_mut();
+
+                match state.messages.pop_front() {
+                    Some(message) => {
+                        state.current = message;
+                        let size = state.current.len() as DWORD;
+
+                        Ok((state.current.as_mut_ptr() as *mut SIMCONNECT_RECV, size))
+                    }
+                    None => match state.empty_queue_failure {
+                        Some(hresult) => Err(SimConnectError::CallFailed {
+                            call: "SimConnect_GetNextDispatch",
+                            hresult,
+                        }),
+                        None => Ok((ptr::null_mut(), 0)),
+                    },
+                }
+            }
+
+            $(fn $name(&self, $($arg: $ty),*) -> Result<(), SimConnectError> {
+                self.call(stringify!($ffi), vec![$((stringify!($arg), format!("{:?}", $arg))),*])
+            })*
+
+            $(unsafe fn $unsafe_name(&self, $($unsafe_arg: $unsafe_ty),*) -> Result<(), SimConnectError> {
+                let args = vec![$((stringify!($unsafe_arg), format!("{:?}", $unsafe_arg))),*];
+                let len = Self::data_len(&args);
+                let mut data = Vec::new();
+                $(if let Some(bytes) = DataArg::data(&$unsafe_arg, len) {
+                    data = bytes;
+                })*
+
+                self.call_with_data(stringify!($unsafe_ffi), args, data)
+            })*
+        }
+    };
+}
+
+/// How an argument of an `unsafe` backend function contributes to [`MockCall::data`]
+trait DataArg {
+    /// The `len` bytes the argument points to, if it is a data pointer.
+    ///
+    /// # Safety
+    ///
+    /// Data pointers have to be valid for `len` bytes.
+    unsafe fn data(&self, _len: usize) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+impl DataArg for *mut c_void {
+    unsafe fn data(&self, len: usize) -> Option<Vec<u8>> {
+        if self.is_null() || len == 0 {
+            return Some(Vec::new());
+        }
+
+        Some(std::slice::from_raw_parts(*self as *const u8, len).to_vec())
+    }
+}
+
+// Plain values, and pointers SimConnect writes to
+impl DataArg for DWORD {}
+impl DataArg for c_int {}
+impl DataArg for u64 {}
+impl DataArg for f32 {}
+impl DataArg for &CStr {}
+impl DataArg for DispatchProc {}
+impl DataArg for *mut c_int {}
+impl DataArg for *mut f32 {}
+
+for_each_backend_function!(impl_mock_backend);
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A `SIMCONNECT_RECV_*` message that can be queued on a [`MockBackend`]
+pub trait RecvMessage: Copy + private::Sealed {
+    /// The `SIMCONNECT_RECV_ID` of the message.
+    const ID: SIMCONNECT_RECV_ID;
+
+    /// The message with all fields set to zero.
+    fn zeroed() -> Self {
+        // The implementors are plain C structs of integers and floats.
+        unsafe { std::mem::zeroed() }
+    }
+
+    /// The raw bytes of the message with `dwSize` and `dwID` filled in, as queued by
+    /// [`MockBackend::queue_message`] or returned from a [`MockBackend::respond_to`] handler.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = unsafe {
+            std::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+        }
+        .to_vec();
+        write_dword(&mut bytes, 0, size_of::<Self>() as DWORD);
+        write_dword(&mut bytes, 8, Self::ID as DWORD);
+        bytes
+    }
+}
+
+/// A [`RecvMessage`] whose last field is where its variable sized data starts, like `dwData` of
+/// `SIMCONNECT_RECV_SIMOBJECT_DATA`
+pub trait WithPayload: RecvMessage {
+    /// Offset of the variable sized data within the message.
+    const PAYLOAD_OFFSET: usize;
+
+    /// The raw bytes of the message up to its last field, followed by `payload`, with `dwSize`
+    /// covering the payload.
+    fn with_payload(&self, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = self.to_bytes();
+        bytes.truncate(Self::PAYLOAD_OFFSET);
+        bytes.extend_from_slice(payload);
+        let size = bytes.len() as DWORD;
+        write_dword(&mut bytes, 0, size);
+        bytes
+    }
+}
+
+macro_rules! recv_messages {
+    ($($ty:ident => $id:ident,)*) => {
+        $(
+            impl private::Sealed for $ty {}
+
+            impl RecvMessage for $ty {
+                const ID: SIMCONNECT_RECV_ID = $id;
+            }
+        )*
+    };
+}
+
+recv_messages! {
+    SIMCONNECT_RECV_EXCEPTION => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION,
+    SIMCONNECT_RECV_OPEN => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN,
+    SIMCONNECT_RECV_QUIT => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT,
+    SIMCONNECT_RECV_EVENT => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT,
+    SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE,
+    SIMCONNECT_RECV_EVENT_FILENAME => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME,
+    SIMCONNECT_RECV_EVENT_FRAME => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FRAME,
+    SIMCONNECT_RECV_SIMOBJECT_DATA => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA,
+    SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE,
+    SIMCONNECT_RECV_WEATHER_OBSERVATION => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WEATHER_OBSERVATION,
+    SIMCONNECT_RECV_CLOUD_STATE => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLOUD_STATE,
+    SIMCONNECT_RECV_ASSIGNED_OBJECT_ID => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ASSIGNED_OBJECT_ID,
+    SIMCONNECT_RECV_RESERVED_KEY => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_RESERVED_KEY,
+    SIMCONNECT_RECV_CUSTOM_ACTION => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CUSTOM_ACTION,
+    SIMCONNECT_RECV_SYSTEM_STATE => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SYSTEM_STATE,
+    SIMCONNECT_RECV_CLIENT_DATA => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA,
+    SIMCONNECT_RECV_EVENT_WEATHER_MODE => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_WEATHER_MODE,
+    SIMCONNECT_RECV_AIRPORT_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST,
+    SIMCONNECT_RECV_VOR_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST,
+    SIMCONNECT_RECV_NDB_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST,
+    SIMCONNECT_RECV_WAYPOINT_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST,
+    SIMCONNECT_RECV_EVENT_MULTIPLAYER_SERVER_STARTED => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SERVER_STARTED,
+    SIMCONNECT_RECV_EVENT_MULTIPLAYER_CLIENT_STARTED => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_CLIENT_STARTED,
+    SIMCONNECT_RECV_EVENT_MULTIPLAYER_SESSION_ENDED => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_MULTIPLAYER_SESSION_ENDED,
+    SIMCONNECT_RECV_EVENT_RACE_END => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_END,
+    SIMCONNECT_RECV_EVENT_RACE_LAP => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_RACE_LAP,
+    SIMCONNECT_RECV_EVENT_EX1 => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_EX1,
+    SIMCONNECT_RECV_FACILITY_DATA => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA,
+    SIMCONNECT_RECV_FACILITY_DATA_END => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA_END,
+    SIMCONNECT_RECV_FACILITY_MINIMAL_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_MINIMAL_LIST,
+    SIMCONNECT_RECV_JETWAY_DATA => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_JETWAY_DATA,
+    SIMCONNECT_RECV_CONTROLLERS_LIST => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CONTROLLERS_LIST,
+    SIMCONNECT_RECV_ACTION_CALLBACK => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ACTION_CALLBACK,
+    SIMCONNECT_RECV_ENUMERATE_INPUT_EVENTS => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENTS,
+    SIMCONNECT_RECV_GET_INPUT_EVENT => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_GET_INPUT_EVENT,
+    SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SUBSCRIBE_INPUT_EVENT,
+    SIMCONNECT_RECV_ENUMERATE_INPUT_EVENT_PARAMS => SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS,
+}
+
+macro_rules! payload_messages {
+    ($($ty:ident => $last:ty,)*) => {
+        $(
+            impl WithPayload for $ty {
+                const PAYLOAD_OFFSET: usize = size_of::<$ty>() - size_of::<$last>();
+            }
+        )*
+    };
+}
+
+payload_messages! {
+    SIMCONNECT_RECV_SIMOBJECT_DATA => DWORD,
+    SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE => DWORD,
+    SIMCONNECT_RECV_CLIENT_DATA => DWORD,
+    SIMCONNECT_RECV_WEATHER_OBSERVATION => [c_char; 1],
+    SIMCONNECT_RECV_CLOUD_STATE => [BYTE; 1],
+}
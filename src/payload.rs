@@ -0,0 +1,228 @@
+//! Bounds-checked access to the data carried by `SIMOBJECT_DATA` style messages
+
+use std::convert::TryInto;
+use std::fmt;
+use std::mem::size_of;
+use std::ops::Deref;
+use std::ptr::addr_of;
+use std::slice;
+
+use crate::*;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A message whose data follows the fixed fields, starting at `dwData` of a
+/// [`SIMCONNECT_RECV_SIMOBJECT_DATA`]
+pub trait PayloadMessage: private::Sealed {
+    #[doc(hidden)]
+    fn header(&self) -> &SIMCONNECT_RECV_SIMOBJECT_DATA;
+}
+
+macro_rules! payload_messages {
+    ($($ty:ident => |$m:ident| $header:expr,)*) => {
+        $(
+            impl private::Sealed for $ty {}
+
+            impl PayloadMessage for $ty {
+                fn header(&self) -> &SIMCONNECT_RECV_SIMOBJECT_DATA {
+                    let $m = self;
+                    $header
+                }
+            }
+        )*
+    };
+}
+
+payload_messages! {
+    SIMCONNECT_RECV_SIMOBJECT_DATA => |m| m,
+    SIMCONNECT_RECV_SIMOBJECT_DATA_BYTYPE => |m| &m._base,
+    SIMCONNECT_RECV_CLIENT_DATA => |m| &m._base,
+}
+
+/// A received message together with the size SimConnect reported for it
+///
+/// Derefs to the message, so the fixed fields read as before. The data after them is only
+/// reachable through [`payload`](Self::payload), [`reader`](Self::reader) and
+/// [`DispatchResult::to_owned`], which stop at the end of what was actually received.
+#[derive(Clone, Copy)]
+pub struct Payload<'a, T> {
+    message: &'a T,
+    size: usize,
+}
+
+impl<'a, T> Payload<'a, T> {
+    /// Wraps `message`, which is `size` bytes long.
+    ///
+    /// # Safety
+    ///
+    /// `message` has to point to a buffer of at least `size` bytes.
+    pub(crate) unsafe fn new(message: &'a T, size: usize) -> Self {
+        Self { message, size }
+    }
+
+    /// The message itself, with the lifetime of the buffer.
+    pub fn message(&self) -> &'a T {
+        self.message
+    }
+
+    /// Size of the whole message in bytes, as reported by `SimConnect_GetNextDispatch`.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<'a, T: PayloadMessage> Payload<'a, T> {
+    /// The bytes from `dwData` to the end of the message.
+    pub fn payload(&self) -> &'a [u8] {
+        let header = self.message.header();
+        let data = addr_of!(header.dwData) as *const u8;
+        let offset = size_of::<SIMCONNECT_RECV_SIMOBJECT_DATA>() - size_of::<DWORD>();
+
+        // `new` guarantees `size` bytes behind the message
+        unsafe { slice::from_raw_parts(data, self.size.saturating_sub(offset)) }
+    }
+
+    /// A reader over [`payload`](Self::payload), starting at its beginning.
+    pub fn reader(&self) -> PayloadReader<'a> {
+        PayloadReader::new(self.payload())
+    }
+
+    /// Decodes the payload into a [`SimConnectData`] struct.
+    pub fn decode<D: SimConnectData>(&self) -> Result<D, SimConnectError> {
+        D::from_payload(self)
+    }
+}
+
+impl<T> Deref for Payload<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.message
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Payload<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payload")
+            .field("message", self.message)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Reads the values of a data definition one after the other, in the layout SimConnect sends
+/// them: packed and little-endian.
+///
+/// Every read fails with [`SimConnectError::PayloadOutOfBounds`] instead of reading past the
+/// end, and leaves the position unchanged in that case.
+#[derive(Debug, Clone)]
+pub struct PayloadReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+macro_rules! read_numbers {
+    ($($name:ident => $ty:ty,)*) => {
+        $(
+            pub fn $name(&mut self) -> Result<$ty, SimConnectError> {
+                let bytes = self.read_bytes(size_of::<$ty>())?;
+                Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        )*
+    };
+}
+
+macro_rules! read_strings {
+    ($($name:ident => $len:expr,)*) => {
+        $(
+            #[doc = concat!("Reads a `STRING", stringify!($len), "`, up to its first NUL.")]
+            pub fn $name(&mut self) -> Result<String, SimConnectError> {
+                self.read_string($len)
+            }
+        )*
+    };
+}
+
+impl<'a> PayloadReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Offset of the next read from the start of the payload.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Reads the next `len` bytes.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], SimConnectError> {
+        if len > self.remaining() {
+            return Err(SimConnectError::PayloadOutOfBounds {
+                offset: self.position,
+                len,
+                size: self.data.len(),
+            });
+        }
+
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+
+        Ok(bytes)
+    }
+
+    /// Skips `len` bytes.
+    pub fn skip(&mut self, len: usize) -> Result<(), SimConnectError> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    read_numbers! {
+        read_i32 => i32,
+        read_u32 => u32,
+        read_i64 => i64,
+        read_u64 => u64,
+        read_f32 => f32,
+        read_f64 => f64,
+    }
+
+    /// Reads a fixed size string field of `len` bytes, up to its first NUL.
+    pub fn read_string(&mut self, len: usize) -> Result<String, SimConnectError> {
+        let bytes = self.read_bytes(len)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// Reads a `STRINGV`: a string of any length up to and including its terminating NUL.
+    pub fn read_string_v(&mut self) -> Result<String, SimConnectError> {
+        let rest = &self.data[self.position..];
+        let len = match rest.iter().position(|&b| b == 0) {
+            Some(len) => len,
+            // Unterminated, so it runs past the end
+            None => {
+                return Err(SimConnectError::PayloadOutOfBounds {
+                    offset: self.position,
+                    len: rest.len() + 1,
+                    size: self.data.len(),
+                })
+            }
+        };
+
+        let bytes = self.read_bytes(len + 1)?;
+        Ok(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    read_strings! {
+        read_string8 => 8,
+        read_string32 => 32,
+        read_string64 => 64,
+        read_string128 => 128,
+        read_string256 => 256,
+        read_string260 => 260,
+    }
+}
@@ -0,0 +1,536 @@
+//! Request/response messages over a pair of client data areas
+//!
+//! An [`RpcChannel`] writes requests to a command area and reads the answers of its peer, e.g.
+//! a WASM gauge, from a response area. Both areas hold one [`Frame`] of [`FRAME_SIZE`] bytes,
+//! little-endian:
+//!
+//! | Offset | Size | Field                                                     |
+//! |--------|------|-----------------------------------------------------------|
+//! | 0      | 4    | `sequence`, the same in a request and its response        |
+//! | 4      | 2    | `chunk`, index of the chunk within the message            |
+//! | 6      | 2    | `chunks`, number of chunks of the message                 |
+//! | 8      | 2    | `len`, bytes of `data` in use                             |
+//! | 10     | 2    | `status`, [`STATUS_OK`] or [`STATUS_ERROR`] for responses |
+//! | 12     | 1012 | `data`                                                    |
+//!
+//! A message longer than [`CHUNK_SIZE`] is written as several frames one after the other, and
+//! the peer subscribes to every write of the command area. The peer answers with the sequence
+//! number of the request, and an error as UTF-8 text with [`STATUS_ERROR`].
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # use simconnect::rpc::{RpcChannel, RpcConfig};
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("RPC client")?;
+//!
+//! let mut channel = RpcChannel::open(&conn, RpcConfig::new("MyGauge"))?;
+//! let answer: String = channel.call(&mut conn, &"GET FUEL".to_string())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::message::Message;
+use crate::*;
+
+/// Size of a [`Frame`], and of each client data area.
+pub const FRAME_SIZE: usize = 1024;
+/// Size of the fields of a [`Frame`] before its data.
+pub const FRAME_HEADER_SIZE: usize = 12;
+/// Message bytes carried by one [`Frame`].
+pub const CHUNK_SIZE: usize = FRAME_SIZE - FRAME_HEADER_SIZE;
+/// Size of the longest message, split into `u16::MAX` chunks.
+pub const MAX_MESSAGE_SIZE: usize = CHUNK_SIZE * u16::MAX as usize;
+
+/// [`Frame::status`] of requests and successful responses.
+pub const STATUS_OK: u16 = 0;
+/// [`Frame::status`] of a response carrying an error message.
+pub const STATUS_ERROR: u16 = 1;
+
+/// First of the IDs [`RpcConfig::new`] uses for client data, definitions and the request.
+pub const RPC_ID_BASE: DWORD = 0x5250_0000;
+
+/// How long [`RpcChannel::call`] sleeps when no message is waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Errors of the RPC layer, returned as [`SimConnectError::Rpc`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// No response to the request `sequence` arrived within `timeout`.
+    Timeout { sequence: u32, timeout: Duration },
+    /// The peer answered the request `sequence` with an error.
+    Remote { sequence: u32, message: String },
+    /// A message of `len` bytes is longer than [`MAX_MESSAGE_SIZE`].
+    MessageTooLarge { len: usize },
+    /// A received frame is inconsistent.
+    InvalidFrame(&'static str),
+    /// A message couldn't be decoded as the expected [`RpcMessage`].
+    InvalidMessage(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout { sequence, timeout } => write!(
+                f,
+                "no response to request {} within {:?}",
+                sequence, timeout
+            ),
+            Self::Remote { sequence, message } => {
+                write!(f, "request {} failed: {}", sequence, message)
+            }
+            Self::MessageTooLarge { len } => write!(
+                f,
+                "message of {} bytes is longer than the maximum of {}",
+                len, MAX_MESSAGE_SIZE
+            ),
+            Self::InvalidFrame(reason) => write!(f, "invalid frame: {}", reason),
+            Self::InvalidMessage(reason) => write!(f, "invalid message: {}", reason),
+        }
+    }
+}
+
+impl Error for RpcError {}
+
+/// The content of both client data areas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Frame {
+    pub sequence: u32,
+    pub chunk: u16,
+    pub chunks: u16,
+    pub len: u16,
+    pub status: u16,
+    pub data: [u8; CHUNK_SIZE],
+}
+
+// `#[repr(C)]` without padding, all fields are plain integers
+unsafe impl ClientData for Frame {
+    fn fields() -> Vec<ClientDataField> {
+        let field = |offset, size| ClientDataField {
+            offset,
+            size,
+            size_or_type: size as DWORD,
+            epsilon: 0.0,
+        };
+
+        vec![
+            field(0, 4),
+            field(4, 2),
+            field(6, 2),
+            field(8, 2),
+            field(10, 2),
+            field(FRAME_HEADER_SIZE, CHUNK_SIZE),
+        ]
+    }
+}
+
+impl Frame {
+    /// Splits `message` into the frames to write for it, at least one.
+    pub fn split(sequence: u32, status: u16, message: &[u8]) -> Result<Vec<Frame>, RpcError> {
+        if message.len() > MAX_MESSAGE_SIZE {
+            return Err(RpcError::MessageTooLarge { len: message.len() });
+        }
+
+        let chunks = message.chunks(CHUNK_SIZE).count().max(1) as u16;
+        let mut frames = Vec::with_capacity(chunks as usize);
+        for chunk in 0..chunks {
+            let start = chunk as usize * CHUNK_SIZE;
+            let bytes = &message[start..message.len().min(start + CHUNK_SIZE)];
+
+            let mut frame = Frame {
+                sequence,
+                chunk,
+                chunks,
+                len: bytes.len() as u16,
+                status,
+                data: [0; CHUNK_SIZE],
+            };
+            frame.data[..bytes.len()].copy_from_slice(bytes);
+            frames.push(frame);
+        }
+
+        Ok(frames)
+    }
+
+    /// The bytes of `data` in use.
+    pub fn payload(&self) -> &[u8] {
+        &self.data[..(self.len as usize).min(CHUNK_SIZE)]
+    }
+
+    /// The frame as it is laid out in the client data area.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(FRAME_SIZE);
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        for value in [self.chunk, self.chunks, self.len, self.status] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.data);
+
+        bytes
+    }
+
+    /// Reads a frame laid out as in the client data area.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Frame, RpcError> {
+        if bytes.len() < FRAME_SIZE {
+            return Err(RpcError::InvalidFrame("shorter than FRAME_SIZE"));
+        }
+
+        let u16_at = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let mut frame = Frame {
+            sequence: u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            chunk: u16_at(4),
+            chunks: u16_at(6),
+            len: u16_at(8),
+            status: u16_at(10),
+            data: [0; CHUNK_SIZE],
+        };
+        frame
+            .data
+            .copy_from_slice(&bytes[FRAME_HEADER_SIZE..FRAME_SIZE]);
+
+        Ok(frame)
+    }
+}
+
+/// A complete message, reassembled from its frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub sequence: u32,
+    pub status: u16,
+    pub data: Vec<u8>,
+}
+
+impl Envelope {
+    /// Decodes the message, or returns the error the peer answered with.
+    pub fn decode<M: RpcMessage>(&self) -> Result<M, RpcError> {
+        if self.status != STATUS_OK {
+            return Err(RpcError::Remote {
+                sequence: self.sequence,
+                message: String::from_utf8_lossy(&self.data).into_owned(),
+            });
+        }
+
+        M::decode(&self.data)
+    }
+}
+
+/// Collects the frames of a message until it is complete
+///
+/// Only one message is assembled at a time. A frame of another sequence number drops the
+/// chunks received so far, as the peer moved on to the next message.
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    sequence: u32,
+    status: u16,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `frame`, and returns the message once all of its chunks arrived.
+    pub fn push(&mut self, frame: &Frame) -> Result<Option<Envelope>, RpcError> {
+        if frame.chunks == 0 || frame.chunk >= frame.chunks {
+            return Err(RpcError::InvalidFrame("chunk index out of range"));
+        }
+        if frame.len as usize > CHUNK_SIZE {
+            return Err(RpcError::InvalidFrame("length exceeds CHUNK_SIZE"));
+        }
+
+        if self.chunks.is_empty()
+            || frame.sequence != self.sequence
+            || frame.chunks as usize != self.chunks.len()
+        {
+            self.sequence = frame.sequence;
+            self.status = frame.status;
+            self.chunks = vec![None; frame.chunks as usize];
+        }
+        self.chunks[frame.chunk as usize] = Some(frame.payload().to_vec());
+
+        if self.chunks.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+
+        let data = self.chunks.drain(..).flatten().flatten().collect();
+        Ok(Some(Envelope {
+            sequence: self.sequence,
+            status: self.status,
+            data,
+        }))
+    }
+}
+
+/// A request or response that can be sent over an [`RpcChannel`]
+pub trait RpcMessage: Sized {
+    fn encode(&self) -> Vec<u8>;
+
+    fn decode(bytes: &[u8]) -> Result<Self, RpcError>;
+}
+
+impl RpcMessage for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, RpcError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl RpcMessage for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, RpcError> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| RpcError::InvalidMessage(e.to_string()))
+    }
+}
+
+impl RpcMessage for () {
+    fn encode(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, RpcError> {
+        if !bytes.is_empty() {
+            return Err(RpcError::InvalidMessage(format!(
+                "expected no data, got {} bytes",
+                bytes.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Names and IDs of the two client data areas of an [`RpcChannel`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcConfig {
+    /// Name of the area requests are written to.
+    pub command_area: String,
+    /// Name of the area responses are read from.
+    pub response_area: String,
+    pub command_data_id: SIMCONNECT_CLIENT_DATA_ID,
+    pub response_data_id: SIMCONNECT_CLIENT_DATA_ID,
+    pub command_define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    pub response_define_id: SIMCONNECT_CLIENT_DATA_DEFINITION_ID,
+    /// Request ID of the subscription to the response area.
+    pub request_id: SIMCONNECT_DATA_REQUEST_ID,
+    /// How long [`RpcChannel::call`] waits for a response.
+    pub timeout: Duration,
+    /// Whether this side creates both areas, rather than the peer.
+    pub create_areas: bool,
+}
+
+impl RpcConfig {
+    /// The areas `"{name}.Command"` and `"{name}.Response"`, with IDs from [`RPC_ID_BASE`] and
+    /// a timeout of 5 seconds.
+    pub fn new(name: &str) -> Self {
+        Self {
+            command_area: format!("{}.Command", name),
+            response_area: format!("{}.Response", name),
+            command_data_id: RPC_ID_BASE,
+            response_data_id: RPC_ID_BASE + 1,
+            command_define_id: RPC_ID_BASE,
+            response_define_id: RPC_ID_BASE + 1,
+            request_id: RPC_ID_BASE,
+            timeout: Duration::from_secs(5),
+            create_areas: true,
+        }
+    }
+
+    /// The configuration of the peer: the same areas with their roles swapped, created by the
+    /// other side.
+    pub fn peer(&self) -> Self {
+        Self {
+            command_area: self.response_area.clone(),
+            response_area: self.command_area.clone(),
+            command_data_id: self.response_data_id,
+            response_data_id: self.command_data_id,
+            command_define_id: self.response_define_id,
+            response_define_id: self.command_define_id,
+            create_areas: !self.create_areas,
+            ..self.clone()
+        }
+    }
+}
+
+/// One side of a request/response channel over client data
+///
+/// The peer side opens a channel with [`RpcConfig::peer`], [`receive`](Self::receive)s the
+/// requests and answers them with [`reply`](Self::reply).
+#[derive(Debug)]
+pub struct RpcChannel {
+    config: RpcConfig,
+    command: ClientDataArea<Frame>,
+    response: ClientDataArea<Frame>,
+    last_sequence: u32,
+    incoming: Reassembler,
+    messages: Vec<Message>,
+}
+
+impl RpcChannel {
+    /// Creates or maps both areas, and subscribes to the response area.
+    #[track_caller]
+    pub fn open<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        config: RpcConfig,
+    ) -> Result<Self, SimConnectError> {
+        let area = |name: &str, data_id, define_id| {
+            if config.create_areas {
+                ClientDataArea::create(conn, name, data_id, define_id)
+            } else {
+                ClientDataArea::map(conn, name, data_id, define_id)
+            }
+        };
+        let command = area(
+            &config.command_area,
+            config.command_data_id,
+            config.command_define_id,
+        )?;
+        let response = area(
+            &config.response_area,
+            config.response_data_id,
+            config.response_define_id,
+        )?;
+        response.subscribe(conn, config.request_id)?;
+
+        Ok(Self {
+            config,
+            command,
+            response,
+            last_sequence: 0,
+            incoming: Reassembler::new(),
+            messages: Vec::new(),
+        })
+    }
+
+    pub fn config(&self) -> &RpcConfig {
+        &self.config
+    }
+
+    /// Writes `message` to the command area under a new sequence number, which is returned.
+    #[track_caller]
+    pub fn send<B: SimConnectBackend, M: RpcMessage>(
+        &mut self,
+        conn: &SimConnector<B>,
+        message: &M,
+    ) -> Result<u32, SimConnectError> {
+        // 0 is left out, so a zeroed area never looks like a message
+        self.last_sequence = self.last_sequence.wrapping_add(1).max(1);
+        self.write(conn, self.last_sequence, STATUS_OK, &message.encode())?;
+
+        Ok(self.last_sequence)
+    }
+
+    /// Answers the request `sequence` with `result`, the error as text.
+    #[track_caller]
+    pub fn reply<B: SimConnectBackend, M: RpcMessage>(
+        &mut self,
+        conn: &SimConnector<B>,
+        sequence: u32,
+        result: Result<&M, &str>,
+    ) -> Result<(), SimConnectError> {
+        match result {
+            Ok(message) => self.write(conn, sequence, STATUS_OK, &message.encode()),
+            Err(error) => self.write(conn, sequence, STATUS_ERROR, error.as_bytes()),
+        }
+    }
+
+    #[track_caller]
+    fn write<B: SimConnectBackend>(
+        &self,
+        conn: &SimConnector<B>,
+        sequence: u32,
+        status: u16,
+        message: &[u8],
+    ) -> Result<(), SimConnectError> {
+        for frame in Frame::split(sequence, status, message).map_err(SimConnectError::Rpc)? {
+            self.command.write(conn, &frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `message` is a write to the response area.
+    pub fn is_response(&self, message: &DispatchResult<'_>) -> bool {
+        matches!(message, DispatchResult::ClientData(data)
+            if data._base.dwDefineID == self.response.define_id())
+    }
+
+    /// Adds `message` if it is a write to the response area, and returns the message of the
+    /// peer once all of its frames arrived.
+    pub fn receive(
+        &mut self,
+        message: &DispatchResult<'_>,
+    ) -> Result<Option<Envelope>, SimConnectError> {
+        let frame = match message {
+            DispatchResult::ClientData(data) => match self.response.decode(data)? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        self.incoming.push(&frame).map_err(SimConnectError::Rpc)
+    }
+
+    /// Sends `request` and waits for its response, up to [`RpcConfig::timeout`].
+    ///
+    /// Other messages received in the meantime are kept for
+    /// [`take_messages`](Self::take_messages), and responses to earlier requests are dropped.
+    pub fn call<B: SimConnectBackend, Req: RpcMessage, Resp: RpcMessage>(
+        &mut self,
+        conn: &mut SimConnector<B>,
+        request: &Req,
+    ) -> Result<Resp, SimConnectError> {
+        let sequence = self.send(conn, request)?;
+        let deadline = Instant::now() + self.config.timeout;
+
+        loop {
+            let message = conn.get_next_message()?;
+            if self.is_response(&message) {
+                if let Some(response) = self.receive(&message)? {
+                    if response.sequence == sequence {
+                        return response.decode().map_err(SimConnectError::Rpc);
+                    }
+                }
+            } else if let DispatchResult::Null = message {
+                thread::sleep(POLL_INTERVAL);
+            } else {
+                self.messages.push(message.to_owned());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(SimConnectError::Rpc(RpcError::Timeout {
+                    sequence,
+                    timeout: self.config.timeout,
+                }));
+            }
+        }
+    }
+
+    /// Returns and forgets the messages [`call`](Self::call) received besides the responses.
+    pub fn take_messages(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.messages)
+    }
+
+    /// Stops the subscription to the response area.
+    #[track_caller]
+    pub fn close<B: SimConnectBackend>(
+        self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        self.response.unsubscribe(conn, self.config.request_id)
+    }
+}
@@ -0,0 +1,19 @@
+//! Stand-ins for the `SimConnect_*` functions when the crate is built without the SimConnect
+//! library (see `build.rs`). Every call fails with [`SimConnectError::Unavailable`].
+
+use crate::error::E_UNAVAILABLE;
+use crate::functions::for_each_function;
+use crate::*;
+
+macro_rules! declare_stubs {
+    ($(fn $name:ident($($arg:ident: $ty:ty),* $(,)?);)*) => {
+        $(
+            #[allow(unused_variables)]
+            pub unsafe extern "C" fn $name($($arg: $ty),*) -> HRESULT {
+                E_UNAVAILABLE
+            }
+        )*
+    };
+}
+
+for_each_function!(declare_stubs);
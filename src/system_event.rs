@@ -0,0 +1,261 @@
+//! Typed system events and their decoded notifications
+//!
+//! [`SimConnector::subscribe`] subscribes to a [`SystemEvent`] under a client event ID derived
+//! from the event, so [`DispatchResult::system_event`] can recognise and decode the
+//! notifications without any state.
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("System events")?;
+//! conn.subscribe(SystemEvent::Pause)?;
+//! conn.subscribe(SystemEvent::AircraftLoaded)?;
+//!
+//! loop {
+//!     match conn.get_next_message()?.system_event() {
+//!         Some(SystemNotification::Pause(paused)) => println!("paused: {}", paused),
+//!         Some(SystemNotification::AircraftLoaded(path)) => println!("{}", path.display()),
+//!         _ => (),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::path::PathBuf;
+
+use crate::message::string;
+use crate::*;
+
+/// First client event ID used for system events, followed by one ID per [`SystemEvent`]
+pub const SYSTEM_EVENT_ID_BASE: SIMCONNECT_CLIENT_EVENT_ID = 0x2000_0000;
+
+macro_rules! system_events {
+    ($($(#[$doc:meta])* $variant:ident => $name:literal,)*) => {
+        /// A system event, as passed to `SimConnect_SubscribeToSystemEvent`
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum SystemEvent {
+            $($(#[$doc])* $variant,)*
+        }
+
+        impl SystemEvent {
+            /// Every system event.
+            pub const ALL: &'static [SystemEvent] = &[$(SystemEvent::$variant),*];
+
+            /// The name SimConnect knows the event by.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+system_events! {
+    /// Every second while the simulation runs.
+    OneSec => "1sec",
+    /// Every four seconds while the simulation runs.
+    FourSec => "4sec",
+    /// Six times per second while the simulation runs.
+    SixHz => "6Hz",
+    AircraftLoaded => "AircraftLoaded",
+    Crashed => "Crashed",
+    CrashReset => "CrashReset",
+    FlightLoaded => "FlightLoaded",
+    FlightSaved => "FlightSaved",
+    FlightPlanActivated => "FlightPlanActivated",
+    FlightPlanDeactivated => "FlightPlanDeactivated",
+    /// Every visual frame.
+    Frame => "Frame",
+    ObjectAdded => "ObjectAdded",
+    ObjectRemoved => "ObjectRemoved",
+    /// The pause state changed.
+    Pause => "Pause",
+    /// The pause state changed, with the MSFS pause flags.
+    PauseEx1 => "Pause_EX1",
+    Paused => "Paused",
+    /// Every visual frame while paused.
+    PauseFrame => "PauseFrame",
+    PositionChanged => "PositionChanged",
+    /// The simulation started or stopped running.
+    Sim => "Sim",
+    SimStart => "SimStart",
+    SimStop => "SimStop",
+    /// The master sound switch changed.
+    Sound => "Sound",
+    Unpaused => "Unpaused",
+    /// The user aircraft view changed.
+    View => "View",
+    WeatherModeChanged => "WeatherModeChanged",
+}
+
+impl SystemEvent {
+    /// The client event ID [`SimConnector::subscribe`] uses for the event.
+    pub fn event_id(self) -> SIMCONNECT_CLIENT_EVENT_ID {
+        let index = Self::ALL.iter().position(|&event| event == self).unwrap();
+        SYSTEM_EVENT_ID_BASE + index as SIMCONNECT_CLIENT_EVENT_ID
+    }
+
+    /// The event subscribed to under `event_id`.
+    pub fn from_event_id(event_id: SIMCONNECT_CLIENT_EVENT_ID) -> Option<Self> {
+        let index = event_id.checked_sub(SYSTEM_EVENT_ID_BASE)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+/// The user aircraft view of a [`SystemNotification::View`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewKind {
+    /// `SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_2D`
+    Cockpit2d,
+    /// `SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_VIRTUAL`
+    CockpitVirtual,
+    /// `SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_ORTHOGONAL`, the map view.
+    Orthogonal,
+    /// Any other value, e.g. the external views of newer simulators.
+    Other(DWORD),
+}
+
+impl From<DWORD> for ViewKind {
+    fn from(data: DWORD) -> Self {
+        match data {
+            SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_2D => Self::Cockpit2d,
+            SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_VIRTUAL => Self::CockpitVirtual,
+            SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_ORTHOGONAL => Self::Orthogonal,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// A decoded system event notification
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemNotification {
+    OneSec,
+    FourSec,
+    SixHz,
+    /// Path of the `aircraft.cfg` that was loaded.
+    AircraftLoaded(PathBuf),
+    Crashed,
+    CrashReset,
+    FlightLoaded(PathBuf),
+    FlightSaved(PathBuf),
+    FlightPlanActivated(PathBuf),
+    FlightPlanDeactivated,
+    Frame {
+        fps: f32,
+        sim_speed: f32,
+    },
+    ObjectAdded {
+        object_id: SIMCONNECT_OBJECT_ID,
+        object_type: SIMCONNECT_SIMOBJECT_TYPE,
+    },
+    ObjectRemoved {
+        object_id: SIMCONNECT_OBJECT_ID,
+        object_type: SIMCONNECT_SIMOBJECT_TYPE,
+    },
+    /// Whether the simulation is now paused.
+    Pause(bool),
+    /// The `PAUSE_STATE_FLAG_*` bits, `0` when running.
+    PauseEx1(DWORD),
+    Paused,
+    PauseFrame {
+        fps: f32,
+        sim_speed: f32,
+    },
+    PositionChanged,
+    /// Whether the simulation is now running.
+    Sim(bool),
+    SimStart,
+    SimStop,
+    /// Whether the master sound switch is now on.
+    Sound(bool),
+    Unpaused,
+    View(ViewKind),
+    WeatherModeChanged,
+}
+
+impl SystemNotification {
+    fn decode(event: SystemEvent, message: &DispatchResult<'_>) -> Option<Self> {
+        use SystemEvent as E;
+
+        let data = match message {
+            DispatchResult::Event(m) => m.dwData,
+            _ => 0,
+        };
+        let filename = || match message {
+            DispatchResult::EventFilename(m) => Some(PathBuf::from(string(&m.szFileName))),
+            _ => None,
+        };
+        let frame = || match message {
+            DispatchResult::EventFrame(m) => Some((m.fFrameRate, m.fSimSpeed)),
+            _ => None,
+        };
+        let object = || match message {
+            DispatchResult::EventObjectAddRemove(m) => Some((m._base.dwData, m.eObjType)),
+            _ => None,
+        };
+
+        Some(match event {
+            E::OneSec => Self::OneSec,
+            E::FourSec => Self::FourSec,
+            E::SixHz => Self::SixHz,
+            E::AircraftLoaded => Self::AircraftLoaded(filename()?),
+            E::Crashed => Self::Crashed,
+            E::CrashReset => Self::CrashReset,
+            E::FlightLoaded => Self::FlightLoaded(filename()?),
+            E::FlightSaved => Self::FlightSaved(filename()?),
+            E::FlightPlanActivated => Self::FlightPlanActivated(filename()?),
+            E::FlightPlanDeactivated => Self::FlightPlanDeactivated,
+            E::Frame => {
+                let (fps, sim_speed) = frame()?;
+                Self::Frame { fps, sim_speed }
+            }
+            E::PauseFrame => {
+                let (fps, sim_speed) = frame()?;
+                Self::PauseFrame { fps, sim_speed }
+            }
+            E::ObjectAdded => {
+                let (object_id, object_type) = object()?;
+                Self::ObjectAdded {
+                    object_id,
+                    object_type,
+                }
+            }
+            E::ObjectRemoved => {
+                let (object_id, object_type) = object()?;
+                Self::ObjectRemoved {
+                    object_id,
+                    object_type,
+                }
+            }
+            E::Pause => Self::Pause(data != 0),
+            E::PauseEx1 => Self::PauseEx1(data),
+            E::Paused => Self::Paused,
+            E::PositionChanged => Self::PositionChanged,
+            E::Sim => Self::Sim(data != 0),
+            E::SimStart => Self::SimStart,
+            E::SimStop => Self::SimStop,
+            E::Sound => Self::Sound(data & SIMCONNECT_SOUND_SYSTEM_EVENT_DATA_MASTER != 0),
+            E::Unpaused => Self::Unpaused,
+            E::View => Self::View(data.into()),
+            E::WeatherModeChanged => Self::WeatherModeChanged,
+        })
+    }
+}
+
+impl DispatchResult<'_> {
+    /// The system event notification this message carries, for events subscribed to with
+    /// [`SimConnector::subscribe`].
+    pub fn system_event(&self) -> Option<SystemNotification> {
+        let event_id = match self {
+            Self::Event(m) => m.uEventID,
+            Self::EventFilename(m) => m._base.uEventID,
+            Self::EventFrame(m) => m._base.uEventID,
+            Self::EventObjectAddRemove(m) => m._base.uEventID,
+            _ => return None,
+        };
+
+        SystemNotification::decode(SystemEvent::from_event_id(event_id)?, self)
+    }
+}
@@ -0,0 +1,507 @@
+//! [`SimConnectBackend`] speaking the SimConnect network protocol
+//!
+//! This is the protocol the SimConnect library uses for remote connections configured in
+//! `SimConnect.cfg`, so a [`TcpBackend`] can talk to a simulator on another machine from any OS,
+//! without the library:
+//!
+//! ```no_run
+//! use simconnect::{SimConnector, TcpBackend};
+//!
+//! let mut conn = SimConnector::with_backend(TcpBackend::new("192.168.1.20:500"));
+//! conn.connect("Remote client").unwrap();
+//! ```
+//!
+//! The simulator has to be set up to accept IPv4 connections in its `SimConnect.xml`. The
+//! backend speaks protocol version 4 (FSX SP2 / Acceleration), which covers every function up
+//! to `SimConnect_RequestFacilitiesList`. The functions added by MSFS, as well as the ones the
+//! library implements locally (`CallDispatch`, `RequestResponseTimes`), fail with
+//! [`SimConnectError::Unavailable`].
+
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+use std::io::{self, ErrorKind, Read, Write};
+use std::mem::size_of;
+use std::net::{Shutdown, TcpStream};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use crate::backend::for_each_backend_function;
+use crate::*;
+
+/// Protocol version sent in the header of every packet, the one of FSX SP2.
+const PROTOCOL_VERSION: DWORD = 4;
+
+/// Marks the packet type of client packets.
+const CLIENT_PACKET: DWORD = 0xF000_0000;
+
+/// Packet type of `SimConnect_Open`.
+const OPEN_PACKET: DWORD = 0x01;
+
+/// Size of the header preceding every client packet: size, version, type and send ID.
+const HEADER_SIZE: usize = 16;
+
+/// The packet type of every function that goes over the wire, in the order of the protocol.
+const FUNCTION_IDS: &[(&str, DWORD)] = &[
+    ("SimConnect_MapClientEventToSimEvent", 0x04),
+    ("SimConnect_TransmitClientEvent", 0x05),
+    ("SimConnect_SetSystemEventState", 0x06),
+    ("SimConnect_AddClientEventToNotificationGroup", 0x07),
+    ("SimConnect_RemoveClientEvent", 0x08),
+    ("SimConnect_SetNotificationGroupPriority", 0x09),
+    ("SimConnect_ClearNotificationGroup", 0x0A),
+    ("SimConnect_RequestNotificationGroup", 0x0B),
+    ("SimConnect_AddToDataDefinition", 0x0C),
+    ("SimConnect_ClearDataDefinition", 0x0D),
+    ("SimConnect_RequestDataOnSimObject", 0x0E),
+    ("SimConnect_RequestDataOnSimObjectType", 0x0F),
+    ("SimConnect_SetDataOnSimObject", 0x10),
+    ("SimConnect_MapInputEventToClientEvent", 0x11),
+    ("SimConnect_SetInputGroupPriority", 0x12),
+    ("SimConnect_RemoveInputEvent", 0x13),
+    ("SimConnect_ClearInputGroup", 0x14),
+    ("SimConnect_SetInputGroupState", 0x15),
+    ("SimConnect_RequestReservedKey", 0x16),
+    ("SimConnect_SubscribeToSystemEvent", 0x17),
+    ("SimConnect_UnsubscribeFromSystemEvent", 0x18),
+    ("SimConnect_WeatherRequestInterpolatedObservation", 0x19),
+    ("SimConnect_WeatherRequestObservationAtStation", 0x1A),
+    ("SimConnect_WeatherRequestObservationAtNearestStation", 0x1B),
+    ("SimConnect_WeatherCreateStation", 0x1C),
+    ("SimConnect_WeatherRemoveStation", 0x1D),
+    ("SimConnect_WeatherSetObservation", 0x1E),
+    ("SimConnect_WeatherSetModeServer", 0x1F),
+    ("SimConnect_WeatherSetModeTheme", 0x20),
+    ("SimConnect_WeatherSetModeGlobal", 0x21),
+    ("SimConnect_WeatherSetModeCustom", 0x22),
+    ("SimConnect_WeatherSetDynamicUpdateRate", 0x23),
+    ("SimConnect_WeatherRequestCloudState", 0x24),
+    ("SimConnect_WeatherCreateThermal", 0x25),
+    ("SimConnect_WeatherRemoveThermal", 0x26),
+    ("SimConnect_AICreateParkedATCAircraft", 0x27),
+    ("SimConnect_AICreateEnrouteATCAircraft", 0x28),
+    ("SimConnect_AICreateNonATCAircraft", 0x29),
+    ("SimConnect_AICreateSimulatedObject", 0x2A),
+    ("SimConnect_AIReleaseControl", 0x2B),
+    ("SimConnect_AIRemoveObject", 0x2C),
+    ("SimConnect_AISetAircraftFlightPlan", 0x2D),
+    ("SimConnect_ExecuteMissionAction", 0x2E),
+    ("SimConnect_CompleteCustomMissionAction", 0x2F),
+    ("SimConnect_CameraSetRelative6DOF", 0x30),
+    ("SimConnect_MenuAddItem", 0x31),
+    ("SimConnect_MenuDeleteItem", 0x32),
+    ("SimConnect_MenuAddSubItem", 0x33),
+    ("SimConnect_MenuDeleteSubItem", 0x34),
+    ("SimConnect_RequestSystemState", 0x35),
+    ("SimConnect_SetSystemState", 0x36),
+    ("SimConnect_MapClientDataNameToID", 0x37),
+    ("SimConnect_CreateClientData", 0x38),
+    ("SimConnect_AddToClientDataDefinition", 0x39),
+    ("SimConnect_ClearClientDataDefinition", 0x3A),
+    ("SimConnect_RequestClientData", 0x3B),
+    ("SimConnect_SetClientData", 0x3C),
+    ("SimConnect_FlightLoad", 0x3D),
+    ("SimConnect_FlightSave", 0x3E),
+    ("SimConnect_FlightPlanLoad", 0x3F),
+    ("SimConnect_Text", 0x40),
+    ("SimConnect_SubscribeToFacilities", 0x41),
+    ("SimConnect_UnsubscribeToFacilities", 0x42),
+    ("SimConnect_RequestFacilitiesList", 0x43),
+];
+
+fn function_id(call: &str) -> Option<DWORD> {
+    FUNCTION_IDS
+        .iter()
+        .find(|(name, _)| *name == call)
+        .map(|(_, id)| *id)
+}
+
+/// Width of the string field the argument `name` is sent in, fixed apart from the METAR of
+/// `SimConnect_WeatherSetObservation`, which takes as much room as `string` needs.
+fn string_width(name: &str, string: &CStr) -> usize {
+    match name {
+        "key_choice_1" | "key_choice_2" | "key_choice_3" => 30,
+        "tail_number" => 12,
+        "airport_id" | "icao" => 5,
+        "flight_plan_path" | "file_name" => 260,
+        "metar" => string.to_bytes_with_nul().len(),
+        _ => 256,
+    }
+}
+
+/// [`SimConnectBackend`] connecting to a remote simulator over TCP
+///
+/// Messages are handed out in the layout of the `SIMCONNECT_RECV_*` structs as they arrive, so
+/// [`SimConnector::get_next_message`] returns the same [`DispatchResult`]s as with the library.
+/// Like the protocol itself this assumes a little-endian host.
+#[derive(Debug)]
+pub struct TcpBackend {
+    address: String,
+    nodelay: bool,
+    max_receive_size: Option<usize>,
+    stream: Option<TcpStream>,
+    send_id: Cell<DWORD>,
+    received: RefCell<Vec<u8>>,
+    current: RefCell<Vec<u8>>,
+}
+
+impl TcpBackend {
+    /// Backend connecting to `address`, e.g. `"192.168.1.20:500"`, once opened.
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            nodelay: true,
+            max_receive_size: None,
+            stream: None,
+            send_id: Cell::new(0),
+            received: RefCell::default(),
+            current: RefCell::default(),
+        }
+    }
+
+    /// Backend for the IPv4 or IPv6 section `index` of a `SimConnect.cfg`, honouring its
+    /// `MaxReceiveSize` and `DisableNagle`.
+    pub fn from_config(config: &SimConnectConfig, index: DWORD) -> Result<Self, ConfigError> {
+        let section = config.validate(index)?;
+        let address = section
+            .socket_address()
+            .ok_or_else(|| ConfigError::InvalidValue {
+                index,
+                key: "Protocol",
+                value: format!("{:?}", section.protocol),
+            })?;
+
+        Ok(Self {
+            nodelay: section.disable_nagle.unwrap_or(true),
+            max_receive_size: section.max_receive_size.map(|size| size as usize),
+            ..Self::new(address)
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    fn stream(&self, call: &'static str) -> Result<&TcpStream, SimConnectError> {
+        self.stream.as_ref().ok_or_else(|| SimConnectError::Io {
+            call,
+            source: io::Error::new(ErrorKind::NotConnected, "not connected"),
+        })
+    }
+
+    /// Assigns the next send ID to `packet` and writes it to the server.
+    fn send(&self, call: &'static str, packet: Packet) -> Result<(), SimConnectError> {
+        let send_id = self.send_id.get() + 1;
+        let bytes = packet.finish(send_id);
+
+        self.stream(call)?
+            .write_all(&bytes)
+            .map_err(|source| SimConnectError::Io { call, source })?;
+        self.send_id.set(send_id);
+
+        Ok(())
+    }
+
+    /// Reads whatever the server sent since the last call, without blocking.
+    fn receive(&self, stream: &TcpStream) -> io::Result<usize> {
+        let mut received = self.received.borrow_mut();
+        let mut buffer = [0; 4096];
+        let mut total = 0;
+
+        stream.set_nonblocking(true)?;
+        let result = loop {
+            match (&*stream).read(&mut buffer) {
+                Ok(0) if total == 0 => {
+                    break Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "the server closed the connection",
+                    ))
+                }
+                Ok(0) => break Ok(total),
+                Ok(n) => {
+                    received.extend_from_slice(&buffer[..n]);
+                    total += n;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(total),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        stream.set_nonblocking(false)?;
+
+        result
+    }
+
+    /// Moves the first complete message out of the receive buffer into `current`.
+    fn next_message(&self) -> io::Result<bool> {
+        let mut received = self.received.borrow_mut();
+        if received.len() < 4 {
+            return Ok(false);
+        }
+
+        let size = u32::from_le_bytes([received[0], received[1], received[2], received[3]]);
+        let size = size as usize;
+        if size < size_of::<SIMCONNECT_RECV>() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("message of {} bytes is shorter than its header", size),
+            ));
+        }
+        if matches!(self.max_receive_size, Some(max) if size > max) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("message of {} bytes exceeds MaxReceiveSize", size),
+            ));
+        }
+        if received.len() < size {
+            return Ok(false);
+        }
+
+        *self.current.borrow_mut() = received.drain(..size).collect();
+
+        Ok(true)
+    }
+}
+
+/// A client packet being assembled
+struct Packet {
+    bytes: Vec<u8>,
+    /// `unit_size` and `array_count` seen so far, they give the length of a data argument.
+    unit_size: DWORD,
+    array_count: DWORD,
+}
+
+impl Packet {
+    fn new(id: DWORD) -> Self {
+        let mut bytes = vec![0; HEADER_SIZE];
+        bytes[4..8].copy_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        bytes[8..12].copy_from_slice(&(CLIENT_PACKET | id).to_le_bytes());
+
+        Self {
+            bytes,
+            unit_size: 0,
+            array_count: 0,
+        }
+    }
+
+    /// Appends the argument called `name`.
+    ///
+    /// # Safety
+    ///
+    /// See [`WireArg::put`].
+    unsafe fn arg(mut self, name: &'static str, value: impl WireArg) -> Self {
+        value.put(name, &mut self);
+        self
+    }
+
+    fn put(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// Writes `string` NUL padded into a field of `width` bytes, truncating it if needed.
+    fn put_string(&mut self, string: &CStr, width: usize) {
+        let string = string.to_bytes();
+        let len = string.len().min(width - 1);
+
+        self.put(&string[..len]);
+        self.bytes.resize(self.bytes.len() + width - len, 0);
+    }
+
+    fn finish(mut self, send_id: DWORD) -> Vec<u8> {
+        let size = self.bytes.len() as DWORD;
+        self.bytes[0..4].copy_from_slice(&size.to_le_bytes());
+        self.bytes[12..16].copy_from_slice(&send_id.to_le_bytes());
+
+        self.bytes
+    }
+}
+
+/// Encoding of a backend argument in a client packet.
+trait WireArg {
+    /// Appends the argument called `name` to `packet`.
+    ///
+    /// # Safety
+    ///
+    /// Pointers have to be valid for the length given by the preceding size arguments.
+    unsafe fn put(self, name: &'static str, packet: &mut Packet);
+}
+
+macro_rules! wire_arg_le_bytes {
+    ($($ty:ty),*) => {
+        $(impl WireArg for $ty {
+            unsafe fn put(self, _name: &'static str, packet: &mut Packet) {
+                packet.put(&self.to_le_bytes());
+            }
+        })*
+    };
+}
+
+wire_arg_le_bytes!(i8, i32, u64, f32, f64);
+
+impl WireArg for u32 {
+    unsafe fn put(self, name: &'static str, packet: &mut Packet) {
+        match name {
+            "unit_size" => packet.unit_size = self,
+            "array_count" => packet.array_count = self,
+            _ => {}
+        }
+        packet.put(&self.to_le_bytes());
+    }
+}
+
+impl WireArg for bool {
+    unsafe fn put(self, name: &'static str, packet: &mut Packet) {
+        BOOL::from(self).put(name, packet)
+    }
+}
+
+impl WireArg for GUID {
+    unsafe fn put(self, _name: &'static str, packet: &mut Packet) {
+        packet.put(&self.Data1.to_le_bytes());
+        packet.put(&self.Data2.to_le_bytes());
+        packet.put(&self.Data3.to_le_bytes());
+        packet.put(&self.Data4);
+    }
+}
+
+impl WireArg for SIMCONNECT_DATA_INITPOSITION {
+    unsafe fn put(self, _name: &'static str, packet: &mut Packet) {
+        for value in [
+            self.Latitude,
+            self.Longitude,
+            self.Altitude,
+            self.Pitch,
+            self.Bank,
+            self.Heading,
+        ] {
+            packet.put(&value.to_le_bytes());
+        }
+        packet.put(&{ self.OnGround }.to_le_bytes());
+        packet.put(&{ self.Airspeed }.to_le_bytes());
+    }
+}
+
+impl WireArg for &CStr {
+    unsafe fn put(self, name: &'static str, packet: &mut Packet) {
+        packet.put_string(self, string_width(name, self));
+    }
+}
+
+impl WireArg for Option<&CStr> {
+    unsafe fn put(self, name: &'static str, packet: &mut Packet) {
+        let string = self.unwrap_or_default();
+        packet.put_string(string, string_width(name, string));
+    }
+}
+
+impl WireArg for *mut c_void {
+    unsafe fn put(self, _name: &'static str, packet: &mut Packet) {
+        let len = packet.unit_size as usize * packet.array_count.max(1) as usize;
+        if len > 0 {
+            packet.put(std::slice::from_raw_parts(self as *const u8, len));
+        }
+    }
+}
+
+// Only taken by functions the library implements locally or that have no packet type in
+// protocol version 4, which fail before anything is encoded.
+macro_rules! wire_arg_local {
+    ($($ty:ty),*) => {
+        $(impl WireArg for $ty {
+            unsafe fn put(self, _name: &'static str, _packet: &mut Packet) {}
+        })*
+    };
+}
+
+wire_arg_local!(DispatchProc, *mut c_int, *mut f32);
+
+macro_rules! impl_tcp_backend {
+    (
+        safe { $(fn $name:ident => $ffi:ident($($arg:ident: $ty:ty),* $(,)?);)* }
+        unsafe { $(fn $unsafe_name:ident => $unsafe_ffi:ident($($unsafe_arg:ident: $unsafe_ty:ty),* $(,)?);)* }
+    ) => {
+        impl SimConnectBackend for TcpBackend {
+            // The address comes from the backend, and there is no Win32 event to signal
+            fn open(
+                &mut self,
+                name: &CStr,
+                _config_index: DWORD,
+                _event_handle: HANDLE,
+            ) -> Result<(), SimConnectError> {
+                let call = "SimConnect_Open";
+                let nodelay = self.nodelay;
+                let stream = TcpStream::connect(&self.address)
+                    .and_then(|stream| stream.set_nodelay(nodelay).map(|_| stream))
+                    .map_err(|source| SimConnectError::Io { call, source })?;
+
+                self.stream = Some(stream);
+                self.send_id.set(0);
+                self.received.get_mut().clear();
+
+                let mut packet = Packet::new(OPEN_PACKET);
+                packet.put_string(name, 256);
+                packet.put(&0_u32.to_le_bytes());
+                // Alias and simulator name ("FSX" reversed), followed by the FSX SP2 version
+                packet.put(&[0]);
+                packet.put(b"XSF");
+                for value in [10_u32, 0, 61259, 0] {
+                    packet.put(&value.to_le_bytes());
+                }
+
+                self.send(call, packet)
+            }
+
+            fn close(&self) -> Result<(), SimConnectError> {
+                let call = "SimConnect_Close";
+
+                match self.stream(call)?.shutdown(Shutdown::Both) {
+                    Err(e) if e.kind() != ErrorKind::NotConnected => {
+                        Err(SimConnectError::Io { call, source: e })
+                    }
+                    _ => Ok(()),
+                }
+            }
+
+            fn get_last_sent_packet_id(&self) -> Result<DWORD, SimConnectError> {
+                Ok(self.send_id.get())
+            }
+
+            fn get_next_dispatch(&self) -> Result<(*mut SIMCONNECT_RECV, DWORD), SimConnectError> {
+                let call = "SimConnect_GetNextDispatch";
+                let io = |source| SimConnectError::Io { call, source };
+
+                if !self.next_message().map_err(io)? {
+                    self.receive(self.stream(call)?).map_err(io)?;
+
+                    if !self.next_message().map_err(io)? {
+                        return Ok((ptr::null_mut(), 0));
+                    }
+                }
+
+                let mut current = self.current.borrow_mut();
+                let size = current.len() as DWORD;
+
+                Ok((current.as_mut_ptr() as *mut SIMCONNECT_RECV, size))
+            }
+
+            $(fn $name(&self, $($arg: $ty),*) -> Result<(), SimConnectError> {
+                let call = stringify!($ffi);
+                let id = function_id(call).ok_or(SimConnectError::Unavailable { call })?;
+
+                // The arguments of the safe functions contain no pointers
+                #[allow(unused_unsafe)]
+                let packet = unsafe { Packet::new(id)$(.arg(stringify!($arg), $arg))* };
+
+                self.send(call, packet)
+            })*
+
+            $(unsafe fn $unsafe_name(&self, $($unsafe_arg: $unsafe_ty),*) -> Result<(), SimConnectError> {
+                let call = stringify!($unsafe_ffi);
+                let id = function_id(call).ok_or(SimConnectError::Unavailable { call })?;
+
+                self.send(call, Packet::new(id)$(.arg(stringify!($unsafe_arg), $unsafe_arg))*)
+            })*
+        }
+    };
+}
+
+for_each_backend_function!(impl_tcp_backend);
@@ -0,0 +1,600 @@
+//! Fields of thermals, placed with `SimConnect_WeatherCreateThermal`
+//!
+//! A [`ThermalField`] is generated from a seed by [`ThermalField::random`], or read from the
+//! `Point` features of a GeoJSON file by `ThermalField::from_geojson` with the `geojson`
+//! feature. Once placed, it keeps the object IDs the sim assigns to the thermals, moves them
+//! with the wind and removes them again:
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # use simconnect::thermal::{ThermalDistribution, ThermalField};
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("Soaring")?;
+//!
+//! let mut field = ThermalField::random(42, &ThermalDistribution::new(47.48, 8.9, 20_000.0, 30));
+//! field.place(&conn, 1000)?;
+//!
+//! loop {
+//!     let message = conn.get_next_message()?;
+//!     if let DispatchResult::Quit(_) = message {
+//!         break;
+//!     }
+//!     field.handle(&message);
+//!
+//!     // Wind from 270° at 5 m/s, one second later
+//!     field.drift(&conn, 270.0, 5.0, 1.0)?;
+//! }
+//! field.remove_all(&conn)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Thermals exist in FSX and Prepar3D only.
+
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+#[cfg(feature = "geojson")]
+use serde_json::Value;
+
+/// Mean radius of the earth in meters.
+const EARTH_RADIUS: f64 = 6_371_000.0;
+#[cfg(feature = "geojson")]
+const FEET_PER_METER: f64 = 3.280_84;
+
+/// Errors describing or reading thermals
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThermalError {
+    /// `field` is `value`, which isn't within `min..=max` or not finite.
+    OutOfRange {
+        field: &'static str,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    /// The GeoJSON isn't valid JSON, or a feature isn't a thermal.
+    InvalidGeoJson(String),
+}
+
+impl fmt::Display for ThermalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "thermal {} {} is not within {}..={}",
+                field, value, min, max
+            ),
+            Self::InvalidGeoJson(message) => write!(f, "invalid GeoJSON: {}", message),
+        }
+    }
+}
+
+impl Error for ThermalError {}
+
+/// The arguments of `SimConnect_WeatherCreateThermal`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Thermal {
+    pub lat: f32,
+    pub lon: f32,
+    /// Altitude of the base in feet.
+    pub alt: f32,
+    /// Radius in meters.
+    pub radius: f32,
+    /// Height in meters.
+    pub height: f32,
+    /// Lift in the core in meters per second.
+    pub core_rate: f32,
+    pub core_turbulence: f32,
+    /// Lift around the core in meters per second, usually negative.
+    pub sink_rate: f32,
+    pub sink_turbulence: f32,
+    /// Radius of the core in meters.
+    pub core_size: f32,
+    pub core_transition_size: f32,
+    pub sink_layer_size: f32,
+    pub sink_transition_size: f32,
+}
+
+impl Thermal {
+    /// A thermal on the ground with a core of half its radius, and neither turbulence nor
+    /// sink.
+    pub fn new(lat: f32, lon: f32, radius: f32, height: f32, core_rate: f32) -> Self {
+        Self {
+            lat,
+            lon,
+            alt: 0.0,
+            radius,
+            height,
+            core_rate,
+            core_turbulence: 0.0,
+            sink_rate: 0.0,
+            sink_turbulence: 0.0,
+            core_size: radius / 2.0,
+            core_transition_size: radius / 4.0,
+            sink_layer_size: 0.0,
+            sink_transition_size: 0.0,
+        }
+    }
+
+    /// Checks the position, that sizes are within `0..=MAX_THERMAL_SIZE` and that rates and
+    /// turbulences are within `MAX_THERMAL_RATE` of zero.
+    pub fn validate(&self) -> Result<(), ThermalError> {
+        let sizes = [
+            ("radius", self.radius),
+            ("height", self.height),
+            ("core_size", self.core_size),
+            ("core_transition_size", self.core_transition_size),
+            ("sink_layer_size", self.sink_layer_size),
+            ("sink_transition_size", self.sink_transition_size),
+        ];
+        let rates = [
+            ("core_rate", self.core_rate),
+            ("core_turbulence", self.core_turbulence),
+            ("sink_rate", self.sink_rate),
+            ("sink_turbulence", self.sink_turbulence),
+        ];
+
+        check_range("lat", self.lat, -90.0, 90.0)?;
+        check_range("lon", self.lon, -180.0, 180.0)?;
+        check_range("alt", self.alt, f32::MIN, f32::MAX)?;
+        for (field, value) in sizes {
+            check_range(field, value, 0.0, MAX_THERMAL_SIZE)?;
+        }
+        for (field, value) in rates {
+            check_range(field, value, -MAX_THERMAL_RATE, MAX_THERMAL_RATE)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a GeoJSON `Point` feature: `coordinates` are longitude, latitude and an optional
+    /// altitude in meters, the `properties` are the fields of the thermal by name. `radius`,
+    /// `height` and `core_rate` are required, the rest default as in [`new`](Self::new).
+    #[cfg(feature = "geojson")]
+    fn from_feature(feature: &Value) -> Result<Self, ThermalError> {
+        let invalid = |message: &str| ThermalError::InvalidGeoJson(message.to_owned());
+
+        let geometry = feature
+            .get("geometry")
+            .ok_or_else(|| invalid("feature without a geometry"))?;
+        if geometry.get("type").and_then(Value::as_str) != Some("Point") {
+            return Err(invalid("thermals have to be `Point` features"));
+        }
+        let coordinates = geometry
+            .get("coordinates")
+            .and_then(Value::as_array)
+            .and_then(|c| c.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+            .filter(|c| (2..=3).contains(&c.len()))
+            .ok_or_else(|| invalid("a point needs two or three numbers as coordinates"))?;
+
+        let properties = feature.get("properties");
+        let property = |name: &str| -> Result<Option<f32>, ThermalError> {
+            match properties.and_then(|p| p.get(name)) {
+                None | Some(Value::Null) => Ok(None),
+                Some(value) => value
+                    .as_f64()
+                    .map(|v| Some(v as f32))
+                    .ok_or_else(|| invalid(&format!("property `{}` isn't a number", name))),
+            }
+        };
+        let required = |name: &str| {
+            property(name)?.ok_or_else(|| invalid(&format!("missing property `{}`", name)))
+        };
+
+        let mut thermal = Self::new(
+            coordinates[1] as f32,
+            coordinates[0] as f32,
+            required("radius")?,
+            required("height")?,
+            required("core_rate")?,
+        );
+        if let Some(&alt) = coordinates.get(2) {
+            thermal.alt = (alt * FEET_PER_METER) as f32;
+        }
+        let optional = [
+            ("core_turbulence", &mut thermal.core_turbulence),
+            ("sink_rate", &mut thermal.sink_rate),
+            ("sink_turbulence", &mut thermal.sink_turbulence),
+            ("core_size", &mut thermal.core_size),
+            ("core_transition_size", &mut thermal.core_transition_size),
+            ("sink_layer_size", &mut thermal.sink_layer_size),
+            ("sink_transition_size", &mut thermal.sink_transition_size),
+        ];
+        for (name, field) in optional {
+            if let Some(value) = property(name)? {
+                *field = value;
+            }
+        }
+
+        Ok(thermal)
+    }
+
+    /// Moves the thermal `north` and `east` meters.
+    fn offset(&mut self, north: f64, east: f64) {
+        let lat = f64::from(self.lat);
+        let lon =
+            f64::from(self.lon) + (east / (EARTH_RADIUS * lat.to_radians().cos())).to_degrees();
+
+        self.lat = (lat + (north / EARTH_RADIUS).to_degrees()).clamp(-90.0, 90.0) as f32;
+        self.lon = ((lon + 540.0).rem_euclid(360.0) - 180.0) as f32;
+    }
+}
+
+fn check_range(field: &'static str, value: f32, min: f32, max: f32) -> Result<(), ThermalError> {
+    if value.is_finite() && (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ThermalError::OutOfRange {
+            field,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+/// Distance in meters between two positions, on a sphere
+fn distance((lat1, lon1): (f32, f32), (lat2, lon2): (f32, f32)) -> f64 {
+    let (lat1, lat2) = (f64::from(lat1).to_radians(), f64::from(lat2).to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (f64::from(lon2) - f64::from(lon1)).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// SplitMix64, so a seed gives the same field on every platform and version
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0.0..1.0`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    fn between(&mut self, (min, max): (f32, f32)) -> f32 {
+        (f64::from(min) + f64::from(max - min) * self.next_f64()) as f32
+    }
+}
+
+/// Where and how strong [`ThermalField::random`] makes thermals
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThermalDistribution {
+    /// Center of the field.
+    pub lat: f32,
+    pub lon: f32,
+    /// Altitude of the bases in feet.
+    pub alt: f32,
+    /// Radius of the field in meters.
+    pub radius: f32,
+    pub count: usize,
+    /// Range of the radius of a thermal in meters.
+    pub thermal_radius: (f32, f32),
+    /// Range of the height in meters.
+    pub height: (f32, f32),
+    /// Range of the core rate in meters per second.
+    pub core_rate: (f32, f32),
+    /// Sink rate as a fraction of the core rate, e.g. `-0.2`.
+    pub sink_ratio: f32,
+}
+
+impl ThermalDistribution {
+    /// `count` thermals within `radius` meters of a point, of 300 to 1000 meters radius,
+    /// 1000 to 2500 meters height and 1 to 4 m/s lift, with a fifth of that as sink.
+    pub fn new(lat: f32, lon: f32, radius: f32, count: usize) -> Self {
+        Self {
+            lat,
+            lon,
+            alt: 0.0,
+            radius,
+            count,
+            thermal_radius: (300.0, 1000.0),
+            height: (1000.0, 2500.0),
+            core_rate: (1.0, 4.0),
+            sink_ratio: -0.2,
+        }
+    }
+}
+
+/// State of a thermal of a [`ThermalField`] in the sim
+#[derive(Debug, Clone, Copy, Default)]
+struct Placement {
+    /// Request the thermal was created with, until its object ID arrived.
+    request_id: Option<SIMCONNECT_DATA_REQUEST_ID>,
+    object_id: Option<SIMCONNECT_OBJECT_ID>,
+    /// Where the sim has the thermal.
+    position: (f32, f32),
+}
+
+/// Thermals placed in the sim together
+///
+/// Every creation uses the next request ID, counting up from the one passed to
+/// [`place`](Self::place). The object IDs the sim assigns arrive as
+/// [`DispatchResult::AssignedObjectId`], which have to be passed to [`handle`](Self::handle).
+#[derive(Debug, Clone)]
+pub struct ThermalField {
+    thermals: Vec<Thermal>,
+    placements: Vec<Placement>,
+    next_request_id: SIMCONNECT_DATA_REQUEST_ID,
+    /// Requests of thermals removed before their object ID arrived.
+    abandoned: Vec<SIMCONNECT_DATA_REQUEST_ID>,
+    /// Objects to remove with the next call that has a connection.
+    stale: Vec<SIMCONNECT_OBJECT_ID>,
+    redraw_distance: f64,
+}
+
+impl ThermalField {
+    pub fn new(thermals: Vec<Thermal>) -> Self {
+        Self {
+            placements: vec![Placement::default(); thermals.len()],
+            thermals,
+            next_request_id: 0,
+            abandoned: Vec::new(),
+            stale: Vec::new(),
+            redraw_distance: 200.0,
+        }
+    }
+
+    /// A field whose thermals are spread evenly over the area of `distribution`. The same
+    /// seed always gives the same field.
+    pub fn random(seed: u64, distribution: &ThermalDistribution) -> Self {
+        let mut rng = Rng(seed);
+        let thermals = (0..distribution.count)
+            .map(|_| {
+                let distance = f64::from(distribution.radius) * rng.next_f64().sqrt();
+                let bearing = 2.0 * PI * rng.next_f64();
+                let core_rate = rng.between(distribution.core_rate);
+
+                let mut thermal = Thermal::new(
+                    distribution.lat,
+                    distribution.lon,
+                    rng.between(distribution.thermal_radius),
+                    rng.between(distribution.height),
+                    core_rate,
+                );
+                thermal.alt = distribution.alt;
+                thermal.sink_rate = core_rate * distribution.sink_ratio;
+                thermal.offset(distance * bearing.cos(), distance * bearing.sin());
+                thermal
+            })
+            .collect();
+
+        Self::new(thermals)
+    }
+
+    /// The thermals of the `Point` features of a GeoJSON `FeatureCollection` or `Feature`. Needs
+    /// the `geojson` feature.
+    ///
+    /// The coordinates are longitude, latitude and an optional altitude in meters. The
+    /// `properties` hold the fields of [`Thermal`] by name, of which `radius`, `height` and
+    /// `core_rate` are required:
+    ///
+    /// ```
+    /// # use simconnect::thermal::ThermalField;
+    /// let field = ThermalField::from_geojson(r#"{
+    ///     "type": "Feature",
+    ///     "geometry": { "type": "Point", "coordinates": [8.9, 47.48, 500] },
+    ///     "properties": { "radius": 800, "height": 2000, "core_rate": 2.5 }
+    /// }"#).unwrap();
+    /// assert_eq!(field.thermals()[0].core_rate, 2.5);
+    /// ```
+    #[cfg(feature = "geojson")]
+    pub fn from_geojson(text: &str) -> Result<Self, ThermalError> {
+        let json = serde_json::from_str::<Value>(text)
+            .map_err(|e| ThermalError::InvalidGeoJson(e.to_string()))?;
+
+        let thermals = match json.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => json
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or_else(|| {
+                    ThermalError::InvalidGeoJson("collection without features".to_owned())
+                })?
+                .iter()
+                .map(Thermal::from_feature)
+                .collect::<Result<_, _>>()?,
+            Some("Feature") => vec![Thermal::from_feature(&json)?],
+            _ => {
+                return Err(ThermalError::InvalidGeoJson(
+                    "expected a `FeatureCollection` or a `Feature`".to_owned(),
+                ))
+            }
+        };
+
+        Ok(Self::new(thermals))
+    }
+
+    /// The thermals, where they are now.
+    pub fn thermals(&self) -> &[Thermal] {
+        &self.thermals
+    }
+
+    /// The object ID of the thermal at `index`, once the sim assigned it.
+    pub fn object_id(&self, index: usize) -> Option<SIMCONNECT_OBJECT_ID> {
+        self.placements.get(index)?.object_id
+    }
+
+    /// Whether object IDs are still to arrive, or objects to be removed.
+    pub fn is_pending(&self) -> bool {
+        !self.stale.is_empty()
+            || !self.abandoned.is_empty()
+            || self.placements.iter().any(|p| p.request_id.is_some())
+    }
+
+    /// Sets how far a thermal drifts before it is removed and created again at its new
+    /// position, 200 meters by default.
+    pub fn set_redraw_distance(&mut self, meters: f64) {
+        self.redraw_distance = meters;
+    }
+
+    /// Validates every thermal, then creates them with the request IDs from `request_id` on.
+    /// Nothing is created if a thermal is invalid.
+    #[track_caller]
+    pub fn place<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        for thermal in &self.thermals {
+            thermal.validate().map_err(SimConnectError::Thermal)?;
+        }
+
+        self.next_request_id = request_id;
+        for index in 0..self.thermals.len() {
+            self.create(conn, index)?;
+        }
+
+        Ok(())
+    }
+
+    #[track_caller]
+    fn create<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        index: usize,
+    ) -> Result<(), SimConnectError> {
+        let t = self.thermals[index];
+        let request_id = self.next_request_id;
+        conn.weather_create_thermal(
+            request_id,
+            t.lat,
+            t.lon,
+            t.alt,
+            t.radius,
+            t.height,
+            t.core_rate,
+            t.core_turbulence,
+            t.sink_rate,
+            t.sink_turbulence,
+            t.core_size,
+            t.core_transition_size,
+            t.sink_layer_size,
+            t.sink_transition_size,
+        )?;
+
+        self.next_request_id = request_id.wrapping_add(1);
+        self.placements[index] = Placement {
+            request_id: Some(request_id),
+            object_id: None,
+            position: (t.lat, t.lon),
+        };
+
+        Ok(())
+    }
+
+    /// Keeps the object ID of an [`DispatchResult::AssignedObjectId`] answering a creation of
+    /// the field. Returns whether the message was one.
+    pub fn handle(&mut self, message: &DispatchResult<'_>) -> bool {
+        let assigned = match message {
+            DispatchResult::AssignedObjectId(assigned) => assigned,
+            _ => return false,
+        };
+        let (request_id, object_id) = (assigned.dwRequestID, assigned.dwObjectID);
+
+        if let Some(placement) = self
+            .placements
+            .iter_mut()
+            .find(|p| p.request_id == Some(request_id))
+        {
+            placement.request_id = None;
+            placement.object_id = Some(object_id);
+            true
+        } else if let Some(i) = self.abandoned.iter().position(|&id| id == request_id) {
+            self.abandoned.swap_remove(i);
+            self.stale.push(object_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves the thermals with a wind from `direction` degrees at `speed` meters per second
+    /// for `seconds`. Thermals that drifted further than the
+    /// [redraw distance](Self::set_redraw_distance) are removed and created again.
+    #[track_caller]
+    pub fn drift<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        direction: f32,
+        speed: f32,
+        seconds: f32,
+    ) -> Result<(), SimConnectError> {
+        self.remove_stale(conn)?;
+
+        // The wind blows towards the opposite of where it comes from
+        let bearing = (f64::from(direction) + 180.0).to_radians();
+        let travelled = f64::from(speed) * f64::from(seconds);
+        for thermal in &mut self.thermals {
+            thermal.offset(travelled * bearing.cos(), travelled * bearing.sin());
+        }
+
+        for index in 0..self.thermals.len() {
+            let placement = self.placements[index];
+            let thermal = &self.thermals[index];
+            if let Some(object_id) = placement.object_id {
+                if distance(placement.position, (thermal.lat, thermal.lon)) > self.redraw_distance {
+                    conn.weather_remove_thermal(object_id)?;
+                    self.placements[index].object_id = None;
+                    self.create(conn, index)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes every thermal of the field from the sim. Thermals whose object ID is still to
+    /// arrive are removed once [`handle`](Self::handle) got it and this is called again, see
+    /// [`is_pending`](Self::is_pending).
+    #[track_caller]
+    pub fn remove_all<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        for placement in &mut self.placements {
+            if let Some(request_id) = placement.request_id.take() {
+                self.abandoned.push(request_id);
+            }
+            if let Some(object_id) = placement.object_id.take() {
+                self.stale.push(object_id);
+            }
+        }
+
+        self.remove_stale(conn)
+    }
+
+    #[track_caller]
+    fn remove_stale<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        while let Some(&object_id) = self.stale.last() {
+            conn.weather_remove_thermal(object_id)?;
+            self.stale.pop();
+        }
+
+        Ok(())
+    }
+}
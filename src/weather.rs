@@ -0,0 +1,105 @@
+//! Typed results of the `SimConnect_Weather*` functions
+//!
+//! The requests are methods of [`SimConnector`], e.g.
+//! [`weather_request_observation_at_station`](SimConnector::weather_request_observation_at_station).
+//! Their answers arrive as messages, which the methods here decode:
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # fn main() -> Result<(), SimConnectError> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("Weather")?;
+//! conn.weather_request_observation_at_station(1, "EGLL")?;
+//!
+//! loop {
+//!     if let Some(observation) = conn.get_next_message()?.weather_observation() {
+//!         println!("{}", observation.metar);
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! These functions exist in FSX and Prepar3D. MSFS fails them with
+//! [`SimConnectError::Unavailable`] or an exception.
+
+use crate::message::{CloudState, WeatherObservation};
+use crate::*;
+
+/// Longest ICAO code a weather station can have.
+pub const MAX_ICAO_LENGTH: usize = 4;
+/// Longest name a weather station can have.
+pub const MAX_STATION_NAME_LENGTH: usize = 255;
+
+/// The weather mode of a [`DispatchResult::EventWeatherMode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeatherMode {
+    /// `SIMCONNECT_WEATHER_MODE_THEME`
+    Theme,
+    /// `SIMCONNECT_WEATHER_MODE_RWW`, real world weather.
+    RealWorld,
+    /// `SIMCONNECT_WEATHER_MODE_CUSTOM`
+    Custom,
+    /// `SIMCONNECT_WEATHER_MODE_GLOBAL`
+    Global,
+    /// Any other value.
+    Other(SIMCONNECT_WEATHER_MODE),
+}
+
+impl From<SIMCONNECT_WEATHER_MODE> for WeatherMode {
+    fn from(mode: SIMCONNECT_WEATHER_MODE) -> Self {
+        match mode {
+            SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_THEME => Self::Theme,
+            SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_RWW => Self::RealWorld,
+            SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_CUSTOM => Self::Custom,
+            SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_GLOBAL => Self::Global,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl CloudState {
+    /// Number of cells per row and column of the grid.
+    pub const WIDTH: usize = SIMCONNECT_CLOUD_STATE_ARRAY_WIDTH as usize;
+
+    /// Density of the cell in `row` and `column`, if the message covered it.
+    pub fn density(&self, row: usize, column: usize) -> Option<u8> {
+        if column >= Self::WIDTH {
+            return None;
+        }
+
+        self.data.get(row * Self::WIDTH + column).copied()
+    }
+}
+
+impl DispatchResult<'_> {
+    /// The METAR of a [`DispatchResult::WeatherObservation`], read up to its NUL or the end of
+    /// the message.
+    pub fn weather_observation(&self) -> Option<WeatherObservation> {
+        match self {
+            Self::WeatherObservation(_) => match self.to_owned() {
+                Message::WeatherObservation(observation) => Some(observation),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The densities of a [`DispatchResult::CloudState`], bounded by the size of the message.
+    pub fn cloud_state(&self) -> Option<CloudState> {
+        match self {
+            Self::CloudState(_) => match self.to_owned() {
+                Message::CloudState(state) => Some(state),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The mode a [`DispatchResult::EventWeatherMode`] announces.
+    pub fn weather_mode(&self) -> Option<WeatherMode> {
+        match self {
+            Self::EventWeatherMode(m) => Some(WeatherMode::from(m._base.dwData as i32)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,673 @@
+//! Replays archived METARs as the zulu time of the simulator advances
+//!
+//! A [`WeatherArchive`] reads a directory of text files with one report per line, each
+//! preceded by the time it was issued:
+//!
+//! ```text
+//! # NOAA style: the time on a line of its own
+//! 2024/03/12 12:50
+//! EGLL 121250Z 24012KT 9999 BKN025 12/08 Q1009
+//! # One line per report, the time as YYYYMMDDHHMM or YYYY-MM-DDTHH:MMZ
+//! 202403121320 METAR EGLL 121320Z 25015KT 9999 SCT030 13/07 Q1010=
+//! ```
+//!
+//! A [`WeatherReplay`] creates the stations the sim doesn't know, and sets the observation of
+//! every station in the archive at the sim's zulu time, interpolated between the reports
+//! before and after it:
+//!
+//! ```no_run
+//! # use simconnect::*;
+//! # use simconnect::weather_replay::{ReplayStation, WeatherArchive, WeatherReplay};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut conn = SimConnector::new();
+//! conn.connect("Weather replay")?;
+//!
+//! let archive = WeatherArchive::from_dir("archive/2024-03-12")?;
+//! let mut replay = WeatherReplay::new(archive);
+//! replay.add_station(ReplayStation::new("ZZZZ", "Training field", 47.5, 8.5, 1400.0));
+//! replay.start(&conn, 100)?;
+//! replay.request_zulu_time(&conn, 100, 100)?;
+//!
+//! loop {
+//!     let time = replay.zulu_time(&conn.get_next_message()?);
+//!     if let Some(time) = time {
+//!         replay.update(&conn, time)?;
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::metar::{Distance, Metar, MetarError, ObservationTime, Pressure};
+use crate::*;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A UTC time, in seconds since 1970-01-01T00:00Z
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ZuluTime(pub i64);
+
+impl ZuluTime {
+    /// The time `seconds` after midnight of a date, or `None` if the date doesn't exist.
+    pub fn from_date(year: i64, month: u32, day: u32, seconds: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || i64::from(seconds) >= SECONDS_PER_DAY {
+            return None;
+        }
+
+        let days = days_from_civil(year, month, day);
+        if civil_from_days(days) != (year, month, day) {
+            return None;
+        }
+
+        Some(Self(days * SECONDS_PER_DAY + i64::from(seconds)))
+    }
+
+    /// Parses `YYYYMMDDHHMM[SS]`, allowing any of `/-: T` between the fields and a trailing
+    /// `Z`, so `2024/03/12 12:50` and `2024-03-12T12:50:00Z` are accepted too.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_suffix('Z').unwrap_or(text);
+        if !text
+            .chars()
+            .all(|c| c.is_ascii_digit() || "/-: T".contains(c))
+        {
+            return None;
+        }
+
+        let digits = text
+            .bytes()
+            .filter(u8::is_ascii_digit)
+            .map(|b| u32::from(b - b'0'))
+            .collect::<Vec<_>>();
+        if digits.len() != 12 && digits.len() != 14 {
+            return None;
+        }
+        let field = |range: std::ops::Range<usize>| {
+            digits[range]
+                .iter()
+                .fold(0, |value, digit| value * 10 + digit)
+        };
+
+        let (hour, minute) = (field(8..10), field(10..12));
+        let second = if digits.len() == 14 { field(12..14) } else { 0 };
+        if hour >= 24 || minute >= 60 || second >= 60 {
+            return None;
+        }
+
+        Self::from_date(
+            i64::from(field(0..4)),
+            field(4..6),
+            field(6..8),
+            hour * 3600 + minute * 60 + second,
+        )
+    }
+
+    /// Year, month and day.
+    pub fn date(self) -> (i64, u32, u32) {
+        civil_from_days(self.0.div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// Seconds since midnight.
+    pub fn seconds_of_day(self) -> u32 {
+        self.0.rem_euclid(SECONDS_PER_DAY) as u32
+    }
+
+    /// The `DDHHMMZ` group of a report issued at this time.
+    pub fn observation_time(self) -> ObservationTime {
+        let (_, _, day) = self.date();
+        let seconds = self.seconds_of_day();
+
+        ObservationTime {
+            day: day as u8,
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+        }
+    }
+}
+
+impl fmt::Display for ZuluTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.date();
+        let seconds = self.seconds_of_day();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
+
+/// Days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Errors reading a [`WeatherArchive`]
+#[derive(Debug)]
+pub enum ReplayError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A report without a time before it.
+    MissingTime {
+        path: PathBuf,
+        line: usize,
+    },
+    /// A report that isn't a valid METAR.
+    Metar {
+        path: PathBuf,
+        line: usize,
+        source: MetarError,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {}", path.display(), source),
+            Self::MissingTime { path, line } => {
+                write!(f, "{}:{}: report without a time", path.display(), line)
+            }
+            Self::Metar { path, line, source } => {
+                write!(f, "{}:{}: {}", path.display(), line, source)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Metar { source, .. } => Some(source),
+            Self::MissingTime { .. } => None,
+        }
+    }
+}
+
+/// An archived report and the time it was issued
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    pub time: ZuluTime,
+    pub metar: Metar,
+}
+
+/// The reports of any number of stations, sorted by time
+#[derive(Debug, Clone, Default)]
+pub struct WeatherArchive {
+    stations: BTreeMap<String, Vec<Report>>,
+}
+
+impl WeatherArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads every file of `dir`, except hidden ones.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let dir = dir.as_ref();
+        let io_error = |source| ReplayError::Io {
+            path: dir.to_owned(),
+            source,
+        };
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if !hidden && entry.file_type().map_err(io_error)?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut archive = Self::new();
+        for path in paths {
+            archive.add_file(path)?;
+        }
+
+        Ok(archive)
+    }
+
+    /// Adds the reports of the file at `path`.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| ReplayError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        let mut time = None;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(t) = ZuluTime::parse(line) {
+                time = Some(t);
+                continue;
+            }
+            let report = match line.split_once(char::is_whitespace) {
+                Some((prefix, report)) if ZuluTime::parse(prefix).is_some() => {
+                    time = ZuluTime::parse(prefix);
+                    report
+                }
+                _ => line,
+            };
+
+            let report_time = time.take().ok_or_else(|| ReplayError::MissingTime {
+                path: path.to_owned(),
+                line: i + 1,
+            })?;
+            let metar = Metar::parse(report.trim_end_matches('=')).map_err(|source| {
+                ReplayError::Metar {
+                    path: path.to_owned(),
+                    line: i + 1,
+                    source,
+                }
+            })?;
+            self.insert(report_time, metar);
+        }
+
+        Ok(())
+    }
+
+    /// Adds `metar`, issued at `time`. It replaces a report of its station with the same time.
+    pub fn insert(&mut self, time: ZuluTime, metar: Metar) {
+        let reports = self.stations.entry(metar.station.clone()).or_default();
+        let index = reports.partition_point(|report| report.time < time);
+        let report = Report { time, metar };
+
+        match reports.get_mut(index) {
+            Some(existing) if existing.time == time => *existing = report,
+            _ => reports.insert(index, report),
+        }
+    }
+
+    /// ICAO codes of the stations with reports, in alphabetical order.
+    pub fn stations(&self) -> impl Iterator<Item = &str> {
+        self.stations.keys().map(String::as_str)
+    }
+
+    /// The reports of `icao`, oldest first.
+    pub fn reports(&self, icao: &str) -> &[Report] {
+        self.stations.get(icao).map_or(&[], Vec::as_slice)
+    }
+
+    /// The weather at `icao` at `time`.
+    ///
+    /// Between two reports, wind directions and speeds, visibilities in meters, temperatures,
+    /// pressures and the bases of matching cloud layers are interpolated. Everything else is
+    /// taken from the closer report. Before the first and after the last report, that report
+    /// is used as it is. The time of the result is `time`.
+    pub fn observation(&self, icao: &str, time: ZuluTime) -> Option<Metar> {
+        let reports = self.stations.get(icao)?;
+        let index = reports.partition_point(|report| report.time <= time);
+
+        let mut metar = match (
+            index.checked_sub(1).map(|i| &reports[i]),
+            reports.get(index),
+        ) {
+            (Some(before), Some(after)) => {
+                let fraction =
+                    (time.0 - before.time.0) as f64 / (after.time.0 - before.time.0) as f64;
+                interpolate(&before.metar, &after.metar, fraction)
+            }
+            (Some(report), None) | (None, Some(report)) => report.metar.clone(),
+            (None, None) => return None,
+        };
+        metar.time = Some(time.observation_time());
+
+        Some(metar)
+    }
+}
+
+fn lerp(from: f64, to: f64, fraction: f64) -> f64 {
+    from + (to - from) * fraction
+}
+
+/// Interpolates the numbers of `from` and `to` that can be, keeping everything else from the
+/// closer of the two
+fn interpolate(from: &Metar, to: &Metar, fraction: f64) -> Metar {
+    let mut metar = if fraction < 0.5 { from } else { to }.clone();
+    let round = |from: f64, to: f64| lerp(from, to, fraction).round();
+
+    if from.winds.len() == to.winds.len() {
+        for (wind, (a, b)) in metar.winds.iter_mut().zip(from.winds.iter().zip(&to.winds)) {
+            if a.unit != b.unit {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (a.direction, b.direction) {
+                // The short way around
+                let turn = (f64::from(b) - f64::from(a) + 540.0).rem_euclid(360.0) - 180.0;
+                let direction = (f64::from(a) + turn * fraction).round().rem_euclid(360.0);
+                wind.direction = Some(if direction == 0.0 && (a != 0 || b != 0) {
+                    360
+                } else {
+                    direction as u16
+                });
+            }
+            wind.speed = round(a.speed.into(), b.speed.into()) as u16;
+            if let (Some(a), Some(b)) = (a.gust, b.gust) {
+                wind.gust = Some(round(a.into(), b.into()) as u16);
+            }
+        }
+    }
+
+    if from.visibility.len() == to.visibility.len() {
+        let pairs = from.visibility.iter().zip(&to.visibility);
+        for (visibility, (a, b)) in metar.visibility.iter_mut().zip(pairs) {
+            if let (Distance::Meters(a), Distance::Meters(b)) = (a.distance, b.distance) {
+                visibility.distance = Distance::Meters(round(a.into(), b.into()) as u32);
+            }
+        }
+    }
+
+    if from.temperatures.len() == to.temperatures.len() {
+        let pairs = from.temperatures.iter().zip(&to.temperatures);
+        for (temperature, (a, b)) in metar.temperatures.iter_mut().zip(pairs) {
+            if a.altitude != b.altitude {
+                continue;
+            }
+            temperature.temperature = round(a.temperature.into(), b.temperature.into()) as i32;
+            if let (Some(a), Some(b)) = (a.dew_point, b.dew_point) {
+                temperature.dew_point = Some(round(a.into(), b.into()) as i32);
+            }
+        }
+    }
+
+    metar.pressure = match (from.pressure, to.pressure) {
+        (Some(Pressure::Hectopascals(a)), Some(Pressure::Hectopascals(b))) => {
+            Some(Pressure::Hectopascals(round(a.into(), b.into()) as u16))
+        }
+        (Some(Pressure::InchesOfMercury(a)), Some(Pressure::InchesOfMercury(b))) => {
+            let hundredths = round(f64::from(a) * 100.0, f64::from(b) * 100.0);
+            Some(Pressure::InchesOfMercury((hundredths / 100.0) as f32))
+        }
+        _ => metar.pressure,
+    };
+
+    let same_layers = from.clouds.len() == to.clouds.len()
+        && from
+            .clouds
+            .iter()
+            .zip(&to.clouds)
+            .all(|(a, b)| a.cover == b.cover);
+    if same_layers {
+        for (cloud, (a, b)) in metar
+            .clouds
+            .iter_mut()
+            .zip(from.clouds.iter().zip(&to.clouds))
+        {
+            if let (Some(a), Some(b)) = (a.base, b.base) {
+                // Bases are reported in hundreds of feet
+                cloud.base = Some(round(f64::from(a) / 100.0, f64::from(b) / 100.0) as u32 * 100);
+            }
+        }
+    }
+
+    metar
+}
+
+/// A station [`WeatherReplay::start`] creates
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReplayStation {
+    pub icao: String,
+    pub name: String,
+    pub lat: f32,
+    pub lon: f32,
+    /// Altitude in feet.
+    pub alt: f32,
+}
+
+impl ReplayStation {
+    pub fn new(icao: &str, name: &str, lat: f32, lon: f32, alt: f32) -> Self {
+        Self {
+            icao: icao.to_owned(),
+            name: name.to_owned(),
+            lat,
+            lon,
+            alt,
+        }
+    }
+}
+
+/// Sets the observations of the stations of a [`WeatherArchive`] as the zulu time advances
+///
+/// An observation is only sent when it differs from the last one sent for its station, and
+/// at most once per [`interval`](Self::set_interval) of zulu time. Going back in time sends
+/// the observations again.
+#[derive(Debug)]
+pub struct WeatherReplay {
+    archive: WeatherArchive,
+    stations: Vec<ReplayStation>,
+    request_id: Option<SIMCONNECT_DATA_REQUEST_ID>,
+    time_request_id: Option<SIMCONNECT_DATA_REQUEST_ID>,
+    blend_seconds: DWORD,
+    interval: i64,
+    last_update: Option<ZuluTime>,
+    sent: BTreeMap<String, String>,
+}
+
+impl WeatherReplay {
+    pub fn new(archive: WeatherArchive) -> Self {
+        Self {
+            archive,
+            stations: Vec::new(),
+            request_id: None,
+            time_request_id: None,
+            blend_seconds: 60,
+            interval: 60,
+            last_update: None,
+            sent: BTreeMap::new(),
+        }
+    }
+
+    pub fn archive(&self) -> &WeatherArchive {
+        &self.archive
+    }
+
+    /// Adds a station that [`start`](Self::start) creates. Stations of the archive the sim
+    /// already knows don't need one.
+    pub fn add_station(&mut self, station: ReplayStation) {
+        self.stations.push(station);
+    }
+
+    /// Sets the seconds the sim takes to blend into a new observation, 60 by default.
+    pub fn set_blend_seconds(&mut self, seconds: DWORD) {
+        self.blend_seconds = seconds;
+    }
+
+    /// Sets the zulu seconds [`update`](Self::update) waits between two updates, 60 by
+    /// default.
+    pub fn set_interval(&mut self, seconds: u32) {
+        self.interval = seconds.into();
+    }
+
+    /// Switches the sim to custom weather, so real world weather doesn't replace the
+    /// observations, and creates the added stations with the request IDs from `request_id`
+    /// on.
+    #[track_caller]
+    pub fn start<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        conn.weather_set_mode_custom()?;
+
+        for (id, station) in (request_id..).zip(&self.stations) {
+            conn.weather_create_station(
+                id,
+                &station.icao,
+                &station.name,
+                station.lat,
+                station.lon,
+                station.alt,
+            )?;
+        }
+        self.request_id = Some(request_id);
+
+        Ok(())
+    }
+
+    /// Removes the stations [`start`](Self::start) created. The next
+    /// [`update`](Self::update) sends every observation again.
+    #[track_caller]
+    pub fn stop<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+    ) -> Result<(), SimConnectError> {
+        if let Some(request_id) = self.request_id.take() {
+            for (id, station) in (request_id..).zip(&self.stations) {
+                conn.weather_remove_station(id, &station.icao)?;
+            }
+        }
+        self.last_update = None;
+        self.sent.clear();
+
+        Ok(())
+    }
+
+    /// Requests the zulu date and time of the user aircraft once a second, with the data
+    /// definition `define_id`. [`zulu_time`](Self::zulu_time) decodes the answers.
+    #[track_caller]
+    pub fn request_zulu_time<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+        request_id: SIMCONNECT_DATA_REQUEST_ID,
+    ) -> Result<(), SimConnectError> {
+        let variables = [
+            ("ZULU YEAR", "number"),
+            ("ZULU MONTH OF YEAR", "number"),
+            ("ZULU DAY OF MONTH", "number"),
+            ("ZULU TIME", "seconds"),
+        ];
+        for (datum_id, (name, units)) in (0..).zip(variables) {
+            conn.add_data_definition(
+                define_id,
+                name,
+                units,
+                SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+                datum_id,
+                0.0,
+            )?;
+        }
+        conn.request_data_on_sim_object(
+            request_id,
+            define_id,
+            SIMCONNECT_OBJECT_ID_USER,
+            SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SECOND,
+            0,
+            0,
+            0,
+            0,
+        )?;
+        self.time_request_id = Some(request_id);
+
+        Ok(())
+    }
+
+    /// The zulu time of an answer to [`request_zulu_time`](Self::request_zulu_time).
+    pub fn zulu_time(&self, message: &DispatchResult<'_>) -> Option<ZuluTime> {
+        let data = match message {
+            DispatchResult::SimObjectData(data) => data,
+            _ => return None,
+        };
+        if Some(data.dwRequestID) != self.time_request_id {
+            return None;
+        }
+
+        let mut reader = data.reader();
+        let year = reader.read_f64().ok()?;
+        let month = reader.read_f64().ok()?;
+        let day = reader.read_f64().ok()?;
+        let seconds = reader.read_f64().ok()?;
+
+        ZuluTime::from_date(year as i64, month as u32, day as u32, seconds as u32)
+    }
+
+    /// Sends the observation at `time` of every station whose weather changed, and returns
+    /// how many were sent.
+    #[track_caller]
+    pub fn update<B: SimConnectBackend>(
+        &mut self,
+        conn: &SimConnector<B>,
+        time: ZuluTime,
+    ) -> Result<usize, SimConnectError> {
+        match self.last_update {
+            Some(last) if time < last => self.sent.clear(),
+            Some(last) if time.0 - last.0 < self.interval => return Ok(0),
+            _ => {}
+        }
+        self.last_update = Some(time);
+
+        let mut sent = 0;
+        for icao in self.archive.stations() {
+            let mut metar = match self.archive.observation(icao, time) {
+                Some(metar) => metar,
+                None => continue,
+            };
+
+            // Compared without the time, which changes with every update
+            metar.time = None;
+            let weather = metar.to_string();
+            if self.sent.get(icao) == Some(&weather) {
+                continue;
+            }
+
+            metar.time = Some(time.observation_time());
+            conn.weather_set_metar(self.blend_seconds, &metar)?;
+            self.sent.insert(icao.to_owned(), weather);
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+}
@@ -0,0 +1,96 @@
+use simconnect::catalog::{self, CatalogError, UnitKind};
+use simconnect::mock::MockBackend;
+use simconnect::{SimConnectError, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64};
+
+#[test]
+fn default_units_are_allowed() {
+    for simvar in catalog::simvars() {
+        assert!(
+            simvar.allows_units(simvar.default_units),
+            "{} doesn't allow {}",
+            simvar.name,
+            simvar.default_units
+        );
+        assert_eq!(simvar.name, simvar.name.trim());
+    }
+}
+
+#[test]
+fn looks_up_without_regard_to_case_or_index() {
+    let rpm = catalog::lookup("general eng rpm:2").unwrap();
+    assert_eq!(rpm.name, "GENERAL ENG RPM");
+    assert_eq!(rpm.kind, UnitKind::Rpm);
+    assert!(rpm.indexed);
+    assert!(rpm.sims.fsx && rpm.sims.msfs);
+
+    assert!(
+        !catalog::lookup("PLANE ALT ABOVE GROUND MINUS CG")
+            .unwrap()
+            .sims
+            .fsx
+    );
+    assert!(catalog::search("autopilot").count() > 5);
+}
+
+#[test]
+fn rejects_with_suggestions() {
+    match catalog::check("PLANE ALTITUDE ", "feet") {
+        Err(CatalogError::UnknownVariable { name, suggestions }) => {
+            assert_eq!(name, "PLANE ALTITUDE ");
+            assert_eq!(suggestions, ["PLANE ALTITUDE", "PLANE LATITUDE"]);
+        }
+        other => panic!("expected an unknown variable, got {:?}", other),
+    }
+    match catalog::check("PLANE ALTITUDE", "meter s") {
+        Err(CatalogError::UnknownUnits { suggestions, .. }) => {
+            assert_eq!(suggestions[0], "meters")
+        }
+        other => panic!("expected unknown units, got {:?}", other),
+    }
+    assert_eq!(
+        catalog::check("GENERAL ENG RPM", "rpm"),
+        Err(CatalogError::MissingIndex("GENERAL ENG RPM"))
+    );
+    assert_eq!(
+        catalog::check("PLANE ALTITUDE:1", "feet"),
+        Err(CatalogError::UnexpectedIndex("PLANE ALTITUDE"))
+    );
+    assert!(matches!(
+        catalog::check("GENERAL ENG RPM:one", "rpm"),
+        Err(CatalogError::InvalidIndex { .. })
+    ));
+    assert!(catalog::check("Plane Altitude", "Feet").is_ok());
+    assert!(catalog::check("TITLE", "").is_ok());
+}
+
+#[test]
+fn checked_definition_fails_before_calling_simconnect() {
+    let conn = MockBackend::connected();
+
+    let result = conn.add_data_definition_checked(
+        0,
+        "PLANE ALTITUDE ",
+        "feet",
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+        u32::MAX,
+        0.0,
+    );
+    let error = result.unwrap_err();
+    assert!(matches!(error, SimConnectError::Catalog(_)));
+    assert!(error.is_bad_argument());
+    assert!(error
+        .to_string()
+        .contains("did you mean \"PLANE ALTITUDE\""));
+    assert!(!conn.backend().was_called("SimConnect_AddToDataDefinition"));
+
+    conn.add_data_definition_checked(
+        0,
+        "PLANE ALTITUDE",
+        "feet",
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+        u32::MAX,
+        0.0,
+    )
+    .unwrap();
+    assert!(conn.backend().was_called("SimConnect_AddToDataDefinition"));
+}
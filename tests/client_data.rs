@@ -0,0 +1,167 @@
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    ClientData, ClientDataArea, ClientDataField, ClientDataType, DispatchResult, SimConnectError,
+    SimConnector, SIMCONNECT_CLIENTDATATYPE_FLOAT64, SIMCONNECT_CLIENTDATA_MAX_SIZE,
+    SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY, SIMCONNECT_RECV_CLIENT_DATA,
+};
+
+/// Written out by hand, as `#[derive(ClientData)]` would
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+struct Panel {
+    flags: u8,
+    brightness: f64,
+    label: [u8; 4],
+}
+
+unsafe impl ClientData for Panel {
+    fn fields() -> Vec<ClientDataField> {
+        vec![
+            ClientDataField {
+                offset: 0,
+                size: 1,
+                size_or_type: u8::SIZE_OR_TYPE,
+                epsilon: 0.0,
+            },
+            ClientDataField {
+                offset: 8,
+                size: 8,
+                size_or_type: f64::SIZE_OR_TYPE,
+                epsilon: 0.5,
+            },
+            ClientDataField {
+                offset: 16,
+                size: 4,
+                size_or_type: <[u8; 4]>::SIZE_OR_TYPE,
+                epsilon: 0.0,
+            },
+        ]
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Huge([u8; SIMCONNECT_CLIENTDATA_MAX_SIZE as usize + 1]);
+
+unsafe impl ClientData for Huge {
+    fn fields() -> Vec<ClientDataField> {
+        Vec::new()
+    }
+}
+
+fn queue_client_data(conn: &SimConnector<MockBackend>, define_id: u32, payload: &[u8]) {
+    let mut data = SIMCONNECT_RECV_CLIENT_DATA::zeroed();
+    data._base.dwDefineID = define_id;
+    conn.backend().queue_message_with_payload(data, payload);
+}
+
+#[test]
+fn creates_area_with_field_offsets() {
+    let conn = MockBackend::connected();
+    let area = ClientDataArea::<Panel>::create_read_only(&conn, "Test.Panel", 4, 9).unwrap();
+    assert_eq!(area.data_size(), 13);
+
+    let calls = conn.backend().calls_to("SimConnect_MapClientDataNameToID");
+    assert_eq!(calls[0].arg("client_data_name"), Some("\"Test.Panel\""));
+    assert_eq!(calls[0].arg("client_data_id"), Some("4"));
+
+    let fields = conn
+        .backend()
+        .calls_to("SimConnect_AddToClientDataDefinition");
+    let offsets = fields
+        .iter()
+        .map(|call| call.arg("offset").unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, ["0", "8", "16"]);
+    assert_eq!(fields[1].arg("define_id"), Some("9"));
+    assert_eq!(
+        fields[1].arg("size_or_type"),
+        Some(SIMCONNECT_CLIENTDATATYPE_FLOAT64.to_string().as_str())
+    );
+    assert_eq!(fields[1].arg("epsilon"), Some("0.5"));
+    assert_eq!(fields[2].arg("size_or_type"), Some("4"));
+
+    let create = conn.backend().calls_to("SimConnect_CreateClientData");
+    assert_eq!(create[0].arg("size"), Some("24"));
+    assert_eq!(
+        create[0].arg("flags"),
+        Some(
+            SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY
+                .to_string()
+                .as_str()
+        )
+    );
+}
+
+#[test]
+fn rejects_areas_over_the_maximum_size() {
+    let conn = MockBackend::connected();
+
+    assert!(matches!(
+        ClientDataArea::<Huge>::create(&conn, "Test.Huge", 1, 1),
+        Err(SimConnectError::ClientDataTooLarge { data_id: 1, size }) if size == 8193
+    ));
+    assert!(!conn
+        .backend()
+        .was_called("SimConnect_MapClientDataNameToID"));
+}
+
+#[test]
+fn writes_and_subscribes() {
+    let conn = MockBackend::connected();
+    let area = ClientDataArea::<Panel>::map(&conn, "Test.Panel", 4, 9).unwrap();
+    assert!(!conn.backend().was_called("SimConnect_CreateClientData"));
+
+    area.write(
+        &conn,
+        &Panel {
+            flags: 1,
+            brightness: 0.75,
+            label: *b"OVHD",
+        },
+    )
+    .unwrap();
+    area.subscribe(&conn, 2).unwrap();
+    area.unsubscribe(&conn, 2).unwrap();
+
+    let set = conn.backend().calls_to("SimConnect_SetClientData");
+    assert_eq!(set[0].arg("unit_size"), Some("13"));
+    assert_eq!(set[0].arg("define_id"), Some("9"));
+
+    let requests = conn.backend().calls_to("SimConnect_RequestClientData");
+    assert_eq!(requests[0].arg("period"), Some("3"));
+    assert_eq!(requests[0].arg("flags"), Some("1"));
+    assert_eq!(requests[1].arg("period"), Some("0"));
+}
+
+#[test]
+fn decodes_packed_fields() {
+    let mut conn = MockBackend::connected();
+    let area = ClientDataArea::<Panel>::map(&conn, "Test.Panel", 4, 9).unwrap();
+
+    let mut payload = vec![3];
+    payload.extend_from_slice(&0.25_f64.to_le_bytes());
+    payload.extend_from_slice(b"MAIN");
+    queue_client_data(&conn, 9, &payload);
+    queue_client_data(&conn, 10, &payload);
+    queue_client_data(&conn, 9, &payload[..5]);
+
+    let mut next = || match conn.get_next_message().unwrap() {
+        DispatchResult::ClientData(data) => area.decode(&data),
+        other => panic!("expected client data, got {:?}", other),
+    };
+
+    assert_eq!(
+        next().unwrap(),
+        Some(Panel {
+            flags: 3,
+            brightness: 0.25,
+            label: *b"MAIN",
+        })
+    );
+    assert_eq!(next().unwrap(), None);
+    assert!(matches!(
+        next(),
+        Err(SimConnectError::PayloadOutOfBounds { offset: 1, .. })
+    ));
+}
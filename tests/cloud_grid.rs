@@ -0,0 +1,136 @@
+use std::convert::TryInto;
+
+use simconnect::cloud_grid::{CloudBounds, CloudGrid};
+use simconnect::message::CloudState;
+use simconnect::mock::MockBackend;
+
+const WIDTH: usize = CloudState::WIDTH;
+
+/// A grid over 47..48°N 8..9°E whose densities are `row + column`, with `cells` of them
+fn grid(cells: usize) -> CloudGrid {
+    let state = CloudState {
+        request_id: 3,
+        data: (0..cells).map(|i| (i / WIDTH + i % WIDTH) as u8).collect(),
+    };
+    CloudGrid::new(
+        &state,
+        CloudBounds::new(47.0, 8.0, 0.0, 48.0, 9.0, 10_000.0),
+    )
+}
+
+#[test]
+fn maps_cells_to_coordinates() {
+    let grid = grid(WIDTH * WIDTH);
+    assert_eq!(grid.request_id, 3);
+    assert_eq!(grid.density(2, 5), Some(7));
+    assert_eq!(grid.density(WIDTH, 0), None);
+
+    assert_eq!(grid.cell_center(0, 0), Some((47.007813, 8.0078125)));
+    assert_eq!(
+        grid.cell_center(WIDTH - 1, 32),
+        Some((47.992188, 8.5078125))
+    );
+    assert_eq!(grid.cell_center(0, WIDTH), None);
+
+    assert_eq!(grid.cell_at(47.0, 8.0), Some((0, 0)));
+    assert_eq!(grid.cell_at(48.0, 9.0), Some((WIDTH - 1, WIDTH - 1)));
+    assert_eq!(grid.cell_at(47.5, 8.25), Some((32, 16)));
+    assert_eq!(grid.cell_at(46.9, 8.5), None);
+    assert_eq!(grid.cell_at(47.5, 9.1), None);
+
+    assert_eq!(grid.density_at(47.5, 8.25, 5000.0), Some(48));
+    assert_eq!(grid.density_at(47.5, 8.25, 12_000.0), None);
+    assert_eq!(grid.density_at(49.0, 8.25, 5000.0), None);
+}
+
+#[test]
+fn handles_the_antimeridian() {
+    let state = CloudState {
+        request_id: 1,
+        data: vec![0; WIDTH * WIDTH],
+    };
+    let grid = CloudGrid::new(&state, CloudBounds::new(-1.0, 179.0, 0.0, 1.0, -179.0, 0.0));
+
+    assert_eq!(grid.cell_at(0.0, 179.5), Some((32, 16)));
+    assert_eq!(grid.cell_at(0.0, -179.5), Some((32, 48)));
+    assert_eq!(grid.cell_at(0.0, 178.5), None);
+    assert_eq!(grid.cell_center(0, 48), Some((-0.984375, -179.48438)));
+}
+
+#[test]
+fn leaves_out_cells_the_message_did_not_cover() {
+    let grid = grid(WIDTH + 2);
+    assert_eq!(grid.density(1, 1), Some(2));
+    assert_eq!(grid.density(1, 2), None);
+    assert_eq!(grid.density_at(47.9, 8.5, 0.0), None);
+
+    let mut csv = Vec::new();
+    grid.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1 + WIDTH + 2);
+    assert_eq!(lines[0], "row,column,lat,lon,density");
+    assert_eq!(lines[1], "0,0,47.007813,8.0078125,0");
+    assert_eq!(lines[WIDTH + 2], "1,1,47.023438,8.0234375,2");
+}
+
+#[test]
+fn writes_images_north_up() {
+    let grid = grid(WIDTH * WIDTH);
+
+    let mut pgm = Vec::new();
+    grid.write_pgm(&mut pgm).unwrap();
+    let header = b"P5\n64 64\n255\n";
+    assert_eq!(&pgm[..header.len()], header);
+    let pixels = &pgm[header.len()..];
+    assert_eq!(pixels.len(), WIDTH * WIDTH);
+    // The first line is the northern row
+    assert_eq!(pixels[0], 63);
+    assert_eq!(pixels[WIDTH * WIDTH - 1], 63);
+    assert_eq!(pixels[(WIDTH - 1) * WIDTH], 0);
+
+    let mut png = Vec::new();
+    grid.write_png(&mut png).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[8..16], b"\0\0\0\x0dIHDR");
+    assert_eq!(&png[16..29], b"\0\0\0\x40\0\0\0\x40\x08\0\0\0\0");
+    // The CRC of an empty IEND is well known
+    assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+    // A single stored deflate block holds the filtered lines
+    let idat = &png[33..png.len() - 12];
+    let len = u32::from_be_bytes(idat[..4].try_into().unwrap()) as usize;
+    assert_eq!(&idat[4..8], b"IDAT");
+    let zlib = &idat[8..8 + len];
+    let raw_len = WIDTH * (WIDTH + 1);
+    assert_eq!(&zlib[..3], &[0x78, 0x01, 0x01]);
+    assert_eq!(u16::from_le_bytes([zlib[3], zlib[4]]) as usize, raw_len);
+    assert_eq!(u16::from_le_bytes([zlib[5], zlib[6]]), !(raw_len as u16));
+
+    let raw = &zlib[7..7 + raw_len];
+    for (line, expected) in raw.chunks(WIDTH + 1).zip(pixels.chunks(WIDTH)) {
+        assert_eq!(line[0], 0);
+        assert_eq!(&line[1..], expected);
+    }
+
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65_521;
+        (a, (b + a) % 65_521)
+    });
+    assert_eq!(&zlib[7 + raw_len..], &(b << 16 | a).to_be_bytes());
+}
+
+#[test]
+fn requests_its_bounds() {
+    let conn = MockBackend::connected();
+
+    let bounds = CloudBounds::new(47.0, 8.0, 0.0, 48.0, 9.0, 10_000.0);
+    bounds.request(&conn, 6, 0).unwrap();
+
+    let call = &conn
+        .backend()
+        .calls_to("SimConnect_WeatherRequestCloudState")[0];
+    assert_eq!(call.arg("request_id"), Some("6"));
+    assert_eq!(call.arg("max_lat"), Some("48.0"));
+    assert_eq!(call.arg("max_alt"), Some("10000.0"));
+}
@@ -0,0 +1,124 @@
+use simconnect::mock::MockBackend;
+use simconnect::{
+    ConfigError, ConnectOptions, Protocol, SimConnectConfig, SimConnector, TcpBackend,
+    CONFIG_FILE_NAME,
+};
+
+const CONFIG: &str = "
+; Local and remote simulators
+[SimConnect]
+Protocol=Pipe
+Port=Custom/SimConnect
+
+[SimConnect.1]
+Protocol=IPv4
+Address=192.168.1.20
+Port=500
+MaxReceiveSize=4096
+DisableNagle=1
+
+[simconnect.2]
+protocol=IPv4
+Address=192.168.1.21
+";
+
+#[test]
+fn parses_sections() {
+    let config: SimConnectConfig = CONFIG.parse().unwrap();
+    assert_eq!(config.sections().count(), 3);
+
+    let local = config.section(0).unwrap();
+    assert_eq!(local.protocol, Some(Protocol::Pipe));
+    assert_eq!(local.port.as_deref(), Some("Custom/SimConnect"));
+    assert_eq!(local.tcp_port(), None);
+
+    let remote = config.validate(1).unwrap();
+    assert_eq!(remote.tcp_port(), Some(500));
+    assert_eq!(remote.max_receive_size, Some(4096));
+    assert_eq!(remote.disable_nagle, Some(true));
+    assert_eq!(remote.socket_address().as_deref(), Some("192.168.1.20:500"));
+}
+
+#[test]
+fn reports_invalid_config() {
+    let config: SimConnectConfig = CONFIG.parse().unwrap();
+    assert!(matches!(
+        config.validate(2),
+        Err(ConfigError::MissingKey {
+            index: 2,
+            key: "Port"
+        })
+    ));
+    assert!(matches!(
+        config.validate(3),
+        Err(ConfigError::MissingSection(3))
+    ));
+
+    assert!(matches!(
+        "[SimConnect]\nDisableNagle=yes".parse::<SimConnectConfig>(),
+        Err(ConfigError::InvalidValue {
+            key: "DisableNagle",
+            ..
+        })
+    ));
+    assert!(matches!(
+        "Protocol=IPv4".parse::<SimConnectConfig>(),
+        Err(ConfigError::Syntax { line: 1, .. })
+    ));
+}
+
+#[test]
+fn connects_without_reading_the_file() {
+    let mut conn = SimConnector::with_backend(MockBackend::new());
+    conn.connect_with_options(
+        "config test",
+        ConnectOptions {
+            config_index: 7,
+            ..ConnectOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        conn.backend().calls_to("SimConnect_Open")[0].arg("config_index"),
+        Some("7")
+    );
+}
+
+#[test]
+fn validates_a_config_file() {
+    let dir = std::env::temp_dir().join(format!("simconnect-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(CONFIG_FILE_NAME);
+
+    assert!(matches!(
+        SimConnectConfig::from_file(&path),
+        Err(ConfigError::Io { .. })
+    ));
+
+    std::fs::write(&path, CONFIG).unwrap();
+    let config = SimConnectConfig::from_file(&path).unwrap();
+    assert!(config.validate(1).is_ok());
+    assert!(matches!(
+        config.validate(2),
+        Err(ConfigError::MissingKey { index: 2, .. })
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn tcp_backend_from_config() {
+    let config: SimConnectConfig = CONFIG.parse().unwrap();
+    assert_eq!(
+        TcpBackend::from_config(&config, 1).unwrap().address(),
+        "192.168.1.20:500"
+    );
+    assert!(matches!(
+        TcpBackend::from_config(&config, 0),
+        Err(ConfigError::InvalidValue {
+            key: "Protocol",
+            ..
+        })
+    ));
+}
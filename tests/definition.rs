@@ -0,0 +1,258 @@
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    DataDefinition, DispatchResult, LatLonAlt, SimConnectError, SimConnector, Value,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV, SIMCONNECT_DATA_REQUEST_FLAG_CHANGED,
+    SIMCONNECT_DATA_REQUEST_FLAG_TAGGED, SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+fn queue_data(conn: &SimConnector<MockBackend>, flags: u32, define_count: u32, payload: &[u8]) {
+    let mut data = SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed();
+    data.dwFlags = flags;
+    data.dwDefineCount = define_count;
+    conn.backend().queue_message_with_payload(data, payload);
+}
+
+fn string32(s: &str) -> Vec<u8> {
+    let mut bytes = s.as_bytes().to_vec();
+    bytes.resize(32, 0);
+    bytes
+}
+
+fn mixed_definition(conn: &SimConnector<MockBackend>) -> DataDefinition {
+    let mut definition = DataDefinition::new(0);
+    definition
+        .add(
+            conn,
+            "PLANE ALTITUDE",
+            "feet",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+            10,
+            1.0,
+        )
+        .unwrap();
+    definition
+        .add(
+            conn,
+            "ATC ID",
+            "",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32,
+            20,
+            0.0,
+        )
+        .unwrap();
+    definition
+        .add(
+            conn,
+            "TRANSPONDER CODE:1",
+            "bco16",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32,
+            30,
+            0.0,
+        )
+        .unwrap();
+    definition
+}
+
+#[test]
+fn records_added_datums() {
+    let conn = MockBackend::connected();
+    let definition = mixed_definition(&conn);
+
+    assert_eq!(definition.datums().len(), 3);
+    assert_eq!(
+        definition.datum(20).unwrap().datatype,
+        SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32
+    );
+    let calls = conn.backend().calls_to("SimConnect_AddToDataDefinition");
+    assert_eq!(calls[1].arg("datum_id"), Some("20"));
+}
+
+#[test]
+fn decodes_tagged_values_of_mixed_types() {
+    let mut conn = MockBackend::connected();
+    let definition = mixed_definition(&conn);
+
+    // Only the string and the transponder changed, in an arbitrary order
+    let mut payload = 30_u32.to_le_bytes().to_vec();
+    payload.extend_from_slice(&0x1200_i32.to_le_bytes());
+    payload.extend_from_slice(&20_u32.to_le_bytes());
+    payload.extend_from_slice(&string32("D-EABC"));
+    queue_data(
+        &conn,
+        SIMCONNECT_DATA_REQUEST_FLAG_CHANGED | SIMCONNECT_DATA_REQUEST_FLAG_TAGGED,
+        2,
+        &payload,
+    );
+
+    let values = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => definition
+            .values(&data)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        other => panic!("expected object data, got {:?}", other),
+    };
+
+    assert_eq!(
+        values,
+        vec![
+            (30, Value::Int32(0x1200)),
+            (20, Value::String("D-EABC".to_string())),
+        ]
+    );
+    assert_eq!(values[1].1.as_str(), Some("D-EABC"));
+    assert_eq!(values[0].1.as_f64(), Some(4608.0));
+}
+
+#[test]
+fn decodes_untagged_values_in_order() {
+    let mut conn = MockBackend::connected();
+    let definition = mixed_definition(&conn);
+
+    let mut payload = 2500.0_f64.to_le_bytes().to_vec();
+    payload.extend_from_slice(&string32("N12345"));
+    payload.extend_from_slice(&0x7000_i32.to_le_bytes());
+    queue_data(&conn, 0, 3, &payload);
+
+    let values = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => definition
+            .values(&data)
+            .map(|value| value.unwrap().1)
+            .collect::<Vec<_>>(),
+        other => panic!("expected object data, got {:?}", other),
+    };
+
+    assert_eq!(
+        values,
+        vec![
+            Value::Float64(2500.0),
+            Value::String("N12345".to_string()),
+            Value::Int32(0x7000),
+        ]
+    );
+}
+
+#[test]
+fn reports_unknown_datums_and_stops() {
+    let mut conn = MockBackend::connected();
+    let definition = mixed_definition(&conn);
+
+    let mut payload = 99_u32.to_le_bytes().to_vec();
+    payload.extend_from_slice(&1.0_f64.to_le_bytes());
+    queue_data(&conn, SIMCONNECT_DATA_REQUEST_FLAG_TAGGED, 2, &payload);
+
+    match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => {
+            let mut values = definition.values(&data);
+            assert!(matches!(
+                values.next(),
+                Some(Err(SimConnectError::UnknownDatum {
+                    define_id: 0,
+                    datum_id: 99
+                }))
+            ));
+            assert!(values.next().is_none());
+        }
+        other => panic!("expected object data, got {:?}", other),
+    }
+}
+
+#[test]
+fn reads_structured_values() {
+    let mut bytes = Vec::new();
+    for value in [47.5_f64, 8.5, 1400.0] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    let mut reader = simconnect::PayloadReader::new(&bytes);
+
+    assert_eq!(
+        Value::read(
+            &mut reader,
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_LATLONALT
+        )
+        .unwrap(),
+        Value::LatLonAlt(LatLonAlt {
+            latitude: 47.5,
+            longitude: 8.5,
+            altitude: 1400.0,
+        })
+    );
+    assert!(matches!(
+        Value::read(&mut reader, 1000),
+        Err(SimConnectError::InvalidDatatype(1000))
+    ));
+}
+
+#[test]
+fn decodes_variable_length_strings() {
+    let mut conn = MockBackend::connected();
+    let mut definition = DataDefinition::new(1);
+    definition
+        .push(1, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV)
+        .push(2, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32)
+        .push(3, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV);
+
+    let mut payload = b"Airbus A320 Neo Asobo\0".to_vec();
+    payload.extend_from_slice(&5_i32.to_le_bytes());
+    payload.extend_from_slice(b"\0");
+    queue_data(&conn, 0, 3, &payload);
+
+    let values = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => definition
+            .values(&data)
+            .map(|value| value.unwrap().1)
+            .collect::<Vec<_>>(),
+        other => panic!("expected object data, got {:?}", other),
+    };
+
+    assert_eq!(
+        values,
+        vec![
+            Value::String("Airbus A320 Neo Asobo".to_string()),
+            Value::Int32(5),
+            Value::String(String::new()),
+        ]
+    );
+}
+
+#[test]
+fn encodes_values_for_set_data() {
+    let mut definition = DataDefinition::new(2);
+    definition
+        .push(1, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV)
+        .push(2, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8)
+        .push(3, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64);
+
+    let bytes = definition
+        .encode(&[
+            Value::String("N172SP".to_string()),
+            Value::String("too long for it".to_string()),
+            Value::Float64(1.5),
+        ])
+        .unwrap();
+
+    let mut expected = b"N172SP\0too lon\0".to_vec();
+    expected.extend_from_slice(&1.5_f64.to_le_bytes());
+    assert_eq!(bytes, expected);
+
+    let tagged = definition
+        .encode_tagged(&[(1, Value::String("abc".to_string()))])
+        .unwrap();
+    assert_eq!(tagged, b"\x01\0\0\0abc\0");
+
+    assert!(matches!(
+        definition.encode(&[Value::Float64(1.5)]),
+        Err(SimConnectError::ValueCountMismatch {
+            expected: 3,
+            found: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        definition.encode_tagged(&[(3, Value::Int32(1))]),
+        Err(SimConnectError::InvalidDatatype(_))
+    ));
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "dynamic")]
+
+use std::path::Path;
+
+use simconnect::{SimConnectError, SimConnector, SimulatorDll};
+
+#[test]
+fn missing_library_is_reported() {
+    let mut conn =
+        SimConnector::with_dll(SimulatorDll::Path("does/not/exist/SimConnect.dll".into()));
+
+    match conn.connect("dynamic") {
+        Err(SimConnectError::LibraryNotFound { path, .. }) => {
+            assert_eq!(path, Path::new("does/not/exist/SimConnect.dll"))
+        }
+        other => panic!("expected LibraryNotFound, got {:?}", other),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn library_without_simconnect_is_reported() {
+    let mut conn = SimConnector::with_dll(SimulatorDll::Path("libc.so.6".into()));
+
+    match conn.connect("dynamic") {
+        Err(SimConnectError::SymbolMissing { symbol, .. }) => {
+            assert_eq!(symbol, "SimConnect_Open")
+        }
+        other => panic!("expected SymbolMissing, got {:?}", other),
+    }
+}
+
+#[test]
+fn simulator_dlls_have_distinct_names() {
+    let paths = [SimulatorDll::Msfs, SimulatorDll::P3d, SimulatorDll::Fsx];
+
+    assert_eq!(paths[0].path(), Path::new("SimConnect.dll"));
+    assert_ne!(paths[0].path(), paths[1].path());
+    assert_ne!(paths[1].path(), paths[2].path());
+}
@@ -0,0 +1,107 @@
+use simconnect::mock::MockBackend;
+use simconnect::{
+    key_event, KeyEvent, KeyParam, KeyValue, SimConnectError, SIMCONNECT_OBJECT_ID_USER,
+};
+
+#[test]
+fn encodes_parameters() {
+    assert_eq!(KeyEvent::PARKING_BRAKES.encode(()).unwrap(), 0);
+    assert_eq!(
+        KeyEvent::AXIS_ELEVATOR_SET.encode(-16383).unwrap(),
+        -16383_i32 as u32
+    );
+    assert_eq!(
+        KeyEvent::AXIS_AILERONS_SET.encode(-1.0).unwrap(),
+        -16383_i32 as u32
+    );
+    assert_eq!(KeyEvent::COM_STBY_RADIO_SET.encode(121.5).unwrap(), 0x2150);
+    assert_eq!(KeyEvent::COM_RADIO_SET.encode(118.025).unwrap(), 0x1802);
+    assert_eq!(KeyEvent::NAV1_RADIO_SET.encode(110.3).unwrap(), 0x1030);
+    assert_eq!(KeyEvent::XPNDR_SET.encode(7000).unwrap(), 0x7000);
+    assert_eq!(KeyEvent::XPNDR_SET.encode(1234).unwrap(), 0x1234);
+    assert_eq!(KeyEvent::GEAR_SET.encode(true).unwrap(), 1);
+    assert_eq!(KeyEvent::HEADING_BUG_SET.encode(270).unwrap(), 270);
+}
+
+#[test]
+fn rejects_invalid_parameters() {
+    for (event, value) in [
+        (KeyEvent::AXIS_ELEVATOR_SET, KeyValue::Int(20000)),
+        (KeyEvent::COM_RADIO_SET, KeyValue::Float(110.3)),
+        (KeyEvent::COM_RADIO_SET, KeyValue::Int(12150)),
+        (KeyEvent::XPNDR_SET, KeyValue::Int(7800)),
+        (KeyEvent::PARKING_BRAKES, KeyValue::Int(1)),
+        (KeyEvent::THROTTLE_SET, KeyValue::Int(-1)),
+    ] {
+        match event.encode(value) {
+            Err(SimConnectError::InvalidEventData {
+                event: name,
+                value: rejected,
+                expected,
+            }) => {
+                assert_eq!(name, event.name);
+                assert_eq!(rejected, value);
+                assert_eq!(expected, event.param);
+            }
+            other => panic!("expected {:?} to be rejected, got {:?}", value, other),
+        }
+    }
+}
+
+#[test]
+fn looks_up_events() {
+    let event = key_event::lookup("axis_throttle_set").unwrap();
+    assert_eq!(event, KeyEvent::AXIS_THROTTLE_SET);
+    assert_eq!(event.param, KeyParam::Axis);
+    assert!(key_event::lookup("GEAR_SIDEWAYS").is_none());
+    assert!(key_event::key_events().contains(&KeyEvent::XPNDR_SET));
+}
+
+#[test]
+fn maps_each_event_once() {
+    let mut conn = MockBackend::connected();
+
+    conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::PARKING_BRAKES, ())
+        .unwrap();
+    conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::AXIS_RUDDER_SET, -500)
+        .unwrap();
+    conn.transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::PARKING_BRAKES, ())
+        .unwrap();
+
+    let mapped = conn
+        .backend()
+        .calls_to("SimConnect_MapClientEventToSimEvent");
+    assert_eq!(mapped.len(), 2);
+    assert_eq!(mapped[0].arg("event_name"), Some("\"PARKING_BRAKES\""));
+
+    let brakes = conn.key_event_id(KeyEvent::PARKING_BRAKES).unwrap();
+    let rudder = conn.key_event_id(KeyEvent::AXIS_RUDDER_SET).unwrap();
+    assert_eq!(brakes, key_event::KEY_EVENT_ID_BASE);
+    assert_eq!(
+        conn.key_event_for_id(rudder),
+        Some(KeyEvent::AXIS_RUDDER_SET)
+    );
+    assert_eq!(conn.key_event_for_id(5), None);
+
+    let transmitted = conn.backend().calls_to("SimConnect_TransmitClientEvent");
+    assert_eq!(transmitted.len(), 3);
+    assert_eq!(
+        transmitted[1].arg("data"),
+        Some((-500_i32 as u32).to_string().as_str())
+    );
+
+    // Invalid values are rejected before anything is sent
+    assert!(conn
+        .transmit_key_event(SIMCONNECT_OBJECT_ID_USER, KeyEvent::XPNDR_SET, 8888)
+        .is_err());
+    assert_eq!(
+        conn.backend()
+            .calls_to("SimConnect_MapClientEventToSimEvent")
+            .len(),
+        2
+    );
+
+    // A new connection maps again
+    conn.connect("key event test").unwrap();
+    assert_eq!(conn.key_event_for_id(rudder), None);
+}
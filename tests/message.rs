@@ -0,0 +1,112 @@
+use std::thread;
+
+use simconnect::message::{Event, List};
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    Message, SIMCONNECT_DATA_FACILITY_AIRPORT, SIMCONNECT_RECV_AIRPORT_LIST, SIMCONNECT_RECV_EVENT,
+    SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+fn assert_send_static<T: Send + 'static>() {}
+
+#[test]
+fn message_is_send_and_static() {
+    assert_send_static::<Message>();
+}
+
+#[test]
+fn copies_variable_sized_data() {
+    let mut conn = MockBackend::connected();
+
+    // A SIMOBJECT_DATA carrying two f64s, starting at dwData
+    let mut data = SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed();
+    data.dwRequestID = 5;
+    let mut payload = 1.5_f64.to_ne_bytes().to_vec();
+    payload.extend_from_slice(&(-2.0_f64).to_ne_bytes());
+    conn.backend().queue_message_with_payload(data, &payload);
+
+    let message = conn.get_next_message().unwrap().to_owned();
+    // The buffer is reused for the next message
+    assert!(matches!(
+        conn.get_next_message().unwrap().to_owned(),
+        Message::Null
+    ));
+
+    let data = match message {
+        Message::SimObjectData(data) => data,
+        other => panic!("expected object data, got {:?}", other),
+    };
+    assert_eq!(data.request_id, 5);
+    assert_eq!(data.data.len(), 16);
+    assert_eq!(&data.data[..8], &1.5_f64.to_ne_bytes());
+}
+
+#[test]
+fn copies_list_items() {
+    let mut conn = MockBackend::connected();
+
+    let mut airport = SIMCONNECT_DATA_FACILITY_AIRPORT {
+        Ident: [0; 6],
+        Region: [0; 3],
+        Latitude: 47.5,
+        Longitude: 8.5,
+        Altitude: 400.0,
+    };
+    for (c, b) in airport.Ident.iter_mut().zip(b"LSZH") {
+        *c = *b as _;
+    }
+    let mut list = SIMCONNECT_RECV_AIRPORT_LIST::zeroed();
+    list._base.dwRequestID = 9;
+    list._base.dwArraySize = 1;
+    list._base.dwOutOf = 1;
+    list.rgData = [airport];
+    conn.backend().queue_message(list);
+
+    match conn.get_next_message().unwrap().to_owned() {
+        Message::AirportList(List {
+            request_id: 9,
+            items,
+            ..
+        }) => {
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].ident, "LSZH");
+            assert_eq!(items[0].latitude, 47.5);
+        }
+        other => panic!("expected an airport list, got {:?}", other),
+    }
+}
+
+#[test]
+fn owned_messages_cross_threads() {
+    let mut conn = MockBackend::connected();
+    let mut event = SIMCONNECT_RECV_EVENT::zeroed();
+    event.uEventID = 3;
+    event.dwData = 42;
+    conn.backend().queue_message(event);
+
+    let message: Message = conn.get_next_message().unwrap().into();
+    let received = thread::spawn(move || message).join().unwrap();
+
+    assert_eq!(
+        received,
+        Message::Event(Event {
+            group_id: 0,
+            event_id: 3,
+            data: 42
+        })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_with_serde() {
+    let message = Message::Event(Event {
+        group_id: 1,
+        event_id: 2,
+        data: 3,
+    });
+
+    let json = serde_json::to_string(&message).unwrap();
+    assert_eq!(json, r#"{"Event":{"group_id":1,"event_id":2,"data":3}}"#);
+    assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+}
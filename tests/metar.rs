@@ -0,0 +1,240 @@
+use simconnect::metar::{
+    Cloud, CloudCover, CloudLayer, CloudTop, CloudType, Convective, Distance, Icing, LayerHeight,
+    Metar, MetarError, Modifier, ObservationTime, Precipitation, PrecipitationRate, Pressure,
+    SpeedUnit, Temperature, Turbulence, Visibility, VisibilityLayer, Wind, WindLayer, WindShear,
+};
+use simconnect::mock::MockBackend;
+use simconnect::{SimConnectError, MAX_METAR_LENGTH};
+
+#[test]
+fn parses_standard_reports() {
+    let report = "METAR KJFK 121251Z AUTO 31015G25KT 280V340 1 1/2SM R04R/2000FT -SHRA BR \
+                  BKN008 OVC015CB M02/M05 A2992 RMK AO2 SLP132";
+    let metar: Metar = report.parse().unwrap();
+
+    assert_eq!(metar.station, "KJFK");
+    assert_eq!(
+        metar.time,
+        Some(ObservationTime {
+            day: 12,
+            hour: 12,
+            minute: 51
+        })
+    );
+    assert_eq!(metar.modifier, Some(Modifier::Auto));
+    assert_eq!(
+        metar.winds,
+        [Wind {
+            gust: Some(25),
+            variation: Some((280, 340)),
+            ..Wind::new(310, 15)
+        }]
+    );
+    assert_eq!(
+        metar.visibility[0].distance,
+        Distance::StatuteMiles {
+            miles: 1.5,
+            less_than: false
+        }
+    );
+    assert_eq!(metar.runway_visual_range, ["R04R/2000FT"]);
+    assert_eq!(metar.weather, ["-SHRA", "BR"]);
+    assert_eq!(
+        metar.clouds,
+        [
+            Cloud::new(CloudCover::Broken, 800),
+            Cloud {
+                convective: Some(Convective::Cumulonimbus),
+                ..Cloud::new(CloudCover::Overcast, 1500)
+            }
+        ]
+    );
+    assert_eq!(
+        metar.surface_temperature(),
+        Some(&Temperature {
+            temperature: -2,
+            dew_point: Some(-5),
+            altitude: None
+        })
+    );
+    assert_eq!(metar.pressure, Some(Pressure::InchesOfMercury(29.92)));
+    assert_eq!(metar.remarks.as_deref(), Some("AO2 SLP132"));
+
+    // The leading METAR is dropped
+    assert_eq!(metar.to_string(), report["METAR ".len()..]);
+}
+
+#[test]
+fn parses_fsx_extensions() {
+    let report = "LSZH 121200Z 27010KT&D980NG 29025KT&A1500LM 9999&B-443&D3500 \
+                  SCT035&CU008FNVN000N 15/10 M05/M15&A3000 Q1013";
+    let metar: Metar = report.parse().unwrap();
+
+    assert_eq!(
+        metar.winds[0].layer,
+        Some(WindLayer {
+            height: LayerHeight::Depth(980),
+            turbulence: Turbulence::None,
+            shear: WindShear::Gradual
+        })
+    );
+    assert_eq!(
+        metar.winds[1].layer,
+        Some(WindLayer {
+            height: LayerHeight::Altitude(1500),
+            turbulence: Turbulence::Light,
+            shear: WindShear::Moderate
+        })
+    );
+    assert_eq!(metar.surface_wind(), Some(&metar.winds[0]));
+    assert_eq!(
+        metar.visibility,
+        [Visibility {
+            distance: Distance::Meters(9999),
+            layer: Some(VisibilityLayer {
+                base: -443,
+                depth: 3500
+            })
+        }]
+    );
+    assert_eq!(
+        metar.clouds[0].layer,
+        Some(CloudLayer {
+            kind: CloudType::Cumulus,
+            depth: 800,
+            top: CloudTop::Flat,
+            turbulence: Turbulence::None,
+            precipitation_rate: PrecipitationRate::VeryLight,
+            precipitation: Precipitation::None,
+            precipitation_base: 0,
+            icing: Icing::None
+        })
+    );
+    assert_eq!(metar.temperatures[1].altitude, Some(3000));
+    assert_eq!(metar.pressure, Some(Pressure::Hectopascals(1013)));
+
+    assert_eq!(metar.encode().unwrap(), report);
+}
+
+#[test]
+fn writes_back_what_it_parsed() {
+    for report in [
+        "EGLL 121250Z VRB03KT CAVOK 22/12 Q1021 NOSIG",
+        "UUEE 121200Z 18005MPS M1/4SM +SN VV/// M10/M12 Q0998 TEMPO 0400",
+        "KDEN 121253Z 00000KT 10SM SKC 05/M03 A3012",
+        "GLOB 27020KT&A3000SI 3/4SM FEW///TCU 20/ Q1013",
+    ] {
+        let metar = Metar::parse(report).unwrap();
+        assert_eq!(metar.to_string(), report);
+        assert_eq!(Metar::parse(&metar.to_string()).unwrap(), metar);
+    }
+}
+
+#[test]
+fn keeps_supplementary_groups() {
+    let report = "EDDF 121250Z 24012KT 9999 BKN025 12/08 Q1009 RERA WS R25C R25L/290095 TEMPO 4000";
+    let metar = Metar::parse(report).unwrap();
+    assert_eq!(metar.pressure, Some(Pressure::Hectopascals(1009)));
+    assert_eq!(metar.supplementary, ["RERA", "WS", "R25C", "R25L/290095"]);
+    assert_eq!(metar.trend.as_deref(), Some("TEMPO 4000"));
+    assert_eq!(metar.to_string(), report);
+
+    let metar = Metar::parse("EGLL 9999 12/08 Q1009 BANANA RMK AO2").unwrap();
+    assert_eq!(metar.supplementary, ["BANANA"]);
+    assert_eq!(metar.trend, None);
+    assert_eq!(metar.remarks.as_deref(), Some("AO2"));
+}
+
+#[test]
+fn builds_reports() {
+    let metar = Metar {
+        winds: vec![Wind {
+            unit: SpeedUnit::Knots,
+            layer: Some(WindLayer {
+                height: LayerHeight::Depth(500),
+                turbulence: Turbulence::Occasional,
+                shear: WindShear::Steep,
+            }),
+            ..Wind::new(90, 8)
+        }],
+        visibility: vec![Visibility::meters(6000)],
+        clouds: vec![Cloud::new(CloudCover::Scattered, 4500)],
+        temperatures: vec![Temperature {
+            temperature: 7,
+            dew_point: None,
+            altitude: None,
+        }],
+        pressure: Some(Pressure::Hectopascals(995)),
+        ..Metar::new("ZZZZ")
+    };
+
+    assert_eq!(
+        metar.encode().unwrap(),
+        "ZZZZ 09008KT&D500OS 6000 SCT045 07/ Q0995"
+    );
+    assert!((Pressure::InchesOfMercury(29.92).hectopascals() - 1013.2).abs() < 0.1);
+}
+
+#[test]
+fn reports_invalid_reports() {
+    assert_eq!(Metar::parse("  "), Err(MetarError::Empty));
+    assert_eq!(
+        Metar::parse("LONDON 121250Z"),
+        Err(MetarError::InvalidStation("LONDON".to_string()))
+    );
+    assert_eq!(
+        Metar::parse("EGLL 24012KT&X12 9999"),
+        Err(MetarError::InvalidExtension("24012KT&X12".to_string()))
+    );
+    assert_eq!(
+        Metar::parse("EGLL 9999 SCT035&CU008"),
+        Err(MetarError::InvalidExtension("SCT035&CU008".to_string()))
+    );
+
+    // Groups are ASCII, other characters are rejected rather than sliced through
+    assert_eq!(
+        Metar::parse("EGLL aaé34KT"),
+        Err(MetarError::UnexpectedToken("aaé34KT".to_string()))
+    );
+    assert_eq!(
+        Metar::parse("EGLL 24012KT&éNG"),
+        Err(MetarError::InvalidExtension("24012KT&éNG".to_string()))
+    );
+    assert_eq!(
+        Metar::parse("EGLL 24012KT&D98é"),
+        Err(MetarError::InvalidExtension("24012KT&D98é".to_string()))
+    );
+    assert_eq!(
+        Metar::parse("EGLL 9999 RMK Zürich"),
+        Err(MetarError::UnexpectedToken("Zürich".to_string()))
+    );
+
+    let metar = Metar {
+        remarks: Some("X".repeat(MAX_METAR_LENGTH as usize)),
+        ..Metar::new("EGLL")
+    };
+    assert!(matches!(metar.encode(), Err(MetarError::TooLong { .. })));
+}
+
+#[test]
+fn sets_observations() {
+    let conn = MockBackend::connected();
+
+    let metar = Metar::parse("EGLL 24012KT 9999 BKN025 12/08 Q1009").unwrap();
+    conn.weather_set_metar(60, &metar).unwrap();
+    assert_eq!(
+        conn.backend().calls_to("SimConnect_WeatherSetObservation")[0].arg("metar"),
+        Some("\"EGLL 24012KT 9999 BKN025 12/08 Q1009\"")
+    );
+
+    let too_long = Metar {
+        remarks: Some("X".repeat(MAX_METAR_LENGTH as usize)),
+        ..metar
+    };
+    let error = conn.weather_set_metar(60, &too_long).unwrap_err();
+    assert!(matches!(
+        error,
+        SimConnectError::Metar(MetarError::TooLong { .. })
+    ));
+    assert!(error.is_bad_argument());
+}
@@ -0,0 +1,224 @@
+use simconnect::message::Message;
+use simconnect::mobiflight::{MobiFlightClient, MobiFlightEvent, MESSAGE_SIZE, MOBIFLIGHT_ID_BASE};
+use simconnect::mock::{MockBackend, MockCall, RecvMessage, WithPayload};
+use simconnect::{
+    SimConnectError, SimConnector, SIMCONNECT_RECV_CLIENT_DATA, SIMCONNECT_RECV_QUIT,
+};
+
+/// A `SIMCONNECT_RECV_CLIENT_DATA` carrying `payload` for `request_id` and `define_id`
+fn client_data(request_id: u32, define_id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = SIMCONNECT_RECV_CLIENT_DATA::zeroed();
+    data._base.dwRequestID = request_id;
+    data._base.dwDefineID = define_id;
+    data.with_payload(payload)
+}
+
+/// A response of the module for the response definition `define_id`
+fn response(define_id: u32, text: &str) -> Vec<u8> {
+    let mut payload = vec![0; MESSAGE_SIZE];
+    payload[..text.len()].copy_from_slice(text.as_bytes());
+    client_data(0, define_id, &payload)
+}
+
+/// The text written by a `SimConnect_SetClientData` call
+fn text(call: &MockCall) -> String {
+    let end = call.data.iter().position(|&b| b == 0).unwrap();
+    String::from_utf8(call.data[..end].to_vec()).unwrap()
+}
+
+/// The commands written to the command area with ID `data_id`
+fn commands(conn: &SimConnector<MockBackend>, data_id: u32) -> Vec<String> {
+    conn.backend()
+        .calls_to("SimConnect_SetClientData")
+        .iter()
+        .filter(|call| call.arg("client_data_id") == Some(data_id.to_string().as_str()))
+        .map(text)
+        .collect()
+}
+
+/// Plays a module that confirms the registration of `Test`
+fn module(conn: &SimConnector<MockBackend>) {
+    conn.backend()
+        .respond_to("SimConnect_SetClientData", |call| {
+            if text(call) == "MF.Clients.Add.Test" {
+                vec![response(
+                    MOBIFLIGHT_ID_BASE + 1,
+                    "MF.Clients.Add.Test.Finished",
+                )]
+            } else {
+                Vec::new()
+            }
+        });
+}
+
+fn registered(conn: &mut SimConnector<MockBackend>) -> MobiFlightClient {
+    module(conn);
+    let mut client = MobiFlightClient::connect(conn, "Test").unwrap();
+    assert_eq!(
+        client.poll(conn).unwrap(),
+        Some(MobiFlightEvent::Registered)
+    );
+    client
+}
+
+#[test]
+fn registers_on_the_default_channel() {
+    let mut conn = MockBackend::connected();
+    let client = registered(&mut conn);
+    assert!(client.is_registered());
+
+    assert_eq!(
+        commands(&conn, MOBIFLIGHT_ID_BASE),
+        ["MF.DummyCmd", "MF.Clients.Add.Test"]
+    );
+
+    let names = conn
+        .backend()
+        .calls_to("SimConnect_MapClientDataNameToID")
+        .iter()
+        .map(|call| call.arg("client_data_name").unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "\"MobiFlight.Command\"",
+            "\"MobiFlight.Response\"",
+            "\"Test.LVars\"",
+            "\"Test.Command\"",
+            "\"Test.Response\"",
+        ]
+    );
+    assert!(!conn.backend().was_called("SimConnect_CreateClientData"));
+}
+
+#[test]
+fn ignores_other_clients() {
+    let mut conn = MockBackend::connected();
+    let mut client = MobiFlightClient::connect(&conn, "Test").unwrap();
+    conn.backend().queue_raw_message(response(
+        MOBIFLIGHT_ID_BASE + 1,
+        "MF.Clients.Add.Other.Finished",
+    ));
+
+    assert_eq!(client.poll(&mut conn).unwrap(), None);
+    assert!(!client.is_registered());
+    assert!(client.take_messages().is_empty());
+}
+
+#[test]
+fn keeps_other_messages() {
+    let mut conn = MockBackend::connected();
+    let mut client = registered(&mut conn);
+    conn.backend().queue_message(SIMCONNECT_RECV_QUIT::zeroed());
+
+    assert_eq!(client.poll(&mut conn).unwrap(), None);
+    assert_eq!(client.poll(&mut conn).unwrap(), None);
+    assert!(matches!(client.take_messages()[..], [Message::Quit]));
+}
+
+#[test]
+fn idles_while_the_library_reports_an_empty_queue() {
+    let mut conn = MockBackend::connected();
+    // SimConnect_GetNextDispatch fails with E_FAIL when there is nothing to dispatch
+    conn.backend().fail_when_empty(0x8000_4005_u32 as i32);
+    let mut client = registered(&mut conn);
+
+    for _ in 0..3 {
+        assert_eq!(client.poll(&mut conn).unwrap(), None);
+    }
+    client.ping(&conn).unwrap();
+    conn.backend()
+        .queue_raw_message(response(MOBIFLIGHT_ID_BASE + 3, "MF.Pong"));
+    assert_eq!(client.poll(&mut conn).unwrap(), Some(MobiFlightEvent::Pong));
+    assert_eq!(client.poll(&mut conn).unwrap(), None);
+}
+
+#[test]
+fn queues_commands_until_registered() {
+    let mut conn = MockBackend::connected();
+    module(&conn);
+    let mut client = MobiFlightClient::connect(&conn, "Test").unwrap();
+    assert_eq!(client.subscribe(&conn, "(L:A)").unwrap(), 0);
+    client.trigger_h_event(&conn, "KNOB").unwrap();
+    assert!(commands(&conn, MOBIFLIGHT_ID_BASE + 3).is_empty());
+
+    client.poll(&mut conn).unwrap();
+    assert_eq!(
+        commands(&conn, MOBIFLIGHT_ID_BASE + 3),
+        ["MF.SimVars.Add.(L:A)", "MF.SimVars.Set.(>H:KNOB)"]
+    );
+
+    let request = &conn.backend().calls_to("SimConnect_RequestClientData")[2];
+    let id = (MOBIFLIGHT_ID_BASE + 0x100).to_string();
+    assert_eq!(
+        request.arg("client_data_id"),
+        Some(format!("{}", MOBIFLIGHT_ID_BASE + 2).as_str())
+    );
+    assert_eq!(request.arg("request_id"), Some(id.as_str()));
+}
+
+#[test]
+fn delivers_values() {
+    let mut conn = MockBackend::connected();
+    let mut client = registered(&mut conn);
+    client.subscribe(&conn, "(L:A)").unwrap();
+    client.subscribe(&conn, "(L:B)").unwrap();
+
+    let offsets = conn
+        .backend()
+        .calls_to("SimConnect_AddToClientDataDefinition")
+        .iter()
+        .filter(|call| {
+            call.arg("define_id").unwrap().parse::<u32>().unwrap() >= MOBIFLIGHT_ID_BASE + 0x100
+        })
+        .map(|call| call.arg("offset").unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert!(offsets.ends_with(&["0".to_string(), "4".to_string()]));
+
+    let id = MOBIFLIGHT_ID_BASE + 0x101;
+    conn.backend()
+        .queue_raw_message(client_data(id, id, &2.5f32.to_le_bytes()));
+    assert_eq!(
+        client.poll(&mut conn).unwrap(),
+        Some(MobiFlightEvent::Value {
+            index: 1,
+            expression: "(L:B)".to_string(),
+            value: 2.5,
+        })
+    );
+    assert_eq!(client.value("(L:B)"), Some(2.5));
+    assert_eq!(client.value("(L:A)"), None);
+
+    client.clear(&conn).unwrap();
+    assert!(client.expressions().is_empty());
+    assert_eq!(
+        conn.backend()
+            .calls_to("SimConnect_ClearClientDataDefinition")
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn answers_pings() {
+    let mut conn = MockBackend::connected();
+    let mut client = registered(&mut conn);
+    client.ping(&conn).unwrap();
+    assert_eq!(commands(&conn, MOBIFLIGHT_ID_BASE + 3), ["MF.Ping"]);
+
+    conn.backend()
+        .queue_raw_message(response(MOBIFLIGHT_ID_BASE + 3, "MF.Pong"));
+    assert_eq!(client.poll(&mut conn).unwrap(), Some(MobiFlightEvent::Pong));
+}
+
+#[test]
+fn rejects_long_commands() {
+    let conn = MockBackend::connected();
+    let mut client = MobiFlightClient::connect(&conn, "Test").unwrap();
+
+    let code = "1".repeat(MESSAGE_SIZE);
+    match client.execute(&conn, &code) {
+        Err(SimConnectError::StringTooLong { max, .. }) => assert_eq!(max, MESSAGE_SIZE - 1),
+        other => panic!("expected StringTooLong, got {:?}", other),
+    }
+}
@@ -0,0 +1,163 @@
+use simconnect::mock::{MockBackend, RecvMessage, WithPayload};
+use simconnect::{
+    DispatchResult, SimConnectError, SimConnectException,
+    SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED, SIMCONNECT_RECV_EVENT,
+    SIMCONNECT_RECV_QUIT, SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+#[test]
+fn records_calls_with_arguments() {
+    let conn = MockBackend::connected();
+    conn.map_client_event_to_sim_event(7, "PARKING_BRAKES")
+        .unwrap();
+
+    let calls = conn
+        .backend()
+        .calls_to("SimConnect_MapClientEventToSimEvent");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].arg("event_id"), Some("7"));
+    assert_eq!(calls[0].arg("event_name"), Some("\"PARKING_BRAKES\""));
+    assert_eq!(conn.backend().program_name().unwrap().to_str(), Ok("mock"));
+}
+
+#[test]
+fn dispatches_queued_messages_in_order() {
+    let mut conn = MockBackend::connected();
+    let mut event = SIMCONNECT_RECV_EVENT::zeroed();
+    event.uEventID = 3;
+    event.dwData = 42;
+    conn.backend().queue_message(event);
+    conn.backend().queue_message(SIMCONNECT_RECV_QUIT::zeroed());
+
+    match conn.get_next_message() {
+        Ok(DispatchResult::Event(event)) => {
+            assert_eq!({ event.uEventID }, 3);
+            assert_eq!({ event.dwData }, 42);
+        }
+        other => panic!("expected an event, got {:?}", other),
+    }
+    assert!(matches!(
+        conn.get_next_message(),
+        Ok(DispatchResult::Quit(_))
+    ));
+    assert!(matches!(conn.get_next_message(), Ok(DispatchResult::Null)));
+}
+
+#[test]
+fn queues_variable_sized_messages() {
+    let mut conn = MockBackend::connected();
+    let mut data = SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed();
+    data.dwRequestID = 5;
+    data.dwDefineCount = 2;
+    let bytes = data.with_payload(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(bytes.len(), std::mem::size_of_val(&data) + 4);
+    assert_eq!(&bytes[0..4], &(bytes.len() as u32).to_ne_bytes());
+    assert_eq!(&bytes[bytes.len() - 8..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    conn.backend().queue_raw_message(bytes);
+    match conn.get_next_message() {
+        Ok(DispatchResult::SimObjectData(data)) => {
+            assert_eq!({ data.dwRequestID }, 5);
+            assert_eq!({ data.dwData }, u32::from_ne_bytes([1, 2, 3, 4]));
+        }
+        other => panic!("expected object data, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_the_empty_queue_error_as_no_message() {
+    let mut conn = MockBackend::connected();
+    // What the SimConnect library returns when nothing is queued
+    conn.backend().fail_when_empty(0x8000_4005_u32 as i32);
+    assert!(matches!(conn.get_next_message(), Ok(DispatchResult::Null)));
+
+    conn.backend().queue_message(SIMCONNECT_RECV_QUIT::zeroed());
+    assert!(matches!(
+        conn.get_next_message(),
+        Ok(DispatchResult::Quit(_))
+    ));
+    assert!(matches!(conn.get_next_message(), Ok(DispatchResult::Null)));
+
+    // Other failures are passed on
+    conn.backend().fail_when_empty(0x8007_0057_u32 as i32);
+    assert!(matches!(
+        conn.get_next_message(),
+        Err(SimConnectError::CallFailed {
+            call: "SimConnect_GetNextDispatch",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn injected_exception_resolves_to_call() {
+    let mut conn = MockBackend::connected();
+    conn.map_client_event_to_sim_event(1, "PARKING_BRAKES")
+        .unwrap();
+    conn.backend().exception_on_next(
+        "SimConnect_MapClientEventToSimEvent",
+        SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED,
+        2,
+    );
+    conn.map_client_event_to_sim_event(2, "NOT_AN_EVENT")
+        .unwrap();
+    conn.map_client_event_to_sim_event(3, "GEAR_DOWN").unwrap();
+
+    let exception = match conn.get_next_message() {
+        Ok(DispatchResult::Exception(exception)) => *exception,
+        other => panic!("expected an exception, got {:?}", other),
+    };
+    let exception = conn.resolve_exception(&exception);
+    assert_eq!(exception.exception, SimConnectException::NameUnrecognized);
+    assert_eq!(exception.index, 2);
+
+    let packet = exception.packet.expect("packet should be in the history");
+    assert_eq!(packet.call, "SimConnect_MapClientEventToSimEvent");
+    assert!(packet.args.contains("NOT_AN_EVENT"));
+    assert_eq!(packet.location.unwrap().file(), file!());
+}
+
+#[test]
+fn injected_failure_is_returned() {
+    let conn = MockBackend::connected();
+    conn.backend()
+        .fail_next("SimConnect_SubscribeToSystemEvent", 0x8000_4005_u32 as i32);
+
+    match conn.subscribe_to_system_event(1, "Pause") {
+        Err(SimConnectError::CallFailed { call, .. }) => {
+            assert_eq!(call, "SimConnect_SubscribeToSystemEvent")
+        }
+        other => panic!("expected a failed call, got {:?}", other),
+    }
+    assert!(conn.subscribe_to_system_event(1, "Pause").is_ok());
+    assert_eq!(
+        conn.backend()
+            .calls_to("SimConnect_SubscribeToSystemEvent")
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn captures_data_and_responds() {
+    let mut conn = MockBackend::connected();
+    conn.backend()
+        .respond_to("SimConnect_SetDataOnSimObject", |call| {
+            let mut event = SIMCONNECT_RECV_EVENT::zeroed();
+            event.dwData = call.data.len() as u32;
+            vec![event.to_bytes()]
+        });
+
+    let mut data = [1_u8, 2, 3, 4, 5, 6];
+    unsafe {
+        conn.set_data_on_sim_object(0, 0, 0, 3, 2, data.as_mut_ptr() as *mut _)
+            .unwrap();
+    }
+
+    let calls = conn.backend().calls_to("SimConnect_SetDataOnSimObject");
+    assert_eq!(calls[0].data, [1, 2, 3, 4, 5, 6]);
+    match conn.get_next_message() {
+        Ok(DispatchResult::Event(event)) => assert_eq!({ event.dwData }, 6),
+        other => panic!("expected an event, got {:?}", other),
+    }
+}
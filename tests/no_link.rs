@@ -0,0 +1,17 @@
+#![cfg(all(any(not(windows), feature = "no-link"), not(feature = "dynamic")))]
+
+use simconnect::{SimConnectError, SimConnector};
+
+#[test]
+fn ffi_calls_are_unavailable() {
+    let mut conn = SimConnector::new();
+
+    match conn.connect("no link") {
+        Err(SimConnectError::Unavailable { call }) => assert_eq!(call, "SimConnect_Open"),
+        other => panic!("expected Unavailable, got {:?}", other),
+    }
+    assert!(matches!(
+        conn.get_next_message(),
+        Err(SimConnectError::Unavailable { .. })
+    ));
+}
@@ -0,0 +1,71 @@
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    DispatchResult, PayloadReader, SimConnectError, SIMCONNECT_RECV_CLIENT_DATA,
+    SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+#[test]
+fn payload_is_sliced_to_received_size() {
+    let mut conn = MockBackend::connected();
+    let mut payload = 7_u32.to_le_bytes().to_vec();
+    payload.extend_from_slice(&2.5_f64.to_le_bytes());
+    let mut data = SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed();
+    data.dwDefineCount = 1;
+    conn.backend().queue_message_with_payload(data, &payload);
+
+    let data = match conn.get_next_message().unwrap() {
+        DispatchResult::SimObjectData(data) => data,
+        other => panic!("expected object data, got {:?}", other),
+    };
+    assert_eq!({ data.dwDefineCount }, 1);
+    assert_eq!(data.payload(), &payload[..]);
+
+    let mut reader = data.reader();
+    assert_eq!(reader.read_u32().unwrap(), 7);
+    assert_eq!(reader.read_f64().unwrap(), 2.5);
+    assert_eq!(reader.remaining(), 0);
+}
+
+#[test]
+fn reading_past_the_end_fails() {
+    let mut conn = MockBackend::connected();
+    let mut payload = b"Cessna".to_vec();
+    payload.resize(256, 0);
+    payload.extend_from_slice(&1_i32.to_le_bytes());
+    let mut data = SIMCONNECT_RECV_CLIENT_DATA::zeroed();
+    data._base.dwDefineCount = 2;
+    conn.backend().queue_message_with_payload(data, &payload);
+
+    let data = match conn.get_next_message().unwrap() {
+        DispatchResult::ClientData(data) => data,
+        other => panic!("expected client data, got {:?}", other),
+    };
+
+    let mut reader = data.reader();
+    assert_eq!(reader.read_string256().unwrap(), "Cessna");
+    match reader.read_f64() {
+        Err(SimConnectError::PayloadOutOfBounds { offset, len, size }) => {
+            assert_eq!((offset, len, size), (256, 8, 260))
+        }
+        other => panic!("expected an out of bounds error, got {:?}", other),
+    }
+    // A failed read doesn't move the reader
+    assert_eq!(reader.read_i32().unwrap(), 1);
+}
+
+#[test]
+fn reads_variable_length_strings() {
+    let mut reader = PayloadReader::new(b"Boeing 747\0B\0\x07\0\0\0unterminated");
+
+    assert_eq!(reader.read_string_v().unwrap(), "Boeing 747");
+    assert_eq!(reader.read_string_v().unwrap(), "B");
+    assert_eq!(reader.read_i32().unwrap(), 7);
+    assert!(matches!(
+        reader.read_string_v(),
+        Err(SimConnectError::PayloadOutOfBounds {
+            offset: 17,
+            len: 13,
+            size: 29
+        })
+    ));
+}
@@ -0,0 +1,203 @@
+use std::time::{Duration, Instant};
+
+use simconnect::message::Message;
+use simconnect::mock::{MockBackend, MockCall, RecvMessage, WithPayload};
+use simconnect::rpc::{
+    Envelope, Frame, Reassembler, RpcChannel, RpcConfig, RpcError, CHUNK_SIZE, STATUS_ERROR,
+};
+use simconnect::{
+    SimConnectError, SimConnector, SIMCONNECT_RECV_CLIENT_DATA, SIMCONNECT_RECV_QUIT,
+};
+
+fn config() -> RpcConfig {
+    RpcConfig {
+        timeout: Duration::from_millis(50),
+        ..RpcConfig::new("Test")
+    }
+}
+
+/// A `SIMCONNECT_RECV_CLIENT_DATA` carrying `frame` for `define_id`
+fn client_data(define_id: u32, frame: &Frame) -> Vec<u8> {
+    let mut data = SIMCONNECT_RECV_CLIENT_DATA::zeroed();
+    data._base.dwDefineID = define_id;
+    data.with_payload(&frame.to_bytes())
+}
+
+/// The frame written by a `SimConnect_SetClientData` call to the command area
+fn command_frame(call: &MockCall, config: &RpcConfig) -> Option<Frame> {
+    if call.arg("define_id") != Some(config.command_define_id.to_string().as_str()) {
+        return None;
+    }
+
+    Some(Frame::from_bytes(&call.data).unwrap())
+}
+
+/// Plays a peer that answers every frame with itself, or with `transform` applied
+fn echo_peer(conn: &SimConnector<MockBackend>, transform: fn(Frame) -> Frame) {
+    let config = config();
+    conn.backend()
+        .respond_to("SimConnect_SetClientData", move |call| {
+            command_frame(call, &config)
+                .map(|frame| vec![client_data(config.response_define_id, &transform(frame))])
+                .unwrap_or_default()
+        });
+}
+
+#[test]
+fn opens_both_areas() {
+    let conn = MockBackend::connected();
+    let config = config();
+    RpcChannel::open(&conn, config.clone()).unwrap();
+
+    let names = conn
+        .backend()
+        .calls_to("SimConnect_MapClientDataNameToID")
+        .iter()
+        .map(|call| call.arg("client_data_name").unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["\"Test.Command\"", "\"Test.Response\""]);
+    assert_eq!(
+        conn.backend().calls_to("SimConnect_CreateClientData")[0].arg("size"),
+        Some("1024")
+    );
+
+    let request = &conn.backend().calls_to("SimConnect_RequestClientData")[0];
+    assert_eq!(
+        request.arg("define_id"),
+        Some(config.response_define_id.to_string().as_str())
+    );
+
+    let peer = config.peer();
+    assert_eq!(peer.command_area, "Test.Response");
+    assert!(!peer.create_areas);
+}
+
+#[test]
+fn calls_an_echoing_peer() {
+    let mut conn = MockBackend::connected();
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+    echo_peer(&conn, |frame| frame);
+
+    let answer: String = channel.call(&mut conn, &"PING".to_string()).unwrap();
+    assert_eq!(answer, "PING");
+
+    let empty: Vec<u8> = channel.call(&mut conn, &Vec::new()).unwrap();
+    assert!(empty.is_empty());
+
+    let frames = conn.backend().calls_to("SimConnect_SetClientData");
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].arg("unit_size"), Some("1024"));
+}
+
+#[test]
+fn chunks_large_messages() {
+    let mut conn = MockBackend::connected();
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+    echo_peer(&conn, |frame| frame);
+
+    let request = (0..CHUNK_SIZE * 2 + 100)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    let answer: Vec<u8> = channel.call(&mut conn, &request).unwrap();
+    assert_eq!(answer, request);
+
+    let frames = conn
+        .backend()
+        .calls_to("SimConnect_SetClientData")
+        .iter()
+        .map(|call| Frame::from_bytes(&call.data).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(frames.len(), 3);
+    assert!(frames.iter().all(|frame| frame.chunks == 3));
+    assert_eq!(frames[2].len, 100);
+}
+
+#[test]
+fn reports_remote_errors() {
+    let mut conn = MockBackend::connected();
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+    echo_peer(&conn, |frame| {
+        let mut error = Frame::split(frame.sequence, STATUS_ERROR, b"unknown command").unwrap();
+        error.remove(0)
+    });
+
+    match channel.call::<_, String, String>(&mut conn, &"FLY".to_string()) {
+        Err(SimConnectError::Rpc(RpcError::Remote { sequence, message })) => {
+            assert_eq!(sequence, 1);
+            assert_eq!(message, "unknown command");
+        }
+        other => panic!("expected a remote error, got {:?}", other),
+    }
+}
+
+#[test]
+fn times_out_and_keeps_other_messages() {
+    let mut conn = MockBackend::connected();
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+    conn.backend().queue_message(SIMCONNECT_RECV_QUIT::zeroed());
+
+    assert!(matches!(
+        channel.call::<_, String, String>(&mut conn, &"PING".to_string()),
+        Err(SimConnectError::Rpc(RpcError::Timeout { sequence: 1, .. }))
+    ));
+    assert!(matches!(channel.take_messages()[..], [Message::Quit]));
+}
+
+#[test]
+fn waits_while_the_library_reports_an_empty_queue() {
+    let mut conn = MockBackend::connected();
+    // SimConnect_GetNextDispatch fails with E_FAIL when there is nothing to dispatch
+    conn.backend().fail_when_empty(0x8000_4005_u32 as i32);
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+
+    let start = Instant::now();
+    assert!(matches!(
+        channel.call::<_, String, String>(&mut conn, &"PING".to_string()),
+        Err(SimConnectError::Rpc(RpcError::Timeout { sequence: 1, .. }))
+    ));
+    assert!(start.elapsed() >= config().timeout);
+
+    echo_peer(&conn, |frame| frame);
+    let answer: String = channel.call(&mut conn, &"PING".to_string()).unwrap();
+    assert_eq!(answer, "PING");
+}
+
+#[test]
+fn drops_stale_responses() {
+    let mut conn = MockBackend::connected();
+    let mut channel = RpcChannel::open(&conn, config()).unwrap();
+
+    // The answer to request 1 arrives after request 2 was sent
+    channel.send(&conn, &"FIRST".to_string()).unwrap();
+    echo_peer(&conn, |frame| frame);
+    let stale = Frame::split(1, 0, b"FIRST").unwrap();
+    conn.backend()
+        .queue_raw_message(client_data(config().response_define_id, &stale[0]));
+
+    let answer: String = channel.call(&mut conn, &"SECOND".to_string()).unwrap();
+    assert_eq!(answer, "SECOND");
+}
+
+#[test]
+fn reassembles_frames_in_any_order() {
+    let message = vec![7; CHUNK_SIZE + 1];
+    let frames = Frame::split(9, 0, &message).unwrap();
+    let mut reassembler = Reassembler::new();
+
+    assert_eq!(reassembler.push(&frames[1]).unwrap(), None);
+    assert_eq!(
+        reassembler.push(&frames[0]).unwrap(),
+        Some(Envelope {
+            sequence: 9,
+            status: 0,
+            data: message,
+        })
+    );
+
+    let mut invalid = frames[0];
+    invalid.chunk = 5;
+    assert!(matches!(
+        reassembler.push(&invalid),
+        Err(RpcError::InvalidFrame(_))
+    ));
+}
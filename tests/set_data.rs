@@ -0,0 +1,231 @@
+use simconnect::mock::MockBackend;
+use simconnect::{
+    PayloadReader, SimConnectBackend, SimConnectData, SimConnectError, SimConnector, Value,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64, SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32,
+    SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV, SIMCONNECT_DATA_DEFINITION_ID,
+    SIMCONNECT_DATA_SET_FLAG_TAGGED, SIMCONNECT_OBJECT_ID_USER,
+};
+
+/// Written out by hand, as `#[derive(SimConnectData)]` would
+struct Radio {
+    frequency: f64,
+    standby: i32,
+    ident: String,
+}
+
+impl SimConnectData for Radio {
+    fn add_definition<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        conn.add_data_definition(
+            define_id,
+            "COM ACTIVE FREQUENCY:1",
+            "mhz",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+            0,
+            0.0,
+        )?;
+        conn.add_data_definition(
+            define_id,
+            "COM STANDBY FREQUENCY:1",
+            "bco16",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32,
+            1,
+            0.0,
+        )?;
+        conn.add_data_definition(
+            define_id,
+            "ATC ID",
+            "",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32,
+            2,
+            0.0,
+        )
+    }
+
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError> {
+        Ok(Self {
+            frequency: reader.read_f64()?,
+            standby: reader.read_i32()?,
+            ident: reader.read_string(32)?,
+        })
+    }
+
+    fn values(&self) -> Vec<Value> {
+        vec![
+            Value::Float64(self.frequency),
+            Value::Int32(self.standby),
+            Value::String(self.ident.clone()),
+        ]
+    }
+}
+
+/// A definition whose single field doesn't match its datum
+struct Mismatched(f32);
+
+impl SimConnectData for Mismatched {
+    fn add_definition<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        conn.add_data_definition(
+            define_id,
+            "PLANE ALTITUDE",
+            "feet",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64,
+            0,
+            0.0,
+        )
+    }
+
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError> {
+        reader.read_f32().map(Self)
+    }
+
+    fn values(&self) -> Vec<Value> {
+        vec![Value::Float32(self.0)]
+    }
+}
+
+/// A single `STRINGV` datum
+struct Title(String);
+
+impl SimConnectData for Title {
+    fn add_definition<B: SimConnectBackend>(
+        conn: &SimConnector<B>,
+        define_id: SIMCONNECT_DATA_DEFINITION_ID,
+    ) -> Result<(), SimConnectError> {
+        conn.add_data_definition(
+            define_id,
+            "TITLE",
+            "",
+            SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV,
+            0,
+            0.0,
+        )
+    }
+
+    fn read(reader: &mut PayloadReader<'_>) -> Result<Self, SimConnectError> {
+        reader.read_string_v().map(Self)
+    }
+
+    fn values(&self) -> Vec<Value> {
+        vec![Value::String(self.0.clone())]
+    }
+}
+
+fn radio() -> Radio {
+    Radio {
+        frequency: 121.5,
+        standby: 0x1850,
+        ident: "D-EABC".to_string(),
+    }
+}
+
+#[test]
+fn writes_registered_data() {
+    let conn = MockBackend::connected();
+    conn.define_data::<Radio>(3).unwrap();
+    assert_eq!(conn.data_definition_id::<Radio>(), Some(3));
+
+    conn.set_data(SIMCONNECT_OBJECT_ID_USER, &radio()).unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_SetDataOnSimObject");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].arg("define_id"), Some("3"));
+    assert_eq!(calls[0].arg("flags"), Some("0"));
+    assert_eq!(calls[0].arg("array_count"), Some("1"));
+    assert_eq!(calls[0].arg("unit_size"), Some("44"));
+}
+
+#[test]
+fn writes_tagged_datums() {
+    let conn = MockBackend::connected();
+    conn.define_data::<Radio>(3).unwrap();
+
+    conn.set_data_tagged(
+        3,
+        SIMCONNECT_OBJECT_ID_USER,
+        &[(1, Value::Int32(0x2200)), (0, Value::Float64(118.0))],
+    )
+    .unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_SetDataOnSimObject");
+    assert_eq!(
+        calls[0].arg("flags"),
+        Some(SIMCONNECT_DATA_SET_FLAG_TAGGED.to_string().as_str())
+    );
+    assert_eq!(calls[0].arg("unit_size"), Some("20"));
+
+    assert!(matches!(
+        conn.set_data_tagged(3, SIMCONNECT_OBJECT_ID_USER, &[(7, Value::Int32(0))]),
+        Err(SimConnectError::UnknownDatum {
+            define_id: 3,
+            datum_id: 7
+        })
+    ));
+    assert!(matches!(
+        conn.set_data_tagged(4, SIMCONNECT_OBJECT_ID_USER, &[]),
+        Err(SimConnectError::UndefinedDefinition(4))
+    ));
+}
+
+#[test]
+fn writes_arrays_of_equal_elements() {
+    let conn = MockBackend::connected();
+    conn.define_data::<Radio>(3).unwrap();
+    conn.define_data::<Title>(4).unwrap();
+
+    conn.set_data_array(SIMCONNECT_OBJECT_ID_USER, &[radio(), radio(), radio()])
+        .unwrap();
+    conn.set_data_array::<Radio>(SIMCONNECT_OBJECT_ID_USER, &[])
+        .unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_SetDataOnSimObject");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].arg("array_count"), Some("3"));
+    assert_eq!(calls[0].arg("unit_size"), Some("44"));
+
+    assert!(matches!(
+        conn.set_data_array(
+            SIMCONNECT_OBJECT_ID_USER,
+            &[
+                Title("C172".to_string()),
+                Title("A320".to_string()),
+                Title("B747-8".to_string())
+            ]
+        ),
+        Err(SimConnectError::DataSizeMismatch {
+            define_id: 4,
+            expected: 5,
+            found: 7
+        })
+    ));
+}
+
+#[test]
+fn rejects_unchecked_writes_before_sending() {
+    let conn = MockBackend::connected();
+
+    assert!(matches!(
+        conn.set_data(SIMCONNECT_OBJECT_ID_USER, &radio()),
+        Err(SimConnectError::UnregisteredData(name)) if name.ends_with("Radio")
+    ));
+
+    conn.define_data::<Mismatched>(5).unwrap();
+    assert!(matches!(
+        conn.set_data(SIMCONNECT_OBJECT_ID_USER, &Mismatched(1.0)),
+        Err(SimConnectError::InvalidDatatype(_))
+    ));
+
+    conn.clear_data_definition(5).unwrap();
+    assert!(conn.data_definition(5).is_none());
+    assert!(matches!(
+        conn.set_data(SIMCONNECT_OBJECT_ID_USER, &Mismatched(1.0)),
+        Err(SimConnectError::UnregisteredData(_))
+    ));
+
+    assert!(!conn.backend().was_called("SimConnect_SetDataOnSimObject"));
+}
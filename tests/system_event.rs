@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::{
+    SimConnector, SystemEvent, SystemNotification, ViewKind, SIMCONNECT_RECV_EVENT,
+    SIMCONNECT_RECV_EVENT_FILENAME, SIMCONNECT_RECV_EVENT_FRAME,
+    SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE,
+    SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_AIRCRAFT,
+    SIMCONNECT_SOUND_SYSTEM_EVENT_DATA_MASTER, SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_VIRTUAL,
+};
+
+fn event(event: SystemEvent, data: u32) -> SIMCONNECT_RECV_EVENT {
+    let mut message = SIMCONNECT_RECV_EVENT::zeroed();
+    message.uEventID = event.event_id();
+    message.dwData = data;
+    message
+}
+
+fn next_notification(conn: &mut SimConnector<MockBackend>) -> Option<SystemNotification> {
+    conn.get_next_message().unwrap().system_event()
+}
+
+#[test]
+fn subscribes_by_name() {
+    let conn = MockBackend::connected();
+    let event_id = conn.subscribe(SystemEvent::PauseEx1).unwrap();
+    conn.unsubscribe(SystemEvent::PauseEx1).unwrap();
+
+    let calls = conn.backend().calls_to("SimConnect_SubscribeToSystemEvent");
+    assert_eq!(calls[0].arg("system_event_name"), Some("\"Pause_EX1\""));
+    assert_eq!(
+        calls[0].arg("event_id"),
+        Some(event_id.to_string().as_str())
+    );
+    let calls = conn
+        .backend()
+        .calls_to("SimConnect_UnsubscribeFromSystemEvent");
+    assert_eq!(
+        calls[0].arg("event_id"),
+        Some(event_id.to_string().as_str())
+    );
+
+    for &event in SystemEvent::ALL {
+        assert_eq!(SystemEvent::from_event_id(event.event_id()), Some(event));
+    }
+}
+
+#[test]
+fn decodes_event_data() {
+    let mut conn = MockBackend::connected();
+    conn.backend().queue_message(event(SystemEvent::Pause, 1));
+    conn.backend().queue_message(event(
+        SystemEvent::View,
+        SIMCONNECT_VIEW_SYSTEM_EVENT_DATA_COCKPIT_VIRTUAL,
+    ));
+    conn.backend().queue_message(event(SystemEvent::View, 0x20));
+    conn.backend().queue_message(event(
+        SystemEvent::Sound,
+        SIMCONNECT_SOUND_SYSTEM_EVENT_DATA_MASTER,
+    ));
+    conn.backend().queue_message(event(SystemEvent::Sim, 0));
+
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::Pause(true))
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::View(ViewKind::CockpitVirtual))
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::View(ViewKind::Other(0x20)))
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::Sound(true))
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::Sim(false))
+    );
+}
+
+#[test]
+fn decodes_extended_events() {
+    let mut conn = MockBackend::connected();
+
+    let mut filename = SIMCONNECT_RECV_EVENT_FILENAME::zeroed();
+    filename._base.uEventID = SystemEvent::AircraftLoaded.event_id();
+    for (dst, &src) in filename
+        .szFileName
+        .iter_mut()
+        .zip(b"SimObjects\\Airplanes\\C172\\aircraft.cfg")
+    {
+        *dst = src as _;
+    }
+    conn.backend().queue_message(filename);
+
+    let mut frame = SIMCONNECT_RECV_EVENT_FRAME::zeroed();
+    frame._base.uEventID = SystemEvent::Frame.event_id();
+    frame.fFrameRate = 30.0;
+    frame.fSimSpeed = 2.0;
+    conn.backend().queue_message(frame);
+
+    let mut added = SIMCONNECT_RECV_EVENT_OBJECT_ADDREMOVE::zeroed();
+    added._base.uEventID = SystemEvent::ObjectAdded.event_id();
+    added._base.dwData = 42;
+    added.eObjType = SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_AIRCRAFT;
+    conn.backend().queue_message(added);
+
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::AircraftLoaded(PathBuf::from(
+            "SimObjects\\Airplanes\\C172\\aircraft.cfg"
+        )))
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::Frame {
+            fps: 30.0,
+            sim_speed: 2.0
+        })
+    );
+    assert_eq!(
+        next_notification(&mut conn),
+        Some(SystemNotification::ObjectAdded {
+            object_id: 42,
+            object_type: SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_AIRCRAFT
+        })
+    );
+}
+
+#[test]
+fn ignores_other_events() {
+    let mut conn = MockBackend::connected();
+    let mut message = SIMCONNECT_RECV_EVENT::zeroed();
+    message.uEventID = 7;
+    conn.backend().queue_message(message);
+
+    assert_eq!(next_notification(&mut conn), None);
+}
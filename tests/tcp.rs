@@ -0,0 +1,209 @@
+use std::convert::TryInto;
+use std::io::{Read, Write};
+use std::mem::size_of;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use simconnect::message::Event;
+use simconnect::{
+    Message, SimConnectError, SimConnector, TcpBackend, SIMCONNECT_RECV_EVENT,
+    SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT, SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN,
+    SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA, SIMCONNECT_RECV_OPEN,
+    SIMCONNECT_RECV_SIMOBJECT_DATA,
+};
+
+fn read_packet(stream: &mut TcpStream) -> Vec<u8> {
+    let mut size = [0; 4];
+    stream.read_exact(&mut size).unwrap();
+
+    let mut packet = size.to_vec();
+    packet.resize(u32::from_le_bytes(size) as usize, 0);
+    stream.read_exact(&mut packet[4..]).unwrap();
+    packet
+}
+
+fn dword(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn message(id: u32, size: usize, body: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for value in [size as u32, 4, id].iter().chain(body) {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.resize(size, 0);
+    bytes
+}
+
+/// Polls until a message arrives, the backend never blocks.
+fn next_message(conn: &mut SimConnector<TcpBackend>) -> Message {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        match conn.get_next_message().unwrap().to_owned() {
+            Message::Null if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+            message => return message,
+        }
+    }
+}
+
+#[test]
+fn talks_to_stand_in_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let open = read_packet(&mut stream);
+        assert_eq!(dword(&open, 4), 4);
+        assert_eq!(dword(&open, 8), 0xF000_0001);
+        assert_eq!(dword(&open, 12), 1);
+        assert_eq!(&open[16..27], b"tcp test\0\0\0");
+
+        let map = read_packet(&mut stream);
+        assert_eq!(map.len(), 16 + 4 + 256);
+        assert_eq!(dword(&map, 8), 0xF000_0004);
+        assert_eq!(dword(&map, 12), 2);
+        assert_eq!(dword(&map, 16), 7);
+        assert_eq!(&map[20..35], b"PARKING_BRAKES\0");
+
+        let mut reply = message(
+            SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN as u32,
+            size_of::<SIMCONNECT_RECV_OPEN>(),
+            &[],
+        );
+        // Split the event across two writes to exercise reassembly
+        let event = message(
+            SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT as u32,
+            size_of::<SIMCONNECT_RECV_EVENT>(),
+            &[0, 7, 42],
+        );
+        reply.extend_from_slice(&event[..6]);
+        stream.write_all(&reply).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(&event[6..]).unwrap();
+    });
+
+    let mut conn = SimConnector::with_backend(TcpBackend::new(address.to_string()));
+    conn.connect("tcp test").unwrap();
+    conn.map_client_event_to_sim_event(7, "PARKING_BRAKES")
+        .unwrap();
+    assert_eq!(conn.get_last_sent_packet_id().unwrap(), 2);
+
+    assert!(matches!(next_message(&mut conn), Message::Open(_)));
+    assert_eq!(
+        next_message(&mut conn),
+        Message::Event(Event {
+            group_id: 0,
+            event_id: 7,
+            data: 42
+        })
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+fn sends_weather_packets() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_packet(&mut stream);
+
+        let station = read_packet(&mut stream);
+        assert_eq!(dword(&station, 8), 0xF000_001A);
+        assert_eq!(station.len(), 16 + 4 + 5);
+        assert_eq!(&station[20..25], b"LSZH\0");
+
+        // The METAR takes as much room as it needs
+        let observation = read_packet(&mut stream);
+        assert_eq!(dword(&observation, 8), 0xF000_001E);
+        assert_eq!(dword(&observation, 16), 30);
+        assert_eq!(&observation[20..], b"LSZH 121200Z CAVOK\0");
+    });
+
+    let mut conn = SimConnector::with_backend(TcpBackend::new(address.to_string()));
+    conn.connect("tcp test").unwrap();
+    conn.weather_request_observation_at_station(1, "LSZH")
+        .unwrap();
+    conn.weather_set_observation(30, "LSZH 121200Z CAVOK")
+        .unwrap();
+
+    server.join().unwrap();
+}
+
+#[test]
+fn rejects_messages_shorter_than_their_struct() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (done, wait) = mpsc::channel::<()>();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_packet(&mut stream);
+
+        // Only the header of a SIMCONNECT_RECV_SIMOBJECT_DATA
+        let header = message(
+            SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA as u32,
+            12,
+            &[],
+        );
+        stream.write_all(&header).unwrap();
+        let _ = wait.recv();
+    });
+
+    let mut conn = SimConnector::with_backend(TcpBackend::new(address.to_string()));
+    conn.connect("tcp test").unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let error = loop {
+        match conn.get_next_message().map(|message| message.to_owned()) {
+            Ok(Message::Null) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(5))
+            }
+            Ok(message) => panic!("expected an error, got {:?}", message),
+            Err(error) => break error,
+        }
+    };
+    assert!(matches!(
+        error,
+        SimConnectError::TruncatedMessage { id, size: 12, expected }
+            if id == SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA as u32
+                && expected == size_of::<SIMCONNECT_RECV_SIMOBJECT_DATA>()
+    ));
+
+    done.send(()).unwrap();
+    server.join().unwrap();
+}
+
+#[test]
+fn msfs_functions_are_unavailable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut conn =
+        SimConnector::with_backend(TcpBackend::new(listener.local_addr().unwrap().to_string()));
+    conn.connect("tcp test").unwrap();
+
+    assert!(matches!(
+        conn.enumerate_controllers(),
+        Err(SimConnectError::Unavailable {
+            call: "SimConnect_EnumerateControllers"
+        })
+    ));
+}
+
+#[test]
+fn refused_connection_means_sim_not_running() {
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let mut conn = SimConnector::with_backend(TcpBackend::new(address.to_string()));
+    let error = conn.connect("tcp test").unwrap_err();
+    assert!(matches!(error, SimConnectError::Io { .. }));
+    assert!(error.is_sim_not_running());
+}
@@ -0,0 +1,260 @@
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::thermal::{Thermal, ThermalDistribution, ThermalError, ThermalField};
+use simconnect::{
+    SimConnectError, SimConnector, MAX_THERMAL_RATE, MAX_THERMAL_SIZE,
+    SIMCONNECT_RECV_ASSIGNED_OBJECT_ID,
+};
+
+/// Delivers the object ID the sim assigned for `request_id` to `field`
+fn assign(
+    conn: &mut SimConnector<MockBackend>,
+    field: &mut ThermalField,
+    request_id: u32,
+    object_id: u32,
+) -> bool {
+    let mut message = SIMCONNECT_RECV_ASSIGNED_OBJECT_ID::zeroed();
+    message.dwRequestID = request_id;
+    message.dwObjectID = object_id;
+    conn.backend().queue_message(message);
+
+    field.handle(&conn.get_next_message().unwrap())
+}
+
+fn calls(conn: &SimConnector<MockBackend>, call: &str, arg: &str) -> Vec<String> {
+    conn.backend()
+        .calls_to(call)
+        .iter()
+        .map(|call| call.arg(arg).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn generates_reproducible_fields() {
+    let distribution = ThermalDistribution::new(47.5, 8.9, 10_000.0, 50);
+    let field = ThermalField::random(7, &distribution);
+    assert_eq!(field.thermals().len(), 50);
+    assert_eq!(
+        field.thermals(),
+        ThermalField::random(7, &distribution).thermals()
+    );
+    assert_ne!(
+        field.thermals(),
+        ThermalField::random(8, &distribution).thermals()
+    );
+
+    for thermal in field.thermals() {
+        // About 111 km per degree of latitude, 75 km per degree of longitude
+        let north = (thermal.lat - 47.5) * 111_200.0;
+        let east = (thermal.lon - 8.9) * 75_100.0;
+        assert!((north * north + east * east).sqrt() < 10_050.0);
+
+        assert!((300.0..=1000.0).contains(&thermal.radius));
+        assert!((1000.0..=2500.0).contains(&thermal.height));
+        assert!((1.0..=4.0).contains(&thermal.core_rate));
+        assert!((thermal.sink_rate + thermal.core_rate * 0.2).abs() < 1e-6);
+        assert_eq!(thermal.validate(), Ok(()));
+    }
+}
+
+#[cfg(feature = "geojson")]
+#[test]
+fn reads_geojson() {
+    let field = ThermalField::from_geojson(
+        r#"{
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [8.9, 47.5, 100] },
+                    "properties": {
+                        "name": "Lägern \"north\"",
+                        "radius": 800,
+                        "height": 2e3,
+                        "core_rate": 2.5,
+                        "sink_rate": -0.5,
+                        "core_size": null
+                    }
+                },
+                {
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [-8.5, -47.25] },
+                    "properties": { "radius": 500, "height": 1500, "core_rate": 1 }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let first = field.thermals()[0];
+    assert_eq!((first.lat, first.lon), (47.5, 8.9));
+    assert!((first.alt - 328.084).abs() < 1e-3);
+    assert_eq!(first.height, 2000.0);
+    assert_eq!(first.sink_rate, -0.5);
+    assert_eq!(first.core_size, 400.0);
+    assert_eq!(
+        field.thermals()[1],
+        Thermal::new(-47.25, -8.5, 500.0, 1500.0, 1.0)
+    );
+
+    for (geojson, message) in [
+        (
+            r#"{"type": "Feature", "geometry": {"type": "LineString", "coordinates": []}}"#,
+            "thermals have to be `Point` features",
+        ),
+        (
+            r#"{"type": "Feature", "geometry": {"type": "Point", "coordinates": [1, 2]},
+                "properties": {"radius": 500, "height": 1500}}"#,
+            "missing property `core_rate`",
+        ),
+        (
+            r#"{"type": "Feature", "geometry": {"type": "Point", "coordinates": [1, 2]},
+                "properties": {"radius": "big", "height": 1500, "core_rate": 1}}"#,
+            "property `radius` isn't a number",
+        ),
+        (r#"[1, 2]"#, "expected a `FeatureCollection` or a `Feature`"),
+    ] {
+        assert_eq!(
+            ThermalField::from_geojson(geojson).unwrap_err(),
+            ThermalError::InvalidGeoJson(message.to_string())
+        );
+    }
+
+    // Syntax errors, and nesting too deep for the parser
+    match ThermalField::from_geojson(r#"{"type": "Feature""#) {
+        Err(ThermalError::InvalidGeoJson(message)) => assert!(message.starts_with("EOF")),
+        other => panic!("expected invalid GeoJSON, got {:?}", other),
+    }
+    match ThermalField::from_geojson(&"[".repeat(200_000)) {
+        Err(ThermalError::InvalidGeoJson(message)) => {
+            assert!(message.starts_with("recursion limit exceeded"))
+        }
+        other => panic!("expected invalid GeoJSON, got {:?}", other),
+    }
+}
+
+#[test]
+fn validates_limits() {
+    let conn = MockBackend::connected();
+    let thermal = Thermal::new(47.5, 8.9, 800.0, 2000.0, 2.5);
+    assert_eq!(thermal.validate(), Ok(()));
+
+    let too_wide = Thermal {
+        radius: MAX_THERMAL_SIZE * 2.0,
+        ..thermal
+    };
+    assert_eq!(
+        too_wide.validate(),
+        Err(ThermalError::OutOfRange {
+            field: "radius",
+            value: MAX_THERMAL_SIZE * 2.0,
+            min: 0.0,
+            max: MAX_THERMAL_SIZE,
+        })
+    );
+    let too_strong = Thermal {
+        sink_rate: -MAX_THERMAL_RATE - 1.0,
+        ..thermal
+    };
+    assert!(too_strong.validate().is_err());
+    let nowhere = Thermal {
+        height: f32::NAN,
+        ..thermal
+    };
+    assert!(nowhere.validate().is_err());
+
+    let error = conn
+        .weather_create_thermal(
+            1, 47.5, 8.9, 0.0, 800.0, 2000.0, 2000.0, 0.0, 0.0, 0.0, 400.0, 200.0, 0.0, 0.0,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        SimConnectError::Thermal(ThermalError::OutOfRange {
+            field: "core_rate",
+            ..
+        })
+    ));
+    assert!(error.is_bad_argument());
+
+    // A field is only placed if all of its thermals are valid
+    let mut field = ThermalField::new(vec![thermal, too_wide]);
+    assert!(field.place(&conn, 1).is_err());
+    assert!(!conn.backend().was_called("SimConnect_WeatherCreateThermal"));
+}
+
+#[test]
+fn drifts_with_the_wind() {
+    let mut conn = MockBackend::connected();
+    let mut field = ThermalField::new(vec![
+        Thermal::new(47.5, 8.9, 800.0, 2000.0, 2.5),
+        Thermal::new(47.6, 8.8, 600.0, 1500.0, 1.5),
+    ]);
+    field.place(&conn, 100).unwrap();
+    assert_eq!(
+        calls(&conn, "SimConnect_WeatherCreateThermal", "request_id"),
+        ["100", "101"]
+    );
+    assert!(field.is_pending());
+
+    assert!(assign(&mut conn, &mut field, 101, 11));
+    assert!(assign(&mut conn, &mut field, 100, 10));
+    assert!(!assign(&mut conn, &mut field, 7, 12));
+    assert_eq!(field.object_id(0), Some(10));
+    assert_eq!(field.object_id(1), Some(11));
+    assert!(!field.is_pending());
+
+    // 100 m to the east
+    field.drift(&conn, 270.0, 10.0, 10.0).unwrap();
+    assert!(field.thermals()[0].lon > 8.9);
+    assert!((field.thermals()[0].lat - 47.5).abs() < 1e-5);
+    assert!(!conn.backend().was_called("SimConnect_WeatherRemoveThermal"));
+
+    // 250 m, further than the redraw distance
+    field.drift(&conn, 270.0, 10.0, 15.0).unwrap();
+    assert_eq!(
+        calls(&conn, "SimConnect_WeatherRemoveThermal", "object_id"),
+        ["10", "11"]
+    );
+    assert_eq!(
+        calls(&conn, "SimConnect_WeatherCreateThermal", "request_id"),
+        ["100", "101", "102", "103"]
+    );
+    let lon = calls(&conn, "SimConnect_WeatherCreateThermal", "lon")[2]
+        .parse::<f32>()
+        .unwrap();
+    assert_eq!(lon, field.thermals()[0].lon);
+    assert_eq!(field.object_id(0), None);
+
+    // Wind from the north moves thermals south
+    let mut field = ThermalField::new(vec![Thermal::new(47.5, 8.9, 800.0, 2000.0, 2.5)]);
+    field.drift(&conn, 360.0, 10.0, 10.0).unwrap();
+    assert!(field.thermals()[0].lat < 47.5);
+}
+
+#[test]
+fn removes_thermals_on_shutdown() {
+    let mut conn = MockBackend::connected();
+    let mut field = ThermalField::new(vec![
+        Thermal::new(47.5, 8.9, 800.0, 2000.0, 2.5),
+        Thermal::new(47.6, 8.8, 600.0, 1500.0, 1.5),
+    ]);
+    field.place(&conn, 100).unwrap();
+    assign(&mut conn, &mut field, 100, 10);
+
+    field.remove_all(&conn).unwrap();
+    assert_eq!(
+        calls(&conn, "SimConnect_WeatherRemoveThermal", "object_id"),
+        ["10"]
+    );
+    assert_eq!(field.object_id(0), None);
+    assert!(field.is_pending());
+
+    // The ID of the second thermal arrives after it was removed
+    assert!(assign(&mut conn, &mut field, 101, 11));
+    field.remove_all(&conn).unwrap();
+    assert_eq!(
+        calls(&conn, "SimConnect_WeatherRemoveThermal", "object_id"),
+        ["10", "11"]
+    );
+    assert!(!field.is_pending());
+}
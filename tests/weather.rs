@@ -0,0 +1,172 @@
+use simconnect::message::CloudState;
+use simconnect::mock::{MockBackend, RecvMessage, WithPayload};
+use simconnect::{
+    SimConnectError, WeatherMode, MAX_METAR_LENGTH, SIMCONNECT_RECV_CLOUD_STATE,
+    SIMCONNECT_RECV_EVENT_WEATHER_MODE, SIMCONNECT_RECV_WEATHER_OBSERVATION,
+    SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_RWW,
+};
+
+fn observation_message(request_id: u32) -> SIMCONNECT_RECV_WEATHER_OBSERVATION {
+    let mut message = SIMCONNECT_RECV_WEATHER_OBSERVATION::zeroed();
+    message.dwRequestID = request_id;
+    message
+}
+
+#[test]
+fn wraps_every_weather_function() {
+    let conn = MockBackend::connected();
+    conn.weather_request_interpolated_observation(1, 47.5, 8.5, 500.0)
+        .unwrap();
+    conn.weather_request_observation_at_station(2, "LSZH")
+        .unwrap();
+    conn.weather_request_observation_at_nearest_station(3, 47.5, 8.5)
+        .unwrap();
+    conn.weather_create_station(4, "ZZZZ", "Test station", 47.5, 8.5, 400.0)
+        .unwrap();
+    conn.weather_remove_station(5, "ZZZZ").unwrap();
+    conn.weather_set_observation(60, "ZZZZ 121200Z 27010KT 9999 FEW030 15/10 Q1013")
+        .unwrap();
+    conn.weather_set_mode_server(80, 30).unwrap();
+    conn.weather_set_mode_theme("Stormy").unwrap();
+    conn.weather_set_mode_global().unwrap();
+    conn.weather_set_mode_custom().unwrap();
+    conn.weather_set_dynamic_update_rate(2).unwrap();
+    conn.weather_request_cloud_state(6, 47.0, 8.0, 0.0, 48.0, 9.0, 10000.0, 0)
+        .unwrap();
+    conn.weather_create_thermal(
+        7, 47.5, 8.5, 500.0, 1000.0, 3000.0, 3.0, 0.5, -1.0, 0.5, 300.0, 100.0, 200.0, 100.0,
+    )
+    .unwrap();
+    conn.weather_remove_thermal(8).unwrap();
+
+    let backend = conn.backend();
+    let station = &backend.calls_to("SimConnect_WeatherCreateStation")[0];
+    assert_eq!(station.arg("icao"), Some("\"ZZZZ\""));
+    assert_eq!(station.arg("name"), Some("\"Test station\""));
+    assert_eq!(station.arg("alt"), Some("400.0"));
+
+    let thermal = &backend.calls_to("SimConnect_WeatherCreateThermal")[0];
+    assert_eq!(thermal.arg("height"), Some("3000.0"));
+    assert_eq!(thermal.arg("sink_transition_size"), Some("100.0"));
+
+    assert_eq!(
+        backend.calls_to("SimConnect_WeatherSetObservation")[0].arg("seconds"),
+        Some("60")
+    );
+    assert!(backend.was_called("SimConnect_WeatherSetModeGlobal"));
+    assert_eq!(
+        backend.calls_to("SimConnect_WeatherRemoveThermal")[0].arg("object_id"),
+        Some("8")
+    );
+}
+
+#[test]
+fn rejects_strings_that_do_not_fit() {
+    let conn = MockBackend::connected();
+
+    let error = conn
+        .weather_request_observation_at_station(1, "LSZHX")
+        .unwrap_err();
+    assert_eq!(
+        error.call(),
+        Some("SimConnect_WeatherRequestObservationAtStation")
+    );
+    assert!(matches!(
+        error,
+        SimConnectError::StringTooLong {
+            argument: "icao",
+            len: 5,
+            max: 4,
+            ..
+        }
+    ));
+
+    let metar = "A".repeat(MAX_METAR_LENGTH as usize);
+    let error = conn.weather_set_observation(0, &metar).unwrap_err();
+    assert!(error.is_bad_argument());
+    assert!(!conn
+        .backend()
+        .was_called("SimConnect_WeatherSetObservation"));
+}
+
+#[test]
+fn decodes_observations() {
+    let mut conn = MockBackend::connected();
+    let metar = b"LSZH 121200Z 27010KT CAVOK 15/10 Q1013\0garbage";
+    conn.backend()
+        .queue_message_with_payload(observation_message(2), metar);
+
+    let observation = conn
+        .get_next_message()
+        .unwrap()
+        .weather_observation()
+        .unwrap();
+    assert_eq!(observation.request_id, 2);
+    assert_eq!(observation.metar, "LSZH 121200Z 27010KT CAVOK 15/10 Q1013");
+
+    // Without a terminating NUL the METAR ends with the message
+    conn.backend()
+        .queue_message_with_payload(observation_message(3), b"LSZH");
+    let observation = conn
+        .get_next_message()
+        .unwrap()
+        .weather_observation()
+        .unwrap();
+    assert_eq!(observation.metar, "LSZH");
+}
+
+#[test]
+fn decodes_cloud_states() {
+    let mut conn = MockBackend::connected();
+    let size = (CloudState::WIDTH * CloudState::WIDTH) as u32;
+    let mut message = SIMCONNECT_RECV_CLOUD_STATE::zeroed();
+    message.dwRequestID = 6;
+    message.dwArraySize = size;
+    let data = (0..size).map(|i| (i % 256) as u8).collect::<Vec<_>>();
+    conn.backend().queue_message_with_payload(message, &data);
+
+    let state = conn.get_next_message().unwrap().cloud_state().unwrap();
+    assert_eq!(state.request_id, 6);
+    assert_eq!(state.data.len(), size as usize);
+    assert_eq!(state.density(1, 2), Some(66));
+    assert_eq!(state.density(0, CloudState::WIDTH), None);
+    assert_eq!(state.density(CloudState::WIDTH, 0), None);
+}
+
+#[test]
+fn reads_no_further_than_the_received_size() {
+    let mut conn = MockBackend::connected();
+    let claim_size = |mut bytes: Vec<u8>| {
+        bytes[..4].copy_from_slice(&4096_u32.to_le_bytes());
+        bytes
+    };
+
+    let observation = observation_message(4).with_payload(b"LSZH");
+    conn.backend().queue_raw_message(claim_size(observation));
+    let observation = conn
+        .get_next_message()
+        .unwrap()
+        .weather_observation()
+        .unwrap();
+    assert_eq!(observation.metar, "LSZH");
+
+    let mut message = SIMCONNECT_RECV_CLOUD_STATE::zeroed();
+    message.dwArraySize = 4096;
+    conn.backend()
+        .queue_raw_message(claim_size(message.with_payload(&[1, 2, 3])));
+    let state = conn.get_next_message().unwrap().cloud_state().unwrap();
+    assert_eq!(state.data, [1, 2, 3]);
+}
+
+#[test]
+fn decodes_weather_modes() {
+    let mut conn = MockBackend::connected();
+    let mut event = SIMCONNECT_RECV_EVENT_WEATHER_MODE::zeroed();
+    event._base.dwData = SIMCONNECT_WEATHER_MODE_SIMCONNECT_WEATHER_MODE_RWW as u32;
+    conn.backend().queue_message(event);
+
+    assert_eq!(
+        conn.get_next_message().unwrap().weather_mode(),
+        Some(WeatherMode::RealWorld)
+    );
+}
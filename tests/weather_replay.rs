@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::PathBuf;
+
+use simconnect::metar::{Distance, Metar, MetarError, Pressure};
+use simconnect::mock::{MockBackend, RecvMessage};
+use simconnect::weather_replay::{
+    ReplayError, ReplayStation, WeatherArchive, WeatherReplay, ZuluTime,
+};
+use simconnect::{SimConnector, SIMCONNECT_RECV_SIMOBJECT_DATA};
+
+/// A fresh directory for the archive of the test `name`
+fn archive_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("simconnect-replay-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn time(text: &str) -> ZuluTime {
+    ZuluTime::parse(text).unwrap()
+}
+
+fn archive() -> WeatherArchive {
+    let mut archive = WeatherArchive::new();
+    for (time_text, report) in [
+        (
+            "2024-03-12T12:00Z",
+            "ZZZZ 121200Z 35010KT 4000 BKN010 10/05 Q1010",
+        ),
+        (
+            "2024-03-12T13:00Z",
+            "ZZZZ 121300Z 03020G30KT 8000 BKN020 14/07 Q1020",
+        ),
+        (
+            "2024-03-12T14:00Z",
+            "ZZZZ 121400Z 03020KT CAVOK 15/07 Q1020",
+        ),
+    ] {
+        archive.insert(time(time_text), Metar::parse(report).unwrap());
+    }
+    archive
+}
+
+/// The METARs sent with `SimConnect_WeatherSetObservation`
+fn observations(conn: &SimConnector<MockBackend>) -> Vec<String> {
+    conn.backend()
+        .calls_to("SimConnect_WeatherSetObservation")
+        .iter()
+        .map(|call| call.arg("metar").unwrap().trim_matches('"').to_string())
+        .collect()
+}
+
+#[test]
+fn converts_times() {
+    let noon = time("2024-02-29T12:00Z");
+    assert_eq!(time("2024/02/29 12:00"), noon);
+    assert_eq!(time("202402291200"), noon);
+    assert_eq!(noon.0, 1_709_208_000);
+    assert_eq!(noon.date(), (2024, 2, 29));
+    assert_eq!(noon.to_string(), "2024-02-29T12:00:00Z");
+    assert_eq!(noon.observation_time().to_string(), "291200Z");
+
+    assert_eq!(
+        ZuluTime::from_date(1969, 12, 31, 86_399),
+        Some(ZuluTime(-1))
+    );
+    assert_eq!(ZuluTime::parse("2023/02/29 12:00"), None);
+    assert_eq!(ZuluTime::parse("2024/03/12 24:00"), None);
+    assert_eq!(ZuluTime::parse("EGLL 121250Z"), None);
+}
+
+#[test]
+fn reads_archive_directories() {
+    let dir = archive_dir("read");
+    fs::write(
+        dir.join("noaa.txt"),
+        "# EGLL\n2024/03/12 12:50\nEGLL 121250Z 24012KT 9999 BKN025 12/08 Q1009\n\n\
+         2024/03/12 12:20\nEGLL 121220Z 24010KT 9999 BKN020 11/08 Q1009\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("ogimet.txt"),
+        "202403121320 METAR LSZH 121320Z 25015KT 9999 SCT030 13/07 Q1010=\n\
+         2024-03-12T13:50Z LSZH 121350Z 25015KT 9999 SCT030 13/07 Q1011 RERA WS R28 NOSIG=\n",
+    )
+    .unwrap();
+    fs::write(dir.join(".hidden"), "not a report").unwrap();
+
+    let archive = WeatherArchive::from_dir(&dir).unwrap();
+    assert_eq!(archive.stations().collect::<Vec<_>>(), ["EGLL", "LSZH"]);
+
+    let reports = archive.reports("EGLL");
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].time, time("2024-03-12T12:20Z"));
+    assert_eq!(
+        reports[1].metar.pressure,
+        Some(Pressure::Hectopascals(1009))
+    );
+    let lszh = &archive.reports("LSZH")[1];
+    assert_eq!(lszh.time, time("2024-03-12T13:50Z"));
+    assert_eq!(lszh.metar.supplementary, ["RERA", "WS", "R28"]);
+    assert_eq!(lszh.metar.trend.as_deref(), Some("NOSIG"));
+    assert!(archive.reports("KJFK").is_empty());
+
+    fs::write(dir.join("z.txt"), "2024/03/12 12:50\nEGLL\nEGLL 121250Z\n").unwrap();
+    match WeatherArchive::from_dir(&dir) {
+        Err(ReplayError::MissingTime { path, line }) => {
+            assert_eq!(path, dir.join("z.txt"));
+            assert_eq!(line, 3);
+        }
+        other => panic!("expected MissingTime, got {:?}", other),
+    }
+
+    fs::write(dir.join("z.txt"), "202403121250 LONDON 121250Z\n").unwrap();
+    assert!(matches!(
+        WeatherArchive::from_dir(&dir),
+        Err(ReplayError::Metar { line: 1, .. })
+    ));
+    fs::write(
+        dir.join("z.txt"),
+        "202403121250 EGLL 121250Z 24012KT 9999\n202403121320 EGLL 121320Z 24012KT&éNG\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        WeatherArchive::from_dir(&dir),
+        Err(ReplayError::Metar {
+            line: 2,
+            source: MetarError::InvalidExtension(_),
+            ..
+        })
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(
+        WeatherArchive::from_dir(&dir),
+        Err(ReplayError::Io { .. })
+    ));
+}
+
+#[test]
+fn interpolates_between_reports() {
+    let archive = archive();
+
+    let metar = archive
+        .observation("ZZZZ", time("2024-03-12T12:30Z"))
+        .unwrap();
+    assert_eq!(
+        metar.to_string(),
+        "ZZZZ 121230Z 01015G30KT 6000 BKN015 12/06 Q1015"
+    );
+
+    // Gusts and clouds come from the closer report, as do groups only one of them has
+    let metar = archive
+        .observation("ZZZZ", time("2024-03-12T12:45Z"))
+        .unwrap();
+    assert_eq!(metar.winds[0].gust, Some(30));
+    assert_eq!(metar.visibility[0].distance, Distance::Meters(7000));
+    let metar = archive
+        .observation("ZZZZ", time("2024-03-12T13:45Z"))
+        .unwrap();
+    assert!(metar.cavok);
+    assert_eq!(metar.to_string(), "ZZZZ 121345Z 03020KT CAVOK 15/07 Q1020");
+
+    // Outside the archive the closest report is used as it is
+    assert_eq!(
+        archive
+            .observation("ZZZZ", time("2024-03-12T06:00Z"))
+            .unwrap()
+            .to_string(),
+        "ZZZZ 120600Z 35010KT 4000 BKN010 10/05 Q1010"
+    );
+    assert_eq!(
+        archive
+            .observation("ZZZZ", time("2024-03-13T06:00Z"))
+            .unwrap()
+            .to_string(),
+        "ZZZZ 130600Z 03020KT CAVOK 15/07 Q1020"
+    );
+    assert_eq!(archive.observation("EGLL", time("2024-03-12T12:00Z")), None);
+}
+
+#[test]
+fn replays_as_zulu_time_advances() {
+    let conn = MockBackend::connected();
+    let mut replay = WeatherReplay::new(archive());
+    replay.add_station(ReplayStation::new(
+        "ZZZZ",
+        "Training field",
+        47.5,
+        8.5,
+        1400.0,
+    ));
+    replay.start(&conn, 10).unwrap();
+
+    let backend = conn.backend();
+    assert!(backend.was_called("SimConnect_WeatherSetModeCustom"));
+    let station = &backend.calls_to("SimConnect_WeatherCreateStation")[0];
+    assert_eq!(station.arg("request_id"), Some("10"));
+    assert_eq!(station.arg("icao"), Some("\"ZZZZ\""));
+    assert_eq!(station.arg("alt"), Some("1400.0"));
+
+    assert_eq!(replay.update(&conn, time("2024-03-12T12:30Z")).unwrap(), 1);
+    // Within the interval
+    assert_eq!(
+        replay.update(&conn, time("2024-03-12T12:30:30Z")).unwrap(),
+        0
+    );
+    assert_eq!(
+        replay.update(&conn, time("2024-03-12T12:30:59Z")).unwrap(),
+        0
+    );
+    assert_eq!(replay.update(&conn, time("2024-03-12T12:42Z")).unwrap(), 1);
+    // After the archive the weather doesn't change any more
+    assert_eq!(replay.update(&conn, time("2024-03-12T15:00Z")).unwrap(), 1);
+    assert_eq!(replay.update(&conn, time("2024-03-12T16:00Z")).unwrap(), 0);
+    // Back in time
+    assert_eq!(replay.update(&conn, time("2024-03-12T12:30Z")).unwrap(), 1);
+
+    assert_eq!(
+        observations(&conn),
+        [
+            "ZZZZ 121230Z 01015G30KT 6000 BKN015 12/06 Q1015",
+            "ZZZZ 121242Z 01817G30KT 6800 BKN017 13/06 Q1017",
+            "ZZZZ 121500Z 03020KT CAVOK 15/07 Q1020",
+            "ZZZZ 121230Z 01015G30KT 6000 BKN015 12/06 Q1015",
+        ]
+    );
+
+    replay.stop(&conn).unwrap();
+    let removed = &conn.backend().calls_to("SimConnect_WeatherRemoveStation")[0];
+    assert_eq!(removed.arg("icao"), Some("\"ZZZZ\""));
+}
+
+#[test]
+fn decodes_zulu_time() {
+    let mut conn = MockBackend::connected();
+    let mut replay = WeatherReplay::new(WeatherArchive::new());
+    replay.request_zulu_time(&conn, 3, 4).unwrap();
+
+    let names = conn
+        .backend()
+        .calls_to("SimConnect_AddToDataDefinition")
+        .iter()
+        .map(|call| call.arg("datum_name").unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "\"ZULU YEAR\"",
+            "\"ZULU MONTH OF YEAR\"",
+            "\"ZULU DAY OF MONTH\"",
+            "\"ZULU TIME\""
+        ]
+    );
+
+    for request_id in [5, 4] {
+        let mut data = SIMCONNECT_RECV_SIMOBJECT_DATA::zeroed();
+        data.dwRequestID = request_id;
+        let mut payload = Vec::new();
+        for value in [2024.0f64, 3.0, 12.0, 45_045.5] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        conn.backend().queue_message_with_payload(data, &payload);
+    }
+
+    assert_eq!(replay.zulu_time(&conn.get_next_message().unwrap()), None);
+    assert_eq!(
+        replay.zulu_time(&conn.get_next_message().unwrap()),
+        Some(time("2024-03-12T12:30:45Z"))
+    );
+}